@@ -1,4 +1,6 @@
+use std::ops::Range;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use clap::arg;
 use clap::command;
@@ -9,15 +11,22 @@ use clap::ArgAction;
 use clap::ArgMatches;
 use clap::Command;
 use clap::ValueHint;
+use delphinus_zkwasm::runtime::monitor::limits::ExecutionLimits;
+use delphinus_zkwasm::runtime::monitor::plugins::tracer::TraceFilter;
 
 use crate::args::HostMode;
 use crate::args::Scheme;
+use crate::args::TraceOutputFormat;
 use crate::command::DryRunArg;
+use crate::command::MergeProofsArg;
+use crate::command::MigrateArg;
 use crate::command::ProveArg;
 use crate::command::RunningArg;
 use crate::command::SetupArg;
 use crate::command::Subcommands;
+use crate::command::TraceArg;
 use crate::command::VerifyArg;
+use crate::command::WitnessArg;
 use crate::ZkWasmCli;
 
 trait ArgBuilder<T> {
@@ -112,6 +121,35 @@ impl ArgBuilder<Option<String>> for ContextOutputArg {
     }
 }
 
+struct StateDiffOutputArg;
+impl ArgBuilder<Option<String>> for StateDiffOutputArg {
+    fn builder() -> Arg<'static> {
+        Arg::new("state-diff")
+            .long("state-diff")
+            .value_name("STATE_DIFF")
+            .help("Path to the JSON file to write the state diff of the merkle trees")
+            .required(false)
+            .multiple_values(false)
+    }
+
+    fn parse(matches: &ArgMatches) -> Option<String> {
+        matches.get_one("state-diff").cloned()
+    }
+}
+
+struct TreeDbArg;
+impl ArgBuilder<Option<PathBuf>> for TreeDbArg {
+    fn builder() -> Arg<'static> {
+        arg!(--db [DB] "Path to the local tree db file used by the merkle and datacache host functions, MongoDB is used if not specified")
+            .value_parser(value_parser!(PathBuf))
+            .value_hint(ValueHint::FilePath)
+    }
+
+    fn parse(matches: &ArgMatches) -> Option<PathBuf> {
+        matches.get_one::<PathBuf>("db").cloned()
+    }
+}
+
 struct FileBackendArg;
 impl ArgBuilder<bool> for FileBackendArg {
     fn builder() -> Arg<'static> {
@@ -124,6 +162,38 @@ impl ArgBuilder<bool> for FileBackendArg {
     }
 }
 
+struct FromTracesArg;
+impl ArgBuilder<Option<PathBuf>> for FromTracesArg {
+    fn builder() -> Arg<'static> {
+        Arg::new("from-traces")
+            .long("from-traces")
+            .value_name("TRACE_DIR")
+            .help("Prove the traces written by witness instead of executing the Wasm image")
+            .value_parser(value_parser!(PathBuf))
+            .value_hint(ValueHint::DirPath)
+            .required(false)
+            .multiple_values(false)
+            // the traces are already executed with the arguments passed to witness
+            .conflicts_with_all(&[
+                "wasm",
+                "public",
+                "private",
+                "ctxin",
+                "ctxout",
+                "state-diff",
+                "db",
+                "file",
+                "limit",
+                "slice-limit",
+                "time-limit",
+            ])
+    }
+
+    fn parse(matches: &ArgMatches) -> Option<PathBuf> {
+        matches.get_one::<PathBuf>("from-traces").cloned()
+    }
+}
+
 struct MockTestArg;
 impl ArgBuilder<bool> for MockTestArg {
     fn builder() -> Arg<'static> {
@@ -149,6 +219,82 @@ impl ArgBuilder<usize> for SkipArg {
     }
 }
 
+struct ResumeArg;
+impl ArgBuilder<bool> for ResumeArg {
+    fn builder() -> Arg<'static> {
+        arg!(--resume "Only prove the slices without a complete proof in the output directory")
+            .action(ArgAction::SetTrue)
+    }
+
+    fn parse(matches: &ArgMatches) -> bool {
+        matches.get_flag("resume")
+    }
+}
+
+/// Parses a range of slices with format `start..end`.
+fn parse_slice_range(range: &str) -> Result<Range<usize>, String> {
+    let (start, end) = range
+        .split_once("..")
+        .ok_or_else(|| format!("{} is not a range with format start..end", range))?;
+
+    let start = start.parse::<usize>().map_err(|err| err.to_string())?;
+    let end = end.parse::<usize>().map_err(|err| err.to_string())?;
+
+    if start >= end {
+        return Err(format!("{} is an empty range", range));
+    }
+
+    Ok(start..end)
+}
+
+struct SliceRangeArg;
+impl ArgBuilder<Option<Range<usize>>> for SliceRangeArg {
+    fn builder() -> Arg<'static> {
+        Arg::new("slice-range")
+            .long("slice-range")
+            .value_name("START..END")
+            .help("Only prove the slices whose index is in [START, END), the proofs of the ranges are merged by merge-proofs")
+            .value_parser(parse_slice_range)
+            .required(false)
+            .multiple_values(false)
+    }
+
+    fn parse(matches: &ArgMatches) -> Option<Range<usize>> {
+        matches.get_one::<Range<usize>>("slice-range").cloned()
+    }
+}
+
+struct ProofDirsArg;
+impl ArgBuilder<Vec<PathBuf>> for ProofDirsArg {
+    fn builder() -> Arg<'static> {
+        arg!(--proofs <PROOF_DIR> ... "Output directories of the proofs to merge, values can be separated by `,` or multiple occurrence of `--proofs`")
+            .value_parser(value_parser!(PathBuf))
+            .value_hint(ValueHint::DirPath)
+            .value_delimiter(',')
+    }
+
+    fn parse(matches: &ArgMatches) -> Vec<PathBuf> {
+        matches
+            .get_many::<PathBuf>("proofs")
+            .unwrap_or_default()
+            .cloned()
+            .collect()
+    }
+}
+
+struct SlicesArg;
+impl ArgBuilder<Option<usize>> for SlicesArg {
+    fn builder() -> Arg<'static> {
+        arg!(--slices [N] "Number of slices of the execution, required with a uniform circuit")
+            .value_parser(value_parser!(usize))
+            .multiple_values(false)
+    }
+
+    fn parse(matches: &ArgMatches) -> Option<usize> {
+        matches.get_one("slices").copied()
+    }
+}
+
 struct PaddingArg;
 impl ArgBuilder<Option<usize>> for PaddingArg {
     fn builder() -> Arg<'static> {
@@ -175,6 +321,106 @@ impl ArgBuilder<Option<usize>> for InstructionLimitArg {
     }
 }
 
+struct SliceLimitArg;
+impl ArgBuilder<Option<usize>> for SliceLimitArg {
+    fn builder() -> Arg<'static> {
+        Arg::new("slice-limit")
+            .long("slice-limit")
+            .value_name("SLICE_LIMIT")
+            .help("Terminate if the number of committed slices exceeds [SLICE_LIMIT]")
+            .value_parser(value_parser!(usize))
+            .required(false)
+            .multiple_values(false)
+    }
+
+    fn parse(matches: &ArgMatches) -> Option<usize> {
+        matches.get_one("slice-limit").cloned()
+    }
+}
+
+struct TimeLimitArg;
+impl ArgBuilder<Option<Duration>> for TimeLimitArg {
+    fn builder() -> Arg<'static> {
+        Arg::new("time-limit")
+            .long("time-limit")
+            .value_name("SECONDS")
+            .help("Terminate if the execution takes more than [SECONDS] seconds")
+            .value_parser(value_parser!(u64))
+            .required(false)
+            .multiple_values(false)
+    }
+
+    fn parse(matches: &ArgMatches) -> Option<Duration> {
+        matches
+            .get_one::<u64>("time-limit")
+            .map(|seconds| Duration::from_secs(*seconds))
+    }
+}
+
+struct TraceFormatArg;
+impl ArgBuilder<TraceOutputFormat> for TraceFormatArg {
+    fn builder() -> Arg<'static> {
+        arg!(--format <FORMAT> "Format of the trace")
+            .default_value("text")
+            .value_parser(value_parser!(TraceOutputFormat))
+            .required(false)
+    }
+
+    fn parse(matches: &ArgMatches) -> TraceOutputFormat {
+        *matches.get_one::<TraceOutputFormat>("format").unwrap()
+    }
+}
+
+struct TraceFunctionsArg;
+impl ArgBuilder<Vec<String>> for TraceFunctionsArg {
+    fn builder() -> Arg<'static> {
+        arg!(--function <FUNCTION> ... "Only trace the steps of the functions, a function is specified by its name or index, values can be separated by `,` or multiple occurrence of `--function`")
+            .takes_value(true).value_delimiter(',').required(false)
+    }
+
+    fn parse(matches: &ArgMatches) -> Vec<String> {
+        matches
+            .get_many::<String>("function")
+            .unwrap_or_default()
+            .map(|s| s.to_string())
+            .collect()
+    }
+}
+
+struct FromEidArg;
+impl ArgBuilder<Option<u32>> for FromEidArg {
+    fn builder() -> Arg<'static> {
+        Arg::new("from-eid")
+            .long("from-eid")
+            .value_name("EID")
+            .help("Only trace the steps whose eid is at least [EID]")
+            .value_parser(value_parser!(u32))
+            .required(false)
+            .multiple_values(false)
+    }
+
+    fn parse(matches: &ArgMatches) -> Option<u32> {
+        matches.get_one("from-eid").cloned()
+    }
+}
+
+struct ToEidArg;
+impl ArgBuilder<Option<u32>> for ToEidArg {
+    fn builder() -> Arg<'static> {
+        Arg::new("to-eid")
+            .long("to-eid")
+            .value_name("EID")
+            .help("Only trace the steps whose eid is at most [EID]")
+            .value_parser(value_parser!(u32))
+            .required(false)
+            .multiple_values(false)
+    }
+
+    fn parse(matches: &ArgMatches) -> Option<u32> {
+        matches.get_one("to-eid").cloned()
+    }
+}
+
 fn setup_command() -> Command<'static> {
     let command = Command::new("setup")
         .about("Setup a new zkWasm circuit for provided Wasm image")
@@ -192,6 +438,15 @@ fn setup_command() -> Command<'static> {
             .value_parser(value_parser!(HostMode))
             .required(false),
         )
+        .arg(
+            Arg::new("host-config")
+                .long("host-config")
+                .value_name("HOST_CONFIG")
+                .help("Path to a JSON file listing the host ops enabled in the standard host environment")
+                .value_parser(value_parser!(PathBuf))
+                .value_hint(ValueHint::FilePath)
+                .required(false),
+        )
         .arg(
             arg!(
                 --phantom <PHANTOM_FUNCTIONS> "Specify phantom functions whose body will be ignored in the circuit"
@@ -225,24 +480,70 @@ fn dry_run_command() -> Command<'static> {
         .arg(PrivateInputsArg::builder())
         .arg(ContextInputsArg::builder())
         .arg(ContextOutputArg::builder())
+        .arg(StateDiffOutputArg::builder())
+        .arg(TreeDbArg::builder())
         .arg(OutputDirArg::builder())
         .arg(InstructionLimitArg::builder())
 }
 
+fn trace_command() -> Command<'static> {
+    Command::new("trace")
+        .about("Execute the Wasm image and write each step of the execution trace")
+        .arg(WasmImageArg::builder())
+        .arg(PublicInputsArg::builder())
+        .arg(PrivateInputsArg::builder())
+        .arg(ContextInputsArg::builder())
+        .arg(ContextOutputArg::builder())
+        .arg(StateDiffOutputArg::builder())
+        .arg(TreeDbArg::builder())
+        .arg(OutputDirArg::builder())
+        .arg(InstructionLimitArg::builder())
+        .arg(TraceFormatArg::builder())
+        .arg(TraceFunctionsArg::builder())
+        .arg(FromEidArg::builder())
+        .arg(ToEidArg::builder())
+}
+
+fn witness_command() -> Command<'static> {
+    Command::new("witness")
+        .about("Execute the Wasm image and write the traces to generate a proof by prove --from-traces")
+        .arg(WasmImageArg::builder())
+        .arg(PublicInputsArg::builder())
+        .arg(PrivateInputsArg::builder())
+        .arg(ContextInputsArg::builder())
+        .arg(ContextOutputArg::builder())
+        .arg(StateDiffOutputArg::builder())
+        .arg(TreeDbArg::builder())
+        .arg(OutputDirArg::builder())
+        .arg(InstructionLimitArg::builder())
+        .arg(SliceLimitArg::builder())
+        .arg(TimeLimitArg::builder())
+}
+
 fn prove_command() -> Command<'static> {
     let command = Command::new("prove")
         .about("Execute the Wasm image and generate a proof")
-        .arg(WasmImageArg::builder())
+        .arg(WasmImageArg::builder().required_unless_present("from-traces"))
+        .arg(FromTracesArg::builder())
         .arg(PublicInputsArg::builder())
         .arg(PrivateInputsArg::builder())
         .arg(ContextInputsArg::builder())
         .arg(ContextOutputArg::builder())
+        .arg(StateDiffOutputArg::builder())
+        .arg(TreeDbArg::builder())
         .arg(OutputDirArg::builder())
         .arg(MockTestArg::builder())
-        .arg(FileBackendArg::builder());
+        .arg(FileBackendArg::builder())
+        .arg(ResumeArg::builder())
+        .arg(InstructionLimitArg::builder())
+        .arg(SliceLimitArg::builder())
+        .arg(TimeLimitArg::builder());
 
     if cfg!(feature = "continuation") {
-        command.arg(SkipArg::builder()).arg(PaddingArg::builder())
+        command
+            .arg(SkipArg::builder())
+            .arg(SliceRangeArg::builder())
+            .arg(PaddingArg::builder())
     } else {
         command
     }
@@ -254,6 +555,24 @@ fn verify_command() -> Command<'static> {
         .arg(OutputDirArg::builder())
 }
 
+fn merge_proofs_command() -> Command<'static> {
+    Command::new("merge-proofs")
+        .about("Merge the proofs of slice ranges into one proof")
+        .arg(ProofDirsArg::builder())
+        .arg(OutputDirArg::builder())
+        .arg(SlicesArg::builder())
+}
+
+fn info_command() -> Command<'static> {
+    Command::new("info").about("Print what the configuration was set up with")
+}
+
+fn migrate_command() -> Command<'static> {
+    Command::new("migrate")
+        .about("Migrate the configuration set up by an older version")
+        .arg(WasmImageArg::builder().required(false))
+}
+
 pub(crate) fn app() -> App<'static> {
     command!()
         .author("delphinus-lab")
@@ -266,8 +585,13 @@ pub(crate) fn app() -> App<'static> {
         )
         .subcommand(setup_command())
         .subcommand(dry_run_command())
+        .subcommand(trace_command())
+        .subcommand(witness_command())
         .subcommand(prove_command())
         .subcommand(verify_command())
+        .subcommand(merge_proofs_command())
+        .subcommand(info_command())
+        .subcommand(migrate_command())
         .subcommand_required(true)
 }
 
@@ -276,6 +600,7 @@ impl From<&ArgMatches> for SetupArg {
         SetupArg {
             k: *val.get_one::<u32>("K").unwrap(),
             host_mode: *val.get_one::<HostMode>("host").unwrap(),
+            host_config: val.get_one::<PathBuf>("host-config").cloned(),
             phantom_functions: val
                 .get_many::<String>("phantom")
                 .unwrap_or_default()
@@ -295,6 +620,8 @@ impl From<&ArgMatches> for RunningArg {
             private_inputs: PrivateInputsArg::parse(val),
             context_inputs: ContextInputsArg::parse(val),
             context_output: ContextOutputArg::parse(val),
+            state_diff_output: StateDiffOutputArg::parse(val),
+            db: TreeDbArg::parse(val),
         }
     }
 }
@@ -309,16 +636,58 @@ impl From<&ArgMatches> for DryRunArg {
     }
 }
 
+impl From<&ArgMatches> for TraceArg {
+    fn from(val: &ArgMatches) -> Self {
+        let eids = match (FromEidArg::parse(val), ToEidArg::parse(val)) {
+            (None, None) => None,
+            (from, to) => Some(from.unwrap_or(0)..=to.unwrap_or(u32::MAX)),
+        };
+
+        TraceArg {
+            wasm_image: WasmImageArg::parse(val).unwrap(),
+            running_arg: val.into(),
+            instruction_limit: InstructionLimitArg::parse(val),
+            format: TraceFormatArg::parse(val),
+            filter: TraceFilter {
+                functions: TraceFunctionsArg::parse(val),
+                eids,
+            },
+        }
+    }
+}
+
+impl From<&ArgMatches> for WitnessArg {
+    fn from(val: &ArgMatches) -> Self {
+        WitnessArg {
+            wasm_image: WasmImageArg::parse(val).unwrap(),
+            running_arg: val.into(),
+            limits: ExecutionLimits {
+                instructions: InstructionLimitArg::parse(val),
+                slices: SliceLimitArg::parse(val),
+                wall_clock: TimeLimitArg::parse(val),
+            },
+        }
+    }
+}
+
 impl From<&ArgMatches> for ProveArg {
     fn from(val: &ArgMatches) -> Self {
         ProveArg {
-            wasm_image: WasmImageArg::parse(val).unwrap(),
+            wasm_image: WasmImageArg::parse(val),
+            from_traces: FromTracesArg::parse(val),
             output_dir: OutputDirArg::parse(val),
             running_arg: val.into(),
             mock_test: MockTestArg::parse(val),
             file_backend: FileBackendArg::parse(val),
             skip: SkipArg::parse(val),
+            resume: ResumeArg::parse(val),
+            slice_range: SliceRangeArg::parse(val),
             padding: PaddingArg::parse(val),
+            limits: ExecutionLimits {
+                instructions: InstructionLimitArg::parse(val),
+                slices: SliceLimitArg::parse(val),
+                wall_clock: TimeLimitArg::parse(val),
+            },
         }
     }
 }
@@ -331,13 +700,36 @@ impl From<&ArgMatches> for VerifyArg {
     }
 }
 
+impl From<&ArgMatches> for MergeProofsArg {
+    fn from(val: &ArgMatches) -> Self {
+        MergeProofsArg {
+            proof_dirs: ProofDirsArg::parse(val),
+            output_dir: OutputDirArg::parse(val),
+            slices: SlicesArg::parse(val),
+        }
+    }
+}
+
+impl From<&ArgMatches> for MigrateArg {
+    fn from(val: &ArgMatches) -> Self {
+        MigrateArg {
+            wasm_image: WasmImageArg::parse(val),
+        }
+    }
+}
+
 impl From<ArgMatches> for ZkWasmCli {
     fn from(arg: ArgMatches) -> ZkWasmCli {
         let subcommand = match arg.subcommand() {
             Some(("setup", sub_matches)) => Subcommands::Setup(sub_matches.into()),
             Some(("dry-run", sub_matches)) => Subcommands::DryRun(sub_matches.into()),
+            Some(("trace", sub_matches)) => Subcommands::Trace(sub_matches.into()),
+            Some(("witness", sub_matches)) => Subcommands::Witness(sub_matches.into()),
             Some(("prove", sub_matches)) => Subcommands::Prove(sub_matches.into()),
             Some(("verify", sub_matches)) => Subcommands::Verify(sub_matches.into()),
+            Some(("merge-proofs", sub_matches)) => Subcommands::MergeProofs(sub_matches.into()),
+            Some(("info", _)) => Subcommands::Info,
+            Some(("migrate", sub_matches)) => Subcommands::Migrate(sub_matches.into()),
             _ => unreachable!("unknown subcommand"),
         };
 
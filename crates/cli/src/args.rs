@@ -1,4 +1,5 @@
 use circuits_batcher::args::OpenSchema;
+use delphinus_zkwasm::runtime::monitor::plugins::tracer::TraceFormat;
 use serde::Deserialize;
 use serde::Serialize;
 
@@ -28,3 +29,22 @@ impl From<Scheme> for OpenSchema {
         }
     }
 }
+
+#[derive(clap::ArgEnum, Copy, Clone, Debug, Default)]
+pub(crate) enum TraceOutputFormat {
+    /// One human-readable line per step
+    #[default]
+    Text,
+
+    /// One JSON object per step
+    Jsonl,
+}
+
+impl From<TraceOutputFormat> for TraceFormat {
+    fn from(val: TraceOutputFormat) -> TraceFormat {
+        match val {
+            TraceOutputFormat::Text => TraceFormat::Text,
+            TraceOutputFormat::Jsonl => TraceFormat::JsonLines,
+        }
+    }
+}
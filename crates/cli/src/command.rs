@@ -1,20 +1,25 @@
 use std::fs::File;
-use std::fs::OpenOptions;
 use std::fs::{self};
-use std::io::Read;
+use std::io::Cursor;
+use std::ops::Range;
 use std::path::Path;
 use std::path::PathBuf;
 
 use crate::args::Scheme;
+use crate::args::TraceOutputFormat;
+use crate::config::check_unproven_ops;
 use crate::config::CircuitDataConfig;
-use crate::config::CircuitDataMd5;
+use crate::config::CircuitDataSha256;
 use crate::TRIVIAL_WASM;
 use clap::Args;
 use console::style;
+use delphinus_host::HostEnvConfig;
 use delphinus_zkwasm::checksum::ImageCheckSum;
 use delphinus_zkwasm::circuits::ZkWasmCircuit;
 use delphinus_zkwasm::loader::ZkWasmLoader;
 use delphinus_zkwasm::runtime::host::HostEnvBuilder;
+use delphinus_zkwasm::runtime::monitor::limits::ExecutionLimits;
+use delphinus_zkwasm::runtime::monitor::plugins::tracer::TraceFilter;
 use delphinus_zkwasm::runtime::monitor::table_monitor::TableMonitor;
 use halo2_proofs::arithmetic::CurveAffine;
 use halo2_proofs::pairing::bn256::Bn256;
@@ -32,79 +37,96 @@ use crate::config::Config;
 use crate::names::name_of_circuit_data;
 use crate::names::name_of_config;
 use crate::names::name_of_params;
+use crate::names::params_sha256_of_name;
+use crate::utils::sha256_hex;
 
 #[derive(Debug)]
 pub(crate) struct SetupArg {
     pub(crate) k: u32,
     pub(crate) host_mode: HostMode,
+    pub(crate) host_config: Option<PathBuf>,
     pub(crate) phantom_functions: Vec<String>,
     pub(crate) wasm_image: Option<PathBuf>,
     pub(crate) scheme: Scheme,
 }
 
 impl SetupArg {
+    /// Host ops of the standard host env, read from the host config file if
+    /// provided. `None` for the default host env. The ops must fit into the
+    /// circuit of K, e.g. the height of the Merkle tree.
+    pub(crate) fn host_env_config(&self) -> anyhow::Result<Option<HostEnvConfig>> {
+        let host_config = match (self.host_mode, &self.host_config) {
+            (HostMode::Default, None) => return Ok(None),
+            (HostMode::Default, Some(_)) => {
+                anyhow::bail!("A host config is only supported by the standard host environment.")
+            }
+            (HostMode::Standard, None) => HostEnvConfig::default(),
+            (HostMode::Standard, Some(path)) => HostEnvConfig::read(File::open(path)?)?,
+        };
+
+        host_config.check(self.k)?;
+        check_unproven_ops(&host_config)?;
+
+        Ok(Some(host_config))
+    }
+
     fn _setup_circuit_data<C: CurveAffine, ConcreteCircuit: Circuit<C::Scalar>>(
         params: &Params<C>,
         circuit: &ConcreteCircuit,
-        path_of_circuit_data: PathBuf,
-    ) -> anyhow::Result<CircuitDataMd5> {
+        params_dir: &Path,
+    ) -> anyhow::Result<CircuitDataSha256> {
         let vkey = keygen_vk(params, circuit)?;
         let circuit_data = CircuitData::new(params, vkey, circuit)?;
 
-        let verifying_key_md5 = {
+        let verifying_key_sha256 = {
             let mut buf = Vec::new();
             circuit_data.get_vkey().write(&mut buf)?;
 
-            format!("{:x}", md5::compute(&buf))
+            sha256_hex(&buf)
         };
 
-        let circuit_data_md5 = {
-            circuit_data.write(
-                &mut OpenOptions::new()
-                    .read(true)
-                    .write(true)
-                    .create(true)
-                    .truncate(true)
-                    .open(&path_of_circuit_data)?,
-            )?;
-
+        let circuit_data_sha256 = {
             let mut buf = Vec::new();
-            File::open(&path_of_circuit_data)?.read_to_end(&mut buf)?;
+            circuit_data.write(&mut buf)?;
+
+            let circuit_data_sha256 = sha256_hex(&buf);
 
-            format!("{:x}", md5::compute(&buf))
+            // Circuit data is named by its digest, an existing file is the same circuit
+            // which was set up by another image.
+            let path_of_circuit_data = params_dir.join(name_of_circuit_data(&circuit_data_sha256));
+            if !path_of_circuit_data.exists() {
+                fs::write(&path_of_circuit_data, &buf)?;
+            }
+
+            circuit_data_sha256
         };
 
-        Ok(CircuitDataMd5 {
-            circuit_data_md5,
-            verifying_key_md5,
+        Ok(CircuitDataSha256 {
+            circuit_data_sha256,
+            verifying_key_sha256,
         })
     }
 
     pub(crate) fn setup_circuit_data(
         &self,
-        name: &str,
         params_dir: &Path,
         params: &Params<G1Affine>,
         k: u32,
         compilation_tables: &CompilationTable,
     ) -> anyhow::Result<CircuitDataConfig> {
-        let setup_circuit = |is_last_slice| -> anyhow::Result<CircuitDataMd5> {
+        let setup_circuit = |is_last_slice| -> anyhow::Result<CircuitDataSha256> {
             let setup_circuit = ZkWasmCircuit::new(
                 k,
                 Slice::from_compilation_table(compilation_tables, is_last_slice),
             )?;
 
             match setup_circuit {
-                ZkWasmCircuit::Ongoing(circuit) => SetupArg::_setup_circuit_data(
-                    params,
-                    &circuit,
-                    params_dir.join(name_of_circuit_data(name, is_last_slice)),
-                ),
-                ZkWasmCircuit::LastSliceCircuit(circuit) => SetupArg::_setup_circuit_data(
-                    params,
-                    &circuit,
-                    params_dir.join(name_of_circuit_data(name, is_last_slice)),
-                ),
+                ZkWasmCircuit::Ongoing(circuit) => {
+                    SetupArg::_setup_circuit_data(params, &circuit, params_dir)
+                }
+                ZkWasmCircuit::LastSliceCircuit(circuit) => {
+                    SetupArg::_setup_circuit_data(params, &circuit, params_dir)
+                }
             }
         };
 
@@ -123,6 +145,7 @@ impl SetupArg {
     pub(crate) fn setup(
         &self,
         env_builder: &dyn HostEnvBuilder,
+        host_config: Option<HostEnvConfig>,
         name: &str,
         params_dir: &PathBuf,
     ) -> anyhow::Result<()> {
@@ -132,19 +155,49 @@ impl SetupArg {
             wabt::wat2wasm(TRIVIAL_WASM).map_err(|err| anyhow::anyhow!(err)),
             |file| fs::read(file).map_err(|err| anyhow::anyhow!(err)),
         )?;
-        let module = ZkWasmLoader::parse_module(&wasm_image)?;
-        let wasm_image_md5 = md5::compute(&wasm_image);
+        let module = ZkWasmLoader::parse_module(&wasm_image, self.k, &self.phantom_functions)?;
+        let wasm_image_sha256 = sha256_hex(&wasm_image);
+
+        // Params are named by their digest, the params of K in the directory are tried in the
+        // order of their names and the first one matching its digest is reused.
+        let mut params_candidates = fs::read_dir(params_dir)?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let file_name = entry.file_name().into_string().ok()?;
+                let params_sha256 = params_sha256_of_name(self.k, &file_name)?.to_string();
+
+                Some((entry.path(), params_sha256))
+            })
+            .collect::<Vec<_>>();
+        params_candidates.sort();
+
+        let mut existing_params = None;
+        for (params_path, expected_params_sha256) in params_candidates {
+            let buf = fs::read(&params_path)?;
+
+            if sha256_hex(&buf) == expected_params_sha256 {
+                existing_params = Some((params_path, buf, expected_params_sha256));
+                break;
+            }
 
-        let params_path = params_dir.join(name_of_params(self.k));
-        let params = {
-            if params_path.exists() {
+            println!(
+                "{} Params at {:?} is inconsistent with the digest in its name, skipping it...",
+                style("[1/5]").bold().dim(),
+                params_path
+            );
+        }
+
+        let (params_path, params, params_sha256) = {
+            if let Some((params_path, mut buf, params_sha256)) = existing_params {
                 println!(
                     "{} Found existing params at {:?}. Using it instead of building a new one...",
                     style("[1/5]").bold().dim(),
                     params_path.canonicalize()?
                 );
 
-                Params::<G1Affine>::read(&mut File::open(&params_path)?)?
+                let params = Params::<G1Affine>::read(&mut Cursor::new(&mut buf))?;
+
+                (params_path, params, params_sha256)
             } else {
                 println!(
                     "{} Building params for K = {}...",
@@ -153,8 +206,14 @@ impl SetupArg {
                 );
                 let params = Params::<G1Affine>::unsafe_setup::<Bn256>(self.k);
 
-                params.write(&mut File::create(&params_path)?)?;
-                params
+                let mut buf = Vec::new();
+                params.write(&mut buf)?;
+
+                let params_sha256 = sha256_hex(&buf);
+                let params_path = params_dir.join(name_of_params(self.k, &params_sha256));
+                fs::write(&params_path, &buf)?;
+
+                (params_path, params, params_sha256)
             }
         };
 
@@ -165,6 +224,7 @@ impl SetupArg {
             env_builder.create_flush_strategy(),
             &self.phantom_functions,
             &env,
+            ExecutionLimits::default(),
         );
 
         let loader = ZkWasmLoader::new(self.k, env)?;
@@ -175,7 +235,7 @@ impl SetupArg {
 
         println!("{} Building circuit data...", style("[3/5]").bold().dim(),);
         let circuit_datas =
-            self.setup_circuit_data(name, params_dir, &params, self.k, &compilation_table)?;
+            self.setup_circuit_data(params_dir, &params, self.k, &compilation_table)?;
 
         println!("{} Computing checksum...", style("[4/5]").bold().dim(),);
         let checksum = {
@@ -188,15 +248,6 @@ impl SetupArg {
         {
             println!("{} Writing config...", style("[5/5]").bold().dim(),);
 
-            let params_md5 = {
-                let mut buf = Vec::new();
-
-                params.write(&mut buf)?;
-                let md5 = md5::compute(&buf);
-
-                format!("{:x}", md5)
-            };
-
             let config_path = params_dir.join(name_of_config(name));
 
             let config = Config {
@@ -206,17 +257,18 @@ impl SetupArg {
                 params: params_path,
                 is_uniform_circuit: cfg!(feature = "uniform-circuit"),
 
-                params_md5,
-                wasm_image_md5: if cfg!(feature = "uniform-circuit") {
+                params_sha256,
+                wasm_image_sha256: if cfg!(feature = "uniform-circuit") {
                     None
                 } else {
-                    Some(format!("{:x}", wasm_image_md5))
+                    Some(wasm_image_sha256)
                 },
                 circuit_datas,
 
                 checksum,
                 phantom_functions: self.phantom_functions.clone(),
                 host_mode: self.host_mode,
+                host_config,
 
                 scheme: self.scheme,
             };
@@ -254,6 +306,14 @@ pub(crate) struct RunningArg {
     /// Filename to the file to write the context output.
     #[clap(long = "context-out")]
     pub(crate) context_output: Option<String>,
+
+    /// Filename to the file to write the state diff of the merkle trees.
+    #[clap(long = "state-diff")]
+    pub(crate) state_diff_output: Option<String>,
+
+    /// Path to the local tree db file, MongoDB is used if not specified.
+    #[clap(long = "db")]
+    pub(crate) db: Option<PathBuf>,
 }
 
 #[derive(Debug)]
@@ -263,18 +323,44 @@ pub(crate) struct DryRunArg {
     pub(crate) instruction_limit: Option<usize>,
 }
 
+/// Execute the Wasm image and write its execution trace.
+#[derive(Debug)]
+pub(crate) struct TraceArg {
+    pub(crate) wasm_image: PathBuf,
+    pub(crate) running_arg: RunningArg,
+    pub(crate) instruction_limit: Option<usize>,
+    pub(crate) format: TraceOutputFormat,
+    pub(crate) filter: TraceFilter,
+}
+
+/// Execute the Wasm image and write the traces to prove it later.
+#[derive(Debug)]
+pub(crate) struct WitnessArg {
+    pub(crate) wasm_image: PathBuf,
+    pub(crate) running_arg: RunningArg,
+    pub(crate) limits: ExecutionLimits,
+}
+
 /// Execute the Wasm image and generate a proof.
 #[derive(Debug)]
 pub(crate) struct ProveArg {
-    pub(crate) wasm_image: PathBuf,
+    // required unless the proof is created from traces
+    pub(crate) wasm_image: Option<PathBuf>,
+    // the traces directory written by witness
+    pub(crate) from_traces: Option<PathBuf>,
     pub(crate) output_dir: PathBuf,
     pub(crate) running_arg: RunningArg,
     pub(crate) mock_test: bool,
     pub(crate) file_backend: bool,
     // skip first n slice(s) proving.
     pub(crate) skip: usize,
+    // reuse the complete proofs in the output directory.
+    pub(crate) resume: bool,
+    // only prove the slices in the range.
+    pub(crate) slice_range: Option<Range<usize>>,
     // add trivial circuits to padding
     pub(crate) padding: Option<usize>,
+    pub(crate) limits: ExecutionLimits,
 }
 
 /// Verify the proof.
@@ -285,10 +371,108 @@ pub(crate) struct VerifyArg {
     pub(crate) output_dir: PathBuf,
 }
 
+/// Merge the proofs of slice ranges into one.
+#[derive(Debug)]
+pub(crate) struct MergeProofsArg {
+    pub(crate) proof_dirs: Vec<PathBuf>,
+    pub(crate) output_dir: PathBuf,
+    pub(crate) slices: Option<usize>,
+}
+
+/// Migrate the configuration of an older version.
+#[derive(Debug)]
+pub(crate) struct MigrateArg {
+    // required if the configuration records the digest of the image
+    pub(crate) wasm_image: Option<PathBuf>,
+}
+
 #[derive(Debug)]
 pub(crate) enum Subcommands {
     Setup(SetupArg),
     DryRun(DryRunArg),
+    Trace(TraceArg),
+    Witness(WitnessArg),
     Prove(ProveArg),
     Verify(VerifyArg),
+    MergeProofs(MergeProofsArg),
+    Info,
+    Migrate(MigrateArg),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::SetupArg;
+    use crate::args::HostMode;
+    use crate::args::Scheme;
+    use crate::test::TempDir;
+
+    fn setup_arg(k: u32, host_config: &str, dir: &TempDir) -> SetupArg {
+        let path = dir.path().join("host_config.json");
+        fs::write(&path, host_config).unwrap();
+
+        SetupArg {
+            k,
+            host_mode: HostMode::Standard,
+            host_config: Some(path),
+            phantom_functions: vec![],
+            wasm_image: None,
+            scheme: Scheme::Gwc,
+        }
+    }
+
+    #[test]
+    fn test_setup_merkle_tree_height() {
+        let dir = TempDir::new("setup-merkle-tree-height");
+
+        let arg = setup_arg(
+            22,
+            r#"{ "ops": ["MERKLE"], "merkle_tree_height": 20 }"#,
+            &dir,
+        );
+        assert!(arg.host_env_config().unwrap().is_some());
+
+        let arg = setup_arg(
+            22,
+            r#"{ "ops": ["MERKLE"], "merkle_tree_height": 21 }"#,
+            &dir,
+        );
+        assert!(arg.host_env_config().is_err());
+
+        let arg = setup_arg(
+            10,
+            r#"{ "ops": ["MERKLE"], "merkle_tree_height": 32 }"#,
+            &dir,
+        );
+        assert!(arg.host_env_config().is_err());
+    }
+
+    #[test]
+    fn test_setup_sha256() {
+        let dir = TempDir::new("setup-sha256");
+
+        let arg = setup_arg(22, r#"{ "ops": ["SHA256"] }"#, &dir);
+        assert!(arg.host_env_config().is_err());
+
+        let arg = setup_arg(22, r#"{ "ops": ["SHA256"], "allow_unproven": true }"#, &dir);
+        assert!(arg.host_env_config().unwrap().is_some());
+    }
+
+    #[test]
+    fn test_setup_secp256k1() {
+        let dir = TempDir::new("setup-secp256k1");
+
+        let arg = setup_arg(22, r#"{ "ops": ["SECP256K1ECRECOVER"] }"#, &dir);
+        assert!(arg.host_env_config().is_err());
+
+        let host_config = r#"{ "ops": ["SECP256K1ECRECOVER"], "allow_unproven": true }"#;
+
+        let arg = setup_arg(22, host_config, &dir);
+        assert!(arg.host_env_config().unwrap().is_some());
+
+        // the circuit has no room for a recovery
+        let arg = setup_arg(16, host_config, &dir);
+        assert!(arg.host_env_config().is_err());
+    }
 }
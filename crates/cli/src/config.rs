@@ -1,7 +1,12 @@
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::fs;
 use std::fs::File;
+use std::io::BufWriter;
 use std::io::Cursor;
 use std::io::Read;
-use std::io::Write;
+use std::ops::Range;
 use std::path::Path;
 use std::path::PathBuf;
 
@@ -12,52 +17,233 @@ use circuits_batcher::proof::ProofInfo;
 use circuits_batcher::proof::ProofPieceInfo;
 use circuits_batcher::proof::Prover;
 use console::style;
+use delphinus_host::HostEnvConfig;
 use delphinus_zkwasm::circuits::ZkWasmCircuit;
 use delphinus_zkwasm::loader::slice::Slices;
 use delphinus_zkwasm::loader::Module;
 use delphinus_zkwasm::loader::ZkWasmLoader;
 use delphinus_zkwasm::runtime::host::default_env::ExecutionArg;
 use delphinus_zkwasm::runtime::host::HostEnvBuilder;
+use delphinus_zkwasm::runtime::monitor::limits::ExecutionLimits;
+use delphinus_zkwasm::runtime::monitor::plugins::tracer::TraceFilter;
+use delphinus_zkwasm::runtime::monitor::plugins::tracer::TraceFormat;
+use delphinus_zkwasm::runtime::monitor::plugins::tracer::TracerPlugin;
 use delphinus_zkwasm::runtime::monitor::statistic_monitor::StatisticMonitor;
 use delphinus_zkwasm::runtime::monitor::table_monitor::TableMonitor;
+use delphinus_zkwasm::runtime::ExecutionResult;
+use halo2_proofs::arithmetic::BaseExt;
 use halo2_proofs::pairing::bn256::Bn256;
+use halo2_proofs::pairing::bn256::Fr;
 use halo2_proofs::pairing::bn256::G1Affine;
 use halo2_proofs::plonk::CircuitData;
 use halo2_proofs::poly::commitment::Params;
 use indicatif::ProgressBar;
 use serde::Deserialize;
 use serde::Serialize;
+use specs::slice_backend::InMemoryBackendBuilder;
+use specs::slice_backend::SliceBackend;
 use specs::slice_backend::SliceBackendBuilder;
+use specs::Tables;
 
 use crate::args::HostMode;
 use crate::args::Scheme;
+use crate::file_backend::FileBackendBuilder;
+use crate::file_backend::TracedTables;
+use crate::names::index_of_instance;
 use crate::names::name_of_circuit_data;
+use crate::names::name_of_config;
 use crate::names::name_of_etable_slice;
 use crate::names::name_of_external_host_call_table_slice;
 use crate::names::name_of_frame_table_slice;
 use crate::names::name_of_instance;
 use crate::names::name_of_loadinfo;
 use crate::names::name_of_params;
+use crate::names::name_of_trace;
+use crate::names::name_of_traced_tables;
 use crate::names::name_of_transcript;
 use crate::names::name_of_witness;
+use crate::utils::sha256_hex;
+
+mod legacy;
+
+/// Version of the config format, a change of `Config` bumps it and the older
+/// versions are upgraded by `Config::migrate`.
+const CONFIG_VERSION: u32 = 1;
+
+/// Writes the state diffs of the trees written by host functions as JSON.
+fn write_state_diffs<R>(
+    result: &ExecutionResult<R>,
+    output_dir: &Path,
+    state_diff_filename: Option<String>,
+) -> Result<()> {
+    if let Some(state_diff_filename) = state_diff_filename {
+        let state_diff_path = output_dir.join(state_diff_filename);
+
+        println!("Write state diff to file {:?}...", state_diff_path);
+
+        serde_json::to_writer_pretty(File::create(&state_diff_path)?, &result.state_diffs)?;
+    }
+
+    Ok(())
+}
+
+/// Rejects the unproven ops of a host config which does not allow them and
+/// warns about the allowed ones, the results of the latter are trusted by the
+/// proofs.
+pub(crate) fn check_unproven_ops(host_config: &HostEnvConfig) -> Result<()> {
+    let unproven_ops = host_config.unproven_ops()?;
+
+    if !unproven_ops.is_empty() {
+        eprintln!(
+            "{} {:?} {}",
+            style("WARNING: host ops").red().bold(),
+            unproven_ops,
+            style(
+                "have no circuit, their results are NOT constrained by the proofs and \
+                    can be forged by the prover. Use them for development only."
+            )
+            .red()
+            .bold()
+        );
+    }
+
+    Ok(())
+}
+
+/// Content of an instance file, every instance is written by its repr.
+fn instance_file_content(instances: &[Fr]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    for instance in instances {
+        instance.write(&mut buf).unwrap();
+    }
+    buf
+}
+
+/// Saves the proof load info followed by `later_proofs`, the resumed proofs
+/// of the slices after the proved range.
+fn save_with_later_proofs(
+    proof_load_info: &mut ProofGenerationInfo,
+    later_proofs: &mut Vec<ProofPieceInfo>,
+    name: &str,
+    output_dir: &Path,
+) -> Result<()> {
+    let proved = proof_load_info.proofs.len();
+    proof_load_info.proofs.append(later_proofs);
+
+    let result = save_proof_load_info(proof_load_info, name, output_dir);
+
+    *later_proofs = proof_load_info.proofs.split_off(proved);
+
+    result
+}
+
+/// Checks that the pre image table commitment of each slice is the post image
+/// table commitment of the slice before it, `post_image_table_commitments[i]`
+/// belongs to slice `i` and `pre_image_table_commitments[i]` to slice `i + 1`.
+#[cfg(feature = "continuation")]
+fn check_image_table_chain(
+    post_image_table_commitments: &[G1Affine],
+    pre_image_table_commitments: &[G1Affine],
+) -> Result<()> {
+    for (index, (post, pre)) in post_image_table_commitments
+        .iter()
+        .zip(pre_image_table_commitments)
+        .enumerate()
+    {
+        if pre != post {
+            anyhow::bail!(
+                "The pre image of slice {} is inconsistent with the post image of slice {}.",
+                index + 1,
+                index
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Saves the proof load info by renaming a staged file so that a crash never
+/// leaves a partial one behind.
+fn save_proof_load_info(
+    proof_load_info: &ProofGenerationInfo,
+    name: &str,
+    output_dir: &Path,
+) -> Result<()> {
+    let staging_dir = output_dir.join(".loadinfo");
+    fs::create_dir_all(&staging_dir)?;
+
+    proof_load_info.save(&staging_dir);
+    fs::rename(
+        staging_dir.join(name_of_loadinfo(name)),
+        output_dir.join(name_of_loadinfo(name)),
+    )?;
+
+    Ok(())
+}
 
 #[derive(Serialize, Deserialize)]
-pub(crate) struct CircuitDataMd5 {
-    pub(crate) circuit_data_md5: String,
-    pub(crate) verifying_key_md5: String,
+pub(crate) struct CircuitDataSha256 {
+    pub(crate) circuit_data_sha256: String,
+    pub(crate) verifying_key_sha256: String,
 }
 
 #[cfg(not(feature = "continuation"))]
 #[derive(Serialize, Deserialize)]
 pub(crate) struct CircuitDataConfig {
-    pub(crate) finalized_circuit: CircuitDataMd5,
+    pub(crate) finalized_circuit: CircuitDataSha256,
 }
 
 #[cfg(feature = "continuation")]
 #[derive(Serialize, Deserialize)]
 pub(crate) struct CircuitDataConfig {
-    pub(crate) on_going_circuit: CircuitDataMd5,
-    pub(crate) finalized_circuit: CircuitDataMd5,
+    pub(crate) on_going_circuit: CircuitDataSha256,
+    pub(crate) finalized_circuit: CircuitDataSha256,
+}
+
+impl CircuitDataConfig {
+    #[cfg(not(feature = "continuation"))]
+    fn circuits(&self) -> Vec<(&'static str, &CircuitDataSha256)> {
+        vec![("finalized", &self.finalized_circuit)]
+    }
+
+    #[cfg(feature = "continuation")]
+    fn circuits(&self) -> Vec<(&'static str, &CircuitDataSha256)> {
+        vec![
+            ("ongoing", &self.on_going_circuit),
+            ("finalized", &self.finalized_circuit),
+        ]
+    }
+
+    /// The circuit of a slice, only the last slice is a finalized circuit.
+    #[cfg(not(feature = "continuation"))]
+    fn of(&self, _is_finalized_circuit: bool) -> &CircuitDataSha256 {
+        &self.finalized_circuit
+    }
+
+    #[cfg(feature = "continuation")]
+    fn of(&self, is_finalized_circuit: bool) -> &CircuitDataSha256 {
+        if is_finalized_circuit {
+            &self.finalized_circuit
+        } else {
+            &self.on_going_circuit
+        }
+    }
+}
+
+/// Format of the config file, `continuation` tells which `CircuitDataConfig`
+/// the config contains.
+#[derive(Serialize, Deserialize)]
+struct ConfigHeader {
+    version: u32,
+    continuation: bool,
+}
+
+#[derive(Serialize)]
+struct VersionedConfig<'a> {
+    #[serde(flatten)]
+    header: ConfigHeader,
+    #[serde(flatten)]
+    config: &'a Config,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -67,23 +253,25 @@ pub(crate) struct Config {
     pub(crate) is_uniform_circuit: bool,
     pub(crate) k: u32,
     pub(crate) params: PathBuf,
-    pub(crate) params_md5: String,
-    pub(crate) wasm_image_md5: Option<String>,
+    pub(crate) params_sha256: String,
+    pub(crate) wasm_image_sha256: Option<String>,
     pub(crate) circuit_datas: CircuitDataConfig,
 
     pub(crate) checksum: (String, String),
     pub(crate) phantom_functions: Vec<String>,
     pub(crate) host_mode: HostMode,
+    /// Host ops of the standard host env.
+    pub(crate) host_config: Option<HostEnvConfig>,
 
     pub(crate) scheme: Scheme,
 }
 
 impl Config {
     fn image_consistent_check(&self, wasm_image: &[u8]) -> anyhow::Result<()> {
-        if let Some(expected_wasm_image_md5) = &self.wasm_image_md5 {
-            let wasm_image_md5 = format!("{:x}", md5::compute(wasm_image));
+        if let Some(expected_wasm_image_sha256) = &self.wasm_image_sha256 {
+            let wasm_image_sha256 = sha256_hex(wasm_image);
 
-            if expected_wasm_image_md5 != &wasm_image_md5 {
+            if expected_wasm_image_sha256 != &wasm_image_sha256 {
                 anyhow::bail!(
                     "Wasm image is inconsistent with the one used to build the circuit. \
                         Maybe you have changed the Wasm image after setup the circuit?",
@@ -95,9 +283,9 @@ impl Config {
     }
 
     fn params_consistent_check(&self, params: &[u8]) -> anyhow::Result<()> {
-        let params_md5 = format!("{:x}", md5::compute(params));
+        let params_sha256 = sha256_hex(params);
 
-        if params_md5 != self.params_md5 {
+        if params_sha256 != self.params_sha256 {
             anyhow::bail!(
                 "Params is inconsistent with the one used to build the circuit. \
                     Maybe you have changed the params after setup the circuit?",
@@ -110,11 +298,11 @@ impl Config {
     fn veryfying_key_consistent_check(
         &self,
         verifying_key: &[u8],
-        expected_md5: &str,
+        expected_sha256: &str,
     ) -> anyhow::Result<()> {
-        let verifying_key_md5 = format!("{:x}", md5::compute(verifying_key));
+        let verifying_key_sha256 = sha256_hex(verifying_key);
 
-        if verifying_key_md5 != expected_md5 {
+        if verifying_key_sha256 != expected_sha256 {
             anyhow::bail!(
                 "Verifying key is inconsistent with the one used to build the circuit. \
                     Maybe you have changed the circuit data after setup the circuit?",
@@ -127,17 +315,150 @@ impl Config {
 
 impl Config {
     pub(crate) fn write(&self, fd: &mut File) -> anyhow::Result<()> {
-        fd.write_all(&bincode::serialize(self)?)?;
+        let config = VersionedConfig {
+            header: ConfigHeader {
+                version: CONFIG_VERSION,
+                continuation: cfg!(feature = "continuation"),
+            },
+            config: self,
+        };
+
+        serde_json::to_writer_pretty(fd, &config)?;
 
         Ok(())
     }
 
+    /// Parses the header of a versioned config and checks that it was set up
+    /// with the same continuation feature, `None` if the config is not JSON.
+    fn read_header(buf: &[u8]) -> anyhow::Result<Option<(ConfigHeader, serde_json::Value)>> {
+        // Configs before versioning (version 0) were written as bincode.
+        let config = match serde_json::from_slice::<serde_json::Value>(buf) {
+            Ok(config) => config,
+            Err(_) => return Ok(None),
+        };
+
+        let header: ConfigHeader = serde_json::from_value(config.clone())?;
+
+        if header.continuation != cfg!(feature = "continuation") {
+            anyhow::bail!(
+                "Config was set up with continuation={} but this build has continuation={}.",
+                header.continuation,
+                cfg!(feature = "continuation"),
+            );
+        }
+
+        Ok(Some((header, config)))
+    }
+
     pub(crate) fn read(fd: &mut File) -> anyhow::Result<Self> {
         let mut buf = Vec::new();
         fd.read_to_end(&mut buf)?;
-        let config = bincode::deserialize(&buf)?;
 
-        Ok(config)
+        let (header, config) = match Config::read_header(&buf)? {
+            Some(header_and_config) => header_and_config,
+            None => anyhow::bail!(
+                "Config is not a versioned config. Configs of version 0 record MD5 digests of \
+                    the artifacts which are no longer accepted, please run the migrate \
+                    subcommand to upgrade it."
+            ),
+        };
+
+        match header.version {
+            CONFIG_VERSION => {
+                let config: Config = serde_json::from_value(config)?;
+
+                if let Some(host_config) = &config.host_config {
+                    check_unproven_ops(host_config)?;
+                }
+
+                Ok(config)
+            }
+            version => anyhow::bail!(
+                "Config version {} is not supported, the latest version is {}.",
+                version,
+                CONFIG_VERSION
+            ),
+        }
+    }
+
+    /// Upgrades the unversioned config after checking the artifacts against
+    /// the recorded digests, the old config is kept with the version as a
+    /// suffix.
+    pub(crate) fn migrate(
+        params_dir: &Path,
+        name: &str,
+        wasm_image: Option<&Path>,
+    ) -> anyhow::Result<()> {
+        let config_path = params_dir.join(name_of_config(name));
+        let buf = fs::read(&config_path)?;
+
+        let config = match Config::read_header(&buf)? {
+            None => legacy::ConfigV0::read(&buf)?,
+            Some((header, _)) if header.version == CONFIG_VERSION => {
+                println!("Config is already version {}.", CONFIG_VERSION);
+
+                return Ok(());
+            }
+            Some((header, _)) => anyhow::bail!(
+                "Config version {} is not supported, the latest version is {}.",
+                header.version,
+                CONFIG_VERSION
+            ),
+        };
+
+        let config = config.migrate(params_dir, wasm_image)?;
+
+        fs::rename(
+            &config_path,
+            params_dir.join(format!("{}.v0", name_of_config(name))),
+        )?;
+        config.write(&mut File::create(&config_path)?)?;
+
+        println!(
+            "{} {:?}",
+            style(format!(
+                "The configuration is migrated to version {} at",
+                CONFIG_VERSION
+            ))
+            .green()
+            .bold()
+            .dim(),
+            config_path
+        );
+
+        Ok(())
+    }
+
+    /// Prints what the params directory was built with.
+    pub(crate) fn info(&self) {
+        println!("Name: {}", self.name);
+        println!("K: {}", self.k);
+        println!("Host mode: {:?}", self.host_mode);
+        if let Some(host_config) = &self.host_config {
+            println!("Host ops: {:?}", host_config.ops);
+            println!(
+                "Merkle tree height: {}",
+                usize::from(host_config.merkle_tree_height)
+            );
+            if host_config.allow_unproven {
+                println!("Unproven host ops are allowed, proofs do not constrain their results");
+            }
+        }
+        println!("Scheme: {:?}", self.scheme);
+        println!("Uniform circuit: {}", self.is_uniform_circuit);
+        println!("Phantom functions: {:?}", self.phantom_functions);
+        println!("Checksum: ({}, {})", self.checksum.0, self.checksum.1);
+        println!("Params: {:?} (sha256 {})", self.params, self.params_sha256);
+        println!(
+            "Wasm image sha256: {}",
+            self.wasm_image_sha256.as_deref().unwrap_or("none")
+        );
+        for (name, circuit_data) in self.circuit_datas.circuits() {
+            println!(
+                "Circuit data ({}): sha256 {}, verifying key sha256 {}",
+                name, circuit_data.circuit_data_sha256, circuit_data.verifying_key_sha256
+            );
+        }
     }
 }
 
@@ -148,11 +469,11 @@ impl Config {
 
         self.image_consistent_check(&buf)?;
 
-        ZkWasmLoader::parse_module(&buf)
+        ZkWasmLoader::parse_module(&buf, self.k, &self.phantom_functions)
     }
 
     fn read_params(&self, params_dir: &Path) -> anyhow::Result<Params<G1Affine>> {
-        let path = params_dir.join(name_of_params(self.k));
+        let path = params_dir.join(name_of_params(self.k, &self.params_sha256));
 
         let mut buf = Vec::new();
         File::open(path)?.read_to_end(&mut buf)?;
@@ -166,26 +487,120 @@ impl Config {
 
     fn read_circuit_data(
         &self,
-        path: &PathBuf,
-        expected_md5: &str,
+        params_dir: &Path,
+        expected_sha256: &str,
     ) -> anyhow::Result<CircuitData<G1Affine>> {
         let mut buf = Vec::new();
-        File::open(path)?.read_to_end(&mut buf)?;
+        File::open(params_dir.join(name_of_circuit_data(expected_sha256)))?
+            .read_to_end(&mut buf)?;
 
-        let circuit_data_md5 = format!("{:x}", md5::compute(&buf));
+        let circuit_data_sha256 = sha256_hex(&buf);
 
-        if circuit_data_md5 != expected_md5 {
+        if circuit_data_sha256 != expected_sha256 {
             anyhow::bail!(
                 "Circuit data is inconsistent with the one used to build the circuit. \
                     Maybe you have changed the circuit data after setup the circuit?",
             );
         }
 
-        let circuit_data = CircuitData::<G1Affine>::read(&mut File::open(path)?)?;
+        let circuit_data = CircuitData::<G1Affine>::read(&mut Cursor::new(&mut buf))?;
 
         Ok(circuit_data)
     }
 
+    /// Whether a proof of the last run is a complete proof of the slice, its
+    /// transcript is named by its digest and its instance file holds `instance`.
+    fn is_proof_piece_complete(
+        &self,
+        proof_piece_info: &ProofPieceInfo,
+        output_dir: &Path,
+        index: usize,
+        circuit_data_sha256: &str,
+        instance_size: usize,
+        instance: &[u8],
+    ) -> bool {
+        let instance_is_expected = fs::read(output_dir.join(&proof_piece_info.instance))
+            .map_or(false, |buf| buf == instance);
+
+        let transcript_is_intact =
+            fs::read(output_dir.join(&proof_piece_info.transcript)).map_or(false, |transcript| {
+                proof_piece_info.transcript
+                    == name_of_transcript(&self.name, index, &sha256_hex(&transcript))
+            });
+
+        proof_piece_info.circuit == name_of_circuit_data(circuit_data_sha256)
+            && proof_piece_info.instance_size == instance_size as u32
+            && instance_is_expected
+            && transcript_is_intact
+    }
+
+    /// Whether a proof of the last run can be reused for the slice. With
+    /// continuation its pre image must be the post image of the last proof and
+    /// its post image is recovered from its transcript.
+    fn resume_proof_piece(
+        &self,
+        proof_piece_info: &ProofPieceInfo,
+        _params_dir: &Path,
+        output_dir: &Path,
+        index: usize,
+        is_finalized_circuit: bool,
+        instance: &[u8],
+        instances: &[Fr],
+        _circuit_datas: &mut HashMap<String, CircuitData<G1Affine>>,
+        _last_post_image_table_commitment: &mut Option<(String, String)>,
+    ) -> anyhow::Result<bool> {
+        let circuit_data_sha256 = &self
+            .circuit_datas
+            .of(is_finalized_circuit)
+            .circuit_data_sha256;
+
+        if !self.is_proof_piece_complete(
+            proof_piece_info,
+            output_dir,
+            index,
+            circuit_data_sha256,
+            instances.len(),
+            instance,
+        ) {
+            return Ok(false);
+        }
+
+        #[cfg(feature = "continuation")]
+        {
+            use crate::utils::get_named_advice_commitment;
+            use delphinus_zkwasm::circuits::image_table::IMAGE_COL_NAME;
+            use delphinus_zkwasm::circuits::post_image_table::POST_IMAGE_TABLE;
+
+            use std::collections::hash_map::Entry;
+
+            let circuit_data = match _circuit_datas.entry(circuit_data_sha256.clone()) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    entry.insert(self.read_circuit_data(_params_dir, circuit_data_sha256)?)
+                }
+            };
+            let vkey = circuit_data.get_vkey();
+
+            let proof = fs::read(output_dir.join(&proof_piece_info.transcript))?;
+
+            let pre_image_table_msm = get_named_advice_commitment(vkey, &proof, IMAGE_COL_NAME);
+            if let Some(last_post_image_table_msm) = _last_post_image_table_commitment.as_ref() {
+                if pre_image_table_msm.x.to_string() != last_post_image_table_msm.0
+                    || pre_image_table_msm.y.to_string() != last_post_image_table_msm.1
+                {
+                    return Ok(false);
+                }
+            }
+
+            let post_image_table_msm = get_named_advice_commitment(vkey, &proof, POST_IMAGE_TABLE);
+            *_last_post_image_table_commitment = Some((
+                post_image_table_msm.x.to_string(),
+                post_image_table_msm.y.to_string(),
+            ));
+        }
+        Ok(true)
+    }
+
     pub(crate) fn dry_run(
         self,
         env_builder: &dyn HostEnvBuilder,
@@ -193,6 +608,7 @@ impl Config {
         output_dir: &Path,
         arg: ExecutionArg,
         context_output_filename: Option<String>,
+        state_diff_filename: Option<String>,
         instruction_limit: Option<usize>,
     ) -> Result<()> {
         let module = self.read_wasm_image(wasm_image)?;
@@ -236,6 +652,93 @@ impl Config {
             }
         }
 
+        write_state_diffs(&result, output_dir, state_diff_filename)?;
+
+        Ok(())
+    }
+
+    pub(crate) fn trace(
+        self,
+        env_builder: &dyn HostEnvBuilder,
+        wasm_image: &Path,
+        output_dir: &Path,
+        arg: ExecutionArg,
+        context_output_filename: Option<String>,
+        state_diff_filename: Option<String>,
+        instruction_limit: Option<usize>,
+        format: TraceFormat,
+        filter: TraceFilter,
+    ) -> Result<()> {
+        let module = self.read_wasm_image(wasm_image)?;
+
+        let env = env_builder.create_env(arg);
+
+        let trace_path =
+            output_dir.join(name_of_trace(&self.name, format == TraceFormat::JsonLines));
+        let tracer = TracerPlugin::new(
+            Box::new(BufWriter::new(File::create(&trace_path)?)),
+            format,
+            filter,
+        );
+
+        let mut monitor = TableMonitor::new(
+            self.k,
+            InMemoryBackendBuilder,
+            env_builder.create_flush_strategy(),
+            &self.phantom_functions,
+            &env,
+            ExecutionLimits {
+                instructions: instruction_limit,
+                ..ExecutionLimits::default()
+            },
+        )
+        .with_tracer(tracer);
+
+        let result = {
+            let loader = ZkWasmLoader::new(self.k, env)?;
+
+            let runner = loader.compile(&module, &mut monitor)?;
+
+            println!(
+                "{} Executing and writing trace to {:?}...",
+                style("[1/2]").bold().dim(),
+                trace_path
+            );
+            let result = loader.run(runner, &mut monitor);
+
+            // The trace of a failed execution is kept for debugging.
+            monitor.take_tracer().unwrap().finalize()?;
+            let result = result?;
+
+            println!("total guest instructions used {:?}", result.guest_statics);
+            println!("total host api used {:?}", result.host_statics);
+
+            result
+        };
+
+        {
+            if let Some(context_output_filename) = context_output_filename {
+                let context_output_path = output_dir.join(context_output_filename);
+
+                println!(
+                    "{} Write context output to file {:?}...",
+                    style("[2/2]").bold().dim(),
+                    context_output_path
+                );
+
+                result
+                    .context_outputs
+                    .write(&mut File::create(&context_output_path)?)?;
+            } else {
+                println!(
+                    "{} Context output is not specified. Skip writing context output...",
+                    style("[2/2]").bold().dim()
+                );
+            }
+        }
+
+        write_state_diffs(&result, output_dir, state_diff_filename)?;
+
         Ok(())
     }
 
@@ -248,12 +751,14 @@ impl Config {
         output_dir: &Path,
         arg: ExecutionArg,
         context_output_filename: Option<String>,
+        state_diff_filename: Option<String>,
         mock_test: bool,
         skip: usize,
+        resume: bool,
+        slice_range: Option<Range<usize>>,
         padding: Option<usize>,
+        limits: ExecutionLimits,
     ) -> anyhow::Result<()> {
-        let mut cached_proving_key = None;
-
         println!("{} Load image...", style("[1/8]").bold().dim(),);
         let module = self.read_wasm_image(wasm_image)?;
 
@@ -268,6 +773,7 @@ impl Config {
             env_builder.create_flush_strategy(),
             &self.phantom_functions,
             &env,
+            limits,
         );
 
         let (result, tables) = {
@@ -304,6 +810,8 @@ impl Config {
             }
         }
 
+        write_state_diffs(&result, output_dir, state_diff_filename)?;
+
         {
             let dir = output_dir.join("traces");
 
@@ -320,119 +828,407 @@ impl Config {
             )?;
         }
 
-        println!("{} Build circuit(s)...", style("[6/8]").bold().dim(),);
-        let instances = result
-            .public_inputs_and_outputs
-            .iter()
-            .map(|v| (*v).into())
-            .collect::<Vec<_>>();
-
-        println!("{} Creating proof(s)...", style("[7/8]").bold().dim(),);
+        self.prove_slices(
+            &params,
+            tables,
+            &result.public_inputs_and_outputs,
+            params_dir,
+            output_dir,
+            mock_test,
+            skip,
+            resume,
+            slice_range,
+            padding,
+            8,
+        )
+    }
 
-        let mut proof_load_info =
-            ProofGenerationInfo::new(&self.name, self.k as usize, HashType::Poseidon);
+    /// Executes the image and writes everything needed to prove it to the
+    /// `traces` directory of `output_dir`, see `prove_from_traces`.
+    pub(crate) fn witness(
+        self,
+        env_builder: &dyn HostEnvBuilder,
+        wasm_image: &Path,
+        output_dir: &Path,
+        arg: ExecutionArg,
+        context_output_filename: Option<String>,
+        state_diff_filename: Option<String>,
+        limits: ExecutionLimits,
+    ) -> anyhow::Result<()> {
+        println!("{} Load image...", style("[1/4]").bold().dim(),);
+        let module = self.read_wasm_image(wasm_image)?;
+        let wasm_image_sha256 = sha256_hex(&fs::read(wasm_image)?);
 
-        let progress_bar = ProgressBar::new(if let Some(padding) = padding {
-            usize::max(tables.execution_tables.slice_backend.len(), padding) as u64
-        } else {
-            tables.execution_tables.slice_backend.len() as u64
-        });
+        let trace_dir = output_dir.join("traces");
+        fs::create_dir_all(&trace_dir)?;
 
-        if skip != 0 {
-            progress_bar.inc(skip as u64);
-            println!("skip first {} slice(s)", skip);
-        }
+        let env = env_builder.create_env(arg);
 
-        #[cfg(feature = "continuation")]
-        let mut last_post_image_table_commitment: Option<(String, String)> = None;
+        let mut monitor = TableMonitor::new(
+            self.k,
+            FileBackendBuilder::new(self.name.clone(), trace_dir.clone()),
+            env_builder.create_flush_strategy(),
+            &self.phantom_functions,
+            &env,
+            limits,
+        );
 
-        let mut slices = Slices::new(self.k, tables, padding)?
-            .into_iter()
-            .enumerate()
-            .skip(skip)
-            .peekable();
-        while let Some((index, circuit)) = slices.next() {
-            let _is_finalized_circuit = slices.peek().is_none();
+        let (result, tables) = {
+            println!(
+                "{} Executing and writing traces to {:?}...",
+                style("[2/4]").bold().dim(),
+                trace_dir
+            );
 
-            if mock_test {
-                println!("mock test for slice {}...", index);
-                circuit.mock_test(instances.clone())?;
-            }
+            let loader = ZkWasmLoader::new(self.k, env)?;
+            let runner = loader.compile(&module, &mut monitor)?;
+            let result = loader.run(runner, &mut monitor)?;
 
-            let mut cached_proving_key_or_read =
-                |file_name: &str, is_last_circuit, expected_md5| -> anyhow::Result<()> {
-                    if let Some((name, _)) = cached_proving_key.as_ref() {
-                        if name == file_name {
-                            return Ok(());
-                        }
-                    }
+            println!("total guest instructions used {:?}", result.guest_statics);
+            println!("total host api used {:?}", result.host_statics);
 
-                    let pk = self
-                        .read_circuit_data(
-                            &params_dir.join(name_of_circuit_data(&self.name, is_last_circuit)),
-                            expected_md5,
-                        )?
-                        .into_proving_key(&params);
+            (result, monitor.into_tables())
+        };
 
-                    cached_proving_key = Some((file_name.to_string(), pk));
+        {
+            if let Some(context_output_filename) = context_output_filename {
+                let context_output_path = output_dir.join(context_output_filename);
 
-                    Ok(())
-                };
+                println!(
+                    "{} Write context output to file {:?}...",
+                    style("[3/4]").bold().dim(),
+                    context_output_path
+                );
 
-            #[cfg(feature = "continuation")]
-            if _is_finalized_circuit {
-                cached_proving_key_or_read(
-                    &self.circuit_datas.finalized_circuit.circuit_data_md5,
-                    true,
-                    &self.circuit_datas.finalized_circuit.circuit_data_md5,
-                )?
+                result
+                    .context_outputs
+                    .write(&mut File::create(&context_output_path)?)?;
             } else {
-                cached_proving_key_or_read(
-                    &self.circuit_datas.on_going_circuit.circuit_data_md5,
-                    false,
-                    &self.circuit_datas.on_going_circuit.circuit_data_md5,
-                )?
-            };
-
-            #[cfg(not(feature = "continuation"))]
-            cached_proving_key_or_read(
-                &self.circuit_datas.finalized_circuit.circuit_data_md5,
-                true,
-                &self.circuit_datas.finalized_circuit.circuit_data_md5,
-            )?;
+                println!(
+                    "{} Context output is not specified. Skip writing context output...",
+                    style("[3/4]").bold().dim()
+                );
+            }
+        }
 
-            let circuit_data_name = name_of_circuit_data(&self.name, _is_finalized_circuit);
+        write_state_diffs(&result, output_dir, state_diff_filename)?;
 
-            let proof_piece_info = ProofPieceInfo {
-                circuit: circuit_data_name,
-                instance_size: instances.len() as u32,
-                witness: name_of_witness(&self.name, index),
-                instance: name_of_instance(&self.name, index),
-                transcript: name_of_transcript(&self.name, index),
-            };
+        {
+            let traced_tables_path = trace_dir.join(name_of_traced_tables(&self.name));
+
+            println!(
+                "{} Writing {} slice(s) to {:?}...",
+                style("[4/4]").bold().dim(),
+                tables.execution_tables.slice_backend.len(),
+                traced_tables_path
+            );
+
+            tables.write(
+                &trace_dir,
+                |index| name_of_frame_table_slice(&self.name, index),
+                |index| name_of_etable_slice(&self.name, index),
+                |index| name_of_external_host_call_table_slice(&self.name, index),
+            )?;
+
+            TracedTables::new(wasm_image_sha256, result.public_inputs_and_outputs, tables)?
+                .write(&traced_tables_path)?;
+        }
+
+        Ok(())
+    }
+
+    /// Proves the slices written by `witness` to `trace_dir`, neither the
+    /// image nor a host environment is needed.
+    pub(crate) fn prove_from_traces(
+        self,
+        trace_dir: &Path,
+        params_dir: &Path,
+        output_dir: &Path,
+        mock_test: bool,
+        skip: usize,
+        resume: bool,
+        slice_range: Option<Range<usize>>,
+        padding: Option<usize>,
+    ) -> anyhow::Result<()> {
+        let traced_tables_path = trace_dir.join(name_of_traced_tables(&self.name));
+        println!(
+            "{} Load traces from {:?}...",
+            style("[1/5]").bold().dim(),
+            traced_tables_path
+        );
+        let traced_tables = TracedTables::read(&traced_tables_path).with_context(|| {
+            format!(
+                "Failed to read {:?}. Maybe the traces were not written by witness?",
+                traced_tables_path
+            )
+        })?;
+
+        if let Some(expected_wasm_image_sha256) = &self.wasm_image_sha256 {
+            if expected_wasm_image_sha256 != &traced_tables.wasm_image_sha256 {
+                anyhow::bail!(
+                    "Traces are executed from a Wasm image which is inconsistent with the one \
+                        used to build the circuit.",
+                );
+            }
+        }
+
+        let instances = traced_tables.instances.clone();
+        let tables = traced_tables.into_tables(&self.name, trace_dir)?;
+
+        println!("{} Load params...", style("[2/5]").bold().dim(),);
+        let params = self.read_params(params_dir)?;
+
+        self.prove_slices(
+            &params,
+            tables,
+            &instances,
+            params_dir,
+            output_dir,
+            mock_test,
+            skip,
+            resume,
+            slice_range,
+            padding,
+            5,
+        )
+    }
+
+    /// Creates proofs of the slices of `tables`, the last three of `steps`
+    /// steps are printed.
+    fn prove_slices<B: SliceBackend>(
+        &self,
+        params: &Params<G1Affine>,
+        tables: Tables<B>,
+        public_inputs_and_outputs: &[u64],
+        params_dir: &Path,
+        output_dir: &Path,
+        mock_test: bool,
+        skip: usize,
+        resume: bool,
+        slice_range: Option<Range<usize>>,
+        padding: Option<usize>,
+        steps: usize,
+    ) -> anyhow::Result<()> {
+        let step = |step: usize| style(format!("[{}/{}]", step, steps)).bold().dim();
+
+        let mut cached_proving_key = None;
+
+        println!("{} Build circuit(s)...", step(steps - 2));
+        let instances = public_inputs_and_outputs
+            .iter()
+            .map(|v| (*v).into())
+            .collect::<Vec<Fr>>();
+        let instance = instance_file_content(&instances);
+
+        println!("{} Creating proof(s)...", step(steps - 1));
+
+        let mut proof_load_info =
+            ProofGenerationInfo::new(&self.name, self.k as usize, HashType::Poseidon);
+
+        let slices_len = if let Some(padding) = padding {
+            usize::max(tables.execution_tables.slice_backend.len(), padding)
+        } else {
+            tables.execution_tables.slice_backend.len()
+        };
+        let progress_bar = ProgressBar::new(slices_len as u64);
+
+        // Slices out of the range are proved by other provers and merged by merge-proofs.
+        let skip = slice_range
+            .as_ref()
+            .map_or(skip, |slice_range| usize::max(skip, slice_range.start));
+
+        if skip != 0 {
+            progress_bar.inc(skip as u64);
+            println!("skip first {} slice(s)", skip);
+        }
+
+        // Proofs of the last run keyed by their slice, a complete one is reused
+        // instead of proving its slice again.
+        let mut resumed_proofs = BTreeMap::new();
+        if resume {
+            let proof_load_info_path = output_dir.join(name_of_loadinfo(&self.name));
+
+            if proof_load_info_path.exists() {
+                resumed_proofs = ProofGenerationInfo::load(&proof_load_info_path)
+                    .proofs
+                    .into_iter()
+                    .filter_map(|proof_piece_info| {
+                        index_of_instance(&self.name, &proof_piece_info.instance)
+                            .map(|index| (index, proof_piece_info))
+                    })
+                    .collect();
+
+                println!("resume from {} proof(s)", resumed_proofs.len());
+            }
+        }
+
+        // Resumed proofs of the slices out of the proved range are kept in the
+        // proof load info, the ones after it are saved behind the proved slices.
+        let range_end = slice_range.as_ref().map_or(slices_len, |slice_range| {
+            usize::min(slice_range.end, slices_len)
+        });
+        let mut later_proofs = resumed_proofs
+            .split_off(&range_end)
+            .into_iter()
+            .filter(|(index, _)| *index < slices_len)
+            .map(|(_, proof_piece_info)| proof_piece_info)
+            .collect::<Vec<_>>();
+        let in_range_proofs = resumed_proofs.split_off(&skip);
+        let earlier_proofs = std::mem::replace(&mut resumed_proofs, in_range_proofs);
+
+        let mut last_post_image_table_commitment: Option<(String, String)> = None;
+
+        // The image table commitments are only chained between adjacent slices.
+        let follows_last_proof = |proof_load_info: &ProofGenerationInfo, index: usize| {
+            index > 0
+                && proof_load_info.proofs.last().and_then(|proof_piece_info| {
+                    index_of_instance(&self.name, &proof_piece_info.instance)
+                }) == Some(index - 1)
+        };
+
+        let mut resumed_circuit_datas = HashMap::new();
+        for (index, proof_piece_info) in earlier_proofs {
+            if !follows_last_proof(&proof_load_info, index) {
+                last_post_image_table_commitment = None;
+            }
+
+            if !self.resume_proof_piece(
+                &proof_piece_info,
+                params_dir,
+                output_dir,
+                index,
+                index + 1 == slices_len,
+                &instance,
+                &instances,
+                &mut resumed_circuit_datas,
+                &mut last_post_image_table_commitment,
+            )? {
+                println!(
+                    "proof of slice {} is incomplete and dropped, it is before the proved range",
+                    index
+                );
+                continue;
+            }
+
+            proof_load_info.append_single_proof(proof_piece_info);
+        }
+        if !follows_last_proof(&proof_load_info, skip) {
+            last_post_image_table_commitment = None;
+        }
+
+        // Circuits of the proofs resumed since the last created proof, they are
+        // proved again if the next created proof does not chain with them.
+        let mut resumed_circuits: Vec<(usize, ZkWasmCircuit<Fr>, bool)> = Vec::new();
+        let mut _commitment_before_resumed_circuits = None;
+        let mut reproved_slices = VecDeque::new();
+
+        let mut slices = Slices::new(self.k, tables, padding)?
+            .into_iter()
+            .enumerate()
+            .skip(skip)
+            .peekable();
+        while let Some((index, circuit, _is_finalized_circuit)) =
+            reproved_slices.pop_front().or_else(|| {
+                slices
+                    .next()
+                    .map(|(index, circuit)| (index, circuit, slices.peek().is_none()))
+            })
+        {
+            if slice_range
+                .as_ref()
+                .map_or(false, |slice_range| index >= slice_range.end)
+            {
+                break;
+            }
+
+            let circuit_data_sha256 = &self
+                .circuit_datas
+                .of(_is_finalized_circuit)
+                .circuit_data_sha256;
+
+            if let Some(proof_piece_info) = resumed_proofs.remove(&index) {
+                let commitment_before = last_post_image_table_commitment.clone();
+
+                if self.resume_proof_piece(
+                    &proof_piece_info,
+                    params_dir,
+                    output_dir,
+                    index,
+                    _is_finalized_circuit,
+                    &instance,
+                    &instances,
+                    &mut resumed_circuit_datas,
+                    &mut last_post_image_table_commitment,
+                )? {
+                    if resumed_circuits.is_empty() {
+                        _commitment_before_resumed_circuits = commitment_before;
+                    }
+                    resumed_circuits.push((index, circuit, _is_finalized_circuit));
+
+                    proof_load_info.append_single_proof(proof_piece_info);
+                    save_with_later_proofs(
+                        &mut proof_load_info,
+                        &mut later_proofs,
+                        &self.name,
+                        output_dir,
+                    )?;
+
+                    progress_bar.inc(1);
+                    continue;
+                }
+            }
+
+            if mock_test {
+                println!("mock test for slice {}...", index);
+                circuit.mock_test(instances.clone())?;
+            }
+
+            if cached_proving_key
+                .as_ref()
+                .map_or(true, |(sha256, _)| sha256 != circuit_data_sha256)
+            {
+                let pk = self
+                    .read_circuit_data(params_dir, circuit_data_sha256)?
+                    .into_proving_key(params);
+
+                cached_proving_key = Some((circuit_data_sha256.clone(), pk));
+            }
+
+            let mut proof_piece_info = ProofPieceInfo {
+                circuit: name_of_circuit_data(circuit_data_sha256),
+                instance_size: instances.len() as u32,
+                witness: name_of_witness(&self.name, index),
+                instance: name_of_instance(&self.name, index),
+                // named by its digest once the proof is created
+                transcript: String::new(),
+            };
 
             let pkey = &cached_proving_key.as_ref().unwrap().1;
 
-            let proof = match circuit {
+            let proof = match &circuit {
                 ZkWasmCircuit::Ongoing(circuit) => proof_piece_info.create_proof::<Bn256, _>(
-                    &circuit,
+                    circuit,
                     &vec![instances.clone()],
-                    &params,
+                    params,
                     pkey,
                     proof_load_info.hashtype,
                     self.scheme.into(),
                 ),
                 ZkWasmCircuit::LastSliceCircuit(circuit) => proof_piece_info
                     .create_proof::<Bn256, _>(
-                        &circuit,
+                        circuit,
                         &vec![instances.clone()],
-                        &params,
+                        params,
                         pkey,
                         proof_load_info.hashtype,
                         self.scheme.into(),
                     ),
             };
 
+            proof_piece_info.transcript =
+                name_of_transcript(&self.name, index, &sha256_hex(&proof));
+            proof_piece_info.save_proof_data(&vec![instances.clone()], &proof, output_dir);
+
             #[cfg(feature = "continuation")]
             {
                 use crate::utils::get_named_advice_commitment;
@@ -445,14 +1241,40 @@ impl Config {
 
                 let last_post_image_table_msm = last_post_image_table_commitment.take();
                 if let Some(last_post_image_table_msm) = last_post_image_table_msm {
-                    assert_eq!(
-                        pre_image_table_msm.x.to_string(),
-                        last_post_image_table_msm.0
-                    );
-                    assert_eq!(
-                        pre_image_table_msm.y.to_string(),
-                        last_post_image_table_msm.1
-                    );
+                    if pre_image_table_msm.x.to_string() != last_post_image_table_msm.0
+                        || pre_image_table_msm.y.to_string() != last_post_image_table_msm.1
+                    {
+                        let Some(&(first, ..)) = resumed_circuits.first() else {
+                            anyhow::bail!(
+                                "The proof of slice {} does not follow the proof of slice {}.",
+                                index,
+                                index - 1
+                            );
+                        };
+
+                        println!(
+                            "resumed proof(s) of slice {} to {} do not chain with slice {}, \
+                                they are dropped and proved again",
+                            first,
+                            index - 1,
+                            index
+                        );
+
+                        // The created proof is resumed once its previous slices are proved again.
+                        let dropped = resumed_circuits.len();
+                        proof_load_info
+                            .proofs
+                            .truncate(proof_load_info.proofs.len() - dropped);
+                        resumed_proofs.insert(index, proof_piece_info);
+                        last_post_image_table_commitment =
+                            _commitment_before_resumed_circuits.take();
+
+                        reproved_slices.extend(resumed_circuits.drain(..));
+                        reproved_slices.push_back((index, circuit, _is_finalized_circuit));
+                        progress_bar.set_position(progress_bar.position() - dropped as u64);
+
+                        continue;
+                    }
                 }
 
                 let post_image_table_msm =
@@ -464,9 +1286,15 @@ impl Config {
                 ));
             }
 
-            proof_piece_info.save_proof_data(&vec![instances.clone()], &proof, output_dir);
+            resumed_circuits.clear();
 
             proof_load_info.append_single_proof(proof_piece_info);
+            save_with_later_proofs(
+                &mut proof_load_info,
+                &mut later_proofs,
+                &self.name,
+                output_dir,
+            )?;
 
             progress_bar.inc(1);
         }
@@ -476,15 +1304,263 @@ impl Config {
             let proof_load_info_path = output_dir.join(name_of_loadinfo(&self.name));
             println!(
                 "{} Saving proof load info to {:?}...",
-                style("[8/8]").bold().dim(),
+                step(steps),
                 proof_load_info_path
             );
-            proof_load_info.save(proof_load_info_path.parent().unwrap());
+            save_with_later_proofs(
+                &mut proof_load_info,
+                &mut later_proofs,
+                &self.name,
+                output_dir,
+            )?;
         }
 
         Ok(())
     }
 
+    /// Merges the proofs of slice ranges proved by `prove --slice-range` into
+    /// one proof load info in `output_dir`, `slices` is the number of slices
+    /// of the execution.
+    pub(crate) fn merge_proofs(
+        self,
+        _params_dir: &Path,
+        proof_dirs: &[PathBuf],
+        output_dir: &Path,
+        slices: Option<usize>,
+    ) -> anyhow::Result<()> {
+        println!(
+            "{} Reading proofs from {:?}...",
+            style("[1/3]").bold().dim(),
+            proof_dirs
+        );
+
+        let mut proofs = Vec::new();
+        for proof_dir in proof_dirs {
+            let proof_load_info_path = proof_dir.join(name_of_loadinfo(&self.name));
+            if !proof_load_info_path.exists() {
+                anyhow::bail!("No proof load info is found at {:?}.", proof_load_info_path);
+            }
+
+            for proof_piece_info in ProofGenerationInfo::load(&proof_load_info_path).proofs {
+                let index = index_of_instance(&self.name, &proof_piece_info.instance)
+                    .with_context(|| {
+                        format!(
+                            "Instance {} in {:?} is not an instance of {}.",
+                            proof_piece_info.instance, proof_dir, self.name
+                        )
+                    })?;
+
+                proofs.push((index, proof_dir, proof_piece_info));
+            }
+        }
+        if proofs.is_empty() {
+            anyhow::bail!("No proof is found in {:?}.", proof_dirs);
+        }
+        proofs.sort_by_key(|(index, _, _)| *index);
+
+        // The slices are contiguous from 0 and the last one is proved by the
+        // finalized circuit.
+        for (expected_index, (index, proof_dir, _)) in proofs.iter().enumerate() {
+            if *index < expected_index {
+                anyhow::bail!(
+                    "Slice {} is proved more than once, the proof in {:?} is a duplicate.",
+                    index,
+                    proof_dir
+                );
+            }
+            if *index > expected_index {
+                anyhow::bail!(
+                    "Slices {}..{} are missing, they are not proved by any of the proofs.",
+                    expected_index,
+                    index
+                );
+            }
+        }
+        // The last slice is told by the number of slices if it is given, or
+        // by the finalized circuit otherwise.
+        if let Some((index, proof_dir, proof_piece_info)) = proofs.last() {
+            match slices {
+                Some(slices) if index + 1 < slices => anyhow::bail!(
+                    "Slices {}..{} are missing, they are not proved by any of the proofs.",
+                    index + 1,
+                    slices
+                ),
+                Some(slices) if index + 1 > slices => anyhow::bail!(
+                    "The proof of slice {} in {:?} is out of the {} slice(s).",
+                    index,
+                    proof_dir,
+                    slices
+                ),
+                Some(_) => (),
+                // The ongoing and finalized circuits are not told apart.
+                None if self.is_uniform_circuit => anyhow::bail!(
+                    "Missing slices after slice {} can not be detected with a uniform circuit, \
+                        please provide the number of slices by --slices.",
+                    index
+                ),
+                None => {
+                    if proof_piece_info.circuit
+                        != name_of_circuit_data(&self.circuit_datas.of(true).circuit_data_sha256)
+                    {
+                        anyhow::bail!(
+                            "Slices {}.. are missing, the proof of slice {} in {:?} is not a \
+                                proof of the finalized circuit.",
+                            index + 1,
+                            index,
+                            proof_dir
+                        );
+                    }
+                }
+            }
+        }
+
+        println!(
+            "{} Checking {} proof(s)...",
+            style("[2/3]").bold().dim(),
+            proofs.len()
+        );
+
+        // All slices are proved with the same instances.
+        let (instance_size, instance) = match proofs.first() {
+            Some((_, proof_dir, proof_piece_info)) => (
+                proof_piece_info.instance_size as usize,
+                fs::read(proof_dir.join(&proof_piece_info.instance))?,
+            ),
+            None => (0, vec![]),
+        };
+
+        let mut transcripts = Vec::with_capacity(proofs.len());
+        for (index, proof_dir, proof_piece_info) in &proofs {
+            let is_finalized_circuit = *index == proofs.len() - 1;
+
+            if !self.is_proof_piece_complete(
+                proof_piece_info,
+                proof_dir,
+                *index,
+                &self
+                    .circuit_datas
+                    .of(is_finalized_circuit)
+                    .circuit_data_sha256,
+                instance_size,
+                &instance,
+            ) {
+                anyhow::bail!(
+                    "Proof of slice {} in {:?} is incomplete, not a proof of the {} circuit \
+                        or proved with other instances.",
+                    index,
+                    proof_dir,
+                    if is_finalized_circuit {
+                        "finalized"
+                    } else {
+                        "ongoing"
+                    }
+                );
+            }
+
+            transcripts.push(fs::read(proof_dir.join(&proof_piece_info.transcript))?);
+        }
+
+        // checks pre image col of each proof equals to the post image col of the last one
+        #[cfg(feature = "continuation")]
+        if transcripts.len() > 1 {
+            use crate::utils::get_named_advice_commitment;
+            use delphinus_zkwasm::circuits::image_table::IMAGE_COL_NAME;
+            use delphinus_zkwasm::circuits::post_image_table::POST_IMAGE_TABLE;
+
+            let on_going_circuit = self.read_circuit_data(
+                _params_dir,
+                &self.circuit_datas.on_going_circuit.circuit_data_sha256,
+            )?;
+            let finalized_circuit = self.read_circuit_data(
+                _params_dir,
+                &self.circuit_datas.finalized_circuit.circuit_data_sha256,
+            )?;
+
+            // The post image of the last slice is not chained.
+            let post_image_table_commitments = transcripts[..transcripts.len() - 1]
+                .iter()
+                .map(|transcript| {
+                    get_named_advice_commitment(
+                        on_going_circuit.get_vkey(),
+                        transcript,
+                        POST_IMAGE_TABLE,
+                    )
+                })
+                .collect::<Vec<_>>();
+            let pre_image_table_commitments = transcripts[1..]
+                .iter()
+                .enumerate()
+                .map(|(index, transcript)| {
+                    let vkey = if index + 1 == proofs.len() - 1 {
+                        finalized_circuit.get_vkey()
+                    } else {
+                        on_going_circuit.get_vkey()
+                    };
+
+                    get_named_advice_commitment(vkey, transcript, IMAGE_COL_NAME)
+                })
+                .collect::<Vec<_>>();
+
+            check_image_table_chain(&post_image_table_commitments, &pre_image_table_commitments)?;
+        }
+
+        let proof_load_info_path = output_dir.join(name_of_loadinfo(&self.name));
+        println!(
+            "{} Saving proof load info to {:?}...",
+            style("[3/3]").bold().dim(),
+            proof_load_info_path
+        );
+
+        let mut proof_load_info =
+            ProofGenerationInfo::new(&self.name, self.k as usize, HashType::Poseidon);
+
+        // Files of the proofs which are not in the output directory yet, an
+        // existing file must be identical to the one of the proof.
+        let mut files_to_copy = Vec::new();
+        for (_, proof_dir, proof_piece_info) in &proofs {
+            if proof_dir.canonicalize()? == output_dir.canonicalize()? {
+                continue;
+            }
+
+            for file_name in [
+                &proof_piece_info.instance,
+                &proof_piece_info.transcript,
+                &proof_piece_info.witness,
+            ] {
+                let path = proof_dir.join(file_name);
+                let destination = output_dir.join(file_name);
+
+                if !path.exists() {
+                    continue;
+                }
+
+                if destination.exists() {
+                    if fs::read(&destination)? != fs::read(&path)? {
+                        anyhow::bail!(
+                            "{:?} already exists and is different from {:?}.",
+                            destination,
+                            path
+                        );
+                    }
+
+                    continue;
+                }
+
+                files_to_copy.push((path, destination));
+            }
+        }
+
+        for (path, destination) in files_to_copy {
+            fs::copy(path, destination)?;
+        }
+
+        for (_, _, proof_piece_info) in proofs {
+            proof_load_info.append_single_proof(proof_piece_info);
+        }
+
+        save_proof_load_info(&proof_load_info, &self.name, output_dir)
+    }
+
     pub(crate) fn verify(self, params_dir: &Path, output_dir: &PathBuf) -> anyhow::Result<()> {
         let mut maximal_public_inputs_size = 0;
 
@@ -533,23 +1609,12 @@ impl Config {
                 let mut buf = Vec::new();
                 proof.vkey.write(&mut Cursor::new(&mut buf))?;
 
-                #[cfg(feature = "continuation")]
-                if proofs.peek().is_none() {
-                    self.veryfying_key_consistent_check(
-                        &buf,
-                        &self.circuit_datas.finalized_circuit.verifying_key_md5,
-                    )?;
-                } else {
-                    self.veryfying_key_consistent_check(
-                        &buf,
-                        &self.circuit_datas.on_going_circuit.verifying_key_md5,
-                    )?;
-                }
-
-                #[cfg(not(feature = "continuation"))]
                 self.veryfying_key_consistent_check(
                     &buf,
-                    &self.circuit_datas.finalized_circuit.verifying_key_md5,
+                    &self
+                        .circuit_datas
+                        .of(proofs.peek().is_none())
+                        .verifying_key_sha256,
                 )?;
             };
 
@@ -566,3 +1631,621 @@ impl Config {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::collections::BTreeMap;
+    use std::collections::HashMap;
+    use std::fs::File;
+    use std::path::Path;
+    use std::path::PathBuf;
+    use std::rc::Rc;
+
+    use circuits_batcher::args::HashType;
+    use circuits_batcher::proof::ProofGenerationInfo;
+    use circuits_batcher::proof::ProofPieceInfo;
+    use delphinus_host::HostEnvConfig;
+    use delphinus_host::HostOp;
+    use delphinus_zkwasm::foreign::context::ContextOutput;
+    use delphinus_zkwasm::runtime::host::default_env::DefaultHostEnvBuilder;
+    use delphinus_zkwasm::runtime::host::default_env::ExecutionArg;
+    use delphinus_zkwasm::runtime::host::StateDiff;
+    use delphinus_zkwasm::runtime::monitor::limits::ExecutionLimits;
+    use delphinus_zkwasm::runtime::ExecutionResult;
+    use halo2_proofs::pairing::bn256::Fr;
+
+    use super::instance_file_content;
+    use super::save_with_later_proofs;
+    use super::write_state_diffs;
+    use super::CircuitDataConfig;
+    use super::CircuitDataSha256;
+    use super::Config;
+    use crate::args::HostMode;
+    use crate::args::Scheme;
+    use crate::file_backend::TracedTables;
+    use crate::names::name_of_circuit_data;
+    use crate::names::name_of_etable_slice;
+    use crate::names::name_of_instance;
+    use crate::names::name_of_loadinfo;
+    use crate::names::name_of_params;
+    use crate::names::name_of_traced_tables;
+    use crate::names::name_of_transcript;
+    use crate::names::name_of_witness;
+    use crate::test::TempDir;
+    use crate::utils::sha256_hex;
+
+    fn circuit_data_sha256(name: &str) -> CircuitDataSha256 {
+        CircuitDataSha256 {
+            circuit_data_sha256: format!("{}-circuit-sha256", name),
+            verifying_key_sha256: format!("{}-vkey-sha256", name),
+        }
+    }
+
+    fn test_config(host_config: Option<HostEnvConfig>) -> Config {
+        Config {
+            name: "test".to_string(),
+            is_uniform_circuit: false,
+            k: 18,
+            params: PathBuf::from("K18.params"),
+            params_sha256: "params-sha256".to_string(),
+            wasm_image_sha256: Some("image-sha256".to_string()),
+            #[cfg(not(feature = "continuation"))]
+            circuit_datas: CircuitDataConfig {
+                finalized_circuit: circuit_data_sha256("finalized"),
+            },
+            #[cfg(feature = "continuation")]
+            circuit_datas: CircuitDataConfig {
+                on_going_circuit: circuit_data_sha256("ongoing"),
+                finalized_circuit: circuit_data_sha256("finalized"),
+            },
+            checksum: ("x".to_string(), "y".to_string()),
+            phantom_functions: vec![],
+            host_mode: HostMode::Standard,
+            host_config,
+            scheme: Scheme::Gwc,
+        }
+    }
+
+    fn write_and_read(config: &Config, path: &Path) -> anyhow::Result<Config> {
+        config.write(&mut File::create(path).unwrap()).unwrap();
+
+        Config::read(&mut File::open(path).unwrap())
+    }
+
+    #[test]
+    fn test_read_config_with_unproven_ops() {
+        let dir = TempDir::new("config-unproven-ops");
+        let path = dir.path().join("test.zkwasm.config");
+
+        let config = write_and_read(&test_config(Some(HostEnvConfig::default())), &path).unwrap();
+        assert_eq!(config.host_config.unwrap().ops.len(), 5);
+
+        // a config edited after setup to enable an unproven op
+        let host_config = HostEnvConfig {
+            ops: vec![HostOp::Sha256],
+            ..HostEnvConfig::default()
+        };
+        assert!(write_and_read(&test_config(Some(host_config.clone())), &path).is_err());
+
+        let host_config = HostEnvConfig {
+            allow_unproven: true,
+            ..host_config
+        };
+        assert!(write_and_read(&test_config(Some(host_config)), &path).is_ok());
+    }
+
+    /// Writes the instance and transcript files of a proof of the slice.
+    fn write_proof_piece(
+        dir: &Path,
+        index: usize,
+        circuit_data_sha256: &str,
+        instance: &[u8],
+    ) -> ProofPieceInfo {
+        let transcript = format!("transcript of slice {}", index).into_bytes();
+
+        let proof_piece_info = ProofPieceInfo {
+            circuit: name_of_circuit_data(circuit_data_sha256),
+            instance_size: 1,
+            witness: name_of_witness("test", index),
+            instance: name_of_instance("test", index),
+            transcript: name_of_transcript("test", index, &sha256_hex(&transcript)),
+        };
+
+        std::fs::write(dir.join(&proof_piece_info.instance), instance).unwrap();
+        std::fs::write(dir.join(&proof_piece_info.transcript), transcript).unwrap();
+
+        proof_piece_info
+    }
+
+    #[cfg(not(feature = "continuation"))]
+    #[test]
+    fn test_resume_proof_piece() {
+        let dir = TempDir::new("config-resume-proof-piece");
+        let config = test_config(None);
+
+        let instances = vec![Fr::from(42)];
+        let instance = instance_file_content(&instances);
+
+        let resume = |proof_piece_info: &ProofPieceInfo, instance: &[u8]| {
+            config
+                .resume_proof_piece(
+                    proof_piece_info,
+                    dir.path(),
+                    dir.path(),
+                    0,
+                    true,
+                    instance,
+                    &instances,
+                    &mut Default::default(),
+                    &mut None,
+                )
+                .unwrap()
+        };
+
+        let proof_piece_info =
+            write_proof_piece(dir.path(), 0, "finalized-circuit-sha256", &instance);
+        assert!(resume(&proof_piece_info, &instance));
+
+        // proved with other instances
+        assert!(!resume(
+            &proof_piece_info,
+            &instance_file_content(&[Fr::from(43)])
+        ));
+
+        // proved by another circuit
+        let other_circuit = write_proof_piece(dir.path(), 0, "other-circuit-sha256", &instance);
+        assert!(!resume(&other_circuit, &instance));
+
+        // transcript partially written by a crashed run
+        std::fs::write(dir.path().join(&proof_piece_info.transcript), b"trans").unwrap();
+        assert!(!resume(&proof_piece_info, &instance));
+
+        std::fs::remove_file(dir.path().join(&proof_piece_info.transcript)).unwrap();
+        assert!(!resume(&proof_piece_info, &instance));
+    }
+
+    #[test]
+    fn test_save_with_later_proofs() {
+        let dir = TempDir::new("config-save-with-later-proofs");
+        let instance = instance_file_content(&[Fr::from(42)]);
+
+        let mut proof_load_info = ProofGenerationInfo::new("test", 18, HashType::Poseidon);
+        proof_load_info.append_single_proof(write_proof_piece(dir.path(), 1, "c", &instance));
+        let mut later_proofs = vec![
+            write_proof_piece(dir.path(), 3, "c", &instance),
+            write_proof_piece(dir.path(), 4, "c", &instance),
+        ];
+
+        save_with_later_proofs(&mut proof_load_info, &mut later_proofs, "test", dir.path())
+            .unwrap();
+
+        // the later proofs are saved behind the proved slices but kept apart
+        assert_eq!(proof_load_info.proofs.len(), 1);
+        assert_eq!(later_proofs.len(), 2);
+
+        let saved = ProofGenerationInfo::load(&dir.path().join(name_of_loadinfo("test")));
+        assert_eq!(
+            saved
+                .proofs
+                .iter()
+                .map(|proof_piece_info| proof_piece_info.instance.clone())
+                .collect::<Vec<_>>(),
+            [1, 3, 4].map(|index| name_of_instance("test", index))
+        );
+
+        // the next proved slice goes before the later proofs
+        proof_load_info.append_single_proof(write_proof_piece(dir.path(), 2, "c", &instance));
+        save_with_later_proofs(&mut proof_load_info, &mut later_proofs, "test", dir.path())
+            .unwrap();
+
+        let saved = ProofGenerationInfo::load(&dir.path().join(name_of_loadinfo("test")));
+        assert_eq!(
+            saved
+                .proofs
+                .iter()
+                .map(|proof_piece_info| proof_piece_info.instance.clone())
+                .collect::<Vec<_>>(),
+            [1, 2, 3, 4].map(|index| name_of_instance("test", index))
+        );
+    }
+
+    #[test]
+    fn test_save_proof_load_info_by_rename() {
+        let dir = TempDir::new("config-save-proof-load-info");
+        let instance = instance_file_content(&[Fr::from(42)]);
+
+        // a partial file left behind by a crashed run
+        let loadinfo = dir.path().join(name_of_loadinfo("test"));
+        std::fs::write(&loadinfo, b"{\"proofs\": [").unwrap();
+
+        let mut proof_load_info = ProofGenerationInfo::new("test", 18, HashType::Poseidon);
+        proof_load_info.append_single_proof(write_proof_piece(dir.path(), 0, "c", &instance));
+        save_with_later_proofs(&mut proof_load_info, &mut vec![], "test", dir.path()).unwrap();
+
+        // the staged file is moved in place, nothing is left in the staging dir
+        assert_eq!(ProofGenerationInfo::load(&loadinfo).proofs.len(), 1);
+        assert_eq!(
+            std::fs::read_dir(dir.path().join(".loadinfo"))
+                .unwrap()
+                .count(),
+            0
+        );
+    }
+
+    /// Writes the proofs of `slices` to `dir`, each slice is proved by the
+    /// circuit of the given digest.
+    fn write_proofs(dir: &Path, slices: &[(usize, &str)]) {
+        let instance = instance_file_content(&[Fr::from(42)]);
+
+        let mut proof_load_info = ProofGenerationInfo::new("test", 18, HashType::Poseidon);
+        for (index, circuit_data_sha256) in slices {
+            proof_load_info.append_single_proof(write_proof_piece(
+                dir,
+                *index,
+                circuit_data_sha256,
+                &instance,
+            ));
+        }
+        proof_load_info.save(dir);
+    }
+
+    fn merge_proofs(
+        config: Config,
+        proof_dirs: &[&TempDir],
+        output_dir: &TempDir,
+        slices: Option<usize>,
+    ) -> anyhow::Result<()> {
+        let proof_dirs = proof_dirs
+            .iter()
+            .map(|dir| dir.path().to_path_buf())
+            .collect::<Vec<_>>();
+
+        config.merge_proofs(output_dir.path(), &proof_dirs, output_dir.path(), slices)
+    }
+
+    fn assert_merge_error(result: anyhow::Result<()>, message: &str) {
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains(message), "unexpected error: {}", err);
+    }
+
+    #[test]
+    fn test_merge_proofs_duplicate_slice() {
+        let (a, b, output) = (
+            TempDir::new("merge-duplicate-a"),
+            TempDir::new("merge-duplicate-b"),
+            TempDir::new("merge-duplicate-output"),
+        );
+        let config = test_config(None);
+        let ongoing = config.circuit_datas.of(false).circuit_data_sha256.clone();
+        let finalized = config.circuit_datas.of(true).circuit_data_sha256.clone();
+
+        write_proofs(a.path(), &[(0, ongoing.as_str()), (1, ongoing.as_str())]);
+        write_proofs(b.path(), &[(1, ongoing.as_str()), (2, finalized.as_str())]);
+
+        assert_merge_error(
+            merge_proofs(config, &[&a, &b], &output, None),
+            "Slice 1 is proved more than once",
+        );
+    }
+
+    #[test]
+    fn test_merge_proofs_missing_slices() {
+        let (a, b, output) = (
+            TempDir::new("merge-missing-a"),
+            TempDir::new("merge-missing-b"),
+            TempDir::new("merge-missing-output"),
+        );
+        let ongoing = test_config(None)
+            .circuit_datas
+            .of(false)
+            .circuit_data_sha256
+            .clone();
+        let finalized = test_config(None)
+            .circuit_datas
+            .of(true)
+            .circuit_data_sha256
+            .clone();
+
+        write_proofs(a.path(), &[(0, ongoing.as_str()), (1, ongoing.as_str())]);
+        write_proofs(b.path(), &[(3, finalized.as_str())]);
+        assert_merge_error(
+            merge_proofs(test_config(None), &[&a, &b], &output, None),
+            "Slices 2..3 are missing",
+        );
+
+        // the trailing slices are told by the finalized circuit
+        write_proofs(b.path(), &[(2, "other")]);
+        assert_merge_error(
+            merge_proofs(test_config(None), &[&a, &b], &output, None),
+            "Slices 3.. are missing",
+        );
+
+        // or by the number of slices
+        assert_merge_error(
+            merge_proofs(test_config(None), &[&a, &b], &output, Some(5)),
+            "Slices 3..5 are missing",
+        );
+        assert_merge_error(
+            merge_proofs(test_config(None), &[&a, &b], &output, Some(2)),
+            "is out of the 2 slice(s)",
+        );
+
+        // which is required with a uniform circuit
+        let uniform_config = || Config {
+            is_uniform_circuit: true,
+            ..test_config(None)
+        };
+        write_proofs(b.path(), &[(2, ongoing.as_str())]);
+        assert_merge_error(
+            merge_proofs(uniform_config(), &[&a, &b], &output, None),
+            "provide the number of slices by --slices",
+        );
+        assert_merge_error(
+            merge_proofs(uniform_config(), &[&a, &b], &output, Some(4)),
+            "Slices 3..4 are missing",
+        );
+    }
+
+    #[test]
+    fn test_merge_proofs_wrong_circuit() {
+        let (a, b, output) = (
+            TempDir::new("merge-wrong-circuit-a"),
+            TempDir::new("merge-wrong-circuit-b"),
+            TempDir::new("merge-wrong-circuit-output"),
+        );
+        let config = test_config(None);
+        let finalized = config.circuit_datas.of(true).circuit_data_sha256.clone();
+
+        write_proofs(a.path(), &[(0, "other")]);
+        write_proofs(b.path(), &[(1, finalized.as_str())]);
+
+        assert_merge_error(
+            merge_proofs(config, &[&a, &b], &output, None),
+            "Proof of slice 0",
+        );
+    }
+
+    #[test]
+    fn test_merge_proofs_existing_files() {
+        let (a, output) = (
+            TempDir::new("merge-existing-files-a"),
+            TempDir::new("merge-existing-files-output"),
+        );
+        let finalized = test_config(None)
+            .circuit_datas
+            .of(true)
+            .circuit_data_sha256
+            .clone();
+
+        write_proofs(a.path(), &[(0, finalized.as_str())]);
+        let proof_load_info = ProofGenerationInfo::load(&a.path().join(name_of_loadinfo("test")));
+        let proof_piece_info = &proof_load_info.proofs[0];
+
+        // a different instance of another run is never overwritten
+        let instance_path = output.path().join(&proof_piece_info.instance);
+        std::fs::write(&instance_path, b"other instance").unwrap();
+        assert_merge_error(
+            merge_proofs(test_config(None), &[&a], &output, None),
+            "already exists and is different",
+        );
+        assert_eq!(std::fs::read(&instance_path).unwrap(), b"other instance");
+        assert!(!output.path().join(&proof_piece_info.transcript).exists());
+
+        // an identical one is kept
+        std::fs::copy(a.path().join(&proof_piece_info.instance), &instance_path).unwrap();
+        merge_proofs(test_config(None), &[&a], &output, None).unwrap();
+        assert!(output.path().join(&proof_piece_info.transcript).exists());
+        assert_eq!(
+            ProofGenerationInfo::load(&output.path().join(name_of_loadinfo("test")))
+                .proofs
+                .len(),
+            1
+        );
+    }
+
+    #[cfg(feature = "continuation")]
+    #[test]
+    fn test_check_image_table_chain() {
+        use halo2_proofs::pairing::bn256::G1Affine;
+        use halo2_proofs::pairing::group::prime::PrimeCurveAffine;
+
+        use super::check_image_table_chain;
+
+        let (a, b) = (G1Affine::generator(), G1Affine::identity());
+
+        assert!(check_image_table_chain(&[], &[]).is_ok());
+        assert!(check_image_table_chain(&[a, b], &[a, b]).is_ok());
+
+        let err = check_image_table_chain(&[a, b], &[a, a]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The pre image of slice 2 is inconsistent with the post image of slice 1."
+        );
+    }
+
+    #[test]
+    fn test_prove_from_tampered_traces() {
+        let dir = TempDir::new("config-tampered-traces");
+        let wasm_image = wabt::wat2wasm(crate::TRIVIAL_WASM).unwrap();
+        let wasm_image_path = dir.path().join("test.wasm");
+        std::fs::write(&wasm_image_path, &wasm_image).unwrap();
+
+        let config = || Config {
+            wasm_image_sha256: Some(sha256_hex(&wasm_image)),
+            ..test_config(None)
+        };
+
+        config()
+            .witness(
+                &DefaultHostEnvBuilder::new(18),
+                &wasm_image_path,
+                dir.path(),
+                ExecutionArg {
+                    public_inputs: vec![],
+                    private_inputs: vec![],
+                    context_inputs: vec![],
+                    indexed_witness: Rc::new(RefCell::new(HashMap::default())),
+                    tree_db: None,
+                },
+                None,
+                None,
+                ExecutionLimits::default(),
+            )
+            .unwrap();
+
+        let trace_dir = dir.path().join("traces");
+        let traced_tables_path = trace_dir.join(name_of_traced_tables("test"));
+        assert!(TracedTables::read(&traced_tables_path)
+            .unwrap()
+            .into_tables("test", &trace_dir)
+            .is_ok());
+
+        let etable_path = trace_dir.join(name_of_etable_slice("test", 0));
+        let mut etable = std::fs::read(&etable_path).unwrap();
+        etable.push(b' ');
+        std::fs::write(&etable_path, etable).unwrap();
+
+        let err = config()
+            .prove_from_traces(
+                &trace_dir,
+                dir.path(),
+                &dir.path().join("output"),
+                false,
+                0,
+                false,
+                None,
+                None,
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "slice 0 is inconsistent with the digests written by witness"
+        );
+    }
+
+    #[test]
+    fn test_write_state_diffs() {
+        let dir = TempDir::new("config-state-diffs");
+
+        let mut diff = StateDiff::new([1, 2, 3, 4]);
+        diff.record(5, [0; 4], [6; 4]);
+        diff.record(2, [0; 4], [7; 4]);
+        diff.record(5, [6; 4], [8; 4]);
+        diff.root_after = [9; 4];
+
+        let result = ExecutionResult::<()> {
+            result: None,
+            public_inputs_and_outputs: vec![],
+            host_statics: HashMap::new(),
+            guest_statics: 0,
+            outputs: vec![],
+            context_outputs: ContextOutput(vec![]),
+            state_diffs: BTreeMap::from([("merkle".to_string(), vec![diff])]),
+        };
+
+        write_state_diffs(&result, dir.path(), None).unwrap();
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+
+        write_state_diffs(&result, dir.path(), Some("state_diff.json".to_string())).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_reader(File::open(dir.path().join("state_diff.json")).unwrap())
+                .unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "merkle": [{
+                    "root_before": [1, 2, 3, 4],
+                    "root_after": [9, 9, 9, 9],
+                    "writes": {
+                        "2": { "old_leaf": [0, 0, 0, 0], "new_leaf": [7, 7, 7, 7] },
+                        "5": { "old_leaf": [0, 0, 0, 0], "new_leaf": [8, 8, 8, 8] }
+                    }
+                }]
+            })
+        );
+    }
+
+    /// Flips a bit in the middle of the file.
+    fn tamper(path: &Path) {
+        let mut buf = std::fs::read(path).unwrap();
+        let middle = buf.len() / 2;
+        buf[middle] ^= 1;
+        std::fs::write(path, buf).unwrap();
+    }
+
+    fn error_of<T>(result: anyhow::Result<T>) -> String {
+        match result {
+            Ok(_) => panic!("the artifact is expected to be rejected"),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_tampered_artifacts() {
+        let dir = TempDir::new("config-tampered-artifacts");
+
+        let params = b"params".to_vec();
+        let circuit_data = b"circuit data".to_vec();
+        let verifying_key = b"verifying key".to_vec();
+        let wasm_image = wabt::wat2wasm(crate::TRIVIAL_WASM).unwrap();
+
+        let mut config = Config {
+            params_sha256: sha256_hex(&params),
+            wasm_image_sha256: Some(sha256_hex(&wasm_image)),
+            ..test_config(None)
+        };
+        config.circuit_datas.finalized_circuit = CircuitDataSha256 {
+            circuit_data_sha256: sha256_hex(&circuit_data),
+            verifying_key_sha256: sha256_hex(&verifying_key),
+        };
+        let config = write_and_read(&config, &dir.path().join("test.zkwasm.config")).unwrap();
+        let circuit_data_sha256 = config
+            .circuit_datas
+            .finalized_circuit
+            .circuit_data_sha256
+            .clone();
+        let verifying_key_sha256 = config
+            .circuit_datas
+            .finalized_circuit
+            .verifying_key_sha256
+            .clone();
+
+        let params_path = dir
+            .path()
+            .join(name_of_params(config.k, &config.params_sha256));
+        let wasm_image_path = dir.path().join("test.wasm");
+        let circuit_data_path = dir.path().join(name_of_circuit_data(&circuit_data_sha256));
+        std::fs::write(&params_path, &params).unwrap();
+        std::fs::write(&wasm_image_path, &wasm_image).unwrap();
+        std::fs::write(&circuit_data_path, &circuit_data).unwrap();
+
+        assert!(config.params_consistent_check(&params).is_ok());
+        assert!(config
+            .veryfying_key_consistent_check(&verifying_key, &verifying_key_sha256)
+            .is_ok());
+        assert!(config.read_wasm_image(&wasm_image_path).is_ok());
+
+        tamper(&params_path);
+        assert!(error_of(config.read_params(dir.path()))
+            .starts_with("Params is inconsistent with the one used to build the circuit."));
+
+        tamper(&wasm_image_path);
+        assert!(error_of(config.read_wasm_image(&wasm_image_path))
+            .starts_with("Wasm image is inconsistent with the one used to build the circuit."));
+
+        tamper(&circuit_data_path);
+        assert!(
+            error_of(config.read_circuit_data(dir.path(), &circuit_data_sha256)).starts_with(
+                "Circuit data is inconsistent with the one used to build the circuit."
+            )
+        );
+
+        let mut verifying_key = verifying_key;
+        verifying_key[0] ^= 1;
+        assert!(error_of(
+            config.veryfying_key_consistent_check(&verifying_key, &verifying_key_sha256)
+        )
+        .starts_with("Verifying key is inconsistent with the one used to build the circuit."));
+    }
+}
@@ -0,0 +1,327 @@
+//! The unversioned config before the versioned JSON config, it records MD5
+//! digests of the artifacts and is only read to migrate old params directories.
+
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+use std::path::PathBuf;
+
+use bincode::Options;
+use halo2_proofs::pairing::bn256::G1Affine;
+use halo2_proofs::plonk::CircuitData;
+use serde::Deserialize;
+
+use super::CircuitDataConfig;
+use super::CircuitDataSha256;
+use super::Config;
+use crate::args::HostMode;
+use crate::args::Scheme;
+use crate::names::name_of_circuit_data;
+use crate::names::name_of_params;
+use crate::utils::sha256_hex;
+
+#[derive(Deserialize)]
+struct CircuitDataMd5 {
+    circuit_data_md5: String,
+    verifying_key_md5: String,
+}
+
+#[cfg(not(feature = "continuation"))]
+#[derive(Deserialize)]
+struct CircuitDataConfigV0 {
+    finalized_circuit: CircuitDataMd5,
+}
+
+#[cfg(feature = "continuation")]
+#[derive(Deserialize)]
+struct CircuitDataConfigV0 {
+    on_going_circuit: CircuitDataMd5,
+    finalized_circuit: CircuitDataMd5,
+}
+
+/// The unversioned config (version 0) which was written by `bincode::serialize`.
+/// Bincode is positional, the fields must stay in the order they were written.
+#[derive(Deserialize)]
+pub(super) struct ConfigV0 {
+    name: String,
+
+    is_uniform_circuit: bool,
+    k: u32,
+    // Recorded relative to the working directory of the setup, params are
+    // looked up in the params directory by their legacy name instead.
+    #[allow(dead_code)]
+    params: PathBuf,
+    params_md5: String,
+    wasm_image_md5: Option<String>,
+    circuit_datas: CircuitDataConfigV0,
+
+    checksum: (String, String),
+    phantom_functions: Vec<String>,
+    host_mode: HostMode,
+
+    scheme: Scheme,
+}
+
+fn md5_hex(bytes: &[u8]) -> String {
+    format!("{:x}", md5::compute(bytes))
+}
+
+/// Reads the artifact at `path` and checks it against the recorded MD5 digest.
+fn read_artifact(path: &Path, expected_md5: &str) -> anyhow::Result<Vec<u8>> {
+    let buf = fs::read(path)?;
+
+    if md5_hex(&buf) != expected_md5 {
+        anyhow::bail!(
+            "{:?} is inconsistent with the digest recorded in the config. \
+                Maybe you have changed it after setup the circuit?",
+            path
+        );
+    }
+
+    Ok(buf)
+}
+
+/// Saves `buf` under its digest name unless an artifact with the same content
+/// already exists, the legacy file is left untouched for other configs.
+fn save_artifact(path: &Path, buf: &[u8]) -> anyhow::Result<()> {
+    if !path.exists() {
+        fs::write(path, buf)?;
+    }
+
+    Ok(())
+}
+
+impl CircuitDataMd5 {
+    /// Checks the circuit data named by `legacy_name` and its verifying key
+    /// and saves it under its SHA-256 name.
+    fn migrate(&self, params_dir: &Path, legacy_name: &str) -> anyhow::Result<CircuitDataSha256> {
+        let buf = read_artifact(&params_dir.join(legacy_name), &self.circuit_data_md5)?;
+
+        let verifying_key = {
+            let circuit_data = CircuitData::<G1Affine>::read(&mut Cursor::new(&buf))?;
+
+            let mut verifying_key = Vec::new();
+            circuit_data.get_vkey().write(&mut verifying_key)?;
+
+            verifying_key
+        };
+
+        if md5_hex(&verifying_key) != self.verifying_key_md5 {
+            anyhow::bail!(
+                "Verifying key of {:?} is inconsistent with the digest recorded in the config.",
+                params_dir.join(legacy_name)
+            );
+        }
+
+        let circuit_data_sha256 = sha256_hex(&buf);
+        save_artifact(
+            &params_dir.join(name_of_circuit_data(&circuit_data_sha256)),
+            &buf,
+        )?;
+
+        Ok(CircuitDataSha256 {
+            circuit_data_sha256,
+            verifying_key_sha256: sha256_hex(&verifying_key),
+        })
+    }
+}
+
+impl CircuitDataConfigV0 {
+    #[cfg(not(feature = "continuation"))]
+    fn migrate(&self, params_dir: &Path, name: &str) -> anyhow::Result<CircuitDataConfig> {
+        Ok(CircuitDataConfig {
+            finalized_circuit: self
+                .finalized_circuit
+                .migrate(params_dir, &format!("{}.circuit.finalized.data", name))?,
+        })
+    }
+
+    #[cfg(feature = "continuation")]
+    fn migrate(&self, params_dir: &Path, name: &str) -> anyhow::Result<CircuitDataConfig> {
+        Ok(CircuitDataConfig {
+            on_going_circuit: self
+                .on_going_circuit
+                .migrate(params_dir, &format!("{}.circuit.ongoing.data", name))?,
+            finalized_circuit: self
+                .finalized_circuit
+                .migrate(params_dir, &format!("{}.circuit.finalized.data", name))?,
+        })
+    }
+}
+
+impl ConfigV0 {
+    /// Parses an unversioned bincode config, trailing bytes are rejected so
+    /// that a config of another layout is not parsed as its prefix.
+    pub(super) fn read(buf: &[u8]) -> anyhow::Result<Self> {
+        let config = bincode::DefaultOptions::new()
+            .with_fixint_encoding()
+            .reject_trailing_bytes()
+            .deserialize(buf)
+            .map_err(|_| {
+                anyhow::anyhow!(
+                    "Config is neither a versioned config nor an unversioned bincode config. \
+                        Maybe it was set up by a build with a different continuation feature?"
+                )
+            })?;
+
+        Ok(config)
+    }
+
+    /// Checks the params, the circuit data and the image against the recorded
+    /// MD5 digests and names the artifacts by their SHA-256 digests.
+    pub(super) fn migrate(
+        self,
+        params_dir: &Path,
+        wasm_image: Option<&Path>,
+    ) -> anyhow::Result<Config> {
+        let params = read_artifact(
+            &params_dir.join(format!("K{}.params", self.k)),
+            &self.params_md5,
+        )?;
+        let params_sha256 = sha256_hex(&params);
+        let params_path = params_dir.join(name_of_params(self.k, &params_sha256));
+        save_artifact(&params_path, &params)?;
+
+        let circuit_datas = self.circuit_datas.migrate(params_dir, &self.name)?;
+
+        let wasm_image_sha256 = match (&self.wasm_image_md5, wasm_image) {
+            (None, _) => None,
+            (Some(_), None) => anyhow::bail!(
+                "Config records the digest of the Wasm image, please provide the image by --wasm."
+            ),
+            (Some(expected_md5), Some(wasm_image)) => {
+                Some(sha256_hex(&read_artifact(wasm_image, expected_md5)?))
+            }
+        };
+
+        Ok(Config {
+            name: self.name,
+            is_uniform_circuit: self.is_uniform_circuit,
+            k: self.k,
+            params: params_path,
+            params_sha256,
+            wasm_image_sha256,
+            circuit_datas,
+            checksum: self.checksum,
+            phantom_functions: self.phantom_functions,
+            host_mode: self.host_mode,
+            host_config: None,
+            scheme: self.scheme,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use serde::Serialize;
+
+    use super::ConfigV0;
+    use crate::args::HostMode;
+    use crate::args::Scheme;
+
+    // The config as it was defined before versioning, serialized the way its
+    // `Config::write` did.
+    #[derive(Serialize)]
+    struct CircuitDataMd5 {
+        circuit_data_md5: String,
+        verifying_key_md5: String,
+    }
+
+    #[cfg(not(feature = "continuation"))]
+    #[derive(Serialize)]
+    struct CircuitDataConfig {
+        finalized_circuit: CircuitDataMd5,
+    }
+
+    #[cfg(feature = "continuation")]
+    #[derive(Serialize)]
+    struct CircuitDataConfig {
+        on_going_circuit: CircuitDataMd5,
+        finalized_circuit: CircuitDataMd5,
+    }
+
+    #[derive(Serialize)]
+    struct Config {
+        name: String,
+
+        is_uniform_circuit: bool,
+        k: u32,
+        params: PathBuf,
+        params_md5: String,
+        wasm_image_md5: Option<String>,
+        circuit_datas: CircuitDataConfig,
+
+        checksum: (String, String),
+        phantom_functions: Vec<String>,
+        host_mode: HostMode,
+
+        scheme: Scheme,
+    }
+
+    fn circuit_data_md5(name: &str) -> CircuitDataMd5 {
+        CircuitDataMd5 {
+            circuit_data_md5: format!("{}-circuit-md5", name),
+            verifying_key_md5: format!("{}-vkey-md5", name),
+        }
+    }
+
+    fn baseline_config() -> Config {
+        Config {
+            name: "test".to_string(),
+            is_uniform_circuit: true,
+            k: 18,
+            params: PathBuf::from("params/K18.params"),
+            params_md5: "params-md5".to_string(),
+            wasm_image_md5: Some("image-md5".to_string()),
+            #[cfg(not(feature = "continuation"))]
+            circuit_datas: CircuitDataConfig {
+                finalized_circuit: circuit_data_md5("finalized"),
+            },
+            #[cfg(feature = "continuation")]
+            circuit_datas: CircuitDataConfig {
+                on_going_circuit: circuit_data_md5("ongoing"),
+                finalized_circuit: circuit_data_md5("finalized"),
+            },
+            checksum: ("x".to_string(), "y".to_string()),
+            phantom_functions: vec!["phantom".to_string()],
+            host_mode: HostMode::Standard,
+            scheme: Scheme::Gwc,
+        }
+    }
+
+    #[test]
+    fn test_read_baseline_config() {
+        let buf = bincode::serialize(&baseline_config()).unwrap();
+
+        let config = ConfigV0::read(&buf).unwrap();
+
+        assert_eq!(config.name, "test");
+        assert!(config.is_uniform_circuit);
+        assert_eq!(config.k, 18);
+        assert_eq!(config.params_md5, "params-md5");
+        assert_eq!(config.wasm_image_md5.as_deref(), Some("image-md5"));
+        assert_eq!(
+            config.circuit_datas.finalized_circuit.circuit_data_md5,
+            "finalized-circuit-md5"
+        );
+        #[cfg(feature = "continuation")]
+        assert_eq!(
+            config.circuit_datas.on_going_circuit.verifying_key_md5,
+            "ongoing-vkey-md5"
+        );
+        assert_eq!(config.checksum, ("x".to_string(), "y".to_string()));
+        assert_eq!(config.phantom_functions, vec!["phantom".to_string()]);
+        assert!(matches!(config.host_mode, HostMode::Standard));
+        assert!(matches!(config.scheme, Scheme::Gwc));
+    }
+
+    #[test]
+    fn test_reject_trailing_bytes() {
+        let mut buf = bincode::serialize(&baseline_config()).unwrap();
+        buf.push(0xff);
+
+        assert!(ConfigV0::read(&buf).is_err());
+    }
+}
@@ -1,5 +1,8 @@
 use std::fs;
+use std::fs::File;
 use std::io;
+use std::io::BufReader;
+use std::io::BufWriter;
 use std::path::Path;
 use std::path::PathBuf;
 
@@ -11,10 +14,14 @@ use specs::jtable::FrameTable;
 use specs::slice_backend::Slice;
 use specs::slice_backend::SliceBackend;
 use specs::slice_backend::SliceBackendBuilder;
+use specs::CompilationTable;
+use specs::ExecutionTable;
+use specs::Tables;
 
 use crate::names::name_of_etable_slice;
 use crate::names::name_of_external_host_call_table_slice;
 use crate::names::name_of_frame_table_slice;
+use crate::utils::sha256_hex;
 
 struct SlicePath {
     event_table: PathBuf,
@@ -33,7 +40,7 @@ impl From<&SlicePath> for Slice {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub(crate) struct FileBackendSlice {
     event_table: PathBuf,
     frame_table: PathBuf,
@@ -51,6 +58,31 @@ impl From<FileBackendSlice> for Slice {
     }
 }
 
+impl FileBackendSlice {
+    fn new(name: &str, dir: &Path, index: usize) -> Self {
+        Self {
+            event_table: dir.join(name_of_etable_slice(name, index)),
+            frame_table: dir.join(name_of_frame_table_slice(name, index)),
+            external_host_call_table: dir.join(name_of_external_host_call_table_slice(name, index)),
+        }
+    }
+
+    fn files(&self) -> [&PathBuf; 3] {
+        [
+            &self.event_table,
+            &self.frame_table,
+            &self.external_host_call_table,
+        ]
+    }
+
+    fn sha256(&self) -> io::Result<Vec<String>> {
+        self.files()
+            .into_iter()
+            .map(|path| Ok(sha256_hex(&fs::read(path)?)))
+            .collect()
+    }
+}
+
 impl SliceBackend for FileBackendSlice {
     fn write(
         &self,
@@ -97,38 +129,115 @@ impl SliceBackendBuilder for FileBackendBuilder {
     type Output = FileBackendSlice;
 
     fn build(&mut self, slice: Slice) -> Self::Output {
-        let event_table = {
-            let path = self
-                .dir
-                .join(PathBuf::from(name_of_etable_slice(&self.name, self.index)));
-            slice.etable.write(&path).unwrap();
-            path
-        };
-
-        let frame_table = {
-            let path = self.dir.join(PathBuf::from(name_of_frame_table_slice(
-                &self.name, self.index,
-            )));
-            slice.frame_table.write(&path).unwrap();
-            path
-        };
-
-        let external_host_call_table = {
-            let path = self
-                .dir
-                .join(PathBuf::from(name_of_external_host_call_table_slice(
-                    &self.name, self.index,
-                )));
-            slice.external_host_call_table.write(&path).unwrap();
-            path
-        };
+        let file_backend_slice = FileBackendSlice::new(&self.name, &self.dir, self.index);
+
+        slice.etable.write(&file_backend_slice.event_table).unwrap();
+        slice
+            .frame_table
+            .write(&file_backend_slice.frame_table)
+            .unwrap();
+        slice
+            .external_host_call_table
+            .write(&file_backend_slice.external_host_call_table)
+            .unwrap();
 
         self.index += 1;
 
-        FileBackendSlice {
-            event_table,
-            frame_table,
-            external_host_call_table,
-        }
+        file_backend_slice
+    }
+}
+
+/// Everything besides the slice files that `Slices::new` needs, it is written
+/// next to the slices by `witness` so that they can be proved without
+/// executing the image again. The slice files are checked against their
+/// SHA-256 digests when they are read.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct TracedTables {
+    pub(crate) wasm_image_sha256: String,
+    pub(crate) instances: Vec<u64>,
+    compilation_tables: CompilationTable,
+    context_input_table: Vec<u64>,
+    context_output_table: Vec<u64>,
+    /// Digests of the event, frame and external host call table of each slice.
+    slices_sha256: Vec<Vec<String>>,
+}
+
+impl TracedTables {
+    pub(crate) fn new(
+        wasm_image_sha256: String,
+        instances: Vec<u64>,
+        tables: Tables<FileBackendSlice>,
+    ) -> io::Result<Self> {
+        let slices_sha256 = tables
+            .execution_tables
+            .slice_backend
+            .iter()
+            .map(|slice| slice.sha256())
+            .collect::<io::Result<Vec<_>>>()?;
+
+        Ok(Self {
+            wasm_image_sha256,
+            instances,
+            compilation_tables: tables.compilation_tables,
+            context_input_table: tables.execution_tables.context_input_table,
+            context_output_table: tables.execution_tables.context_output_table,
+            slices_sha256,
+        })
+    }
+
+    pub(crate) fn write(&self, path: &Path) -> io::Result<()> {
+        serde_json::to_writer(BufWriter::new(File::create(path)?), self)?;
+
+        Ok(())
+    }
+
+    pub(crate) fn read(path: &Path) -> io::Result<Self> {
+        Ok(serde_json::from_reader(BufReader::new(File::open(path)?))?)
+    }
+
+    /// The slices are read from `dir` so that the traces can be moved as a whole.
+    pub(crate) fn into_tables(
+        self,
+        name: &str,
+        dir: &Path,
+    ) -> io::Result<Tables<FileBackendSlice>> {
+        let slice_backend = self
+            .slices_sha256
+            .iter()
+            .enumerate()
+            .map(|(index, expected_sha256)| {
+                let slice = FileBackendSlice::new(name, dir, index);
+
+                for path in slice.files() {
+                    if !path.exists() {
+                        return Err(io::Error::new(
+                            io::ErrorKind::NotFound,
+                            format!("slice {} is incomplete, {:?} is missing", index, path),
+                        ));
+                    }
+                }
+
+                if &slice.sha256()? != expected_sha256 {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "slice {} is inconsistent with the digests written by witness",
+                            index
+                        ),
+                    ));
+                }
+
+                Ok(slice)
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        Ok(Tables {
+            compilation_tables: self.compilation_tables,
+            execution_tables: ExecutionTable {
+                slice_backend,
+                context_input_table: self.context_input_table,
+                context_output_table: self.context_output_table,
+            },
+        })
     }
 }
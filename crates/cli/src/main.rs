@@ -10,6 +10,8 @@ use std::rc::Rc;
 use anyhow::Result;
 use app_builder::app;
 use command::Subcommands;
+use delphinus_host::db::FileDB;
+use delphinus_host::HostEnvConfig;
 use delphinus_host::StandardHostEnvBuilder;
 use delphinus_zkwasm::runtime::host::default_env::DefaultHostEnvBuilder;
 use delphinus_zkwasm::runtime::host::default_env::ExecutionArg;
@@ -18,6 +20,7 @@ use args::HostMode;
 use config::Config;
 use delphinus_zkwasm::runtime::host::HostEnvBuilder;
 use delphinus_zkwasm::zkwasm_host_circuits::host::db::MongoDB;
+use delphinus_zkwasm::zkwasm_host_circuits::host::db::TreeDB;
 use file_backend::FileBackendBuilder;
 use names::name_of_config;
 use specs::args::parse_args;
@@ -29,6 +32,8 @@ mod command;
 mod config;
 mod file_backend;
 mod names;
+#[cfg(test)]
+mod test;
 
 pub mod utils;
 
@@ -45,6 +50,27 @@ struct ZkWasmCli {
     subcommand: Subcommands,
 }
 
+fn host_env_builder(
+    k: u32,
+    host_mode: HostMode,
+    host_config: &Option<HostEnvConfig>,
+) -> Box<dyn HostEnvBuilder> {
+    match host_mode {
+        HostMode::Default => Box::new(DefaultHostEnvBuilder::new(k)),
+        HostMode::Standard => Box::new(StandardHostEnvBuilder::with_config(
+            k,
+            host_config.clone().unwrap_or_default(),
+        )),
+    }
+}
+
+fn open_tree_db(path: &Option<PathBuf>) -> Result<Rc<RefCell<dyn TreeDB>>> {
+    Ok(match path {
+        Some(path) => Rc::new(RefCell::new(FileDB::open(path)?)),
+        None => Rc::new(RefCell::new(MongoDB::new([0; 32], None))),
+    })
+}
+
 /// Simple program to greet a person
 fn main() -> Result<()> {
     {
@@ -57,12 +83,10 @@ fn main() -> Result<()> {
 
     match cli.subcommand {
         Subcommands::Setup(arg) => {
-            let env_builder: Box<dyn HostEnvBuilder> = match arg.host_mode {
-                HostMode::Default => Box::new(DefaultHostEnvBuilder::new(arg.k)),
-                HostMode::Standard => Box::new(StandardHostEnvBuilder::new(arg.k)),
-            };
+            let host_config = arg.host_env_config()?;
+            let env_builder = host_env_builder(arg.k, arg.host_mode, &host_config);
 
-            arg.setup(&*env_builder, &cli.name, &cli.params_dir)?;
+            arg.setup(&*env_builder, host_config, &cli.name, &cli.params_dir)?;
         }
         Subcommands::DryRun(arg) => {
             let config = Config::read(&mut fs::File::open(
@@ -73,10 +97,7 @@ fn main() -> Result<()> {
             let private_inputs = parse_args(&arg.running_arg.private_inputs);
             let context_inputs = parse_args(&arg.running_arg.context_inputs);
 
-            let env_builder: Box<dyn HostEnvBuilder> = match config.host_mode {
-                HostMode::Default => Box::new(DefaultHostEnvBuilder::new(config.k)),
-                HostMode::Standard => Box::new(StandardHostEnvBuilder::new(config.k)),
-            };
+            let env_builder = host_env_builder(config.k, config.host_mode, &config.host_config);
 
             config.dry_run(
                 &*env_builder,
@@ -87,16 +108,95 @@ fn main() -> Result<()> {
                     private_inputs,
                     context_inputs,
                     indexed_witness: Rc::new(RefCell::new(HashMap::default())),
-                    tree_db: Some(Rc::new(RefCell::new(MongoDB::new([0; 32], None)))),
+                    tree_db: Some(open_tree_db(&arg.running_arg.db)?),
                 },
                 arg.running_arg.context_output,
+                arg.running_arg.state_diff_output,
                 arg.instruction_limit,
             )?;
         }
+        Subcommands::Trace(arg) => {
+            let config = Config::read(&mut fs::File::open(
+                cli.params_dir.join(name_of_config(&cli.name)),
+            )?)?;
+
+            let public_inputs = parse_args(&arg.running_arg.public_inputs);
+            let private_inputs = parse_args(&arg.running_arg.private_inputs);
+            let context_inputs = parse_args(&arg.running_arg.context_inputs);
+
+            let env_builder = host_env_builder(config.k, config.host_mode, &config.host_config);
+
+            config.trace(
+                &*env_builder,
+                &arg.wasm_image,
+                &arg.running_arg.output_dir,
+                ExecutionArg {
+                    public_inputs,
+                    private_inputs,
+                    context_inputs,
+                    indexed_witness: Rc::new(RefCell::new(HashMap::default())),
+                    tree_db: Some(open_tree_db(&arg.running_arg.db)?),
+                },
+                arg.running_arg.context_output,
+                arg.running_arg.state_diff_output,
+                arg.instruction_limit,
+                arg.format.into(),
+                arg.filter,
+            )?;
+        }
+        Subcommands::Witness(arg) => {
+            fs::create_dir_all(&arg.running_arg.output_dir)?;
+
+            let config = Config::read(&mut fs::File::open(
+                cli.params_dir.join(name_of_config(&cli.name)),
+            )?)?;
+
+            let public_inputs = parse_args(&arg.running_arg.public_inputs);
+            let private_inputs = parse_args(&arg.running_arg.private_inputs);
+            let context_inputs = parse_args(&arg.running_arg.context_inputs);
+
+            let env_builder = host_env_builder(config.k, config.host_mode, &config.host_config);
+
+            config.witness(
+                &*env_builder,
+                &arg.wasm_image,
+                &arg.running_arg.output_dir,
+                ExecutionArg {
+                    public_inputs,
+                    private_inputs,
+                    context_inputs,
+                    indexed_witness: Rc::new(RefCell::new(HashMap::default())),
+                    tree_db: Some(open_tree_db(&arg.running_arg.db)?),
+                },
+                arg.running_arg.context_output,
+                arg.running_arg.state_diff_output,
+                arg.limits,
+            )?;
+        }
+        Subcommands::Prove(arg) if arg.from_traces.is_some() => {
+            fs::create_dir_all(&arg.output_dir)?;
+
+            let config = Config::read(&mut fs::File::open(
+                cli.params_dir.join(name_of_config(&cli.name)),
+            )?)?;
+
+            config.prove_from_traces(
+                arg.from_traces.as_ref().unwrap(),
+                &cli.params_dir,
+                &arg.output_dir,
+                arg.mock_test,
+                arg.skip,
+                arg.resume,
+                arg.slice_range,
+                arg.padding,
+            )?;
+        }
         Subcommands::Prove(arg) => {
             let trace_dir = arg.output_dir.join("traces");
             fs::create_dir_all(&trace_dir)?;
 
+            let wasm_image = arg.wasm_image.unwrap();
+
             let config = Config::read(&mut fs::File::open(
                 cli.params_dir.join(name_of_config(&cli.name)),
             )?)?;
@@ -105,10 +205,7 @@ fn main() -> Result<()> {
             let private_inputs = parse_args(&arg.running_arg.private_inputs);
             let context_inputs = parse_args(&arg.running_arg.context_inputs);
 
-            let env_builder: Box<dyn HostEnvBuilder> = match config.host_mode {
-                HostMode::Default => Box::new(DefaultHostEnvBuilder::new(config.k)),
-                HostMode::Standard => Box::new(StandardHostEnvBuilder::new(config.k)),
-            };
+            let env_builder = host_env_builder(config.k, config.host_mode, &config.host_config);
 
             if arg.file_backend {
                 let backend_builder = FileBackendBuilder::new(cli.name.clone(), trace_dir);
@@ -116,7 +213,7 @@ fn main() -> Result<()> {
                 config.prove(
                     backend_builder,
                     &*env_builder,
-                    &arg.wasm_image,
+                    &wasm_image,
                     &cli.params_dir,
                     &arg.output_dir,
                     ExecutionArg {
@@ -124,12 +221,16 @@ fn main() -> Result<()> {
                         private_inputs,
                         context_inputs,
                         indexed_witness: Rc::new(RefCell::new(HashMap::default())),
-                        tree_db: Some(Rc::new(RefCell::new(MongoDB::new([0; 32], None)))),
+                        tree_db: Some(open_tree_db(&arg.running_arg.db)?),
                     },
                     arg.running_arg.context_output,
+                    arg.running_arg.state_diff_output,
                     arg.mock_test,
                     arg.skip,
+                    arg.resume,
+                    arg.slice_range,
                     arg.padding,
+                    arg.limits,
                 )?;
             } else {
                 let backend_builder = InMemoryBackendBuilder;
@@ -137,7 +238,7 @@ fn main() -> Result<()> {
                 config.prove(
                     backend_builder,
                     &*env_builder,
-                    &arg.wasm_image,
+                    &wasm_image,
                     &cli.params_dir,
                     &arg.output_dir,
                     ExecutionArg {
@@ -145,12 +246,16 @@ fn main() -> Result<()> {
                         private_inputs,
                         context_inputs,
                         indexed_witness: Rc::new(RefCell::new(HashMap::default())),
-                        tree_db: Some(Rc::new(RefCell::new(MongoDB::new([0; 32], None)))),
+                        tree_db: Some(open_tree_db(&arg.running_arg.db)?),
                     },
                     arg.running_arg.context_output,
+                    arg.running_arg.state_diff_output,
                     arg.mock_test,
                     arg.skip,
+                    arg.resume,
+                    arg.slice_range,
                     arg.padding,
+                    arg.limits,
                 )?;
             }
         }
@@ -161,6 +266,30 @@ fn main() -> Result<()> {
 
             config.verify(&cli.params_dir, &arg.output_dir)?;
         }
+        Subcommands::MergeProofs(arg) => {
+            fs::create_dir_all(&arg.output_dir)?;
+
+            let config = Config::read(&mut fs::File::open(
+                cli.params_dir.join(name_of_config(&cli.name)),
+            )?)?;
+
+            config.merge_proofs(
+                &cli.params_dir,
+                &arg.proof_dirs,
+                &arg.output_dir,
+                arg.slices,
+            )?;
+        }
+        Subcommands::Info => {
+            let config = Config::read(&mut fs::File::open(
+                cli.params_dir.join(name_of_config(&cli.name)),
+            )?)?;
+
+            config.info();
+        }
+        Subcommands::Migrate(arg) => {
+            Config::migrate(&cli.params_dir, &cli.name, arg.wasm_image.as_deref())?;
+        }
     }
 
     Ok(())
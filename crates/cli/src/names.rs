@@ -1,6 +1,14 @@
 #[inline(always)]
-pub(crate) fn name_of_params(k: u32) -> String {
-    format!("K{}.params", k)
+pub(crate) fn name_of_params(k: u32, params_sha256: &str) -> String {
+    format!("K{}.{}.params", k, params_sha256)
+}
+
+/// Whether `file_name` is the name of params of size `k` and returns its digest.
+#[inline(always)]
+pub(crate) fn params_sha256_of_name(k: u32, file_name: &str) -> Option<&str> {
+    file_name
+        .strip_prefix(&format!("K{}.", k))?
+        .strip_suffix(".params")
 }
 
 #[inline(always)]
@@ -9,11 +17,16 @@ pub(crate) fn name_of_config(name: &str) -> String {
 }
 
 #[inline(always)]
-pub(crate) fn name_of_circuit_data(name: &str, is_last_circuit: bool) -> String {
-    if is_last_circuit {
-        format!("{}.circuit.finalized.data", name)
+pub(crate) fn name_of_circuit_data(circuit_data_sha256: &str) -> String {
+    format!("circuit.{}.data", circuit_data_sha256)
+}
+
+#[inline(always)]
+pub(crate) fn name_of_trace(name: &str, is_json_lines: bool) -> String {
+    if is_json_lines {
+        format!("{}.trace.jsonl", name)
     } else {
-        format!("{}.circuit.ongoing.data", name)
+        format!("{}.trace.txt", name)
     }
 }
 
@@ -33,9 +46,24 @@ pub(crate) fn name_of_instance(name: &str, index: usize) -> String {
     format!("{}.{}.instance.data", name, index)
 }
 
+/// The index of the slice whose instance is named `file_name`.
+#[inline(always)]
+pub(crate) fn index_of_instance(name: &str, file_name: &str) -> Option<usize> {
+    file_name
+        .strip_prefix(&format!("{}.", name))?
+        .strip_suffix(".instance.data")?
+        .parse()
+        .ok()
+}
+
+#[inline(always)]
+pub(crate) fn name_of_transcript(name: &str, index: usize, transcript_sha256: &str) -> String {
+    format!("{}.{}.{}.transcript.data", name, index, transcript_sha256)
+}
+
 #[inline(always)]
-pub(crate) fn name_of_transcript(name: &str, index: usize) -> String {
-    format!("{}.{}.transcript.data", name, index)
+pub(crate) fn name_of_traced_tables(name: &str) -> String {
+    format!("{}.tables.json", name)
 }
 
 #[inline(always)]
@@ -3,6 +3,8 @@ use halo2_proofs::pairing::bn256::G1Affine;
 use halo2_proofs::plonk::get_advice_commitments_from_transcript;
 use halo2_proofs::plonk::VerifyingKey;
 use halo2aggregator_s::transcript::poseidon::PoseidonRead;
+use sha2::Digest;
+use sha2::Sha256;
 
 pub fn get_named_advice_commitment(
     vkey: &VerifyingKey<G1Affine>,
@@ -20,3 +22,8 @@ pub fn get_named_advice_commitment(
     get_advice_commitments_from_transcript::<Bn256, _, _>(vkey, &mut PoseidonRead::init(proof))
         .unwrap()[img_col_idx as usize]
 }
+
+/// Hex encoded SHA-256 digest, artifacts are checked and named by it.
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
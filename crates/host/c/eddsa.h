@@ -0,0 +1,78 @@
+#ifndef EDDSA_H
+#define EDDSA_H
+
+#include <stdint.h>
+
+/*
+ * EdDSA over BabyJubJub with a Poseidon challenge, see
+ * `ecc_helper::jubjub::eddsa` of delphinus-host. The verification only drives
+ * the POSEIDONHASH and JUBJUBSUM host ops, so it is proved by their circuits.
+ *
+ * Points are (x, y) and field elements and scalars are 4 little-endian u64
+ * limbs.
+ */
+
+/* Poseidon Plugin */
+extern void poseidon_new(uint64_t);
+extern void poseidon_push(uint64_t);
+extern uint64_t poseidon_finalize();
+
+/* JubJub Sum Plugin */
+extern void babyjubjub_sum_new(uint64_t);
+extern void babyjubjub_sum_push(uint64_t);
+extern uint64_t babyjubjub_sum_finalize();
+
+/* -B8, the negated generator of the prime order subgroup */
+static const uint64_t EDDSA_NEG_B8[8] = {
+	0x1b4e019d346a8fb0, 0xfd7b104774848fdb, 0x69a392d5e41ee09b, 0x24acd4080af32c8e,
+	0x4b3c257a872d7d8b, 0xfce0051fb9e13377, 0x25572e1cd16bf9ed, 0x25797203f7a0b249,
+};
+
+/* Order of the prime order subgroup */
+static const uint64_t EDDSA_SUBORDER[4] = {
+	0x677297dc392126f1, 0xab3eedb83920ee0a, 0x370a08b6d0302b0b, 0x060c89ce5c263405,
+};
+
+/* Adds scalar * point to the accumulator, which is reset if new is set. */
+static inline void eddsa_accumulate(uint64_t new, const uint64_t* point, const uint64_t* scalar, uint64_t* acc)
+{
+	int i;
+	babyjubjub_sum_new(new);
+	for (i = 0; i < 8; i++) babyjubjub_sum_push(point[i]);
+	for (i = 0; i < 4; i++) babyjubjub_sum_push(scalar[i]);
+	for (i = 0; i < 8; i++) acc[i] = babyjubjub_sum_finalize();
+}
+
+/*
+ * Returns 1 if (r, s) is a signature of msg under the public key pk, i.e.
+ * s < suborder and s * B8 = R + Poseidon(R.x, R.y, A.x, A.y, M) * A.
+ */
+static inline int eddsa_verify(const uint64_t* pk, const uint64_t* msg, const uint64_t* r, const uint64_t* s)
+{
+	static const uint64_t one[4] = {1, 0, 0, 0};
+	uint64_t c[4], acc[8];
+	int i;
+
+	/* s is not reduced, (r, s + suborder) would be another valid signature */
+	for (i = 3; i >= 0; i--) {
+		if (s[i] != EDDSA_SUBORDER[i]) break;
+	}
+	if (i < 0 || s[i] > EDDSA_SUBORDER[i]) return 0;
+
+	poseidon_new(1);
+	for (i = 0; i < 8; i++) poseidon_push(r[i]);
+	for (i = 0; i < 8; i++) poseidon_push(pk[i]);
+	for (i = 0; i < 4; i++) poseidon_push(msg[i]);
+	for (i = 0; i < 12; i++) poseidon_push(0);
+	for (i = 0; i < 4; i++) c[i] = poseidon_finalize();
+
+	eddsa_accumulate(1, r, one, acc);
+	eddsa_accumulate(0, pk, c, acc);
+	eddsa_accumulate(0, EDDSA_NEG_B8, s, acc);
+
+	/* the identity is (0, 1) */
+	return acc[0] == 0 && acc[1] == 0 && acc[2] == 0 && acc[3] == 0
+		&& acc[4] == 1 && acc[5] == 0 && acc[6] == 0 && acc[7] == 0;
+}
+
+#endif
@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::Context;
+use serde::Deserialize;
+use serde::Serialize;
+use zkwasm_host_circuits::host::datahash::DataHashRecord;
+use zkwasm_host_circuits::host::db::TreeDB;
+use zkwasm_host_circuits::host::mongomerkle::MerkleRecord;
+
+type Hash = [u8; 32];
+
+/// A line of the log, hashes and data are hex encoded.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum LogEntry {
+    Merkle {
+        index: u64,
+        hash: String,
+        left: Option<String>,
+        right: Option<String>,
+        data: Option<String>,
+    },
+    Data {
+        hash: String,
+        data: String,
+    },
+}
+
+#[derive(PartialEq)]
+struct MerkleNode {
+    index: u64,
+    left: Option<Hash>,
+    right: Option<Hash>,
+    data: Option<Hash>,
+}
+
+fn decode_hash(hex: &str) -> anyhow::Result<Hash> {
+    hex::decode(hex)?
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("hash {} is not 32 bytes", hex))
+}
+
+fn decode_optional_hash(hex: &Option<String>) -> anyhow::Result<Option<Hash>> {
+    hex.as_deref().map(decode_hash).transpose()
+}
+
+/// A `TreeDB` persisted in a local file, so that Merkle and data-cache host
+/// functions work without MongoDB.
+///
+/// Records are appended to the file as JSON lines and loaded into memory when
+/// the file is opened, a later record overwrites an earlier one with the same hash.
+pub struct FileDB {
+    merkle_records: HashMap<Hash, MerkleNode>,
+    data_records: HashMap<Hash, Vec<u8>>,
+    log: BufWriter<File>,
+}
+
+impl FileDB {
+    /// Opens the database at `path`, the file is created if it does not exist.
+    pub fn open(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+
+        let file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)
+            .with_context(|| format!("failed to open tree db {:?}", path))?;
+
+        let mut db = FileDB {
+            merkle_records: HashMap::new(),
+            data_records: HashMap::new(),
+            log: BufWriter::new(file.try_clone()?),
+        };
+
+        for (line_number, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+
+            serde_json::from_str(&line)
+                .map_err(anyhow::Error::from)
+                .and_then(|entry| db.load(entry))
+                .with_context(|| format!("malformed record at {:?}:{}", path, line_number + 1))?;
+        }
+
+        Ok(db)
+    }
+
+    fn load(&mut self, entry: LogEntry) -> anyhow::Result<()> {
+        match entry {
+            LogEntry::Merkle {
+                index,
+                hash,
+                left,
+                right,
+                data,
+            } => {
+                self.merkle_records.insert(
+                    decode_hash(&hash)?,
+                    MerkleNode {
+                        index,
+                        left: decode_optional_hash(&left)?,
+                        right: decode_optional_hash(&right)?,
+                        data: decode_optional_hash(&data)?,
+                    },
+                );
+            }
+            LogEntry::Data { hash, data } => {
+                self.data_records
+                    .insert(decode_hash(&hash)?, hex::decode(data)?);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn append(&mut self, entry: &LogEntry) -> anyhow::Result<()> {
+        serde_json::to_writer(&mut self.log, entry)?;
+        writeln!(self.log)?;
+
+        Ok(())
+    }
+
+    fn insert_merkle_record(&mut self, record: &MerkleRecord) -> anyhow::Result<()> {
+        let node = MerkleNode {
+            index: record.index,
+            left: record.left,
+            right: record.right,
+            data: record.data,
+        };
+
+        if self.merkle_records.get(&record.hash) == Some(&node) {
+            return Ok(());
+        }
+
+        self.append(&LogEntry::Merkle {
+            index: record.index,
+            hash: hex::encode(record.hash),
+            left: record.left.map(hex::encode),
+            right: record.right.map(hex::encode),
+            data: record.data.map(hex::encode),
+        })?;
+        self.merkle_records.insert(record.hash, node);
+
+        Ok(())
+    }
+}
+
+impl TreeDB for FileDB {
+    fn get_merkle_record(&self, hash: &[u8; 32]) -> Result<Option<MerkleRecord>, anyhow::Error> {
+        Ok(self.merkle_records.get(hash).map(|node| MerkleRecord {
+            index: node.index,
+            hash: *hash,
+            left: node.left,
+            right: node.right,
+            data: node.data,
+        }))
+    }
+
+    fn set_merkle_record(&mut self, record: MerkleRecord) -> Result<(), anyhow::Error> {
+        self.insert_merkle_record(&record)?;
+        self.log.flush()?;
+
+        Ok(())
+    }
+
+    fn set_merkle_records(&mut self, records: &Vec<MerkleRecord>) -> Result<(), anyhow::Error> {
+        for record in records {
+            self.insert_merkle_record(record)?;
+        }
+        self.log.flush()?;
+
+        Ok(())
+    }
+
+    fn get_data_record(&self, hash: &[u8; 32]) -> Result<Option<DataHashRecord>, anyhow::Error> {
+        Ok(self.data_records.get(hash).map(|data| DataHashRecord {
+            hash: *hash,
+            data: data.clone(),
+        }))
+    }
+
+    fn set_data_record(&mut self, record: DataHashRecord) -> Result<(), anyhow::Error> {
+        if self.data_records.get(&record.hash) == Some(&record.data) {
+            return Ok(());
+        }
+
+        self.append(&LogEntry::Data {
+            hash: hex::encode(record.hash),
+            data: hex::encode(&record.data),
+        })?;
+        self.log.flush()?;
+
+        self.data_records.insert(record.hash, record.data);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FileDB;
+    use zkwasm_host_circuits::host::datahash::DataHashRecord;
+    use zkwasm_host_circuits::host::db::TreeDB;
+    use zkwasm_host_circuits::host::mongomerkle::MerkleRecord;
+
+    #[test]
+    fn test_records_persist_between_opens() {
+        let path =
+            std::env::temp_dir().join(format!("zkwasm-file-db-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        {
+            let mut db = FileDB::open(&path).unwrap();
+            db.set_merkle_records(&vec![MerkleRecord {
+                index: 3,
+                hash: [1; 32],
+                left: Some([2; 32]),
+                right: Some([3; 32]),
+                data: None,
+            }])
+            .unwrap();
+            db.set_data_record(DataHashRecord {
+                hash: [4; 32],
+                data: vec![5, 6, 7],
+            })
+            .unwrap();
+        }
+
+        let db = FileDB::open(&path).unwrap();
+        let loaded = db.get_merkle_record(&[1; 32]).unwrap().unwrap();
+        assert_eq!(loaded.index, 3);
+        assert_eq!(loaded.left, Some([2; 32]));
+        assert_eq!(loaded.right, Some([3; 32]));
+        assert_eq!(loaded.data, None);
+        assert_eq!(
+            db.get_data_record(&[4; 32]).unwrap().unwrap().data,
+            vec![5, 6, 7]
+        );
+        assert!(db.get_merkle_record(&[0; 32]).unwrap().is_none());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
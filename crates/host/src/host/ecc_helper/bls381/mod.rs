@@ -1,6 +1,8 @@
 pub mod pair;
 pub mod sum;
+use crate::HostOp;
 use ark_std::Zero;
+use delphinus_zkwasm::runtime::monitor::plugins::table::Command;
 use halo2_proofs::arithmetic::CurveAffine;
 use halo2_proofs::pairing::bls12_381::Fp2 as Bls381Fq2;
 use halo2_proofs::pairing::bls12_381::Fq as Bls381Fq;
@@ -8,14 +10,22 @@ use halo2_proofs::pairing::bls12_381::G1Affine;
 use num_bigint::BigUint;
 use num_traits::FromPrimitive;
 use std::ops::AddAssign;
+use zkwasm_host_circuits::circuits::bls::Bls381PairChip;
+use zkwasm_host_circuits::circuits::bls::Bls381SumChip;
+use zkwasm_host_circuits::circuits::host::HostOpSelector;
+use zkwasm_host_circuits::host::ForeignInst;
 
 use super::bn_to_field;
 use super::field_to_bn;
+use crate::PluginFlushStrategy;
+
+const LIMBSZ: usize = 54;
+const LIMBNB: usize = 8;
 
 fn fetch_fq(limbs: &[u64], index: usize) -> Bls381Fq {
     let mut bn = BigUint::zero();
-    for i in 0..8 {
-        bn.add_assign(BigUint::from_u64(limbs[index * 8 + i]).unwrap() << (i * 54))
+    for i in 0..LIMBNB {
+        bn.add_assign(BigUint::from_u64(limbs[index * LIMBNB + i]).unwrap() << (i * LIMBSZ))
     }
     bn_to_field(&bn)
 }
@@ -36,10 +46,10 @@ fn fetch_g1(limbs: &[u64], g1_identity: bool) -> G1Affine {
     }
 }
 
-fn bls381_fq_to_limbs(result_limbs: &mut Vec<u64>, f: Bls381Fq) {
+pub(crate) fn bls381_fq_to_limbs(result_limbs: &mut Vec<u64>, f: Bls381Fq) {
     let mut bn = field_to_bn(&f);
-    for _ in 0..8 {
-        let d: BigUint = BigUint::from(1u64 << 54);
+    for _ in 0..LIMBNB {
+        let d: BigUint = BigUint::from(1u64 << LIMBSZ);
         let r = bn.clone() % d.clone();
         let value = if r == BigUint::from(0_u32) {
             0_u64
@@ -50,3 +60,119 @@ fn bls381_fq_to_limbs(result_limbs: &mut Vec<u64>, f: Bls381Fq) {
         result_limbs.append(&mut vec![value]);
     }
 }
+
+/// A pairing or a sum is a group which starts at its first input and is
+/// committed by the last pop of its result.
+pub(crate) struct Bls381FlushStrategy {
+    op_type: HostOp,
+    result_inst: usize,
+    result_size: usize,
+    results: usize,
+    in_transaction: bool,
+    group: usize,
+    maximal_group: usize,
+}
+
+impl Bls381FlushStrategy {
+    pub(crate) fn new_pair(k: u32) -> Self {
+        Self::new(
+            HostOp::Bls381Pair,
+            ForeignInst::BlsPairG3 as usize,
+            // 24 fq of gt
+            24 * LIMBNB,
+            Bls381PairChip::max_rounds(k as usize),
+        )
+    }
+
+    pub(crate) fn new_sum(k: u32) -> Self {
+        Self::new(
+            HostOp::Bls381Sum,
+            ForeignInst::BlsSumResult as usize,
+            // x + y + identity
+            2 * LIMBNB + 1,
+            Bls381SumChip::max_rounds(k as usize),
+        )
+    }
+
+    fn new(op_type: HostOp, result_inst: usize, result_size: usize, maximal_group: usize) -> Self {
+        Self {
+            op_type,
+            result_inst,
+            result_size,
+            results: 0,
+            in_transaction: false,
+            group: 0,
+            maximal_group,
+        }
+    }
+}
+
+impl PluginFlushStrategy for Bls381FlushStrategy {
+    fn notify(&mut self, op: usize, _value: Option<u64>) -> Vec<Command> {
+        let op_type = self.op_type as usize;
+
+        if !self.in_transaction {
+            self.in_transaction = true;
+
+            return vec![Command::Start(op_type)];
+        }
+
+        if op == self.result_inst {
+            self.results += 1;
+
+            if self.results == self.result_size {
+                self.results = 0;
+                self.in_transaction = false;
+                self.group += 1;
+
+                let mut commands = vec![Command::Commit(op_type, true)];
+
+                if self.group >= self.maximal_group {
+                    commands.push(Command::Abort);
+                }
+
+                return commands;
+            }
+        }
+
+        vec![Command::Noop]
+    }
+
+    fn reset(&mut self) {
+        self.group = 0;
+    }
+
+    fn maximal_group(&self) -> Option<usize> {
+        Some(self.maximal_group)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Bls381FlushStrategy;
+    use crate::PluginFlushStrategy;
+    use delphinus_zkwasm::runtime::monitor::plugins::table::Command;
+    use zkwasm_host_circuits::host::ForeignInst;
+
+    #[test]
+    fn test_bls381_sum_flush_strategy() {
+        let mut strategy = Bls381FlushStrategy::new_sum(22);
+
+        for round in 1..=2 {
+            let mut commands = strategy.notify(ForeignInst::BlsSumG1 as usize, Some(0));
+            assert!(commands == vec![Command::Start(1)]);
+
+            // the rest of two points
+            for _ in 1..34 {
+                commands = strategy.notify(ForeignInst::BlsSumG1 as usize, Some(0));
+                assert!(commands == vec![Command::Noop]);
+            }
+
+            for _ in 0..17 {
+                commands = strategy.notify(ForeignInst::BlsSumResult as usize, None);
+            }
+            assert!(commands == vec![Command::Commit(1, true)]);
+            assert_eq!(strategy.group, round);
+        }
+    }
+}
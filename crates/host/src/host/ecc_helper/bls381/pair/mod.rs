@@ -1,12 +1,11 @@
 use super::bls381_fq_to_limbs;
-use super::fetch_fq;
 use super::fetch_fq2;
+use super::fetch_g1;
+use super::LIMBNB;
 use delphinus_zkwasm::runtime::host::host_env::HostEnv;
 use delphinus_zkwasm::runtime::host::ForeignContext;
 use delphinus_zkwasm::runtime::host::ForeignStatics;
-use halo2_proofs::arithmetic::CurveAffine;
 use halo2_proofs::pairing::bls12_381::pairing;
-use halo2_proofs::pairing::bls12_381::G1Affine;
 use halo2_proofs::pairing::bls12_381::G2Affine;
 use halo2_proofs::pairing::bls12_381::Gt as Bls381Gt;
 use std::rc::Rc;
@@ -15,7 +14,7 @@ use zkwasm_host_circuits::circuits::host::HostOpSelector;
 use zkwasm_host_circuits::host::ForeignInst;
 
 #[derive(Default)]
-struct BlsPairContext {
+pub(crate) struct BlsPairContext {
     pub limbs: Vec<u64>,
     pub g1_identity: bool,
     pub g2_identity: bool,
@@ -26,7 +25,7 @@ struct BlsPairContext {
 }
 
 impl BlsPairContext {
-    fn bls381_gt_to_limbs(&mut self, g: Bls381Gt) {
+    pub(crate) fn bls381_gt_to_limbs(&mut self, g: Bls381Gt) {
         bls381_fq_to_limbs(&mut self.result_limbs, g.0.c0.c0.c0);
         bls381_fq_to_limbs(&mut self.result_limbs, g.0.c0.c0.c1);
         bls381_fq_to_limbs(&mut self.result_limbs, g.0.c0.c1.c0);
@@ -52,6 +51,49 @@ impl BlsPairContext {
         bls381_fq_to_limbs(&mut self.result_limbs, g.0.c1.c2.c0);
         bls381_fq_to_limbs(&mut self.result_limbs, g.0.c1.c2.c1);
     }
+
+    pub(crate) fn blspair_g1(&mut self, v: u64) {
+        if self.input_cursor == 2 * LIMBNB {
+            self.g1_identity = v != 0;
+            self.input_cursor = 0;
+        } else {
+            self.limbs.push(v);
+            self.input_cursor += 1;
+        }
+    }
+
+    pub(crate) fn blspair_g2(&mut self, v: u64) {
+        if self.input_cursor == 4 * LIMBNB {
+            self.g2_identity = v != 0;
+            let g1 = fetch_g1(&self.limbs, self.g1_identity);
+            let g2 = if self.g2_identity {
+                G2Affine::identity()
+            } else {
+                G2Affine {
+                    x: fetch_fq2(&self.limbs, 2),
+                    y: fetch_fq2(&self.limbs, 4),
+                    infinity: 0_u8.into(),
+                }
+            };
+            self.input_cursor = 0;
+            self.limbs = vec![];
+            let ab = pairing(&g1, &g2);
+            log::debug!("gt {:?}", ab);
+            self.result_limbs = vec![];
+            self.result_cursor = 0;
+            self.bls381_gt_to_limbs(ab);
+            self.used_round += 1;
+        } else {
+            self.limbs.push(v);
+            self.input_cursor += 1;
+        }
+    }
+
+    pub(crate) fn blspair_pop(&mut self) -> u64 {
+        let ret = self.result_limbs[self.result_cursor];
+        self.result_cursor += 1;
+        ret
+    }
 }
 
 impl ForeignContext for BlsPairContext {
@@ -77,14 +119,7 @@ pub fn register_blspair_foreign(env: &mut HostEnv) {
         Rc::new(
             |_obs, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<BlsPairContext>().unwrap();
-                if context.input_cursor == 16 {
-                    let t: u64 = args.nth(0);
-                    context.g1_identity = t != 0;
-                    context.input_cursor = 0;
-                } else {
-                    context.limbs.push(args.nth(0));
-                    context.input_cursor += 1;
-                }
+                context.blspair_g1(args.nth(0));
                 None
             },
         ),
@@ -97,32 +132,7 @@ pub fn register_blspair_foreign(env: &mut HostEnv) {
         Rc::new(
             |_obs, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<BlsPairContext>().unwrap();
-                if context.input_cursor == 32 {
-                    let t: u64 = args.nth(0);
-                    context.g2_identity = t != 0;
-                    let g1 = if context.g1_identity {
-                        G1Affine::identity()
-                    } else {
-                        G1Affine::from_xy(fetch_fq(&context.limbs, 0), fetch_fq(&context.limbs, 1))
-                            .unwrap()
-                    };
-                    let g2 = if context.g2_identity {
-                        G2Affine::identity()
-                    } else {
-                        G2Affine {
-                            x: fetch_fq2(&context.limbs, 2),
-                            y: fetch_fq2(&context.limbs, 4),
-                            infinity: 0_u8.into(),
-                        }
-                    };
-                    let ab = pairing(&g1, &g2);
-                    log::debug!("gt {:?}", ab);
-                    context.bls381_gt_to_limbs(ab);
-                    context.used_round += 1;
-                } else {
-                    context.limbs.push(args.nth(0));
-                    context.input_cursor += 1;
-                };
+                context.blspair_g2(args.nth(0));
                 None
             },
         ),
@@ -136,11 +146,7 @@ pub fn register_blspair_foreign(env: &mut HostEnv) {
         Rc::new(
             |_obs, context: &mut dyn ForeignContext, _args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<BlsPairContext>().unwrap();
-                let ret = Some(wasmi::RuntimeValue::I64(
-                    context.result_limbs[context.result_cursor] as i64,
-                ));
-                context.result_cursor += 1;
-                ret
+                Some(wasmi::RuntimeValue::I64(context.blspair_pop() as i64))
             },
         ),
     );
@@ -7,13 +7,14 @@ use std::rc::Rc;
 
 use super::bls381_fq_to_limbs;
 use super::fetch_g1;
+use super::LIMBNB;
 
 use zkwasm_host_circuits::circuits::bls::Bls381SumChip;
 use zkwasm_host_circuits::circuits::host::HostOpSelector;
 use zkwasm_host_circuits::host::ForeignInst;
 
 #[derive(Default)]
-struct BlsSumContext {
+pub(crate) struct BlsSumContext {
     pub limbs: Vec<u64>,
     pub g1_identity: Vec<bool>,
     pub result_limbs: Option<Vec<u64>>,
@@ -34,6 +35,46 @@ impl BlsSumContext {
             self.result_limbs.as_mut().unwrap().append(&mut vec![0u64]);
         }
     }
+
+    pub(crate) fn blssum_g1(&mut self, v: u64) {
+        if self.input_cursor == 2 * LIMBNB {
+            self.g1_identity.push(v != 0);
+            self.input_cursor = 0;
+        } else {
+            self.limbs.push(v);
+            self.input_cursor += 1;
+        }
+    }
+
+    pub(crate) fn blssum_pop(&mut self) -> u64 {
+        if self.result_limbs.is_none() {
+            let g1s = self
+                .limbs
+                .chunks(2 * LIMBNB)
+                .zip(self.g1_identity.iter())
+                .map(|(limbs, identity)| fetch_g1(limbs, *identity))
+                .collect::<Vec<G1Affine>>();
+            let g1result = g1s[1..]
+                .iter()
+                .fold(g1s[0], |acc: G1Affine, x| acc.add(*x).into());
+            self.bls381_result_to_limbs(g1result);
+            self.used_round += 1;
+        }
+
+        let limbs = self.result_limbs.as_ref().unwrap();
+        let ret = limbs[self.result_cursor];
+        self.result_cursor += 1;
+
+        // the whole result is popped, start a new sum
+        if self.result_cursor == limbs.len() {
+            *self = BlsSumContext {
+                used_round: self.used_round,
+                ..Default::default()
+            };
+        }
+
+        ret
+    }
 }
 
 impl ForeignContext for BlsSumContext {
@@ -59,14 +100,7 @@ pub fn register_blssum_foreign(env: &mut HostEnv) {
         Rc::new(
             |_obs, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<BlsSumContext>().unwrap();
-                if context.input_cursor == 16 {
-                    let t: u64 = args.nth(0);
-                    context.g1_identity.push(t != 0);
-                    context.input_cursor = 0;
-                } else {
-                    context.limbs.push(args.nth(0));
-                    context.input_cursor += 1;
-                }
+                context.blssum_g1(args.nth(0));
                 None
             },
         ),
@@ -80,27 +114,7 @@ pub fn register_blssum_foreign(env: &mut HostEnv) {
         Rc::new(
             |_obs, context: &mut dyn ForeignContext, _args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<BlsSumContext>().unwrap();
-                context.result_limbs.clone().map_or_else(
-                    || {
-                        let fqs = context
-                            .limbs
-                            .chunks(16)
-                            .zip(context.g1_identity.clone())
-                            .map(|(limbs, identity)| fetch_g1(limbs, identity))
-                            .collect::<Vec<G1Affine>>();
-                        let g1result = fqs[1..fqs.len()]
-                            .iter()
-                            .fold(fqs[0], |acc: G1Affine, x| acc.add(*x).into());
-                        context.bls381_result_to_limbs(g1result);
-                    },
-                    |_| (),
-                );
-                let limbs = context.result_limbs.clone().unwrap();
-                let ret = Some(wasmi::RuntimeValue::I64(
-                    limbs[context.result_cursor] as i64,
-                ));
-                context.result_cursor += 1;
-                ret
+                Some(wasmi::RuntimeValue::I64(context.blssum_pop() as i64))
             },
         ),
     );
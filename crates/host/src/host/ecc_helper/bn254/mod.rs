@@ -1,3 +1,4 @@
+pub mod multipair;
 pub mod pair;
 pub mod sum;
 use ark_std::Zero;
@@ -0,0 +1,290 @@
+use crate::HostOp;
+use delphinus_zkwasm::error::HostCallError;
+use delphinus_zkwasm::runtime::host::host_env::HostEnv;
+use delphinus_zkwasm::runtime::host::ForeignContext;
+use delphinus_zkwasm::runtime::host::ForeignStatics;
+use delphinus_zkwasm::runtime::monitor::plugins::table::Command;
+use halo2_proofs::arithmetic::CurveAffine;
+use halo2_proofs::pairing::bn256::pairing;
+use halo2_proofs::pairing::bn256::G1Affine;
+use halo2_proofs::pairing::bn256::G2Affine;
+use halo2_proofs::pairing::bn256::Gt as BN254Gt;
+use halo2_proofs::pairing::group::prime::PrimeCurveAffine;
+use std::rc::Rc;
+use zkwasm_host_circuits::circuits::bn256::Bn256PairChip;
+use zkwasm_host_circuits::circuits::host::HostOpSelector;
+
+use super::fetch_fq;
+use super::fetch_fq2;
+use super::LIMBNB;
+use crate::PluginFlushStrategy;
+
+/// Host calls of the multi-pairing, they have no counterpart in `ForeignInst`
+/// of zkwasm-host-circuits and are placed after it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bn254MultiPairInst {
+    G1 = 0x120,
+    G2,
+    Result,
+}
+
+impl Bn254MultiPairInst {
+    pub fn from_usize(op: usize) -> Option<Self> {
+        match op {
+            0x120 => Some(Bn254MultiPairInst::G1),
+            0x121 => Some(Bn254MultiPairInst::G2),
+            0x122 => Some(Bn254MultiPairInst::Result),
+            _ => None,
+        }
+    }
+}
+
+/// Maximal number of pairs of a check, every pair takes a round of the pairing circuit.
+pub const MAX_PAIRS: usize = 4;
+
+fn fetch_g1(limbs: &[u64]) -> Result<G1Affine, HostCallError> {
+    if limbs[LIMBNB * 2] != 0 {
+        return Ok(G1Affine::identity());
+    }
+
+    let opt: Option<_> = G1Affine::from_xy(fetch_fq(limbs, 0), fetch_fq(limbs, 1)).into();
+    opt.ok_or_else(|| HostCallError::InvalidArgument("g1 is not on the bn254 curve".to_string()))
+}
+
+fn fetch_g2(limbs: &[u64]) -> Result<G2Affine, HostCallError> {
+    if limbs[LIMBNB * 4] != 0 {
+        return Ok(G2Affine::identity());
+    }
+
+    let opt: Option<_> = G2Affine::from_xy(fetch_fq2(limbs, 0), fetch_fq2(limbs, 2)).into();
+    opt.ok_or_else(|| HostCallError::InvalidArgument("g2 is not on the bn254 curve".to_string()))
+}
+
+/// Foreign functions that supports the following C code library
+///
+/// ```c
+/// // g1 is x, y and the identity flag, g2 is x.c0, x.c1, y.c0, y.c1 and the
+/// // identity flag, coordinates are 5 limbs of 54 bits
+/// int bn254_multipair(uint64_t* g1, uint64_t* g2, int n)
+/// {
+///     int i, j;
+///     for(i=0; i<n; i++) {
+///         for(j=0; j<11; j++) bn254multipair_g1(g1[i * 11 + j]);
+///         for(j=0; j<21; j++) bn254multipair_g2(g2[i * 21 + j]);
+///     }
+///     // 1 if the product of e(g1[i], g2[i]) is one
+///     return bn254multipair_result();
+/// }
+/// ```
+#[derive(Default)]
+pub(crate) struct BN254MultiPairContext {
+    pub limbs: Vec<u64>,
+    pub g1: Option<G1Affine>,
+    pub gt: Option<BN254Gt>,
+    pub pairs: usize,
+    pub used_round: usize,
+}
+
+impl BN254MultiPairContext {
+    pub(crate) fn bn254multipair_g1(&mut self, v: u64) -> Result<(), HostCallError> {
+        if self.g1.is_some() {
+            return Err(HostCallError::InvalidCallSequence(
+                "g1 of a pair is pushed before the g2 of the previous pair".to_string(),
+            ));
+        }
+
+        self.limbs.push(v);
+
+        if self.limbs.len() == LIMBNB * 2 + 1 {
+            let limbs = std::mem::take(&mut self.limbs);
+            self.g1 = Some(fetch_g1(&limbs)?);
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn bn254multipair_g2(&mut self, v: u64) -> Result<(), HostCallError> {
+        if self.g1.is_none() {
+            return Err(HostCallError::InvalidCallSequence(
+                "g2 of a pair is pushed before its g1".to_string(),
+            ));
+        }
+
+        self.limbs.push(v);
+
+        if self.limbs.len() == LIMBNB * 4 + 1 {
+            let limbs = std::mem::take(&mut self.limbs);
+            let g1 = self.g1.take().unwrap();
+            let g2 = fetch_g2(&limbs)?;
+
+            if self.pairs == MAX_PAIRS {
+                return Err(HostCallError::InvalidCallSequence(format!(
+                    "a multi-pairing takes at most {} pairs",
+                    MAX_PAIRS
+                )));
+            }
+            self.pairs += 1;
+            self.used_round += 1;
+
+            let ab = pairing(&g1, &g2);
+            self.gt = Some(self.gt.map_or_else(|| ab, |x| x + ab));
+        }
+
+        Ok(())
+    }
+
+    /// Returns 1 if the product of the pairings is one and resets the context.
+    pub(crate) fn bn254multipair_result(&mut self) -> Result<u64, HostCallError> {
+        if self.g1.is_some() || !self.limbs.is_empty() {
+            return Err(HostCallError::InvalidCallSequence(
+                "the last pair of the multi-pairing is not complete".to_string(),
+            ));
+        }
+
+        let gt = self.gt.take();
+        self.pairs = 0;
+
+        Ok(gt.map_or(true, |gt| gt == BN254Gt::identity()) as u64)
+    }
+}
+
+impl ForeignContext for BN254MultiPairContext {
+    fn get_statics(&self, k: u32) -> Option<ForeignStatics> {
+        Some(ForeignStatics {
+            used_round: self.used_round,
+            max_round: Bn256PairChip::max_rounds(k as usize),
+        })
+    }
+}
+
+/// A check is a group which starts at its first input and is committed by its
+/// result. Every pair takes a round of `Bn256PairChip`, groups are counted by
+/// their maximal number of pairs so that a slice never exceeds its rounds.
+pub(crate) struct Bn254PairFlushStrategy {
+    in_transaction: bool,
+    group: usize,
+    maximal_group: usize,
+}
+
+impl Bn254PairFlushStrategy {
+    pub(crate) fn new(k: u32) -> Self {
+        Self {
+            in_transaction: false,
+            group: 0,
+            maximal_group: Bn256PairChip::max_rounds(k as usize) / MAX_PAIRS,
+        }
+    }
+}
+
+impl PluginFlushStrategy for Bn254PairFlushStrategy {
+    fn notify(&mut self, op: usize, _value: Option<u64>) -> Vec<Command> {
+        let op_type = HostOp::Bn256MultiPair as usize;
+
+        if op == Bn254MultiPairInst::Result as usize {
+            self.in_transaction = false;
+            self.group += 1;
+
+            let mut commands = vec![Command::Commit(op_type, true)];
+
+            if self.group >= self.maximal_group {
+                commands.push(Command::Abort);
+            }
+
+            return commands;
+        }
+
+        if !self.in_transaction {
+            self.in_transaction = true;
+
+            return vec![Command::Start(op_type)];
+        }
+
+        vec![Command::Noop]
+    }
+
+    fn reset(&mut self) {
+        self.group = 0;
+    }
+
+    fn maximal_group(&self) -> Option<usize> {
+        Some(self.maximal_group)
+    }
+}
+
+use specs::external_host_call_table::ExternalHostCallSignature;
+pub fn register_bn254multipair_foreign(env: &mut HostEnv) {
+    let foreign_multipair_plugin = env.external_env.register_plugin(
+        "foreign_bn254multipair",
+        Box::<BN254MultiPairContext>::default(),
+    );
+
+    env.external_env.register_fallible_function(
+        "bn254multipair_g1",
+        Bn254MultiPairInst::G1 as usize,
+        ExternalHostCallSignature::Argument,
+        foreign_multipair_plugin.clone(),
+        Rc::new(
+            |_obs, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
+                let context = context.downcast_mut::<BN254MultiPairContext>().unwrap();
+                context.bn254multipair_g1(args.nth(0))?;
+                Ok(None)
+            },
+        ),
+    );
+
+    env.external_env.register_fallible_function(
+        "bn254multipair_g2",
+        Bn254MultiPairInst::G2 as usize,
+        ExternalHostCallSignature::Argument,
+        foreign_multipair_plugin.clone(),
+        Rc::new(
+            |_obs, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
+                let context = context.downcast_mut::<BN254MultiPairContext>().unwrap();
+                context.bn254multipair_g2(args.nth(0))?;
+                Ok(None)
+            },
+        ),
+    );
+
+    env.external_env.register_fallible_function(
+        "bn254multipair_result",
+        Bn254MultiPairInst::Result as usize,
+        ExternalHostCallSignature::Return,
+        foreign_multipair_plugin,
+        Rc::new(
+            |_obs, context: &mut dyn ForeignContext, _args: wasmi::RuntimeArgs| {
+                let context = context.downcast_mut::<BN254MultiPairContext>().unwrap();
+                Ok(Some(wasmi::RuntimeValue::I64(
+                    context.bn254multipair_result()? as i64,
+                )))
+            },
+        ),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Bn254MultiPairInst;
+    use super::Bn254PairFlushStrategy;
+    use crate::PluginFlushStrategy;
+    use delphinus_zkwasm::runtime::monitor::plugins::table::Command;
+
+    #[test]
+    fn test_bn254_pair_flush_strategy() {
+        let mut strategy = Bn254PairFlushStrategy::new(22);
+
+        for pairs in 1..=2 {
+            let commands = strategy.notify(Bn254MultiPairInst::G1 as usize, Some(0));
+            assert!(commands == vec![Command::Start(10)]);
+
+            for _ in 1..pairs * 32 {
+                let commands = strategy.notify(Bn254MultiPairInst::G2 as usize, Some(0));
+                assert!(commands == vec![Command::Noop]);
+            }
+
+            let commands = strategy.notify(Bn254MultiPairInst::Result as usize, None);
+            assert!(commands == vec![Command::Commit(10, true)]);
+            assert_eq!(strategy.group, pairs);
+        }
+    }
+}
@@ -0,0 +1,541 @@
+use delphinus_zkwasm::error::HostCallError;
+use delphinus_zkwasm::runtime::host::host_env::HostEnv;
+use delphinus_zkwasm::runtime::host::ForeignContext;
+use delphinus_zkwasm::runtime::host::ForeignStatics;
+use delphinus_zkwasm::runtime::monitor::plugins::table::Command;
+use halo2_proofs::pairing::bn256::Fr;
+use halo2_proofs::pairing::group::ff::Field;
+use num_bigint::BigUint;
+use std::rc::Rc;
+use zkwasm_host_circuits::circuits::babyjub::AltJubChip;
+use zkwasm_host_circuits::circuits::host::HostOpSelector;
+use zkwasm_host_circuits::circuits::poseidon::PoseidonChip;
+use zkwasm_host_circuits::host::jubjub;
+
+use super::super::bn_to_field;
+use super::super::field_to_bn;
+use super::babyjubjub_fq_to_limbs;
+use super::fetch_fq;
+use super::sum::BabyJubjubSumContext;
+use super::LIMBNB;
+use crate::host::hash_helper::poseidon::PoseidonContext;
+use crate::HostOp;
+use crate::PluginFlushStrategy;
+
+/// Host calls of the EdDSA verification, they have no counterpart in
+/// `ForeignInst` of zkwasm-host-circuits and are placed after it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EddsaInst {
+    Push = 0x130,
+    Verify,
+}
+
+impl EddsaInst {
+    pub fn from_usize(op: usize) -> Option<Self> {
+        match op {
+            0x130 => Some(EddsaInst::Push),
+            0x131 => Some(EddsaInst::Verify),
+            _ => None,
+        }
+    }
+}
+
+/// Limbs of a verification, the public key, the message, R and s.
+const VERIFY_LIMBS: usize = 2 * LIMBNB + LIMBNB + 2 * LIMBNB + LIMBNB;
+
+/// EdDSA over BabyJubJub with a Poseidon challenge, a signature `(R, s)` of
+/// the message `M` under the public key `A` is valid if `s < suborder` and
+/// `s * B8 = R + Poseidon(R.x, R.y, A.x, A.y, M) * A`.
+///
+/// The verification is a sequence of Poseidon and JubJub sum host calls. A
+/// guest makes them itself with `eddsa_verify` of `c/eddsa.h`, so they are
+/// proved by their circuits, or calls the `EDDSA` host op which makes them on
+/// the host and whose result is not constrained, see `HostOp::is_proven`.
+///
+/// The `EDDSA` host op supports the following C code library
+///
+/// ```c
+/// // points are (x, y), field elements and scalars are 4 little-endian u64 limbs
+/// int eddsa_verify(uint64_t* pk, uint64_t* msg, uint64_t* r, uint64_t* s)
+/// {
+///     int i;
+///     for(i=0; i<8; i++) eddsa_push(pk[i]);
+///     for(i=0; i<4; i++) eddsa_push(msg[i]);
+///     for(i=0; i<8; i++) eddsa_push(r[i]);
+///     for(i=0; i<4; i++) eddsa_push(s[i]);
+///     return eddsa_verify_result();
+/// }
+/// ```
+#[derive(Default)]
+pub struct EddsaContext {
+    pub poseidon: PoseidonContext,
+    pub sum: BabyJubjubSumContext,
+    pub limbs: Vec<u64>,
+    pub used_round: usize,
+}
+
+pub struct Signature {
+    pub r: jubjub::Point,
+    pub s: BigUint,
+}
+
+fn fr_from_str(s: &str) -> Fr {
+    bn_to_field(&BigUint::parse_bytes(s.as_bytes(), 10).unwrap())
+}
+
+/// The generator of the prime order subgroup.
+pub fn base_point() -> jubjub::Point {
+    jubjub::Point {
+        x: fr_from_str(
+            "5299619240641551281634865583518297030282874472190772894086521144482721001553",
+        ),
+        y: fr_from_str(
+            "16950150798460657717958625567821834550301663161624707787222815936182638968203",
+        ),
+    }
+}
+
+/// Order of the prime order subgroup.
+pub fn suborder() -> BigUint {
+    BigUint::parse_bytes(
+        b"2736030358979909402780800718157159386076813972158567259200215660948447373041",
+        10,
+    )
+    .unwrap()
+}
+
+fn fetch_biguint(limbs: &[u64]) -> BigUint {
+    limbs
+        .iter()
+        .rev()
+        .fold(BigUint::default(), |acc, limb| (acc << 64) + *limb)
+}
+
+fn fetch_field(limbs: &[u64], index: usize) -> Result<Fr, HostCallError> {
+    let modulus = field_to_bn(&-Fr::one()) + 1u64;
+
+    if fetch_biguint(&limbs[index * LIMBNB..(index + 1) * LIMBNB]) >= modulus {
+        return Err(HostCallError::InvalidArgument(
+            "eddsa field element is not reduced".to_string(),
+        ));
+    }
+
+    Ok(fetch_fq(limbs, index))
+}
+
+/// Rounds of a verification, a hash and 3 sums share the capacity of the
+/// Poseidon and JubJub sum circuits.
+fn max_rounds(k: u32) -> usize {
+    PoseidonChip::max_rounds(k as usize).min(AltJubChip::<Fr>::max_rounds(k as usize) / 3)
+}
+
+fn scalar_to_limbs(scalar: &BigUint) -> Vec<u64> {
+    let mut limbs = scalar.to_u64_digits();
+    assert!(limbs.len() <= LIMBNB, "scalar {} exceeds 256 bits", scalar);
+    limbs.resize(LIMBNB, 0);
+    limbs
+}
+
+fn point_to_limbs(point: &jubjub::Point) -> Vec<u64> {
+    let mut limbs = vec![];
+    babyjubjub_fq_to_limbs(&mut limbs, point.x);
+    babyjubjub_fq_to_limbs(&mut limbs, point.y);
+    limbs
+}
+
+impl EddsaContext {
+    /// Poseidon(R.x, R.y, A.x, A.y, M) padded with zeros to 8 field elements.
+    pub fn challenge(&mut self, r: &jubjub::Point, pk: &jubjub::Point, msg: &Fr) -> BigUint {
+        let mut limbs = point_to_limbs(r);
+        limbs.extend(point_to_limbs(pk));
+        babyjubjub_fq_to_limbs(&mut limbs, *msg);
+        limbs.resize(8 * LIMBNB, 0);
+
+        self.poseidon.poseidon_new(1);
+        for limb in limbs {
+            self.poseidon.poseidon_push(limb);
+        }
+
+        let hash = (0..LIMBNB)
+            .map(|_| self.poseidon.poseidon_finalize())
+            .collect::<Vec<_>>();
+
+        fetch_biguint(&hash)
+    }
+
+    /// Adds `scalar * point` to the accumulator, which is reset if `new` is set.
+    fn accumulate(&mut self, new: bool, point: &jubjub::Point, scalar: &BigUint) -> jubjub::Point {
+        self.sum.babyjubjub_sum_new(new as usize);
+        for limb in point_to_limbs(point)
+            .into_iter()
+            .chain(scalar_to_limbs(scalar))
+        {
+            self.sum.babyjubjub_sum_push(limb);
+        }
+
+        let limbs = (0..2 * LIMBNB)
+            .map(|_| self.sum.babyjubjub_sum_finalize())
+            .collect::<Vec<_>>();
+
+        jubjub::Point {
+            x: fetch_fq(&limbs, 0),
+            y: fetch_fq(&limbs, 1),
+        }
+    }
+
+    pub fn verify(&mut self, pk: &jubjub::Point, msg: &Fr, signature: &Signature) -> bool {
+        // s is not reduced, (R, s + suborder) would be another valid signature
+        if signature.s >= suborder() {
+            return false;
+        }
+
+        let c = self.challenge(&signature.r, pk, msg);
+
+        let base = base_point();
+        let neg_base = jubjub::Point {
+            x: -base.x,
+            y: base.y,
+        };
+
+        self.accumulate(true, &signature.r, &BigUint::from(1u64));
+        self.accumulate(false, pk, &c);
+        let acc = self.accumulate(false, &neg_base, &signature.s);
+
+        let identity = jubjub::Point::identity();
+        acc.x == identity.x && acc.y == identity.y
+    }
+
+    pub(crate) fn eddsa_push(&mut self, v: u64) -> Result<(), HostCallError> {
+        if self.limbs.len() == VERIFY_LIMBS {
+            return Err(HostCallError::InvalidCallSequence(format!(
+                "eddsa verification takes {} limbs",
+                VERIFY_LIMBS
+            )));
+        }
+
+        self.limbs.push(v);
+
+        Ok(())
+    }
+
+    /// Returns 1 if the pushed signature is valid and resets the limbs.
+    pub(crate) fn eddsa_verify_result(&mut self) -> Result<u64, HostCallError> {
+        let limbs = std::mem::take(&mut self.limbs);
+        if limbs.len() != VERIFY_LIMBS {
+            return Err(HostCallError::InvalidCallSequence(format!(
+                "eddsa verification takes {} limbs but {} are pushed",
+                VERIFY_LIMBS,
+                limbs.len()
+            )));
+        }
+
+        let pk = jubjub::Point {
+            x: fetch_field(&limbs, 0)?,
+            y: fetch_field(&limbs, 1)?,
+        };
+        let msg = fetch_field(&limbs, 2)?;
+        let signature = Signature {
+            r: jubjub::Point {
+                x: fetch_field(&limbs, 3)?,
+                y: fetch_field(&limbs, 4)?,
+            },
+            s: fetch_biguint(&limbs[5 * LIMBNB..]),
+        };
+
+        self.used_round += 1;
+
+        Ok(self.verify(&pk, &msg, &signature) as u64)
+    }
+
+    /// Signs `msg` with the secret key `sk` and the nonce `k`, the nonce must
+    /// never be reused with the same key.
+    pub fn sign(&mut self, sk: &BigUint, k: &BigUint, msg: &Fr) -> Signature {
+        let base = base_point();
+        let pk = base.mul_scalar(sk);
+        let r = base.mul_scalar(k);
+
+        let c = self.challenge(&r, &pk, msg);
+        let s = (k + c * sk) % suborder();
+
+        Signature { r, s }
+    }
+}
+
+impl ForeignContext for EddsaContext {
+    fn get_statics(&self, k: u32) -> Option<ForeignStatics> {
+        Some(ForeignStatics {
+            used_round: self.used_round,
+            max_round: max_rounds(k),
+        })
+    }
+}
+
+/// A verification is a group which starts at its first limb and is committed
+/// by its result.
+pub(crate) struct EddsaFlushStrategy {
+    in_transaction: bool,
+    group: usize,
+    maximal_group: usize,
+}
+
+impl EddsaFlushStrategy {
+    pub(crate) fn new(k: u32) -> Self {
+        Self {
+            in_transaction: false,
+            group: 0,
+            maximal_group: max_rounds(k),
+        }
+    }
+}
+
+impl PluginFlushStrategy for EddsaFlushStrategy {
+    fn notify(&mut self, op: usize, _value: Option<u64>) -> Vec<Command> {
+        let op_type = HostOp::Eddsa as usize;
+
+        if op == EddsaInst::Verify as usize {
+            self.in_transaction = false;
+            self.group += 1;
+
+            let mut commands = vec![Command::Commit(op_type, true)];
+
+            if self.group >= self.maximal_group {
+                commands.push(Command::Abort);
+            }
+
+            return commands;
+        }
+
+        if !self.in_transaction {
+            self.in_transaction = true;
+
+            return vec![Command::Start(op_type)];
+        }
+
+        vec![Command::Noop]
+    }
+
+    fn reset(&mut self) {
+        self.group = 0;
+    }
+
+    fn maximal_group(&self) -> Option<usize> {
+        Some(self.maximal_group)
+    }
+}
+
+use specs::external_host_call_table::ExternalHostCallSignature;
+pub fn register_eddsa_foreign(env: &mut HostEnv) {
+    let foreign_eddsa_plugin = env
+        .external_env
+        .register_plugin("foreign_eddsa", Box::<EddsaContext>::default());
+
+    env.external_env.register_fallible_function(
+        "eddsa_push",
+        EddsaInst::Push as usize,
+        ExternalHostCallSignature::Argument,
+        foreign_eddsa_plugin.clone(),
+        Rc::new(
+            |_obs, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
+                let context = context.downcast_mut::<EddsaContext>().unwrap();
+                context.eddsa_push(args.nth(0))?;
+                Ok(None)
+            },
+        ),
+    );
+
+    env.external_env.register_fallible_function(
+        "eddsa_verify_result",
+        EddsaInst::Verify as usize,
+        ExternalHostCallSignature::Return,
+        foreign_eddsa_plugin,
+        Rc::new(
+            |_obs, context: &mut dyn ForeignContext, _args: wasmi::RuntimeArgs| {
+                let context = context.downcast_mut::<EddsaContext>().unwrap();
+                Ok(Some(wasmi::RuntimeValue::I64(
+                    context.eddsa_verify_result()? as i64,
+                )))
+            },
+        ),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::babyjubjub_fq_to_limbs;
+    use super::base_point;
+    use super::point_to_limbs;
+    use super::scalar_to_limbs;
+    use super::suborder;
+    use super::EddsaContext;
+    use super::Signature;
+    use crate::test::run_image;
+    use crate::HostEnvConfig;
+    use crate::HostOp;
+    use delphinus_zkwasm::error::HostCallError;
+    use halo2_proofs::pairing::bn256::Fr;
+    use num_bigint::BigUint;
+    use zkwasm_host_circuits::host::jubjub;
+
+    #[test]
+    fn test_eddsa() {
+        let mut context = EddsaContext::default();
+
+        let sk = BigUint::from(0x1234_5678_9abc_def0u64);
+        let pk = base_point().mul_scalar(&sk);
+        let msg = Fr::from(42u64);
+
+        let signature = context.sign(&sk, &BigUint::from(0xfedc_ba98_7654_3210u64), &msg);
+        assert!(context.verify(&pk, &msg, &signature));
+
+        // another message
+        assert!(!context.verify(&pk, &Fr::from(43u64), &signature));
+
+        // another key
+        let other = base_point().mul_scalar(&BigUint::from(7u64));
+        assert!(!context.verify(&other, &msg, &signature));
+
+        // a tampered s
+        let tampered = Signature {
+            r: signature.r.clone(),
+            s: &signature.s + 1u64,
+        };
+        assert!(!context.verify(&pk, &msg, &tampered));
+
+        // s is not reduced
+        let unreduced = Signature {
+            r: signature.r.clone(),
+            s: &signature.s + suborder(),
+        };
+        assert!(!context.verify(&pk, &msg, &unreduced));
+
+        // 1 hash and 3 sums per verification
+        assert_eq!(context.poseidon.used_round, 5);
+        assert_eq!(context.sum.used_round, 12);
+    }
+
+    #[test]
+    fn test_guest_library_constants() {
+        let header = include_str!("../../../../../c/eddsa.h");
+        let limbs = header
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter_map(|word| word.strip_prefix("0x"))
+            .map(|hex| u64::from_str_radix(hex, 16).unwrap())
+            .collect::<Vec<_>>();
+
+        let base = base_point();
+        let neg_base = jubjub::Point {
+            x: -base.x,
+            y: base.y,
+        };
+
+        let mut expected = point_to_limbs(&neg_base);
+        expected.extend(scalar_to_limbs(&suborder()));
+        assert_eq!(limbs, expected);
+    }
+
+    fn verify_limbs(pk: &jubjub::Point, msg: &Fr, signature: &Signature) -> Vec<u64> {
+        let mut limbs = point_to_limbs(pk);
+        babyjubjub_fq_to_limbs(&mut limbs, *msg);
+        limbs.extend(point_to_limbs(&signature.r));
+        limbs.extend(scalar_to_limbs(&signature.s));
+        limbs
+    }
+
+    /// A signature, a tampered one and one whose s is not reduced.
+    fn signatures() -> (jubjub::Point, Fr, Vec<(Signature, u64)>) {
+        let mut context = EddsaContext::default();
+
+        let sk = BigUint::from(0x1234_5678_9abc_def0u64);
+        let pk = base_point().mul_scalar(&sk);
+        let msg = Fr::from(42u64);
+
+        let signature = context.sign(&sk, &BigUint::from(0xfedc_ba98_7654_3210u64), &msg);
+        let tampered = Signature {
+            r: signature.r.clone(),
+            s: &signature.s + 1u64,
+        };
+        let unreduced = Signature {
+            r: signature.r.clone(),
+            s: &signature.s + suborder(),
+        };
+
+        (pk, msg, vec![(signature, 1), (tampered, 0), (unreduced, 0)])
+    }
+
+    #[test]
+    fn test_eddsa_host_calls() {
+        let (pk, msg, signatures) = signatures();
+
+        let mut context = EddsaContext::default();
+        for (signature, expected) in signatures.iter() {
+            for limb in verify_limbs(&pk, &msg, signature) {
+                context.eddsa_push(limb).unwrap();
+            }
+            assert_eq!(context.eddsa_verify_result().unwrap(), *expected);
+        }
+        assert_eq!(context.used_round, 3);
+
+        // the result without a signature
+        assert!(matches!(
+            context.eddsa_verify_result(),
+            Err(HostCallError::InvalidCallSequence(_))
+        ));
+
+        // a limb after the signature
+        let (signature, _) = &signatures[0];
+        for limb in verify_limbs(&pk, &msg, signature) {
+            context.eddsa_push(limb).unwrap();
+        }
+        assert!(matches!(
+            context.eddsa_push(0),
+            Err(HostCallError::InvalidCallSequence(_))
+        ));
+
+        // a coordinate which is not reduced
+        let mut context = EddsaContext::default();
+        for limb in [u64::MAX; 24] {
+            context.eddsa_push(limb).unwrap();
+        }
+        assert!(matches!(
+            context.eddsa_verify_result(),
+            Err(HostCallError::InvalidArgument(_))
+        ));
+    }
+
+    const EDDSA_IMAGE: &str = r#"
+        (module
+            (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+            (import "env" "require" (func $require (param i32)))
+            (import "env" "eddsa_push" (func $eddsa_push (param i64)))
+            (import "env" "eddsa_verify_result" (func $eddsa_verify_result (result i64)))
+            (export "zkmain" (func $zkmain))
+            (func $zkmain
+                (local $limbs i32)
+                ;; pk, msg, r and s
+                (local.set $limbs (i32.const 24))
+                (loop $push
+                    (call $eddsa_push (call $wasm_input (i32.const 0)))
+                    (local.set $limbs (i32.sub (local.get $limbs) (i32.const 1)))
+                    (br_if $push (local.get $limbs)))
+                ;; the expected result is the public input
+                (call $require
+                    (i64.eq (call $eddsa_verify_result) (call $wasm_input (i32.const 1))))))
+    "#;
+
+    #[test]
+    fn test_eddsa_image() {
+        let config = HostEnvConfig {
+            ops: vec![HostOp::Eddsa],
+            allow_unproven: true,
+            ..HostEnvConfig::default()
+        };
+
+        let (pk, msg, signatures) = signatures();
+        for (signature, expected) in signatures.iter() {
+            let limbs = verify_limbs(&pk, &msg, signature);
+
+            run_image(config.clone(), EDDSA_IMAGE, vec![*expected], limbs.clone()).unwrap();
+            assert!(run_image(config.clone(), EDDSA_IMAGE, vec![1 - *expected], limbs).is_err());
+        }
+    }
+}
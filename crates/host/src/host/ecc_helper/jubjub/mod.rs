@@ -1,3 +1,4 @@
+use crate::HostOp;
 use delphinus_zkwasm::runtime::monitor::plugins::table::Command;
 use halo2_proofs::pairing::bn256::Fr;
 use halo2_proofs::pairing::bn256::Fr as BabyJubjubFq;
@@ -10,12 +11,12 @@ use zkwasm_host_circuits::circuits::babyjub::AltJubChip;
 use zkwasm_host_circuits::circuits::host::HostOpSelector;
 use zkwasm_host_circuits::host::jubjub;
 use zkwasm_host_circuits::host::ForeignInst;
-use zkwasm_host_circuits::proof::OpType;
 
 use super::bn_to_field;
 use super::field_to_bn;
 use crate::PluginFlushStrategy;
 
+pub mod eddsa;
 pub mod sum;
 
 const LIMBSZ: usize = 64;
@@ -75,12 +76,12 @@ impl JubJubFlushStrategy {
 }
 
 impl PluginFlushStrategy for JubJubFlushStrategy {
-    fn notify(&mut self, op: &ForeignInst, value: Option<u64>) -> Vec<Command> {
-        let op_type = OpType::JUBJUBSUM as usize;
+    fn notify(&mut self, op: usize, value: Option<u64>) -> Vec<Command> {
+        let op_type = HostOp::JubjubSum as usize;
 
         self.current += 1;
 
-        if *op as usize == ForeignInst::JubjubSumNew as usize {
+        if op == ForeignInst::JubjubSumNew as usize {
             let value = value.unwrap();
             assert!(value == 0 || value == 1);
 
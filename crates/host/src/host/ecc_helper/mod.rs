@@ -1,6 +1,7 @@
-// pub mod bls381;
+pub mod bls381;
 pub mod bn254;
 pub mod jubjub;
+pub mod secp256k1;
 pub mod test;
 
 use halo2_proofs::arithmetic::BaseExt;
@@ -0,0 +1,277 @@
+use delphinus_zkwasm::error::HostCallError;
+use delphinus_zkwasm::runtime::host::host_env::HostEnv;
+use delphinus_zkwasm::runtime::host::ForeignContext;
+use delphinus_zkwasm::runtime::host::ForeignStatics;
+use num_bigint::BigUint;
+use sha3::Digest;
+use sha3::Keccak256;
+use std::rc::Rc;
+
+use super::max_rounds;
+use super::recover;
+use super::Point;
+use super::Secp256k1Inst::EcrecoverNew;
+use super::Secp256k1Inst::EcrecoverPush;
+use super::Secp256k1Inst::EcrecoverResult;
+
+const LIMBNB: usize = 4;
+
+fn fetch_u256(limbs: &[u64]) -> BigUint {
+    limbs
+        .iter()
+        .rev()
+        .fold(BigUint::default(), |acc, limb| (acc << 64) + *limb)
+}
+
+fn to_be_bytes32(v: &BigUint) -> Vec<u8> {
+    let bytes = v.to_bytes_be();
+    let mut padded = vec![0; 32 - bytes.len()];
+    padded.extend(bytes);
+    padded
+}
+
+/// The Ethereum address of a public key, the last 20 bytes of its keccak hash.
+fn address_of(public_key: &Point) -> BigUint {
+    let mut hasher = Keccak256::new();
+    hasher.update(to_be_bytes32(&public_key.x));
+    hasher.update(to_be_bytes32(&public_key.y));
+    BigUint::from_bytes_be(&hasher.finalize()[12..])
+}
+
+/// Foreign functions that supports the following C code library
+///
+/// // hash, r and s are 256-bit little-endian integers, v is 0, 1, 27 or 28
+/// void ecrecover(uint64_t* hash, uint64_t* r, uint64_t* s, uint64_t v, uint64_t* address)
+/// {
+///     int i;
+///     secp256k1_ecrecover_new(v);
+///     for(i=0; i<4; i++) secp256k1_ecrecover_push(hash[i]);
+///     for(i=0; i<4; i++) secp256k1_ecrecover_push(r[i]);
+///     for(i=0; i<4; i++) secp256k1_ecrecover_push(s[i]);
+///     // the address as a 160-bit little-endian integer, zero if the signature is invalid
+///     address[0] = secp256k1_ecrecover_result();
+///     address[1] = secp256k1_ecrecover_result();
+///     address[2] = secp256k1_ecrecover_result();
+/// }
+#[derive(Default)]
+pub(crate) struct EcrecoverContext {
+    pub recovery_id: u64,
+    pub limbs: Vec<u64>,
+    pub result_limbs: Option<Vec<u64>>,
+    pub result_cursor: usize,
+    pub used_round: usize,
+}
+
+impl EcrecoverContext {
+    pub(crate) fn ecrecover_new(&mut self, v: u64) {
+        log::debug!("new ecrecover context");
+        self.recovery_id = if v >= 27 { v - 27 } else { v };
+        self.limbs = vec![];
+        self.result_limbs = None;
+        self.result_cursor = 0;
+        self.used_round += 1;
+    }
+
+    pub(crate) fn ecrecover_push(&mut self, v: u64) {
+        self.limbs.push(v);
+    }
+
+    fn recover_address(&self) -> Result<Option<BigUint>, HostCallError> {
+        if self.limbs.len() != 3 * LIMBNB {
+            return Err(HostCallError::InvalidCallSequence(format!(
+                "ecrecover expects {} limbs but {} are pushed",
+                3 * LIMBNB,
+                self.limbs.len()
+            )));
+        }
+
+        if self.recovery_id > 1 {
+            return Ok(None);
+        }
+
+        let hash = fetch_u256(&self.limbs[0..LIMBNB]);
+        let r = fetch_u256(&self.limbs[LIMBNB..2 * LIMBNB]);
+        let s = fetch_u256(&self.limbs[2 * LIMBNB..3 * LIMBNB]);
+
+        Ok(recover(&hash, &r, &s, self.recovery_id == 1).map(|public_key| address_of(&public_key)))
+    }
+
+    pub(crate) fn ecrecover_result(&mut self) -> Result<u64, HostCallError> {
+        if self.result_limbs.is_none() {
+            let address = self.recover_address()?.unwrap_or_default();
+            log::debug!("recovered address {:x}", address);
+
+            let mut limbs = address.to_u64_digits();
+            limbs.resize(3, 0);
+            self.result_limbs = Some(limbs);
+        }
+
+        let ret = *self
+            .result_limbs
+            .as_ref()
+            .unwrap()
+            .get(self.result_cursor)
+            .ok_or_else(|| {
+                HostCallError::InvalidCallSequence("ecrecover result has only 3 limbs".to_string())
+            })?;
+        self.result_cursor += 1;
+        Ok(ret)
+    }
+}
+
+impl ForeignContext for EcrecoverContext {
+    fn get_statics(&self, k: u32) -> Option<ForeignStatics> {
+        Some(ForeignStatics {
+            used_round: self.used_round,
+            max_round: max_rounds(k),
+        })
+    }
+}
+
+use specs::external_host_call_table::ExternalHostCallSignature;
+pub fn register_ecrecover_foreign(env: &mut HostEnv) {
+    let foreign_ecrecover_plugin = env
+        .external_env
+        .register_plugin("foreign_ecrecover", Box::<EcrecoverContext>::default());
+
+    env.external_env.register_function(
+        "secp256k1_ecrecover_new",
+        EcrecoverNew as usize,
+        ExternalHostCallSignature::Argument,
+        foreign_ecrecover_plugin.clone(),
+        Rc::new(
+            |_obs, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
+                let context = context.downcast_mut::<EcrecoverContext>().unwrap();
+                context.ecrecover_new(args.nth::<u64>(0));
+                None
+            },
+        ),
+    );
+
+    env.external_env.register_function(
+        "secp256k1_ecrecover_push",
+        EcrecoverPush as usize,
+        ExternalHostCallSignature::Argument,
+        foreign_ecrecover_plugin.clone(),
+        Rc::new(
+            |_obs, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
+                let context = context.downcast_mut::<EcrecoverContext>().unwrap();
+                context.ecrecover_push(args.nth::<u64>(0));
+                None
+            },
+        ),
+    );
+
+    env.external_env.register_fallible_function(
+        "secp256k1_ecrecover_result",
+        EcrecoverResult as usize,
+        ExternalHostCallSignature::Return,
+        foreign_ecrecover_plugin,
+        Rc::new(
+            |_obs, context: &mut dyn ForeignContext, _args: wasmi::RuntimeArgs| {
+                let context = context.downcast_mut::<EcrecoverContext>().unwrap();
+                Ok(Some(wasmi::RuntimeValue::I64(
+                    context.ecrecover_result()? as i64
+                )))
+            },
+        ),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use delphinus_zkwasm::error::HostCallError;
+
+    use super::EcrecoverContext;
+
+    fn ecrecover(context: &mut EcrecoverContext, limbs: [[u64; 4]; 3], v: u64) -> Vec<u64> {
+        context.ecrecover_new(v);
+        for limb in limbs.iter().flatten() {
+            context.ecrecover_push(*limb);
+        }
+        (0..3)
+            .map(|_| context.ecrecover_result().unwrap())
+            .collect()
+    }
+
+    // sha256("zkwasm")
+    const HASH: [u64; 4] = [
+        0x5d0d721c32cef521,
+        0x366557c6ac7badaa,
+        0xb6f16519331cb3ca,
+        0xb1eb390030ef5d95,
+    ];
+
+    #[test]
+    fn test_ecrecover() {
+        let mut context = EcrecoverContext::default();
+
+        // signed by the private key 1, 0x7e5f4552091a69125d5dfcb7b8c2659029395bdf
+        let r = [
+            0xc75b0419d7d1b944,
+            0x5c97e092fbe57a51,
+            0x11894b3700042ddb,
+            0x2accea4037612090,
+        ];
+        let s = [
+            0x22539ca0a163043d,
+            0xe56242144f7491e2,
+            0xb707b326c3f95a20,
+            0x26ed5841796fcb1a,
+        ];
+        assert_eq!(
+            ecrecover(&mut context, [HASH, r, s], 28),
+            vec![0xb8c2659029395bdf, 0x091a69125d5dfcb7, 0x7e5f4552]
+        );
+        // the other parity recovers another key
+        assert_ne!(
+            ecrecover(&mut context, [HASH, r, s], 27),
+            vec![0xb8c2659029395bdf, 0x091a69125d5dfcb7, 0x7e5f4552]
+        );
+
+        // signed by the private key 2, 0x2b5ad5c4795c026514f8317c7a215e218dccd6cf
+        let r = [
+            0xbb36166297aa66a4,
+            0x73249d7501bf918a,
+            0x56880e5d9c5d50b1,
+            0x6d961c8ac1b8c9e6,
+        ];
+        let s = [
+            0xad6d7118e7f978bd,
+            0x714895cbcc511263,
+            0x75ac17d6339a94ec,
+            0x2a240d2546db41ff,
+        ];
+        assert_eq!(
+            ecrecover(&mut context, [HASH, r, s], 0),
+            vec![0x7a215e218dccd6cf, 0x795c026514f8317c, 0x2b5ad5c4]
+        );
+
+        // s is zero
+        assert_eq!(ecrecover(&mut context, [HASH, r, [0; 4]], 0), vec![0, 0, 0]);
+
+        assert_eq!(context.used_round, 4);
+    }
+
+    #[test]
+    fn test_ecrecover_invalid_call_sequence() {
+        let mut context = EcrecoverContext::default();
+
+        // a limb of s is missing
+        context.ecrecover_new(27);
+        for limb in HASH.iter().chain(HASH.iter()).chain(HASH[..3].iter()) {
+            context.ecrecover_push(*limb);
+        }
+        assert!(matches!(
+            context.ecrecover_result(),
+            Err(HostCallError::InvalidCallSequence(_))
+        ));
+
+        // the result is read beyond its 3 limbs
+        ecrecover(&mut context, [HASH, HASH, [0; 4]], 27);
+        assert!(matches!(
+            context.ecrecover_result(),
+            Err(HostCallError::InvalidCallSequence(_))
+        ));
+    }
+}
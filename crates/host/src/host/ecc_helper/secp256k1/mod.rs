@@ -0,0 +1,243 @@
+use crate::HostOp;
+use delphinus_zkwasm::runtime::monitor::plugins::table::Command;
+use num_bigint::BigUint;
+use num_traits::Zero;
+
+use crate::PluginFlushStrategy;
+
+pub mod ecrecover;
+
+/// Host calls of secp256k1, they have no counterpart in `ForeignInst` of
+/// zkwasm-host-circuits and are placed after it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Secp256k1Inst {
+    EcrecoverNew = 0x100,
+    EcrecoverPush,
+    EcrecoverResult,
+}
+
+impl Secp256k1Inst {
+    pub fn from_usize(op: usize) -> Option<Self> {
+        match op {
+            0x100 => Some(Secp256k1Inst::EcrecoverNew),
+            0x101 => Some(Secp256k1Inst::EcrecoverPush),
+            0x102 => Some(Secp256k1Inst::EcrecoverResult),
+            _ => None,
+        }
+    }
+}
+
+/// Rows reserved for one recovery. zkwasm-host-circuits has no secp256k1 chip,
+/// so this only caps the recoveries of a slice and does not size any circuit,
+/// a circuit below `K = 17` takes none. The results are unproven, see
+/// `HostOp::is_proven`.
+const ROWS_PER_ROUND: usize = 1 << 17;
+
+/// Number of recoveries that fit into a host circuit of size `k`.
+pub fn max_rounds(k: u32) -> usize {
+    (1 << k) / ROWS_PER_ROUND
+}
+
+fn from_hex(hex: &str) -> BigUint {
+    BigUint::parse_bytes(hex.as_bytes(), 16).unwrap()
+}
+
+/// Modulus of the base field.
+fn modulus() -> BigUint {
+    from_hex("fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f")
+}
+
+/// Order of the group.
+fn order() -> BigUint {
+    from_hex("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141")
+}
+
+/// An affine point of y^2 = x^3 + 7, `None` stands for the identity.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Point {
+    pub x: BigUint,
+    pub y: BigUint,
+}
+
+impl Point {
+    pub fn generator() -> Self {
+        Point {
+            x: from_hex("79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"),
+            y: from_hex("483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8"),
+        }
+    }
+
+    /// Lifts `x` to the point whose y has the parity `odd`.
+    fn lift_x(x: &BigUint, odd: bool) -> Option<Self> {
+        let p = modulus();
+        let alpha = (x * x * x + 7u32) % &p;
+        let beta = alpha.modpow(&((&p + 1u32) >> 2), &p);
+
+        if (&beta * &beta) % &p != alpha {
+            return None;
+        }
+
+        let y = if beta.bit(0) == odd { beta } else { &p - beta };
+
+        Some(Point { x: x.clone(), y })
+    }
+}
+
+fn inverse(a: &BigUint, m: &BigUint) -> BigUint {
+    a.modpow(&(m - 2u32), m)
+}
+
+fn add(a: &Option<Point>, b: &Option<Point>) -> Option<Point> {
+    let p = modulus();
+
+    let (a, b) = match (a, b) {
+        (None, _) => return b.clone(),
+        (_, None) => return a.clone(),
+        (Some(a), Some(b)) => (a, b),
+    };
+
+    let lambda = if a.x == b.x {
+        if (&a.y + &b.y) % &p == BigUint::zero() {
+            return None;
+        }
+
+        (BigUint::from(3u32) * &a.x * &a.x) * inverse(&(&a.y << 1), &p) % &p
+    } else {
+        (&b.y + &p - &a.y) * inverse(&((&b.x + &p - &a.x) % &p), &p) % &p
+    };
+
+    let x = (&lambda * &lambda + (&p << 1) - &a.x - &b.x) % &p;
+    let y = (lambda * (&a.x + &p - &x) + &p - &a.y) % &p;
+
+    Some(Point { x, y })
+}
+
+fn mul(k: &BigUint, point: &Point) -> Option<Point> {
+    let point = Some(point.clone());
+
+    (0..k.bits()).rev().fold(None, |acc, i| {
+        let acc = add(&acc, &acc);
+
+        if k.bit(i) {
+            add(&acc, &point)
+        } else {
+            acc
+        }
+    })
+}
+
+/// Recovers the public key from the signature `(r, s)` of `hash`, `odd` is
+/// the parity of y of the ephemeral point.
+pub fn recover(hash: &BigUint, r: &BigUint, s: &BigUint, odd: bool) -> Option<Point> {
+    let n = order();
+
+    if r.is_zero() || *r >= n || s.is_zero() || *s >= n {
+        return None;
+    }
+
+    let ephemeral = Point::lift_x(r, odd)?;
+
+    let r_inv = inverse(r, &n);
+    let u1 = (&n - hash % &n) * &r_inv % &n;
+    let u2 = s * &r_inv % &n;
+
+    add(&mul(&u1, &Point::generator()), &mul(&u2, &ephemeral))
+}
+
+pub(crate) struct Secp256k1FlushStrategy {
+    current: usize,
+    group: usize,
+    maximal_group: usize,
+}
+
+impl Secp256k1FlushStrategy {
+    pub(crate) fn new(k: u32) -> Self {
+        Self {
+            current: 0,
+            group: 0,
+            maximal_group: max_rounds(k),
+        }
+    }
+
+    fn group_size() -> usize {
+        // new + hash, r and s + address
+        1 + 3 * 4 + 3
+    }
+}
+
+impl PluginFlushStrategy for Secp256k1FlushStrategy {
+    fn notify(&mut self, op: usize, _value: Option<u64>) -> Vec<Command> {
+        let op_type = HostOp::Secp256k1Ecrecover as usize;
+
+        self.current += 1;
+
+        if op == Secp256k1Inst::EcrecoverNew as usize {
+            self.current = 1;
+
+            return vec![Command::Start(op_type)];
+        }
+
+        if self.current == Secp256k1FlushStrategy::group_size() {
+            self.current = 0;
+            self.group += 1;
+
+            let mut commands = vec![Command::Commit(op_type, true)];
+
+            if self.group >= self.maximal_group {
+                commands.push(Command::Abort);
+            }
+
+            return commands;
+        }
+
+        vec![Command::Noop]
+    }
+
+    fn reset(&mut self) {
+        self.current = 0;
+        self.group = 0;
+    }
+
+    fn maximal_group(&self) -> Option<usize> {
+        Some(self.maximal_group)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::max_rounds;
+    use super::Secp256k1FlushStrategy;
+    use super::Secp256k1Inst;
+    use crate::PluginFlushStrategy;
+    use delphinus_zkwasm::runtime::monitor::plugins::table::Command;
+
+    #[test]
+    fn test_secp256k1_flush_strategy() {
+        assert_eq!(max_rounds(16), 0);
+        assert_eq!(max_rounds(18), 2);
+
+        let mut strategy = Secp256k1FlushStrategy::new(18);
+
+        for group in 1..=2 {
+            let commands = strategy.notify(Secp256k1Inst::EcrecoverNew as usize, Some(0));
+            assert!(commands == vec![Command::Start(9)]);
+
+            for _ in 0..3 * 4 {
+                let commands = strategy.notify(Secp256k1Inst::EcrecoverPush as usize, Some(0));
+                assert!(commands == vec![Command::Noop]);
+            }
+
+            for _ in 0..2 {
+                let commands = strategy.notify(Secp256k1Inst::EcrecoverResult as usize, None);
+                assert!(commands == vec![Command::Noop]);
+            }
+
+            let commands = strategy.notify(Secp256k1Inst::EcrecoverResult as usize, None);
+            if group < 2 {
+                assert!(commands == vec![Command::Commit(9, true)]);
+            } else {
+                assert!(commands == vec![Command::Commit(9, true), Command::Abort]);
+            }
+        }
+    }
+}
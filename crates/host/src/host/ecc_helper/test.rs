@@ -1,11 +1,29 @@
 #[cfg(test)]
 pub(crate) mod tests {
+    use super::super::bls381::bls381_fq_to_limbs;
+    use super::super::bls381::pair::BlsPairContext;
+    use super::super::bls381::sum::BlsSumContext;
+    use super::super::bn254::bn254_fq_to_limbs;
+    use super::super::bn254::multipair::BN254MultiPairContext;
+    use super::super::bn254::multipair::MAX_PAIRS;
     use super::super::bn_to_field;
+    use crate::test::run_image;
+    use crate::HostEnvConfig;
+    use crate::HostOp;
+    use delphinus_zkwasm::error::HostCallError;
     use halo2_proofs::arithmetic::CurveAffine;
+    use halo2_proofs::pairing::bls12_381::pairing as bls_pairing;
+    use halo2_proofs::pairing::bls12_381::Fr as BlsFr;
+    use halo2_proofs::pairing::bls12_381::G1Affine as BlsG1Affine;
+    use halo2_proofs::pairing::bls12_381::G2Affine as BlsG2Affine;
     use halo2_proofs::pairing::bn256::pairing;
+    use halo2_proofs::pairing::bn256::Fq;
     use halo2_proofs::pairing::bn256::Fq2;
     use halo2_proofs::pairing::bn256::G1Affine;
     use halo2_proofs::pairing::bn256::G2Affine;
+    use halo2_proofs::pairing::bn256::Gt;
+    use halo2_proofs::pairing::group::ff::Field;
+    use halo2_proofs::pairing::group::Group;
     use num_bigint::BigUint;
     //use core::ops::Add;
     use std::str::FromStr;
@@ -106,4 +124,285 @@ pub(crate) mod tests {
         //println!("\npair sum {:?}", pair_ab +  pair_gamma + pair_delta - pair_alphabeta);
         println!("\npair alpha beta {:?}", pair_alphabeta);
     }
+
+    fn bn254_g1_limbs(g: &G1Affine) -> Vec<u64> {
+        let mut limbs = vec![];
+        bn254_fq_to_limbs(&mut limbs, g.x);
+        bn254_fq_to_limbs(&mut limbs, g.y);
+        limbs.push(0);
+        limbs
+    }
+
+    fn bn254_g2_limbs(g: &G2Affine) -> Vec<u64> {
+        let mut limbs = vec![];
+        bn254_fq_to_limbs(&mut limbs, g.x.c0);
+        bn254_fq_to_limbs(&mut limbs, g.x.c1);
+        bn254_fq_to_limbs(&mut limbs, g.y.c0);
+        bn254_fq_to_limbs(&mut limbs, g.y.c1);
+        limbs.push(0);
+        limbs
+    }
+
+    /// A groth16 proof (a, b, c), its vk_x, the sum of the input commitments,
+    /// and the verifying key as the pairs of its check.
+    fn groth16_pairs() -> Vec<(G1Affine, G2Affine)> {
+        let a = get_g1(vec![
+            "11642537973992842197841129792593344678309686386610379404309438937881134387046",
+            "16038677464958343056835043065894747361239845934514535107990003321228770537757",
+        ]);
+        let b = get_g2(vec![
+            "47825847124631411811576281994805528104052872047575749583058110663112377001",
+            "8744222678452503383790834725135834161307861625945158048012780534319795560143",
+            "9233346029650632520218739027049068980416615687382718198119931453101152827975",
+            "10471280343341576477249226516274439500456322467064958156008066650244635888622",
+        ]);
+        let c = get_g1(vec![
+            "5031514285120295997737123508423040879712685050138383293760355384665904754488",
+            "6823067879265550319809102199938618241998853703030415114792371967260649452853",
+        ]);
+        let vk_x = get_g1(vec![
+            "2209931194823406776915592387028778476783391160523048990321465612709368046962",
+            "7671390665879442188252906915326739284651507307886344317901030355743994332276",
+        ]);
+
+        let alpha = get_g1(vec![
+            "16757480011835372184764354633328853212064194417052169636997013717174005603641",
+            "12432097195700978348921464455579756029074596339242363943410459715706216986941",
+        ]);
+        let beta = get_g2(vec![
+            "17975028126161125746872310568788265990276646416597355097039940884195819358739",
+            "17401920800019416240372951523628528842571722962428319020407322407751134153696",
+            "373336161763481738310767706475057352757645836211335436601460549560077507883",
+            "12596258404235599838080822038113194794755190435330940888082608644071169093526",
+        ]);
+        let gamma = get_g2(vec![
+            "10857046999023057135944570762232829481370756359578518086990519993285655852781",
+            "11559732032986387107991004021392285783925812861821192530917403151452391805634",
+            "8495653923123431417604973247489272438418190587263600148770280649306958101930",
+            "4082367875863433681332203403145435568316851327593401208105741076214120093531",
+        ]);
+        let delta = get_g2(vec![
+            "3257864888291421116173460527534170713717410433509021668023378246238733003616",
+            "19501533296056540312008038527562968311092778436941336050675464372460956461923",
+            "6694920137971073977116331836462792254111247710837228432923435849982682441494",
+            "17519709022773002523554480652241560921521540950810125413580329045084856923004",
+        ]);
+
+        // e(-a, b) * e(alpha, beta) * e(vk_x, gamma) * e(c, delta) = 1
+        vec![(-a, b), (alpha, beta), (vk_x, gamma), (c, delta)]
+    }
+
+    fn bn254_multipair_limbs(pairs: &[(G1Affine, G2Affine)]) -> Vec<u64> {
+        pairs
+            .iter()
+            .flat_map(|(g1, g2)| bn254_g1_limbs(g1).into_iter().chain(bn254_g2_limbs(g2)))
+            .collect()
+    }
+
+    fn bn254_multipair(
+        context: &mut BN254MultiPairContext,
+        pairs: &[(G1Affine, G2Affine)],
+    ) -> Result<u64, HostCallError> {
+        for (g1, g2) in pairs {
+            for limb in bn254_g1_limbs(g1) {
+                context.bn254multipair_g1(limb)?;
+            }
+            for limb in bn254_g2_limbs(g2) {
+                context.bn254multipair_g2(limb)?;
+            }
+        }
+        context.bn254multipair_result()
+    }
+
+    #[test]
+    fn test_bn254_multipair() {
+        let pairs = groth16_pairs();
+        let product = pairs
+            .iter()
+            .fold(Gt::identity(), |acc, (g1, g2)| acc + pairing(g1, g2));
+        assert_eq!(product, Gt::identity());
+
+        let mut context = BN254MultiPairContext::default();
+        assert_eq!(bn254_multipair(&mut context, &pairs).unwrap(), 1);
+
+        // a tampered proof, c is replaced by a
+        let mut tampered = pairs.clone();
+        tampered[3].0 = pairs[0].0;
+        assert_eq!(bn254_multipair(&mut context, &tampered).unwrap(), 0);
+
+        assert_eq!(context.used_round, 8);
+    }
+
+    #[test]
+    fn test_bn254_multipair_invalid_call() {
+        let mut context = BN254MultiPairContext::default();
+        assert!(matches!(
+            context.bn254multipair_g2(0),
+            Err(HostCallError::InvalidCallSequence(_))
+        ));
+
+        // (1, 1) is not on the curve
+        let mut limbs = vec![];
+        bn254_fq_to_limbs(&mut limbs, Fq::one());
+        bn254_fq_to_limbs(&mut limbs, Fq::one());
+        limbs.push(0);
+        let (last, limbs) = limbs.split_last().unwrap();
+        for limb in limbs {
+            context.bn254multipair_g1(*limb).unwrap();
+        }
+        assert!(matches!(
+            context.bn254multipair_g1(*last),
+            Err(HostCallError::InvalidArgument(_))
+        ));
+
+        // a pair without its g2
+        let mut context = BN254MultiPairContext::default();
+        for limb in bn254_g1_limbs(&G1Affine::generator()) {
+            context.bn254multipair_g1(limb).unwrap();
+        }
+        assert!(matches!(
+            context.bn254multipair_result(),
+            Err(HostCallError::InvalidCallSequence(_))
+        ));
+
+        // more pairs than a check takes
+        let pairs = vec![(G1Affine::generator(), G2Affine::generator()); MAX_PAIRS + 1];
+        assert!(matches!(
+            bn254_multipair(&mut BN254MultiPairContext::default(), &pairs),
+            Err(HostCallError::InvalidCallSequence(_))
+        ));
+    }
+
+    const BN254_MULTIPAIR_IMAGE: &str = r#"
+        (module
+            (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+            (import "env" "require" (func $require (param i32)))
+            (import "env" "bn254multipair_g1" (func $g1 (param i64)))
+            (import "env" "bn254multipair_g2" (func $g2 (param i64)))
+            (import "env" "bn254multipair_result" (func $result (result i64)))
+            (export "zkmain" (func $zkmain))
+            (func $zkmain
+                (local $pairs i64)
+                (local $limbs i32)
+                (local.set $pairs (call $wasm_input (i32.const 1)))
+                (block $done
+                    (loop $pair
+                        (br_if $done (i64.eqz (local.get $pairs)))
+                        (local.set $limbs (i32.const 11))
+                        (loop $g1_limbs
+                            (call $g1 (call $wasm_input (i32.const 0)))
+                            (local.set $limbs (i32.sub (local.get $limbs) (i32.const 1)))
+                            (br_if $g1_limbs (local.get $limbs)))
+                        (local.set $limbs (i32.const 21))
+                        (loop $g2_limbs
+                            (call $g2 (call $wasm_input (i32.const 0)))
+                            (local.set $limbs (i32.sub (local.get $limbs) (i32.const 1)))
+                            (br_if $g2_limbs (local.get $limbs)))
+                        (local.set $pairs (i64.sub (local.get $pairs) (i64.const 1)))
+                        (br $pair)))
+                ;; the expected result is the last public input
+                (call $require (i64.eq (call $result) (call $wasm_input (i32.const 1))))))
+    "#;
+
+    #[test]
+    fn test_bn254_multipair_image() {
+        let config = HostEnvConfig {
+            ops: vec![HostOp::Bn256MultiPair],
+            allow_unproven: true,
+            ..HostEnvConfig::default()
+        };
+        let run = |pairs: &[(G1Affine, G2Affine)], expected: u64| {
+            run_image(
+                config.clone(),
+                BN254_MULTIPAIR_IMAGE,
+                vec![pairs.len() as u64, expected],
+                bn254_multipair_limbs(pairs),
+            )
+        };
+
+        let pairs = groth16_pairs();
+        run(&pairs, 1).unwrap();
+        assert!(run(&pairs, 0).is_err());
+
+        let mut tampered = pairs.clone();
+        tampered[3].0 = pairs[0].0;
+        run(&tampered, 0).unwrap();
+        assert!(run(&tampered, 1).is_err());
+    }
+
+    fn bls381_g1_limbs(g: &BlsG1Affine) -> Vec<u64> {
+        let mut limbs = vec![];
+        bls381_fq_to_limbs(&mut limbs, g.x);
+        bls381_fq_to_limbs(&mut limbs, g.y);
+        limbs.push(0);
+        limbs
+    }
+
+    fn bls381_g2_limbs(g: &BlsG2Affine) -> Vec<u64> {
+        let mut limbs = vec![];
+        bls381_fq_to_limbs(&mut limbs, g.x.c0);
+        bls381_fq_to_limbs(&mut limbs, g.x.c1);
+        bls381_fq_to_limbs(&mut limbs, g.y.c0);
+        bls381_fq_to_limbs(&mut limbs, g.y.c1);
+        limbs.push(0);
+        limbs
+    }
+
+    fn bls381_pair(context: &mut BlsPairContext, g1: &BlsG1Affine, g2: &BlsG2Affine) -> Vec<u64> {
+        for limb in bls381_g1_limbs(g1) {
+            context.blspair_g1(limb);
+        }
+        for limb in bls381_g2_limbs(g2) {
+            context.blspair_g2(limb);
+        }
+        (0..24 * 8).map(|_| context.blspair_pop()).collect()
+    }
+
+    #[test]
+    fn test_bls381_pair() {
+        let g1 = BlsG1Affine::generator();
+        let g2 = BlsG2Affine::generator();
+        let a = BlsFr::from(3u64);
+        let b = BlsFr::from(5u64);
+
+        let a_g1: BlsG1Affine = (g1 * a).into();
+        let b_g2: BlsG2Affine = (g2 * b).into();
+        let ab_g1: BlsG1Affine = (g1 * (a * b)).into();
+
+        // e(a * g1, b * g2) = e(ab * g1, g2)
+        assert_eq!(bls_pairing(&a_g1, &b_g2), bls_pairing(&ab_g1, &g2));
+
+        let mut expected = BlsPairContext::default();
+        expected.bls381_gt_to_limbs(bls_pairing(&ab_g1, &g2));
+
+        let mut context = BlsPairContext::default();
+        assert_eq!(
+            bls381_pair(&mut context, &a_g1, &b_g2),
+            expected.result_limbs
+        );
+        // the context is reusable
+        assert_eq!(
+            bls381_pair(&mut context, &ab_g1, &g2),
+            expected.result_limbs
+        );
+        assert_eq!(context.used_round, 2);
+    }
+
+    #[test]
+    fn test_bls381_sum() {
+        let g1 = BlsG1Affine::generator();
+        let a: BlsG1Affine = (g1 * BlsFr::from(3u64)).into();
+        let b: BlsG1Affine = (g1 * BlsFr::from(4u64)).into();
+        let c: BlsG1Affine = (g1 * BlsFr::from(7u64)).into();
+
+        let mut context = BlsSumContext::default();
+        for _ in 0..2 {
+            for limb in bls381_g1_limbs(&a).into_iter().chain(bls381_g1_limbs(&b)) {
+                context.blssum_g1(limb);
+            }
+            let result = (0..17).map(|_| context.blssum_pop()).collect::<Vec<_>>();
+            assert_eq!(result, bls381_g1_limbs(&c));
+        }
+        assert_eq!(context.used_round, 2);
+    }
 }
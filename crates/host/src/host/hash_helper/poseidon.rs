@@ -1,3 +1,4 @@
+use crate::HostOp;
 use delphinus_zkwasm::runtime::host::host_env::HostEnv;
 use delphinus_zkwasm::runtime::host::ForeignContext;
 use delphinus_zkwasm::runtime::host::ForeignStatics;
@@ -8,7 +9,6 @@ use poseidon::Poseidon;
 use std::rc::Rc;
 pub use zkwasm_host_circuits::host::poseidon::POSEIDON_HASHER;
 use zkwasm_host_circuits::host::ForeignInst;
-use zkwasm_host_circuits::proof::OpType;
 
 use zkwasm_host_circuits::host::Reduce;
 use zkwasm_host_circuits::host::ReduceRule;
@@ -200,12 +200,12 @@ impl PoseidonFlushStrategy {
 }
 
 impl PluginFlushStrategy for PoseidonFlushStrategy {
-    fn notify(&mut self, op: &ForeignInst, value: Option<u64>) -> Vec<Command> {
-        let op_type = OpType::POSEIDONHASH as usize;
+    fn notify(&mut self, op: usize, value: Option<u64>) -> Vec<Command> {
+        let op_type = HostOp::PoseidonHash as usize;
 
         self.current += 1;
 
-        if *op as usize == ForeignInst::PoseidonNew as usize {
+        if op == ForeignInst::PoseidonNew as usize {
             let value = value.unwrap();
             assert!(value == 0 || value == 1);
 
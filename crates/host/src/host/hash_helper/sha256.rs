@@ -7,6 +7,11 @@ use zkwasm_host_circuits::host::ForeignInst::SHA256Finalize;
 use zkwasm_host_circuits::host::ForeignInst::SHA256New;
 use zkwasm_host_circuits::host::ForeignInst::SHA256Push;
 
+use crate::HostOp;
+use crate::PluginFlushStrategy;
+use delphinus_zkwasm::runtime::monitor::plugins::table::Command;
+use zkwasm_host_circuits::host::ForeignInst;
+
 use sha2::Sha256;
 
 /// Foreign functions that supports the following C code library
@@ -35,6 +40,7 @@ use sha2::Sha256;
 ///     wasm_dbg(r[3]);
 /// }
 
+#[derive(Default)]
 struct Generator {
     pub cursor: usize,
     pub values: Vec<u64>,
@@ -48,29 +54,69 @@ impl Generator {
     }
 }
 
+/// Rows reserved for the compression of one 64-byte block. zkwasm-host-circuits
+/// has no SHA-256 chip, so this only caps the rounds of a slice (following the
+/// table16 gadget of halo2) and does not size any circuit. The results are
+/// unproven, see `HostOp::is_proven`.
+const ROWS_PER_ROUND: usize = 2099;
+
+/// Number of SHA-256 compression rounds that fit into a host circuit of size `k`.
+pub fn max_rounds(k: u32) -> usize {
+    (1 << k) / ROWS_PER_ROUND
+}
+
+/// Number of compression rounds to hash `size` bytes, including the padding.
+fn rounds_of(size: usize) -> usize {
+    // the message is followed by 0x80 and its bit length in 8 bytes
+    (size + 1 + 8 + 63) / 64
+}
+
+#[derive(Default)]
 struct Sha256Context {
     pub hasher: Option<Sha256>,
     pub generator: Generator,
     pub size: usize,
+    pub used_round: usize,
 }
 
 impl Sha256Context {
-    fn default() -> Self {
-        Sha256Context {
-            hasher: None,
-            generator: Generator {
-                cursor: 0,
-                values: vec![],
-            },
-            size: 0,
+    fn sha256_new(&mut self, size: usize) {
+        if self.hasher.is_none() {
+            self.hasher = Some(Sha256::new());
+            self.size = size;
+            self.used_round += rounds_of(size);
         }
     }
+
+    fn sha256_push(&mut self, v: u64) {
+        if let Some(s) = self.hasher.as_mut() {
+            let sz = usize::min(self.size, 8);
+            self.size -= sz;
+
+            s.update(&v.to_le_bytes()[..sz]);
+        }
+    }
+
+    fn sha256_finalize(&mut self) -> u64 {
+        if let Some(s) = self.hasher.take() {
+            self.generator.values = s
+                .finalize()
+                .chunks(8)
+                .map(|x| u64::from_le_bytes(x.try_into().unwrap()))
+                .collect::<Vec<u64>>();
+            self.generator.cursor = 0;
+        }
+
+        self.generator.gen()
+    }
 }
 
 impl ForeignContext for Sha256Context {
-    fn get_statics(&self, _k: u32) -> Option<ForeignStatics> {
-        // we did not support full sha256 as host yet
-        None
+    fn get_statics(&self, k: u32) -> Option<ForeignStatics> {
+        Some(ForeignStatics {
+            used_round: self.used_round,
+            max_round: max_rounds(k),
+        })
     }
 }
 
@@ -88,14 +134,7 @@ pub fn register_sha256_foreign(env: &mut HostEnv) {
         Rc::new(
             |_obs, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<Sha256Context>().unwrap();
-                let hasher = context
-                    .hasher
-                    .as_mut()
-                    .map_or(Some(Sha256::new()), |_| None);
-                if let Some(s) = hasher {
-                    context.hasher = Some(s);
-                    context.size = args.nth::<u64>(0) as usize;
-                };
+                context.sha256_new(args.nth::<u64>(0) as usize);
                 None
             },
         ),
@@ -109,19 +148,7 @@ pub fn register_sha256_foreign(env: &mut HostEnv) {
         Rc::new(
             |_obs, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<Sha256Context>().unwrap();
-                if let Some(s) = context.hasher.as_mut() {
-                    let sz = if context.size > 8 {
-                        context.size -= 8;
-                        8
-                    } else {
-                        let s = context.size;
-                        context.size = 0;
-                        s
-                    };
-                    let mut r = args.nth::<u64>(0).to_le_bytes().to_vec();
-                    r.truncate(sz);
-                    s.update(r);
-                };
+                context.sha256_push(args.nth::<u64>(0));
                 None
             },
         ),
@@ -135,16 +162,168 @@ pub fn register_sha256_foreign(env: &mut HostEnv) {
         Rc::new(
             |_obs, context: &mut dyn ForeignContext, _args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<Sha256Context>().unwrap();
-                if let Some(s) = context.hasher.as_ref() {
-                    let dwords: Vec<u8> = s.clone().finalize()[..].to_vec();
-                    context.generator.values = dwords
-                        .chunks(8)
-                        .map(|x| u64::from_le_bytes(x.to_vec().try_into().unwrap()))
-                        .collect::<Vec<u64>>();
-                };
-                context.hasher = None;
-                Some(wasmi::RuntimeValue::I64(context.generator.gen() as i64))
+                Some(wasmi::RuntimeValue::I64(context.sha256_finalize() as i64))
             },
         ),
     );
 }
+
+/// A hash is a chain of weak transactions, one per 64-byte block, which is
+/// finalized by the last `sha256_finalize` so that a hash is never split
+/// across slices.
+pub(crate) struct Sha256FlushStrategy {
+    pushes: usize,
+    finalizes: usize,
+    in_transaction: bool,
+    group: usize,
+    maximal_group: usize,
+}
+
+impl Sha256FlushStrategy {
+    pub(crate) fn new(k: u32) -> Self {
+        Self {
+            pushes: 0,
+            finalizes: 0,
+            in_transaction: false,
+            group: 0,
+            maximal_group: max_rounds(k),
+        }
+    }
+
+    // 8 pushes of 8 bytes
+    const PUSHES_PER_ROUND: usize = 8;
+    const FINALIZES: usize = 4;
+
+    fn commit(&mut self, op_type: usize, last: bool) -> Vec<Command> {
+        self.in_transaction = false;
+        self.group += 1;
+
+        let mut commands = vec![Command::Commit(op_type, false)];
+
+        if last {
+            commands.push(Command::Finalize(op_type));
+        }
+
+        if self.group >= self.maximal_group {
+            commands.push(Command::Abort);
+        }
+
+        commands
+    }
+}
+
+impl PluginFlushStrategy for Sha256FlushStrategy {
+    fn notify(&mut self, op: usize, _value: Option<u64>) -> Vec<Command> {
+        let op_type = HostOp::Sha256 as usize;
+
+        let start = !self.in_transaction || op == ForeignInst::SHA256New as usize;
+
+        if op == ForeignInst::SHA256New as usize {
+            self.pushes = 0;
+            self.finalizes = 0;
+        } else if op == ForeignInst::SHA256Push as usize {
+            self.pushes += 1;
+        } else if op == ForeignInst::SHA256Finalize as usize {
+            self.finalizes += 1;
+        }
+
+        if start {
+            self.in_transaction = true;
+
+            return vec![Command::Start(op_type)];
+        }
+
+        if self.pushes == Self::PUSHES_PER_ROUND {
+            self.pushes = 0;
+
+            return self.commit(op_type, false);
+        }
+
+        if self.finalizes == Self::FINALIZES {
+            self.finalizes = 0;
+
+            return self.commit(op_type, true);
+        }
+
+        vec![Command::Noop]
+    }
+
+    fn reset(&mut self) {
+        self.group = 0;
+    }
+
+    fn maximal_group(&self) -> Option<usize> {
+        Some(self.maximal_group)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Sha256Context;
+    use super::Sha256FlushStrategy;
+    use crate::PluginFlushStrategy;
+    use delphinus_zkwasm::runtime::monitor::plugins::table::Command;
+    use zkwasm_host_circuits::host::ForeignInst;
+
+    fn sha256(context: &mut Sha256Context, data: &[u8]) -> String {
+        context.sha256_new(data.len());
+        for chunk in data.chunks(8) {
+            let mut buf = [0u8; 8];
+            buf[..chunk.len()].copy_from_slice(chunk);
+            context.sha256_push(u64::from_le_bytes(buf));
+        }
+        // an extra push as the C library does
+        context.sha256_push(0);
+
+        (0..4)
+            .flat_map(|_| context.sha256_finalize().to_le_bytes())
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    #[test]
+    fn test_sha256_vectors() {
+        let mut context = Sha256Context::default();
+
+        assert_eq!(
+            sha256(&mut context, b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            sha256(&mut context, b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            sha256(
+                &mut context,
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            ),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+
+        // 1 + 1 + 2 rounds
+        assert_eq!(context.used_round, 4);
+    }
+
+    #[test]
+    fn test_sha256_flush_strategy() {
+        let mut strategy = Sha256FlushStrategy::new(22);
+
+        let mut commands = strategy.notify(ForeignInst::SHA256New as usize, Some(64));
+        assert!(commands == vec![Command::Start(8)]);
+
+        for _ in 0..8 {
+            commands = strategy.notify(ForeignInst::SHA256Push as usize, Some(0));
+        }
+        assert!(commands == vec![Command::Commit(8, false)]);
+
+        commands = strategy.notify(ForeignInst::SHA256Push as usize, Some(0));
+        assert!(commands == vec![Command::Start(8)]);
+
+        for _ in 0..4 {
+            commands = strategy.notify(ForeignInst::SHA256Finalize as usize, None);
+        }
+        assert!(commands == vec![Command::Commit(8, false), Command::Finalize(8)]);
+        assert_eq!(strategy.group, 2);
+    }
+}
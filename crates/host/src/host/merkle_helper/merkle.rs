@@ -1,7 +1,11 @@
+use delphinus_zkwasm::error::HostCallError;
 use delphinus_zkwasm::runtime::host::host_env::HostEnv;
 use delphinus_zkwasm::runtime::host::ForeignContext;
 use delphinus_zkwasm::runtime::host::ForeignStatics;
+use delphinus_zkwasm::runtime::host::StateDiff;
 use halo2_proofs::pairing::bn256::Fr;
+use serde::Deserialize;
+use serde::Serialize;
 use std::cell::RefCell;
 use std::rc::Rc;
 use zkwasm_host_circuits::circuits::host::HostOpSelector;
@@ -18,7 +22,112 @@ use zkwasm_host_circuits::host::ForeignInst::MerkleSetRoot;
 use zkwasm_host_circuits::host::Reduce;
 use zkwasm_host_circuits::host::ReduceRule;
 
-use crate::MERKLE_TREE_HEIGHT;
+/// Height of the tree of the Merkle op, the host supports the heights of `MERKLE_TREE_HEIGHTS`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "usize", into = "usize")]
+pub enum MerkleTreeHeight {
+    H16,
+    H20,
+    H24,
+    H28,
+    H32,
+}
+
+/// Heights of the Merkle tree which the host supports.
+pub const MERKLE_TREE_HEIGHTS: [usize; 5] = [16, 20, 24, 28, 32];
+
+impl TryFrom<usize> for MerkleTreeHeight {
+    type Error = anyhow::Error;
+
+    fn try_from(height: usize) -> anyhow::Result<Self> {
+        match height {
+            16 => Ok(MerkleTreeHeight::H16),
+            20 => Ok(MerkleTreeHeight::H20),
+            24 => Ok(MerkleTreeHeight::H24),
+            28 => Ok(MerkleTreeHeight::H28),
+            32 => Ok(MerkleTreeHeight::H32),
+            _ => anyhow::bail!(
+                "Merkle tree height {} is not supported, supported heights are {:?}.",
+                height,
+                MERKLE_TREE_HEIGHTS
+            ),
+        }
+    }
+}
+
+impl From<MerkleTreeHeight> for usize {
+    fn from(height: MerkleTreeHeight) -> usize {
+        match height {
+            MerkleTreeHeight::H16 => 16,
+            MerkleTreeHeight::H20 => 20,
+            MerkleTreeHeight::H24 => 24,
+            MerkleTreeHeight::H28 => 28,
+            MerkleTreeHeight::H32 => 32,
+        }
+    }
+}
+
+/// A Merkle tree whose height is only known at runtime.
+pub trait HeightErasedMerkle {
+    fn root(&self) -> [u8; 32];
+    fn leaf(&self, index: u64) -> [u64; 4];
+    fn update_leaf(&mut self, index: u64, data: &Vec<u8>);
+}
+
+impl<const HEIGHT: usize> HeightErasedMerkle for merklehelper::MongoMerkle<HEIGHT> {
+    fn root(&self) -> [u8; 32] {
+        self.get_root_hash()
+    }
+
+    fn leaf(&self, index: u64) -> [u64; 4] {
+        let (leaf, _) = self
+            .get_leaf_with_proof(index)
+            .expect("Unexpected failure: get leaf fail");
+        leaf.data_as_u64()
+    }
+
+    fn update_leaf(&mut self, index: u64, data: &Vec<u8>) {
+        self.update_leaf_data_with_proof(index, data)
+            .expect("Unexpected failure: update leaf with proof fail");
+    }
+}
+
+fn construct_merkle(
+    height: MerkleTreeHeight,
+    root: [u8; 32],
+    tree_db: Option<Rc<RefCell<dyn TreeDB>>>,
+) -> Box<dyn HeightErasedMerkle> {
+    match height {
+        MerkleTreeHeight::H16 => Box::new(merklehelper::MongoMerkle::<16>::construct(
+            [0; 32], root, tree_db,
+        )),
+        MerkleTreeHeight::H20 => Box::new(merklehelper::MongoMerkle::<20>::construct(
+            [0; 32], root, tree_db,
+        )),
+        MerkleTreeHeight::H24 => Box::new(merklehelper::MongoMerkle::<24>::construct(
+            [0; 32], root, tree_db,
+        )),
+        MerkleTreeHeight::H28 => Box::new(merklehelper::MongoMerkle::<28>::construct(
+            [0; 32], root, tree_db,
+        )),
+        MerkleTreeHeight::H32 => Box::new(merklehelper::MongoMerkle::<32>::construct(
+            [0; 32], root, tree_db,
+        )),
+    }
+}
+
+/// Number of Merkle ops of a tree of `height` that fit into a host circuit of size `k`.
+pub fn max_rounds(height: MerkleTreeHeight, k: u32) -> usize {
+    let k = k as usize;
+
+    match height {
+        MerkleTreeHeight::H16 => MerkleChip::<Fr, 16>::max_rounds(k),
+        MerkleTreeHeight::H20 => MerkleChip::<Fr, 20>::max_rounds(k),
+        MerkleTreeHeight::H24 => MerkleChip::<Fr, 24>::max_rounds(k),
+        MerkleTreeHeight::H28 => MerkleChip::<Fr, 28>::max_rounds(k),
+        MerkleTreeHeight::H32 => MerkleChip::<Fr, 32>::max_rounds(k),
+    }
+}
 
 pub struct MerkleContext {
     pub set_root: Reduce<Fr>,
@@ -28,18 +137,43 @@ pub struct MerkleContext {
     pub data: [u64; 4],
     pub data_cursor: usize,
     pub fetch: bool,
-    pub mongo_merkle: Option<merklehelper::MongoMerkle<MERKLE_TREE_HEIGHT>>,
+    pub height: MerkleTreeHeight,
+    pub mongo_merkle: Option<Box<dyn HeightErasedMerkle>>,
     pub mongo_datahash: datahelper::MongoDataHash,
     pub tree_db: Option<Rc<RefCell<dyn TreeDB>>>,
+    pub state_diffs: Vec<StateDiff>,
     pub used_round: usize,
 }
 
+fn bytes_to_limbs(bytes: &[u8]) -> [u64; 4] {
+    bytes
+        .chunks(8)
+        .map(|x| u64::from_le_bytes(x.to_vec().try_into().unwrap()))
+        .collect::<Vec<u64>>()
+        .try_into()
+        .unwrap()
+}
+
+/// Index of the leaf at `address` in a tree of `height`.
+fn leaf_index(height: MerkleTreeHeight, address: u64) -> Result<u64, HostCallError> {
+    let height = usize::from(height);
+
+    if address >= (1u64 << height) {
+        return Err(HostCallError::InvalidArgument(format!(
+            "merkle address {} exceeds the tree height {}",
+            address, height
+        )));
+    }
+
+    Ok(address + (1u64 << height) - 1)
+}
+
 fn new_reduce(rules: Vec<ReduceRule<Fr>>) -> Reduce<Fr> {
     Reduce { cursor: 0, rules }
 }
 
 impl MerkleContext {
-    pub fn new(tree_db: Option<Rc<RefCell<dyn TreeDB>>>) -> Self {
+    pub fn new(height: MerkleTreeHeight, tree_db: Option<Rc<RefCell<dyn TreeDB>>>) -> Self {
         MerkleContext {
             set_root: new_reduce(vec![ReduceRule::Bytes(vec![], 4)]),
             get_root: new_reduce(vec![ReduceRule::Bytes(vec![], 4)]),
@@ -48,9 +182,11 @@ impl MerkleContext {
             fetch: false,
             data: [0; 4],
             data_cursor: 0,
+            height,
             mongo_merkle: None,
             mongo_datahash: datahelper::MongoDataHash::construct([0; 32], tree_db.clone()),
             tree_db,
+            state_diffs: vec![],
             used_round: 0,
         }
     }
@@ -59,13 +195,17 @@ impl MerkleContext {
         self.set_root.reduce(v);
         if self.set_root.cursor == 0 {
             log::debug!("set root: {:?}", &self.set_root.rules[0].bytes_value());
-            self.mongo_merkle = Some(merklehelper::MongoMerkle::construct(
-                [0; 32],
-                self.set_root.rules[0]
-                    .bytes_value()
-                    .unwrap()
-                    .try_into()
-                    .unwrap(),
+            let root = self.set_root.rules[0].bytes_value().unwrap();
+
+            // a root which continues the last diff extends it
+            let root_limbs = bytes_to_limbs(&root);
+            if self.state_diffs.last().map(|diff| diff.root_after) != Some(root_limbs) {
+                self.state_diffs.push(StateDiff::new(root_limbs));
+            }
+
+            self.mongo_merkle = Some(construct_merkle(
+                self.height,
+                root.try_into().unwrap(),
                 self.tree_db.clone(),
             ));
         }
@@ -76,11 +216,7 @@ impl MerkleContext {
             .mongo_merkle
             .as_ref()
             .expect("merkle db not initialized");
-        let hash = mt.get_root_hash();
-        let values = hash
-            .chunks(8)
-            .map(|x| u64::from_le_bytes(x.to_vec().try_into().unwrap()))
-            .collect::<Vec<u64>>();
+        let values = bytes_to_limbs(&mt.root());
         let cursor = self.get_root.cursor;
         self.get_root.reduce(values[self.get_root.cursor]);
         values[cursor]
@@ -97,57 +233,79 @@ impl MerkleContext {
         self.address.reduce(v);
     }
 
-    pub fn merkle_set(&mut self, v: u64) {
+    /// Writes the leaf at `address` and records it in the current state diff.
+    fn write_leaf(&mut self, address: u64, data: &Vec<u8>) -> Result<(), HostCallError> {
+        let index = leaf_index(self.height, address)?;
+        let mt = self
+            .mongo_merkle
+            .as_mut()
+            .expect("merkle db not initialized");
+
+        let old_leaf = mt.leaf(index);
+        mt.update_leaf(index, data);
+        let root = bytes_to_limbs(&mt.root());
+
+        let diff = self.state_diffs.last_mut().expect("merkle root not set");
+        diff.record(address, old_leaf, bytes_to_limbs(data));
+        diff.root_after = root;
+
+        Ok(())
+    }
+
+    pub fn merkle_set(&mut self, v: u64) -> Result<(), HostCallError> {
         self.set.reduce(v);
         if self.set.cursor == 0 {
-            let address = self.address.rules[0].u64_value().unwrap() as u32;
-            let index = (address as u64) + (1u64 << MERKLE_TREE_HEIGHT) - 1;
-            let mt = self
-                .mongo_merkle
-                .as_mut()
-                .expect("merkle db not initialized");
+            let address = self.address.rules[0].u64_value().unwrap();
             let hash = self.set.rules[0].bytes_value().unwrap();
-            mt.update_leaf_data_with_proof(index, &hash)
-                .expect("Unexpected failure: update leaf with proof fail");
+            self.write_leaf(address, &hash)?;
         }
+
+        Ok(())
     }
 
-    pub fn merkle_get(&mut self) -> u64 {
-        let address = self.address.rules[0].u64_value().unwrap() as u32;
-        let index = (address as u64) + (1u64 << MERKLE_TREE_HEIGHT) - 1;
+    pub fn merkle_get(&mut self) -> Result<u64, HostCallError> {
+        let index = leaf_index(self.height, self.address.rules[0].u64_value().unwrap())?;
         let mt = self
             .mongo_merkle
             .as_ref()
             .expect("merkle db not initialized");
-        let (leaf, _) = mt
-            .get_leaf_with_proof(index)
-            .expect("Unexpected failure: get leaf fail");
-        let values = leaf.data_as_u64();
+        let values = mt.leaf(index);
         if self.data_cursor == 0 {
             self.data = values;
         }
         let v = values[self.data_cursor];
         self.data_cursor += 1;
-        v
+        Ok(v)
     }
 }
 
-impl MerkleContext {}
-
 impl ForeignContext for MerkleContext {
     fn get_statics(&self, k: u32) -> Option<ForeignStatics> {
         Some(ForeignStatics {
             used_round: self.used_round,
-            max_round: MerkleChip::<Fr, MERKLE_TREE_HEIGHT>::max_rounds(k as usize),
+            max_round: max_rounds(self.height, k),
         })
     }
+
+    fn expose_state_diffs(&self) -> Vec<StateDiff> {
+        self.state_diffs
+            .iter()
+            .filter(|diff| !diff.writes.is_empty())
+            .cloned()
+            .collect()
+    }
 }
 
 use specs::external_host_call_table::ExternalHostCallSignature;
-pub fn register_merkle_foreign(env: &mut HostEnv, tree_db: Option<Rc<RefCell<dyn TreeDB>>>) {
-    let foreign_merkle_plugin = env
-        .external_env
-        .register_plugin("foreign_merkle", Box::new(MerkleContext::new(tree_db)));
+pub fn register_merkle_foreign(
+    env: &mut HostEnv,
+    height: MerkleTreeHeight,
+    tree_db: Option<Rc<RefCell<dyn TreeDB>>>,
+) {
+    let foreign_merkle_plugin = env.external_env.register_plugin(
+        "foreign_merkle",
+        Box::new(MerkleContext::new(height, tree_db)),
+    );
 
     env.external_env.register_function(
         "merkle_setroot",
@@ -190,7 +348,7 @@ pub fn register_merkle_foreign(env: &mut HostEnv, tree_db: Option<Rc<RefCell<dyn
         ),
     );
 
-    env.external_env.register_function(
+    env.external_env.register_fallible_function(
         "merkle_set",
         MerkleSet as usize,
         ExternalHostCallSignature::Argument,
@@ -198,13 +356,13 @@ pub fn register_merkle_foreign(env: &mut HostEnv, tree_db: Option<Rc<RefCell<dyn
         Rc::new(
             |_obs, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<MerkleContext>().unwrap();
-                context.merkle_set(args.nth(0));
-                None
+                context.merkle_set(args.nth(0))?;
+                Ok(None)
             },
         ),
     );
 
-    env.external_env.register_function(
+    env.external_env.register_fallible_function(
         "merkle_get",
         MerkleGet as usize,
         ExternalHostCallSignature::Return,
@@ -213,8 +371,52 @@ pub fn register_merkle_foreign(env: &mut HostEnv, tree_db: Option<Rc<RefCell<dyn
             |_obs, context: &mut dyn ForeignContext, _args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<MerkleContext>().unwrap();
 
-                Some(wasmi::RuntimeValue::I64(context.merkle_get() as i64))
+                Ok(Some(wasmi::RuntimeValue::I64(context.merkle_get()? as i64)))
             },
         ),
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use delphinus_zkwasm::error::HostCallError;
+
+    use super::leaf_index;
+    use super::MerkleTreeHeight;
+    use super::MERKLE_TREE_HEIGHTS;
+
+    #[test]
+    fn test_merkle_tree_height() {
+        for height in MERKLE_TREE_HEIGHTS {
+            assert_eq!(
+                usize::from(MerkleTreeHeight::try_from(height).unwrap()),
+                height
+            );
+        }
+
+        assert!(MerkleTreeHeight::try_from(0).is_err());
+        assert!(MerkleTreeHeight::try_from(33).is_err());
+
+        assert_eq!(serde_json::to_string(&MerkleTreeHeight::H24).unwrap(), "24");
+        assert_eq!(
+            serde_json::from_str::<MerkleTreeHeight>("20").unwrap(),
+            MerkleTreeHeight::H20
+        );
+        assert!(serde_json::from_str::<MerkleTreeHeight>("64").is_err());
+    }
+
+    #[test]
+    fn test_leaf_index() {
+        assert_eq!(leaf_index(MerkleTreeHeight::H16, 0).unwrap(), (1 << 16) - 1);
+        assert_eq!(
+            leaf_index(MerkleTreeHeight::H16, (1 << 16) - 1).unwrap(),
+            (1 << 17) - 2
+        );
+        assert_eq!(leaf_index(MerkleTreeHeight::H32, 5).unwrap(), (1 << 32) + 4);
+
+        assert!(matches!(
+            leaf_index(MerkleTreeHeight::H16, 1 << 16),
+            Err(HostCallError::InvalidArgument(_))
+        ));
+    }
+}
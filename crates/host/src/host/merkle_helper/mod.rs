@@ -1,12 +1,9 @@
+use crate::HostOp;
 use delphinus_zkwasm::runtime::monitor::plugins::table::Command;
-use halo2_proofs::pairing::bn256::Fr;
-use zkwasm_host_circuits::circuits::host::HostOpSelector;
-use zkwasm_host_circuits::circuits::merkle::MerkleChip;
 use zkwasm_host_circuits::host::ForeignInst;
-use zkwasm_host_circuits::proof::OpType;
 
 use crate::PluginFlushStrategy;
-use crate::MERKLE_TREE_HEIGHT;
+use merkle::MerkleTreeHeight;
 
 pub mod datacache;
 pub mod merkle;
@@ -19,11 +16,11 @@ pub(crate) struct MerkleFlushStrategy {
 }
 
 impl MerkleFlushStrategy {
-    pub(crate) fn new(k: u32) -> Self {
+    pub(crate) fn new(k: u32, height: MerkleTreeHeight) -> Self {
         Self {
             current: 0,
             group: 0,
-            maximal_group: MerkleChip::<Fr, MERKLE_TREE_HEIGHT>::max_rounds(k as usize),
+            maximal_group: merkle::max_rounds(height, k),
             is_set: false,
         }
     }
@@ -35,22 +32,22 @@ impl MerkleFlushStrategy {
 }
 
 impl PluginFlushStrategy for MerkleFlushStrategy {
-    fn notify(&mut self, op: &ForeignInst, _value: Option<u64>) -> Vec<Command> {
-        let op_type = OpType::MERKLE as usize;
+    fn notify(&mut self, op: usize, _value: Option<u64>) -> Vec<Command> {
+        let op_type = HostOp::Merkle as usize;
 
         self.current += 1;
 
-        if *op as usize == ForeignInst::MerkleAddress as usize {
+        if op == ForeignInst::MerkleAddress as usize {
             self.is_set = false;
 
             return vec![Command::Start(op_type)];
         }
 
-        if *op as usize == ForeignInst::MerkleSet as usize {
+        if op == ForeignInst::MerkleSet as usize {
             self.is_set = true;
         }
 
-        if *op as usize == ForeignInst::MerkleGet as usize {
+        if op == ForeignInst::MerkleGet as usize {
             return vec![Command::Finalize(op_type), Command::Noop];
         }
 
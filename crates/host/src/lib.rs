@@ -11,102 +11,332 @@ use delphinus_zkwasm::runtime::monitor::plugins::table::transaction::Transaction
 use delphinus_zkwasm::runtime::monitor::plugins::table::Command;
 use delphinus_zkwasm::runtime::monitor::plugins::table::Event;
 use delphinus_zkwasm::runtime::monitor::plugins::table::FlushStrategy;
+use host::ecc_helper::bls381::Bls381FlushStrategy;
+use host::ecc_helper::bn254::multipair::Bn254MultiPairInst;
+use host::ecc_helper::bn254::multipair::Bn254PairFlushStrategy;
+use host::ecc_helper::jubjub::eddsa::EddsaFlushStrategy;
+use host::ecc_helper::jubjub::eddsa::EddsaInst;
 use host::ecc_helper::jubjub::JubJubFlushStrategy;
+use host::ecc_helper::secp256k1::Secp256k1FlushStrategy;
+use host::ecc_helper::secp256k1::Secp256k1Inst;
 use host::hash_helper::poseidon::PoseidonFlushStrategy;
+use host::hash_helper::sha256::Sha256FlushStrategy;
+use host::merkle_helper::merkle::MerkleTreeHeight;
 use host::merkle_helper::MerkleFlushStrategy;
 use num_traits::FromPrimitive;
 use serde::Deserialize;
 use serde::Serialize;
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::Read;
 use std::rc::Rc;
 use zkwasm_host_circuits::host::db::TreeDB;
 use zkwasm_host_circuits::host::ForeignInst;
-use zkwasm_host_circuits::proof::OpType;
 
+pub mod db;
 pub mod host;
+#[cfg(test)]
+mod test;
 
 // TODO: move into zkwasm-host-circuits repo
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Host ops of the standard host env. The discriminants follow `OpType` of
+/// zkwasm-host-circuits and are used as transaction ids by the flush strategy.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HostOp {
+    #[serde(rename = "BLS381PAIR")]
+    Bls381Pair = 0,
+    #[serde(rename = "BLS381SUM")]
+    Bls381Sum = 1,
+    #[serde(rename = "BN256PAIR")]
+    Bn256Pair = 2,
+    #[serde(rename = "BN256SUM")]
+    Bn256Sum = 3,
+    #[serde(rename = "POSEIDONHASH")]
+    PoseidonHash = 4,
+    #[serde(rename = "KECCAKHASH")]
+    KeccakHash = 5,
+    #[serde(rename = "MERKLE")]
+    Merkle = 6,
+    #[serde(rename = "JUBJUBSUM")]
+    JubjubSum = 7,
+    /// Has no counterpart in `OpType` and no circuit, see `HostOp::is_proven`.
+    #[serde(rename = "SHA256")]
+    Sha256 = 8,
+    /// Has no counterpart in `OpType` and no circuit, see `HostOp::is_proven`.
+    #[serde(rename = "SECP256K1ECRECOVER")]
+    Secp256k1Ecrecover = 9,
+    /// Has no counterpart in `OpType`, its pairs are sized by the rounds of the
+    /// pairing circuit but the product check is not constrained, see `HostOp::is_proven`.
+    #[serde(rename = "BN256MULTIPAIR")]
+    Bn256MultiPair = 10,
+    /// Has no counterpart in `OpType`, it verifies on the host with the Poseidon and
+    /// JubJub sum contexts but the result is not constrained, see `HostOp::is_proven`.
+    #[serde(rename = "EDDSA")]
+    Eddsa = 11,
+}
+
+impl HostOp {
+    fn from_transaction(transaction: TransactionId) -> Self {
+        match transaction {
+            0 => HostOp::Bls381Pair,
+            1 => HostOp::Bls381Sum,
+            2 => HostOp::Bn256Pair,
+            3 => HostOp::Bn256Sum,
+            4 => HostOp::PoseidonHash,
+            5 => HostOp::KeccakHash,
+            6 => HostOp::Merkle,
+            7 => HostOp::JubjubSum,
+            8 => HostOp::Sha256,
+            9 => HostOp::Secp256k1Ecrecover,
+            10 => HostOp::Bn256MultiPair,
+            11 => HostOp::Eddsa,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Whether the results of the op are constrained by a host circuit. The
+    /// results of an unproven op are trusted by the proof and can be forged by
+    /// the prover, they are only enabled by `allow_unproven` for development.
+    pub fn is_proven(&self) -> bool {
+        !matches!(
+            self,
+            HostOp::Sha256 | HostOp::Secp256k1Ecrecover | HostOp::Bn256MultiPair | HostOp::Eddsa
+        )
+    }
+}
+
+/// Host ops enabled in the standard host env, every enabled op costs circuit capacity.
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct HostEnvConfig {
-    pub ops: Vec<OpType>,
+    pub ops: Vec<HostOp>,
+    /// Height of the tree of the Merkle op, one of `MERKLE_TREE_HEIGHTS`.
+    #[serde(default = "default_merkle_tree_height")]
+    pub merkle_tree_height: MerkleTreeHeight,
+    /// Allows ops which are not `HostOp::is_proven`, for development only.
+    #[serde(default)]
+    pub allow_unproven: bool,
+}
+
+fn default_merkle_tree_height() -> MerkleTreeHeight {
+    MERKLE_TREE_HEIGHT
 }
 
-pub const MERKLE_TREE_HEIGHT: usize = 32;
+impl Default for HostEnvConfig {
+    fn default() -> Self {
+        HostEnvConfig {
+            ops: vec![
+                HostOp::PoseidonHash,
+                HostOp::Merkle,
+                HostOp::JubjubSum,
+                HostOp::KeccakHash,
+                HostOp::Bn256Sum,
+            ],
+            merkle_tree_height: MERKLE_TREE_HEIGHT,
+            allow_unproven: false,
+        }
+    }
+}
+
+/// Default height of the tree of the Merkle op.
+pub const MERKLE_TREE_HEIGHT: MerkleTreeHeight = MerkleTreeHeight::H32;
 
 impl HostEnvConfig {
-    fn register_op(op: &OpType, env: &mut HostEnv, tree_db: Option<Rc<RefCell<dyn TreeDB>>>) {
+    fn register_op(
+        &self,
+        op: &HostOp,
+        env: &mut HostEnv,
+        tree_db: Option<Rc<RefCell<dyn TreeDB>>>,
+    ) {
         match op {
-            // OpType::BLS381PAIR => host::ecc_helper::bls381::pair::register_blspair_foreign(env),
-            // OpType::BLS381SUM => host::ecc_helper::bls381::sum::register_blssum_foreign(env),
-            OpType::BN256PAIR => host::ecc_helper::bn254::pair::register_bn254pair_foreign(env),
-            OpType::BN256SUM => host::ecc_helper::bn254::sum::register_bn254sum_foreign(env),
-            OpType::POSEIDONHASH => host::hash_helper::poseidon::register_poseidon_foreign(env),
-            OpType::MERKLE => {
-                host::merkle_helper::merkle::register_merkle_foreign(env, tree_db.clone());
+            HostOp::Bls381Pair => host::ecc_helper::bls381::pair::register_blspair_foreign(env),
+            HostOp::Bls381Sum => host::ecc_helper::bls381::sum::register_blssum_foreign(env),
+            HostOp::Bn256Pair => host::ecc_helper::bn254::pair::register_bn254pair_foreign(env),
+            HostOp::Bn256Sum => host::ecc_helper::bn254::sum::register_bn254sum_foreign(env),
+            HostOp::PoseidonHash => host::hash_helper::poseidon::register_poseidon_foreign(env),
+            HostOp::Merkle => {
+                host::merkle_helper::merkle::register_merkle_foreign(
+                    env,
+                    self.merkle_tree_height,
+                    tree_db.clone(),
+                );
                 host::merkle_helper::datacache::register_datacache_foreign(env, tree_db);
             }
-            OpType::JUBJUBSUM => host::ecc_helper::jubjub::sum::register_babyjubjubsum_foreign(env),
-            OpType::KECCAKHASH => host::hash_helper::keccak256::register_keccak_foreign(env),
+            HostOp::JubjubSum => host::ecc_helper::jubjub::sum::register_babyjubjubsum_foreign(env),
+            HostOp::KeccakHash => host::hash_helper::keccak256::register_keccak_foreign(env),
+            HostOp::Sha256 => host::hash_helper::sha256::register_sha256_foreign(env),
+            HostOp::Secp256k1Ecrecover => {
+                host::ecc_helper::secp256k1::ecrecover::register_ecrecover_foreign(env)
+            }
+            HostOp::Bn256MultiPair => {
+                host::ecc_helper::bn254::multipair::register_bn254multipair_foreign(env)
+            }
+            HostOp::Eddsa => host::ecc_helper::jubjub::eddsa::register_eddsa_foreign(env),
         }
     }
 
     fn register_ops(&self, env: &mut HostEnv, tree_db: Option<Rc<RefCell<dyn TreeDB>>>) {
         for op in &self.ops {
-            Self::register_op(op, env, tree_db.clone());
+            self.register_op(op, env, tree_db.clone());
         }
     }
+
+    /// Reads a JSON host config, e.g.
+    /// `{ "ops": ["POSEIDONHASH", "MERKLE"], "merkle_tree_height": 24 }`. Unproven
+    /// ops are rejected unless `"allow_unproven": true` is set.
+    pub fn read(reader: impl Read) -> anyhow::Result<Self> {
+        let config: HostEnvConfig = serde_json::from_reader(reader)?;
+
+        let mut enabled = HashSet::new();
+        for op in &config.ops {
+            if !enabled.insert(op) {
+                anyhow::bail!("Host op {:?} is enabled more than once.", op);
+            }
+        }
+
+        config.unproven_ops()?;
+
+        Ok(config)
+    }
+
+    /// Returns the enabled ops which are not `HostOp::is_proven`, they are
+    /// rejected unless `allow_unproven` is set.
+    pub fn unproven_ops(&self) -> anyhow::Result<Vec<HostOp>> {
+        let unproven = self
+            .ops
+            .iter()
+            .filter(|op| !op.is_proven())
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if let Some(op) = unproven.first() {
+            if !self.allow_unproven {
+                anyhow::bail!(
+                    "Host op {:?} has no circuit and its results are not constrained, \
+                        set \"allow_unproven\": true to enable it for development only.",
+                    op
+                );
+            }
+        }
+
+        Ok(unproven)
+    }
+
+    /// Checks that every enabled op fits into the host circuit of size `k`, e.g. a
+    /// Merkle tree which is too high for the circuit.
+    pub fn check(&self, k: u32) -> anyhow::Result<()> {
+        for op in &self.ops {
+            if op.new_plugin_flush_strategy(k, self).maximal_group() == Some(0) {
+                if *op == HostOp::Merkle {
+                    anyhow::bail!(
+                        "The Merkle op with tree height {} does not fit into the circuit of K = {}.",
+                        usize::from(self.merkle_tree_height),
+                        k
+                    );
+                }
+
+                anyhow::bail!(
+                    "Host op {:?} does not fit into the circuit of K = {}.",
+                    op,
+                    k
+                );
+            }
+        }
+
+        Ok(())
+    }
 }
 
 pub struct StandardHostEnvBuilder {
     k: u32,
-    ops: Vec<OpType>,
+    config: HostEnvConfig,
 }
 
 trait GroupedForeign {
-    fn get_optype(&self) -> Option<OpType>;
+    fn get_optype(&self) -> Option<HostOp>;
 }
 
 impl GroupedForeign for ForeignInst {
-    fn get_optype(&self) -> Option<OpType> {
+    fn get_optype(&self) -> Option<HostOp> {
         match self {
-            ForeignInst::MerkleSet => Some(OpType::MERKLE),
-            ForeignInst::MerkleGet => Some(OpType::MERKLE),
-            ForeignInst::MerkleSetRoot => Some(OpType::MERKLE),
-            ForeignInst::MerkleGetRoot => Some(OpType::MERKLE),
-            ForeignInst::MerkleAddress => Some(OpType::MERKLE),
+            ForeignInst::MerkleSet => Some(HostOp::Merkle),
+            ForeignInst::MerkleGet => Some(HostOp::Merkle),
+            ForeignInst::MerkleSetRoot => Some(HostOp::Merkle),
+            ForeignInst::MerkleGetRoot => Some(HostOp::Merkle),
+            ForeignInst::MerkleAddress => Some(HostOp::Merkle),
+
+            ForeignInst::PoseidonPush => Some(HostOp::PoseidonHash),
+            ForeignInst::PoseidonNew => Some(HostOp::PoseidonHash),
+            ForeignInst::PoseidonFinalize => Some(HostOp::PoseidonHash),
 
-            ForeignInst::PoseidonPush => Some(OpType::POSEIDONHASH),
-            ForeignInst::PoseidonNew => Some(OpType::POSEIDONHASH),
-            ForeignInst::PoseidonFinalize => Some(OpType::POSEIDONHASH),
+            ForeignInst::JubjubSumNew => Some(HostOp::JubjubSum),
+            ForeignInst::JubjubSumPush => Some(HostOp::JubjubSum),
+            ForeignInst::JubjubSumResult => Some(HostOp::JubjubSum),
 
-            ForeignInst::JubjubSumNew => Some(OpType::JUBJUBSUM),
-            ForeignInst::JubjubSumPush => Some(OpType::JUBJUBSUM),
-            ForeignInst::JubjubSumResult => Some(OpType::JUBJUBSUM),
+            ForeignInst::SHA256New => Some(HostOp::Sha256),
+            ForeignInst::SHA256Push => Some(HostOp::Sha256),
+            ForeignInst::SHA256Finalize => Some(HostOp::Sha256),
+
+            ForeignInst::BlsPairG1 => Some(HostOp::Bls381Pair),
+            ForeignInst::BlsPairG2 => Some(HostOp::Bls381Pair),
+            ForeignInst::BlsPairG3 => Some(HostOp::Bls381Pair),
+
+            ForeignInst::BlsSumG1 => Some(HostOp::Bls381Sum),
+            ForeignInst::BlsSumResult => Some(HostOp::Bls381Sum),
 
             _ => None,
         }
     }
 }
 
+fn optype_of(op: usize) -> Option<HostOp> {
+    if let Some(inst) = ForeignInst::from_usize(op) {
+        return inst.get_optype();
+    }
+
+    if Bn254MultiPairInst::from_usize(op).is_some() {
+        return Some(HostOp::Bn256MultiPair);
+    }
+
+    if EddsaInst::from_usize(op).is_some() {
+        return Some(HostOp::Eddsa);
+    }
+
+    Secp256k1Inst::from_usize(op).map(|_| HostOp::Secp256k1Ecrecover)
+}
+
 trait GroupedForeignPlugin {
-    fn new_plugin_flush_strategy(&self, k: u32) -> Box<dyn PluginFlushStrategy>;
+    fn new_plugin_flush_strategy(
+        &self,
+        k: u32,
+        config: &HostEnvConfig,
+    ) -> Box<dyn PluginFlushStrategy>;
 }
 
-impl GroupedForeignPlugin for OpType {
-    fn new_plugin_flush_strategy(&self, k: u32) -> Box<dyn PluginFlushStrategy> {
+impl GroupedForeignPlugin for HostOp {
+    fn new_plugin_flush_strategy(
+        &self,
+        k: u32,
+        config: &HostEnvConfig,
+    ) -> Box<dyn PluginFlushStrategy> {
         match self {
-            OpType::POSEIDONHASH => Box::new(PoseidonFlushStrategy::new(k)),
-            OpType::MERKLE => Box::new(MerkleFlushStrategy::new(k)),
-            OpType::JUBJUBSUM => Box::new(JubJubFlushStrategy::new(k)),
+            HostOp::PoseidonHash => Box::new(PoseidonFlushStrategy::new(k)),
+            HostOp::Merkle => Box::new(MerkleFlushStrategy::new(k, config.merkle_tree_height)),
+            HostOp::JubjubSum => Box::new(JubJubFlushStrategy::new(k)),
+            HostOp::Sha256 => Box::new(Sha256FlushStrategy::new(k)),
+            HostOp::Bls381Pair => Box::new(Bls381FlushStrategy::new_pair(k)),
+            HostOp::Bls381Sum => Box::new(Bls381FlushStrategy::new_sum(k)),
+            HostOp::Secp256k1Ecrecover => Box::new(Secp256k1FlushStrategy::new(k)),
+            HostOp::Bn256MultiPair => Box::new(Bn254PairFlushStrategy::new(k)),
+            HostOp::Eddsa => Box::new(EddsaFlushStrategy::new(k)),
             _ => Box::new(TrivialPluginFlushStrategy {}),
         }
     }
 }
 
 trait PluginFlushStrategy {
-    fn notify(&mut self, op: &ForeignInst, value: Option<u64>) -> Vec<Command>;
+    fn notify(&mut self, op: usize, value: Option<u64>) -> Vec<Command>;
     fn reset(&mut self);
 
     fn maximal_group(&self) -> Option<usize>;
@@ -115,7 +345,7 @@ trait PluginFlushStrategy {
 struct TrivialPluginFlushStrategy {}
 
 impl PluginFlushStrategy for TrivialPluginFlushStrategy {
-    fn notify(&mut self, _op: &ForeignInst, _value: Option<u64>) -> Vec<Command> {
+    fn notify(&mut self, _op: usize, _value: Option<u64>) -> Vec<Command> {
         vec![Command::Noop]
     }
 
@@ -128,22 +358,18 @@ impl PluginFlushStrategy for TrivialPluginFlushStrategy {
 
 impl StandardHostEnvBuilder {
     pub fn new(k: u32) -> Self {
-        Self {
-            k,
-            ops: vec![
-                OpType::POSEIDONHASH,
-                OpType::MERKLE,
-                OpType::JUBJUBSUM,
-                OpType::KECCAKHASH,
-                OpType::BN256SUM,
-            ],
-        }
+        Self::with_config(k, HostEnvConfig::default())
+    }
+
+    pub fn with_config(k: u32, config: HostEnvConfig) -> Self {
+        Self { k, config }
     }
 }
 
 #[derive(Default)]
 struct StandardHostEnvFlushStrategy {
     k: u32,
+    config: HostEnvConfig,
     ops: HashMap<usize, Box<dyn PluginFlushStrategy>>,
 }
 
@@ -151,13 +377,7 @@ impl FlushStrategy for StandardHostEnvFlushStrategy {
     fn notify(&mut self, op: Event) -> Vec<Command> {
         match op {
             Event::HostCall(op, value) => {
-                let inst = ForeignInst::from_usize(op);
-                if inst.is_none() {
-                    return vec![Command::Noop];
-                }
-
-                let inst = inst.unwrap();
-                let op_type = inst.get_optype();
+                let op_type = optype_of(op);
                 if op_type.is_none() {
                     return vec![Command::Noop];
                 }
@@ -165,10 +385,10 @@ impl FlushStrategy for StandardHostEnvFlushStrategy {
                 let op_type = op_type.unwrap();
                 let plugin = self
                     .ops
-                    .entry(op_type.clone() as usize)
-                    .or_insert_with(|| op_type.new_plugin_flush_strategy(self.k));
+                    .entry(op_type as usize)
+                    .or_insert_with(|| op_type.new_plugin_flush_strategy(self.k, &self.config));
 
-                plugin.notify(&inst, value)
+                plugin.notify(op, value)
             }
             Event::Reset() => {
                 for (_, plugin) in self.ops.iter_mut() {
@@ -181,22 +401,8 @@ impl FlushStrategy for StandardHostEnvFlushStrategy {
     }
 
     fn maximal_group(&self, transaction: TransactionId) -> Option<usize> {
-        // FIXME: add usize to zkwasm-host-circuits repo
-        fn optype_from_usize(index: usize) -> OpType {
-            match index {
-                // 0 => OpType::BLS381PAIR,
-                // 1 => OpType::BLS381SUM,
-                2 => OpType::BN256PAIR,
-                3 => OpType::BN256SUM,
-                4 => OpType::POSEIDONHASH,
-                5 => OpType::KECCAKHASH,
-                6 => OpType::MERKLE,
-                7 => OpType::JUBJUBSUM,
-                _ => unreachable!(),
-            }
-        }
-        optype_from_usize(transaction)
-            .new_plugin_flush_strategy(self.k)
+        HostOp::from_transaction(transaction)
+            .new_plugin_flush_strategy(self.k, &self.config)
             .maximal_group()
     }
 }
@@ -204,9 +410,6 @@ impl FlushStrategy for StandardHostEnvFlushStrategy {
 impl HostEnvBuilder for StandardHostEnvBuilder {
     fn create_env_without_value(&self) -> HostEnv {
         let mut env = HostEnv::new(self.k);
-        let host_env_config = HostEnvConfig {
-            ops: self.ops.clone(),
-        };
         register_wasm_input_foreign(&mut env, vec![], vec![]);
         register_require_foreign(&mut env);
         register_log_foreign(&mut env);
@@ -215,7 +418,7 @@ impl HostEnvBuilder for StandardHostEnvBuilder {
             &mut env,
             Rc::new(RefCell::new(HashMap::new())),
         );
-        host_env_config.register_ops(&mut env, None);
+        self.config.register_ops(&mut env, None);
 
         env.finalize();
 
@@ -224,16 +427,13 @@ impl HostEnvBuilder for StandardHostEnvBuilder {
 
     fn create_env(&self, arg: ExecutionArg) -> HostEnv {
         let mut env = HostEnv::new(self.k);
-        let host_env_config = HostEnvConfig {
-            ops: self.ops.clone(),
-        };
 
         register_wasm_input_foreign(&mut env, arg.public_inputs, arg.private_inputs);
         register_require_foreign(&mut env);
         register_log_foreign(&mut env);
         register_context_foreign(&mut env, arg.context_inputs);
         host::witness_helper::register_witness_foreign(&mut env, arg.indexed_witness);
-        host_env_config.register_ops(&mut env, arg.tree_db);
+        self.config.register_ops(&mut env, arg.tree_db);
 
         env.finalize();
 
@@ -243,7 +443,70 @@ impl HostEnvBuilder for StandardHostEnvBuilder {
     fn create_flush_strategy(&self) -> Box<dyn FlushStrategy> {
         Box::new(StandardHostEnvFlushStrategy {
             k: self.k,
+            config: self.config.clone(),
             ops: HashMap::new(),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::HostEnvConfig;
+    use super::HostOp;
+    use crate::host::merkle_helper::merkle::MerkleTreeHeight;
+
+    #[test]
+    fn test_read_host_env_config() {
+        let config =
+            HostEnvConfig::read(r#"{ "ops": ["POSEIDONHASH", "MERKLE"] }"#.as_bytes()).unwrap();
+        assert_eq!(config.ops.len(), 2);
+        assert_eq!(config.merkle_tree_height, MerkleTreeHeight::H32);
+
+        assert!(
+            HostEnvConfig::read(r#"{ "ops": ["POSEIDONHASH", "POSEIDONHASH"] }"#.as_bytes())
+                .is_err()
+        );
+
+        let config =
+            HostEnvConfig::read(r#"{ "ops": ["MERKLE"], "merkle_tree_height": 24 }"#.as_bytes())
+                .unwrap();
+        assert_eq!(config.merkle_tree_height, MerkleTreeHeight::H24);
+
+        assert!(HostEnvConfig::read(
+            r#"{ "ops": ["MERKLE"], "merkle_tree_height": 33 }"#.as_bytes()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_check_host_env_config() {
+        assert!(HostEnvConfig::default().check(22).is_ok());
+
+        let config = HostEnvConfig {
+            ops: vec![HostOp::Merkle],
+            ..HostEnvConfig::default()
+        };
+        assert!(config.check(10).is_err());
+    }
+
+    #[test]
+    fn test_unproven_host_op() {
+        assert!(HostEnvConfig::default().ops.iter().all(|op| op.is_proven()));
+
+        assert!(HostEnvConfig::read(r#"{ "ops": ["SHA256"] }"#.as_bytes()).is_err());
+        assert!(HostEnvConfig::read(r#"{ "ops": ["SECP256K1ECRECOVER"] }"#.as_bytes()).is_err());
+
+        let config =
+            HostEnvConfig::read(r#"{ "ops": ["SHA256"], "allow_unproven": true }"#.as_bytes())
+                .unwrap();
+        assert!(config.allow_unproven);
+        assert_eq!(config.unproven_ops().unwrap(), vec![HostOp::Sha256]);
+
+        // a config which was not read, e.g. the host config of a CLI config
+        let config = HostEnvConfig {
+            ops: vec![HostOp::PoseidonHash, HostOp::Sha256],
+            ..HostEnvConfig::default()
+        };
+        assert!(config.unproven_ops().is_err());
+    }
+}
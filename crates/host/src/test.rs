@@ -0,0 +1,46 @@
+use delphinus_zkwasm::loader::ZkWasmLoader;
+use delphinus_zkwasm::runtime::host::default_env::ExecutionArg;
+use delphinus_zkwasm::runtime::host::HostEnvBuilder;
+use delphinus_zkwasm::runtime::monitor::limits::ExecutionLimits;
+use delphinus_zkwasm::runtime::monitor::table_monitor::TableMonitor;
+use specs::slice_backend::InMemoryBackendBuilder;
+
+use crate::HostEnvConfig;
+use crate::StandardHostEnvBuilder;
+
+const K: u32 = 22;
+
+/// Runs `zkmain` of the image in the standard host env of `config` and traces
+/// it with the flush strategies of the enabled ops.
+pub(crate) fn run_image(
+    config: HostEnvConfig,
+    wat: &str,
+    public_inputs: Vec<u64>,
+    private_inputs: Vec<u64>,
+) -> anyhow::Result<()> {
+    let wasm = wabt::wat2wasm(wat)?;
+    let module = ZkWasmLoader::parse_module(&wasm, K, &[])?;
+
+    let env_builder = StandardHostEnvBuilder::with_config(K, config);
+    let env = env_builder.create_env(ExecutionArg {
+        public_inputs,
+        private_inputs,
+        context_inputs: vec![],
+        indexed_witness: Default::default(),
+        tree_db: None,
+    });
+    let mut monitor = TableMonitor::new(
+        K,
+        InMemoryBackendBuilder,
+        env_builder.create_flush_strategy(),
+        &[],
+        &env,
+        ExecutionLimits::default(),
+    );
+
+    let loader = ZkWasmLoader::new(K, env)?;
+    let runner = loader.compile(&module, &mut monitor)?;
+    loader.run(runner, &mut monitor)?;
+
+    Ok(())
+}
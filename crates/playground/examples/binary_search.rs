@@ -5,6 +5,7 @@ use delphinus_zkwasm::loader::ZkWasmLoader;
 use delphinus_zkwasm::runtime::host::default_env::DefaultHostEnvBuilder;
 use delphinus_zkwasm::runtime::host::default_env::ExecutionArg;
 use delphinus_zkwasm::runtime::host::HostEnvBuilder;
+use delphinus_zkwasm::runtime::monitor::limits::ExecutionLimits;
 use delphinus_zkwasm::runtime::monitor::plugins::table::InMemoryBackendBuilder;
 use delphinus_zkwasm::runtime::monitor::table_monitor::TableMonitor;
 use pairing_bn256::bn256::Fr;
@@ -16,7 +17,7 @@ const K: u32 = MIN_K;
 
 fn main() -> Result<()> {
     let wasm = std::fs::read("wasm/binary_search.wasm")?;
-    let module = ZkWasmLoader::parse_module(&wasm)?;
+    let module = ZkWasmLoader::parse_module(&wasm, K, &[])?;
     let env_builder = DefaultHostEnvBuilder::new(K);
 
     let env = env_builder.create_env(ExecutionArg {
@@ -32,6 +33,7 @@ fn main() -> Result<()> {
         env_builder.create_flush_strategy(),
         &vec![],
         &env,
+        ExecutionLimits::default(),
     );
     let loader = ZkWasmLoader::new(K, env)?;
 
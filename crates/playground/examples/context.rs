@@ -5,6 +5,7 @@ use delphinus_zkwasm::loader::ZkWasmLoader;
 use delphinus_zkwasm::runtime::host::default_env::DefaultHostEnvBuilder;
 use delphinus_zkwasm::runtime::host::default_env::ExecutionArg;
 use delphinus_zkwasm::runtime::host::HostEnvBuilder;
+use delphinus_zkwasm::runtime::monitor::limits::ExecutionLimits;
 use delphinus_zkwasm::runtime::monitor::plugins::table::InMemoryBackendBuilder;
 use delphinus_zkwasm::runtime::monitor::table_monitor::TableMonitor;
 use pairing_bn256::bn256::Fr;
@@ -16,7 +17,7 @@ const K: u32 = MIN_K;
 
 fn main() -> Result<()> {
     let wasm = std::fs::read("wasm/context.wasm")?;
-    let module = ZkWasmLoader::parse_module(&wasm)?;
+    let module = ZkWasmLoader::parse_module(&wasm, K, &[])?;
 
     let context_output = {
         let env_builder = DefaultHostEnvBuilder::new(K);
@@ -35,6 +36,7 @@ fn main() -> Result<()> {
             env_builder.create_flush_strategy(),
             &vec![],
             &env,
+            ExecutionLimits::default(),
         );
         let loader = ZkWasmLoader::new(K, env)?;
 
@@ -64,6 +66,7 @@ fn main() -> Result<()> {
             env_builder.create_flush_strategy(),
             &vec![],
             &env,
+            ExecutionLimits::default(),
         );
         let loader = ZkWasmLoader::new(K, env)?;
 
@@ -5,6 +5,7 @@ use delphinus_zkwasm::loader::ZkWasmLoader;
 use delphinus_zkwasm::runtime::host::default_env::DefaultHostEnvBuilder;
 use delphinus_zkwasm::runtime::host::default_env::ExecutionArg;
 use delphinus_zkwasm::runtime::host::HostEnvBuilder;
+use delphinus_zkwasm::runtime::monitor::limits::ExecutionLimits;
 use delphinus_zkwasm::runtime::monitor::plugins::table::InMemoryBackendBuilder;
 use delphinus_zkwasm::runtime::monitor::table_monitor::TableMonitor;
 use pairing_bn256::bn256::Fr;
@@ -16,7 +17,8 @@ const K: u32 = MIN_K;
 
 fn main() -> Result<()> {
     let wasm = std::fs::read("wasm/phantom.wasm")?;
-    let module = ZkWasmLoader::parse_module(&wasm)?;
+    let phantom_functions = vec!["search".to_string()];
+    let module = ZkWasmLoader::parse_module(&wasm, K, &phantom_functions)?;
     let env_builder = DefaultHostEnvBuilder::new(K);
 
     let env = env_builder.create_env(ExecutionArg {
@@ -30,8 +32,9 @@ fn main() -> Result<()> {
         K,
         InMemoryBackendBuilder,
         env_builder.create_flush_strategy(),
-        &vec!["search".to_string()],
+        &phantom_functions,
         &env,
+        ExecutionLimits::default(),
     );
     let loader = ZkWasmLoader::new(K, env)?;
 
@@ -4,6 +4,7 @@ use crate::itable::OpcodeClass;
 use crate::itable::OPCODE_ARG0_SHIFT as OPCODE_ARG0;
 use crate::itable::OPCODE_ARG1_SHIFT as OPCODE_ARG1;
 use crate::itable::OPCODE_CLASS_SHIFT as OPCODE_CLASS;
+use crate::itable::OPCODE_KEEP_TYPE_SHIFT as OPCODE_KEEP_TYPE;
 
 use super::FromBn;
 
@@ -11,6 +12,7 @@ lazy_static! {
     static ref OPCODE_CLASS_SHIFT: BigUint = BigUint::from(1u64) << OPCODE_CLASS;
     static ref OPCODE_ARG0_SHIFT: BigUint = BigUint::from(1u64) << OPCODE_ARG0;
     static ref OPCODE_ARG1_SHIFT: BigUint = BigUint::from(1u64) << OPCODE_ARG1;
+    static ref OPCODE_KEEP_TYPE_SHIFT: BigUint = BigUint::from(1u64) << OPCODE_KEEP_TYPE;
 }
 
 pub fn encode_global_get<T: FromBn>(globalidx: T) -> T {
@@ -39,17 +41,19 @@ pub fn encode_call_host<T: FromBn>(op: T, is_ret: T) -> T {
         + is_ret * T::from_bn(&OPCODE_ARG1_SHIFT)
 }
 
-pub fn encode_br<T: FromBn>(drop: T, keep: T, dst_pc: T) -> T {
+pub fn encode_br<T: FromBn>(drop: T, keep: T, keep_type: T, dst_pc: T) -> T {
     T::from_bn(&(BigUint::from(OpcodeClass::Br as u64))) * T::from_bn(&OPCODE_CLASS_SHIFT)
         + drop * T::from_bn(&OPCODE_ARG0_SHIFT)
         + keep * T::from_bn(&OPCODE_ARG1_SHIFT)
+        + keep_type * T::from_bn(&OPCODE_KEEP_TYPE_SHIFT)
         + dst_pc
 }
 
-pub fn encode_br_if_eqz<T: FromBn>(drop: T, keep: T, dst_pc: T) -> T {
+pub fn encode_br_if_eqz<T: FromBn>(drop: T, keep: T, keep_type: T, dst_pc: T) -> T {
     T::from_bn(&(BigUint::from(OpcodeClass::BrIfEqz as u64))) * T::from_bn(&OPCODE_CLASS_SHIFT)
         + drop * T::from_bn(&OPCODE_ARG0_SHIFT)
         + keep * T::from_bn(&OPCODE_ARG1_SHIFT)
+        + keep_type * T::from_bn(&OPCODE_KEEP_TYPE_SHIFT)
         + dst_pc
 }
 
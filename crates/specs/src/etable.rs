@@ -53,7 +53,7 @@ impl Iterator for RestJops {
     }
 }
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct EventTable(Vec<EventTableEntry>);
 
 impl EventTable {
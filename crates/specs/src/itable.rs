@@ -12,6 +12,7 @@ use crate::encode::opcode::encode_global_set;
 use crate::encode::COMMON_RANGE_OFFSET;
 use crate::external_host_call_table::ExternalHostCallSignature;
 use crate::host_function::HostPlugin;
+use crate::mtable::FloatType;
 use crate::mtable::MemoryReadSize;
 use crate::mtable::MemoryStoreSize;
 use crate::types::ValueType;
@@ -54,6 +55,12 @@ pub enum OpcodeClass {
     MemorySize,
     MemoryGrow,
     Conversion,
+    FloatBin,
+    FloatUnary,
+    FloatRel,
+    FloatConversion,
+    MemoryFill,
+    MemoryCopy,
     ForeignPluginStart,
 }
 
@@ -87,6 +94,14 @@ impl OpcodeClass {
             OpcodeClass::MemorySize => 1,
             OpcodeClass::MemoryGrow => 2,
             OpcodeClass::Conversion => 2,
+            OpcodeClass::FloatBin => 3,
+            OpcodeClass::FloatUnary => 2,
+            OpcodeClass::FloatRel => 3,
+            OpcodeClass::FloatConversion => 2,
+            // Per iteration: write the byte, update the destination and the length
+            OpcodeClass::MemoryFill => 3,
+            // Per iteration: write the byte, update the destination, the source and the length
+            OpcodeClass::MemoryCopy => 4,
             OpcodeClass::ForeignPluginStart => 0,
         }
     }
@@ -185,6 +200,61 @@ pub enum ConversionOp {
     I64Extend32S,
 }
 
+/// Float operations are limited to the exact ones, whose results need no
+/// rounding. The rounding arithmetic (add, sub, mul, div, sqrt and the
+/// rounding to integral values) and the numeric conversions between floats
+/// and integers have no circuit and are rejected by the loader precheck.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FloatBinOp {
+    Min,
+    Max,
+    Copysign,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FloatUnaryOp {
+    Abs,
+    Neg,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FloatRelOp {
+    Eq,
+    Ne,
+    Lt,
+    Gt,
+    Le,
+    Ge,
+}
+
+/// Only the reinterpret conversions, which keep the raw bits untouched.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FloatConversionOp {
+    I32ReinterpretF32,
+    I64ReinterpretF64,
+    F32ReinterpretI32,
+    F64ReinterpretI64,
+}
+
+impl FloatConversionOp {
+    /// Bit width of the popped operand, floats are carried as their raw bits.
+    pub fn value_type(&self) -> VarType {
+        match self {
+            FloatConversionOp::I32ReinterpretF32 | FloatConversionOp::F32ReinterpretI32 => {
+                VarType::I32
+            }
+            FloatConversionOp::I64ReinterpretF64 | FloatConversionOp::F64ReinterpretI64 => {
+                VarType::I64
+            }
+        }
+    }
+
+    /// Bit width of the pushed result, the same as the operand.
+    pub fn result_type(&self) -> VarType {
+        self.value_type()
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub struct BrTarget {
     pub drop: u32,
@@ -214,6 +284,8 @@ pub enum Opcode {
     },
     MemorySize,
     MemoryGrow,
+    MemoryFill,
+    MemoryCopy,
     Const {
         vtype: VarType,
         value: u64,
@@ -296,6 +368,21 @@ pub enum Opcode {
     Conversion {
         class: ConversionOp,
     },
+    FloatBin {
+        class: FloatBinOp,
+        vtype: FloatType,
+    },
+    FloatUnary {
+        class: FloatUnaryOp,
+        vtype: FloatType,
+    },
+    FloatRel {
+        class: FloatRelOp,
+        vtype: FloatType,
+    },
+    FloatConversion {
+        class: FloatConversionOp,
+    },
 }
 
 impl Opcode {
@@ -323,8 +410,22 @@ pub const OPCODE_SHIFT: u32 = OPCODE_CLASS_SHIFT + 16;
 pub const OPCODE_CLASS_SHIFT: u32 = OPCODE_ARG0_SHIFT + COMMON_RANGE_OFFSET;
 pub const OPCODE_ARG0_SHIFT: u32 = OPCODE_ARG1_SHIFT + COMMON_RANGE_OFFSET;
 pub const OPCODE_ARG1_SHIFT: u32 = 64;
+/// Offset of the kept value types of `br`, `br_if` and `br_if_eqz`, `dst_pc`
+/// occupies the bits below.
+pub const OPCODE_KEEP_TYPE_SHIFT: u32 = 32;
 pub const OPCODE_CELL: usize = 4;
 
+/// Max number of values kept by a single `br*`/`return` instruction that the
+/// circuit supports.
+pub const MAX_KEEP_VALUES: usize = 3;
+
+/// Types of the kept values, bit `i` is set if the value at depth `i` is i32.
+fn encode_keep_types(keep: &[ValueType]) -> u64 {
+    keep.iter()
+        .enumerate()
+        .fold(0u64, |acc, (i, x)| acc + ((VarType::from(x) as u64) << i))
+}
+
 impl From<&Opcode> for BigUint {
     fn from(opcode: &Opcode) -> BigUint {
         let bn = match opcode {
@@ -356,7 +457,7 @@ impl From<&Opcode> for BigUint {
                 (BigUint::from(OpcodeClass::Return as u64) << OPCODE_CLASS_SHIFT)
                     + (BigUint::from(*drop as u64) << OPCODE_ARG0_SHIFT)
                     + (BigUint::from(keep.len() as u64) << OPCODE_ARG1_SHIFT)
-                    + keep.first().map_or(0u64, |x| VarType::from(x) as u64)
+                    + encode_keep_types(keep)
             }
             Opcode::Bin { class, vtype } => {
                 (BigUint::from(OpcodeClass::Bin as u64) << OPCODE_CLASS_SHIFT)
@@ -389,22 +490,23 @@ impl From<&Opcode> for BigUint {
                     + (BigUint::from(*vtype as u64) << OPCODE_ARG1_SHIFT)
             }
             Opcode::Br { drop, keep, dst_pc } => {
-                // TODO: should encode type of keep values?
                 (BigUint::from(OpcodeClass::Br as u64) << OPCODE_CLASS_SHIFT)
                     + (BigUint::from(*drop as u64) << OPCODE_ARG0_SHIFT)
                     + (BigUint::from(keep.len() as u64) << OPCODE_ARG1_SHIFT)
+                    + (BigUint::from(encode_keep_types(keep)) << OPCODE_KEEP_TYPE_SHIFT)
                     + dst_pc
             }
             Opcode::BrIf { drop, keep, dst_pc } => {
-                // TODO: should encode type of keep values?
                 (BigUint::from(OpcodeClass::BrIf as u64) << OPCODE_CLASS_SHIFT)
                     + (BigUint::from(*drop as u64) << OPCODE_ARG0_SHIFT)
                     + (BigUint::from(keep.len() as u64) << OPCODE_ARG1_SHIFT)
+                    + (BigUint::from(encode_keep_types(keep)) << OPCODE_KEEP_TYPE_SHIFT)
                     + dst_pc
             }
             Opcode::BrIfEqz { drop, keep, dst_pc } => encode_br_if_eqz(
                 BigUint::from(*drop as u64),
                 BigUint::from(keep.len() as u64),
+                BigUint::from(encode_keep_types(keep)),
                 BigUint::from(*dst_pc),
             ),
             Opcode::BrTable { targets } => encode_br_table(BigUint::from(targets.len())),
@@ -454,6 +556,12 @@ impl From<&Opcode> for BigUint {
             Opcode::MemoryGrow => {
                 BigUint::from(OpcodeClass::MemoryGrow as u64) << OPCODE_CLASS_SHIFT
             }
+            Opcode::MemoryFill => {
+                BigUint::from(OpcodeClass::MemoryFill as u64) << OPCODE_CLASS_SHIFT
+            }
+            Opcode::MemoryCopy => {
+                BigUint::from(OpcodeClass::MemoryCopy as u64) << OPCODE_CLASS_SHIFT
+            }
             Opcode::Conversion { class } => match class {
                 ConversionOp::I32WrapI64 => encode_conversion(
                     0u64.into(),
@@ -536,6 +644,25 @@ impl From<&Opcode> for BigUint {
                     1u64.into(),
                 ),
             },
+            Opcode::FloatBin { class, vtype } => {
+                (BigUint::from(OpcodeClass::FloatBin as u64) << OPCODE_CLASS_SHIFT)
+                    + (BigUint::from(*class as u64) << OPCODE_ARG0_SHIFT)
+                    + (BigUint::from(*vtype as u64) << OPCODE_ARG1_SHIFT)
+            }
+            Opcode::FloatUnary { class, vtype } => {
+                (BigUint::from(OpcodeClass::FloatUnary as u64) << OPCODE_CLASS_SHIFT)
+                    + (BigUint::from(*class as u64) << OPCODE_ARG0_SHIFT)
+                    + (BigUint::from(*vtype as u64) << OPCODE_ARG1_SHIFT)
+            }
+            Opcode::FloatRel { class, vtype } => {
+                (BigUint::from(OpcodeClass::FloatRel as u64) << OPCODE_CLASS_SHIFT)
+                    + (BigUint::from(*class as u64) << OPCODE_ARG0_SHIFT)
+                    + (BigUint::from(*vtype as u64) << OPCODE_ARG1_SHIFT)
+            }
+            Opcode::FloatConversion { class } => {
+                (BigUint::from(OpcodeClass::FloatConversion as u64) << OPCODE_CLASS_SHIFT)
+                    + (BigUint::from(*class as u64) << OPCODE_ARG0_SHIFT)
+            }
         };
         assert!(bn < BigUint::from(1u64) << OPCODE_SHIFT);
         bn
@@ -573,7 +700,13 @@ impl From<&Opcode> for OpcodeClass {
             Opcode::Store { .. } => OpcodeClass::Store,
             Opcode::MemorySize => OpcodeClass::MemorySize,
             Opcode::MemoryGrow => OpcodeClass::MemoryGrow,
+            Opcode::MemoryFill => OpcodeClass::MemoryFill,
+            Opcode::MemoryCopy => OpcodeClass::MemoryCopy,
             Opcode::Conversion { .. } => OpcodeClass::Conversion,
+            Opcode::FloatBin { .. } => OpcodeClass::FloatBin,
+            Opcode::FloatUnary { .. } => OpcodeClass::FloatUnary,
+            Opcode::FloatRel { .. } => OpcodeClass::FloatRel,
+            Opcode::FloatConversion { .. } => OpcodeClass::FloatConversion,
         }
     }
 }
@@ -645,6 +778,10 @@ impl InstructionTableInternal {
             iid as u32, opcode,
         ));
     }
+
+    pub fn get(&self, fid: u32, iid: u32) -> &InstructionTableEntry {
+        self.0[fid as usize][iid as usize].as_ref().unwrap()
+    }
 }
 
 // Use Option because iid may be discontinuous
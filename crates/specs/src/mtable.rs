@@ -30,6 +30,48 @@ pub enum VarType {
     I32 = 1,
 }
 
+/// Floats are carried by the stack and memory tables as their raw bits,
+/// `FloatType` only records which IEEE-754 format the bits are interpreted as.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, EnumIter, Serialize, Deserialize, Hash, PartialOrd, Ord,
+)]
+pub enum FloatType {
+    F64 = 0,
+    F32 = 1,
+}
+
+impl FloatType {
+    pub fn sign_bit(&self) -> u64 {
+        match self {
+            FloatType::F32 => 1 << 31,
+            FloatType::F64 => 1 << 63,
+        }
+    }
+
+    pub fn infinity_bits(&self) -> u64 {
+        match self {
+            FloatType::F32 => f32::INFINITY.to_bits() as u64,
+            FloatType::F64 => f64::INFINITY.to_bits(),
+        }
+    }
+
+    pub fn canonical_nan_bits(&self) -> u64 {
+        match self {
+            FloatType::F32 => 0x7fc0_0000,
+            FloatType::F64 => 0x7ff8_0000_0000_0000,
+        }
+    }
+}
+
+impl From<FloatType> for VarType {
+    fn from(v: FloatType) -> Self {
+        match v {
+            FloatType::F32 => VarType::I32,
+            FloatType::F64 => VarType::I64,
+        }
+    }
+}
+
 impl VarType {
     pub fn byte_size(&self) -> u64 {
         match self {
@@ -78,7 +120,8 @@ impl From<parity_wasm::elements::ValueType> for VarType {
         match v {
             parity_wasm::elements::ValueType::I32 => Self::I32,
             parity_wasm::elements::ValueType::I64 => Self::I64,
-            _ => todo!(),
+            parity_wasm::elements::ValueType::F32 => Self::I32,
+            parity_wasm::elements::ValueType::F64 => Self::I64,
         }
     }
 }
@@ -86,8 +129,8 @@ impl From<parity_wasm::elements::ValueType> for VarType {
 impl From<crate::types::ValueType> for VarType {
     fn from(v: crate::types::ValueType) -> Self {
         match v {
-            crate::types::ValueType::I32 => Self::I32,
-            crate::types::ValueType::I64 => Self::I64,
+            crate::types::ValueType::I32 | crate::types::ValueType::F32 => Self::I32,
+            crate::types::ValueType::I64 | crate::types::ValueType::F64 => Self::I64,
         }
     }
 }
@@ -95,8 +138,8 @@ impl From<crate::types::ValueType> for VarType {
 impl From<&crate::types::ValueType> for VarType {
     fn from(v: &crate::types::ValueType) -> Self {
         match v {
-            crate::types::ValueType::I32 => Self::I32,
-            crate::types::ValueType::I64 => Self::I64,
+            crate::types::ValueType::I32 | crate::types::ValueType::F32 => Self::I32,
+            crate::types::ValueType::I64 | crate::types::ValueType::F64 => Self::I64,
         }
     }
 }
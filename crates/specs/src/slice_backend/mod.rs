@@ -9,7 +9,7 @@ use crate::etable::EventTable;
 use crate::external_host_call_table::ExternalHostCallTable;
 use crate::jtable::FrameTable;
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Slice {
     pub etable: EventTable,
     pub frame_table: FrameTable,
@@ -3,9 +3,14 @@ use crate::host_function::HostPlugin;
 use crate::host_function::Signature;
 use crate::itable::BinOp;
 use crate::itable::BitOp;
+use crate::itable::FloatBinOp;
+use crate::itable::FloatConversionOp;
+use crate::itable::FloatRelOp;
+use crate::itable::FloatUnaryOp;
 use crate::itable::RelOp;
 use crate::itable::ShiftOp;
 use crate::itable::UnaryOp;
+use crate::mtable::FloatType;
 use crate::mtable::MemoryReadSize;
 use crate::mtable::MemoryStoreSize;
 use crate::mtable::VarType;
@@ -13,6 +18,8 @@ use crate::types::ValueType;
 use serde::Deserialize;
 use serde::Serialize;
 
+/// For the `br*` and `return` steps, `keep[i]` and `keep_values[i]` describe the
+/// value at depth `i` of the stack, i.e. `keep[0]` is the topmost kept value.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum StepInfo {
     Br {
@@ -138,6 +145,36 @@ pub enum StepInfo {
         grow_size: i32,
         result: i32,
     },
+    /// One iteration of `memory.fill`, `len` is the remaining length before
+    /// this iteration. The iteration with `len == 0` pops the operands.
+    ///
+    /// An iteration writes a whole block if `is_word` is set, which requires
+    /// `offset` to be aligned and at least 8 bytes left, otherwise one byte.
+    MemoryFill {
+        offset: u32,
+        value: u32,
+        len: u32,
+        is_word: bool,
+        pre_block_value: u64,
+        updated_block_value: u64,
+    },
+    /// One iteration of `memory.copy`, `len` is the remaining length before
+    /// this iteration. The iteration with `len == 0` pops the operands.
+    ///
+    /// Bytes are copied in ascending order if `dst <= src`, otherwise in
+    /// descending order starting from the end of the ranges. An iteration
+    /// copies a whole block if `is_word` is set, which requires both ranges to
+    /// be aligned at the copied end and at least 8 bytes left, otherwise one
+    /// byte.
+    MemoryCopy {
+        dst: u32,
+        src: u32,
+        len: u32,
+        is_word: bool,
+        src_block_value: u64,
+        pre_block_value: u64,
+        updated_block_value: u64,
+    },
 
     I32Const {
         value: i32,
@@ -238,4 +275,31 @@ pub enum StepInfo {
         value: i64,
         result: i64,
     },
+
+    // Float operands and results are the raw IEEE-754 bits.
+    FloatBinOp {
+        class: FloatBinOp,
+        vtype: FloatType,
+        left: u64,
+        right: u64,
+        value: u64,
+    },
+    FloatUnaryOp {
+        class: FloatUnaryOp,
+        vtype: FloatType,
+        operand: u64,
+        result: u64,
+    },
+    FloatComp {
+        class: FloatRelOp,
+        vtype: FloatType,
+        left: u64,
+        right: u64,
+        value: bool,
+    },
+    FloatConversion {
+        class: FloatConversionOp,
+        value: u64,
+        result: u64,
+    },
 }
@@ -3,6 +3,7 @@ use halo2_proofs::circuit::Layouter;
 use halo2_proofs::plonk::Advice;
 use halo2_proofs::plonk::Column;
 use halo2_proofs::plonk::Error;
+use specs::itable::BitOp;
 use specs::itable::UnaryOp;
 use specs::step::StepInfo;
 
@@ -65,6 +66,26 @@ impl BitTableTrait for EventTableWithMemoryInfo {
                     result: *result,
                 }),
 
+                StepInfo::MemoryCopy {
+                    dst,
+                    src,
+                    len,
+                    is_word,
+                    src_block_value,
+                    ..
+                } if *len != 0 => {
+                    let width = if *is_word { 8 } else { 1 };
+                    let src_address = if dst <= src { *src } else { src + len - width };
+                    let modulus = 1u64 << ((src_address & 7) * 8);
+
+                    Some(BitTableAssign {
+                        op: BitTableOp::BinaryBit(BitOp::And),
+                        left: *src_block_value,
+                        right: modulus * 0xff,
+                        result: src_block_value & (modulus * 0xff),
+                    })
+                }
+
                 _ => None,
             })
             .collect::<Vec<_>>()
@@ -155,11 +155,11 @@ pub(crate) enum EventTableCellType {
     MTableLookup,
 }
 
-const BIT_COLUMNS: usize = 12;
+const BIT_COLUMNS: usize = 14;
 const U8_COLUMNS: usize = 1;
 const U32_CELLS: usize = 2;
 const U32_PERMUTATION_CELLS: usize = if cfg!(feature = "continuation") {
-    10
+    12
 } else {
     0
 };
@@ -172,7 +172,7 @@ const UNLIMITED_COLUMNS: usize = if cfg!(feature = "continuation") {
 } else {
     8
 };
-const MEMORY_TABLE_LOOKUP_COLUMNS: usize = 2;
+const MEMORY_TABLE_LOOKUP_COLUMNS: usize = 3;
 
 #[derive(Clone, Copy)]
 pub(crate) struct AllocatedBitTableLookupCells<F: FieldExt> {
@@ -24,12 +24,18 @@ use crate::circuits::etable::op_configure::op_call_indirect::CallIndirectConfigB
 use crate::circuits::etable::op_configure::op_const::ConstConfigBuilder;
 use crate::circuits::etable::op_configure::op_conversion::ConversionConfigBuilder;
 use crate::circuits::etable::op_configure::op_drop::DropConfigBuilder;
+use crate::circuits::etable::op_configure::op_float_bin::FloatBinConfigBuilder;
+use crate::circuits::etable::op_configure::op_float_conversion::FloatConversionConfigBuilder;
+use crate::circuits::etable::op_configure::op_float_rel::FloatRelConfigBuilder;
+use crate::circuits::etable::op_configure::op_float_unary::FloatUnaryConfigBuilder;
 use crate::circuits::etable::op_configure::op_global_get::GlobalGetConfigBuilder;
 use crate::circuits::etable::op_configure::op_global_set::GlobalSetConfigBuilder;
 use crate::circuits::etable::op_configure::op_load::LoadConfigBuilder;
 use crate::circuits::etable::op_configure::op_local_get::LocalGetConfigBuilder;
 use crate::circuits::etable::op_configure::op_local_set::LocalSetConfigBuilder;
 use crate::circuits::etable::op_configure::op_local_tee::LocalTeeConfigBuilder;
+use crate::circuits::etable::op_configure::op_memory_copy::MemoryCopyConfigBuilder;
+use crate::circuits::etable::op_configure::op_memory_fill::MemoryFillConfigBuilder;
 use crate::circuits::etable::op_configure::op_memory_grow::MemoryGrowConfigBuilder;
 use crate::circuits::etable::op_configure::op_memory_size::MemorySizeConfigBuilder;
 use crate::circuits::etable::op_configure::op_rel::RelConfigBuilder;
@@ -73,7 +79,7 @@ type AllocatedU32StateCell<F> = AllocatedU32PermutationCell<F>;
 type AllocatedU32StateCell<F> = AllocatedCommonRangeCell<F>;
 
 pub(crate) const EVENT_TABLE_ENTRY_ROWS: i32 = 4;
-pub(crate) const OP_CAPABILITY: usize = 32;
+pub(crate) const OP_CAPABILITY: usize = 40;
 
 const FOREIGN_LOOKUP_CAPABILITY: usize = 6;
 
@@ -383,8 +389,14 @@ impl<F: FieldExt> EventTableConfig<F> {
         configure!(OpcodeClass::BinBit, BinBitConfigBuilder);
         configure!(OpcodeClass::MemorySize, MemorySizeConfigBuilder);
         configure!(OpcodeClass::MemoryGrow, MemoryGrowConfigBuilder);
+        configure!(OpcodeClass::MemoryFill, MemoryFillConfigBuilder);
+        configure!(OpcodeClass::MemoryCopy, MemoryCopyConfigBuilder);
         configure!(OpcodeClass::BrTable, BrTableConfigBuilder);
         configure!(OpcodeClass::CallIndirect, CallIndirectConfigBuilder);
+        configure!(OpcodeClass::FloatBin, FloatBinConfigBuilder);
+        configure!(OpcodeClass::FloatUnary, FloatUnaryConfigBuilder);
+        configure!(OpcodeClass::FloatRel, FloatRelConfigBuilder);
+        configure!(OpcodeClass::FloatConversion, FloatConversionConfigBuilder);
 
         macro_rules! configure_foreign {
             ($x:ident, $i:expr) => {
@@ -0,0 +1,243 @@
+//! Shared gadgets for `memory.fill` and `memory.copy`.
+//!
+//! Both instructions are executed as one step per aligned block, with one step
+//! per byte for the unaligned head and tail. Every non-final step either
+//! overwrites a single byte of the block containing the destination address,
+//! or the whole block if the destination address is aligned.
+
+use crate::circuits::cell::*;
+use crate::circuits::etable::allocator::*;
+use crate::circuits::etable::ConstraintBuilder;
+use crate::circuits::etable::EventTableCommonConfig;
+use crate::circuits::mtable::utils::block_from_address;
+use crate::circuits::mtable::utils::byte_offset_from_address;
+use crate::circuits::mtable::utils::WASM_BLOCKS_PER_PAGE;
+use crate::circuits::mtable::utils::WASM_BLOCK_BYTE_SIZE;
+use crate::circuits::utils::step_status::StepStatus;
+use crate::circuits::utils::table_entry::MemoryRWEntry;
+use crate::circuits::utils::Context;
+use crate::constant_from;
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::Error;
+use halo2_proofs::plonk::Expression;
+use halo2_proofs::plonk::VirtualCells;
+use specs::mtable::LocationType;
+
+#[derive(Clone, Copy)]
+pub(super) struct MemoryWriteCells<F: FieldExt> {
+    block_index: AllocatedU32Cell<F>,
+    inner_pos_bits: [AllocatedBitCell<F>; 3],
+    inner_pos: AllocatedUnlimitedCell<F>,
+    /// 1 << (inner_pos * 8)
+    modulus: AllocatedUnlimitedCell<F>,
+
+    tailing: AllocatedU64Cell<F>,
+    tailing_diff: AllocatedU64Cell<F>,
+    picked: AllocatedU8Cell<F>,
+    leading: AllocatedU64Cell<F>,
+
+    address_within_allocated_pages_helper: AllocatedCommonRangeCell<F>,
+
+    memory_table_lookup_heap_read: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_heap_write: AllocatedMemoryTableLookupWriteCell<F>,
+}
+
+impl<F: FieldExt> MemoryWriteCells<F> {
+    /// Writes `byte` at `address` if `enable` is set, or replaces the whole
+    /// block starting at `address` with `word` if `is_word` is also set.
+    pub(super) fn configure(
+        common_config: &EventTableCommonConfig<F>,
+        allocator: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+        address: impl Fn(&mut VirtualCells<'_, F>) -> Expression<F> + Copy + 'static,
+        byte: impl Fn(&mut VirtualCells<'_, F>) -> Expression<F> + Copy + 'static,
+        word: impl Fn(&mut VirtualCells<'_, F>) -> Expression<F> + Copy + 'static,
+        is_word: impl Fn(&mut VirtualCells<'_, F>) -> Expression<F> + Copy + 'static,
+        enable: impl Fn(&mut VirtualCells<'_, F>) -> Expression<F> + Copy + 'static,
+    ) -> Self {
+        let block_index = allocator.alloc_u32_cell();
+        let inner_pos_bits = [0; 3].map(|_| allocator.alloc_bit_cell());
+        let inner_pos = allocator.alloc_unlimited_cell();
+        let modulus = allocator.alloc_unlimited_cell();
+
+        let tailing = allocator.alloc_u64_cell();
+        let tailing_diff = allocator.alloc_u64_cell();
+        let picked = allocator.alloc_u8_cell();
+        let leading = allocator.alloc_u64_cell();
+
+        let address_within_allocated_pages_helper = allocator.alloc_common_range_cell();
+
+        let eid = common_config.eid_cell;
+        let current_memory_page_size = common_config.mpages_cell;
+
+        let memory_table_lookup_heap_read = allocator
+            .alloc_memory_table_lookup_read_cell_with_value(
+                "bulk memory load origin",
+                constraint_builder,
+                eid,
+                move |____| constant_from!(LocationType::Heap as u64),
+                move |meta| block_index.expr(meta),
+                move |____| constant_from!(0),
+                enable,
+            );
+
+        let memory_table_lookup_heap_write = allocator
+            .alloc_memory_table_lookup_write_cell_with_value(
+                "bulk memory write res",
+                constraint_builder,
+                eid,
+                move |____| constant_from!(LocationType::Heap as u64),
+                move |meta| block_index.expr(meta),
+                move |____| constant_from!(0),
+                enable,
+            );
+
+        let pre_block_value = memory_table_lookup_heap_read.value_cell;
+        let updated_block_value = memory_table_lookup_heap_write.value_cell;
+
+        constraint_builder.push(
+            "bulk memory: inner pos",
+            Box::new(move |meta| {
+                vec![
+                    inner_pos.expr(meta)
+                        - inner_pos_bits[0].expr(meta)
+                        - inner_pos_bits[1].expr(meta) * constant_from!(2)
+                        - inner_pos_bits[2].expr(meta) * constant_from!(4),
+                    modulus.expr(meta)
+                        - (constant_from!(1) + inner_pos_bits[0].expr(meta) * constant_from!(0xff))
+                            * (constant_from!(1)
+                                + inner_pos_bits[1].expr(meta) * constant_from!(0xffff))
+                            * (constant_from!(1)
+                                + inner_pos_bits[2].expr(meta) * constant_from!(u32::MAX)),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "bulk memory: block index",
+            Box::new(move |meta| {
+                vec![
+                    (block_index.expr(meta) * constant_from!(WASM_BLOCK_BYTE_SIZE)
+                        + inner_pos.expr(meta)
+                        - address(meta))
+                        * enable(meta),
+                    inner_pos.expr(meta) * is_word(meta) * enable(meta),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "bulk memory: write byte",
+            Box::new(move |meta| {
+                vec![
+                    (tailing.expr(meta) + tailing_diff.expr(meta) + constant_from!(1)
+                        - modulus.expr(meta))
+                        * enable(meta),
+                    (tailing.expr(meta)
+                        + picked.expr(meta) * modulus.expr(meta)
+                        + leading.expr(meta) * modulus.expr(meta) * constant_from!(1 << 8)
+                        - pre_block_value.expr(meta))
+                        * enable(meta),
+                    (pre_block_value.expr(meta)
+                        + (byte(meta) - picked.expr(meta)) * modulus.expr(meta)
+                        - updated_block_value.expr(meta))
+                        * (constant_from!(1) - is_word(meta))
+                        * enable(meta),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "bulk memory: write word",
+            Box::new(move |meta| {
+                vec![(word(meta) - updated_block_value.expr(meta)) * is_word(meta) * enable(meta)]
+            }),
+        );
+
+        constraint_builder.push(
+            "bulk memory: allocated address",
+            Box::new(move |meta| {
+                vec![
+                    (block_index.expr(meta)
+                        + constant_from!(1)
+                        + address_within_allocated_pages_helper.expr(meta)
+                        - current_memory_page_size.expr(meta)
+                            * constant_from!(WASM_BLOCKS_PER_PAGE))
+                        * enable(meta),
+                ]
+            }),
+        );
+
+        Self {
+            block_index,
+            inner_pos_bits,
+            inner_pos,
+            modulus,
+            tailing,
+            tailing_diff,
+            picked,
+            leading,
+            address_within_allocated_pages_helper,
+            memory_table_lookup_heap_read,
+            memory_table_lookup_heap_write,
+        }
+    }
+
+    pub(super) fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step: &StepStatus<F>,
+        address: u32,
+        pre_block_value: u64,
+        updated_block_value: u64,
+        (heap_read, heap_write): (&MemoryRWEntry, &MemoryRWEntry),
+    ) -> Result<(), Error> {
+        let block_index = block_from_address(address);
+        let inner_pos = byte_offset_from_address(address);
+
+        self.block_index.assign(ctx, block_index)?;
+        self.inner_pos.assign_u32(ctx, inner_pos)?;
+        for (i, bit) in self.inner_pos_bits.iter().enumerate() {
+            bit.assign_bool(ctx, inner_pos & (1 << i) != 0)?;
+        }
+
+        let modulus = 1u64 << (inner_pos * 8);
+        let tailing = pre_block_value & (modulus - 1);
+        let picked = (pre_block_value >> (inner_pos * 8)) & 0xff;
+        let leading = (pre_block_value >> (inner_pos * 8)) >> 8;
+
+        self.modulus.assign(ctx, modulus.into())?;
+        self.tailing.assign(ctx, tailing)?;
+        self.tailing_diff.assign(ctx, modulus - 1 - tailing)?;
+        self.picked.assign(ctx, picked.into())?;
+        self.leading.assign(ctx, leading)?;
+
+        self.address_within_allocated_pages_helper.assign_u32(
+            ctx,
+            step.current.allocated_memory_pages * WASM_BLOCKS_PER_PAGE - (block_index + 1),
+        )?;
+
+        self.memory_table_lookup_heap_read.assign(
+            ctx,
+            heap_read.start_eid,
+            step.current.eid,
+            heap_read.end_eid,
+            block_index,
+            LocationType::Heap,
+            false,
+            pre_block_value,
+        )?;
+
+        self.memory_table_lookup_heap_write.assign(
+            ctx,
+            step.current.eid,
+            heap_write.end_eid,
+            block_index,
+            LocationType::Heap,
+            false,
+            updated_block_value,
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,159 @@
+//! Shared gadgets for the float opcodes.
+//!
+//! Floats are carried as their raw IEEE-754 bits. With the sign bit taken from
+//! the flag bit of a `AllocatedU64CellWithFlagBitDyn` (bit 31 for f32, bit 63
+//! for f64), the magnitude is `bits - sign * half`.
+
+use crate::circuits::cell::*;
+use crate::circuits::etable::allocator::*;
+use crate::circuits::etable::ConstraintBuilder;
+use crate::circuits::utils::Context;
+use crate::constant_from;
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::Error;
+use halo2_proofs::plonk::Expression;
+use halo2_proofs::plonk::VirtualCells;
+use specs::mtable::FloatType;
+
+pub(super) fn half_expr<F: FieldExt>(
+    meta: &mut VirtualCells<'_, F>,
+    is_f32: AllocatedBitCell<F>,
+) -> Expression<F> {
+    constant_from!(FloatType::F64.sign_bit())
+        - is_f32.expr(meta) * constant_from!(FloatType::F64.sign_bit() - FloatType::F32.sign_bit())
+}
+
+pub(super) fn infinity_expr<F: FieldExt>(
+    meta: &mut VirtualCells<'_, F>,
+    is_f32: AllocatedBitCell<F>,
+) -> Expression<F> {
+    constant_from!(FloatType::F64.infinity_bits())
+        - is_f32.expr(meta)
+            * constant_from!(FloatType::F64.infinity_bits() - FloatType::F32.infinity_bits())
+}
+
+pub(super) fn canonical_nan_expr<F: FieldExt>(
+    meta: &mut VirtualCells<'_, F>,
+    is_f32: AllocatedBitCell<F>,
+) -> Expression<F> {
+    constant_from!(FloatType::F64.canonical_nan_bits())
+        - is_f32.expr(meta)
+            * constant_from!(
+                FloatType::F64.canonical_nan_bits() - FloatType::F32.canonical_nan_bits()
+            )
+}
+
+pub(super) fn magnitude_expr<F: FieldExt>(
+    meta: &mut VirtualCells<'_, F>,
+    is_f32: AllocatedBitCell<F>,
+    value: AllocatedU64CellWithFlagBitDyn<F>,
+) -> Expression<F> {
+    value.u64_cell.expr(meta) - value.flag_bit_cell.expr(meta) * half_expr(meta, is_f32)
+}
+
+pub(super) fn float_magnitude(vtype: FloatType, value: u64) -> u64 {
+    value & (vtype.sign_bit() - 1)
+}
+
+/// Maps the bits of a non-NaN float to an integer preserving the float order,
+/// both zeros map to 0.
+pub(super) fn float_order_key(vtype: FloatType, value: u64) -> i64 {
+    let magnitude = float_magnitude(vtype, value) as i64;
+
+    if value & vtype.sign_bit() != 0 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+pub(super) fn i64_to_field<F: FieldExt>(value: i64) -> F {
+    if value.is_negative() {
+        -F::from(value.unsigned_abs())
+    } else {
+        F::from(value as u64)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(super) struct FloatNanCells<F: FieldExt> {
+    pub(super) is_nan: AllocatedBitCell<F>,
+    // |magnitude - infinity| shifted so that it is non-negative in both cases
+    nan_diff: AllocatedU64Cell<F>,
+}
+
+impl<F: FieldExt> FloatNanCells<F> {
+    pub(super) fn configure(
+        name: &'static str,
+        allocator: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+        is_f32: AllocatedBitCell<F>,
+        value: AllocatedU64CellWithFlagBitDyn<F>,
+    ) -> Self {
+        let is_nan = allocator.alloc_bit_cell();
+        let nan_diff = allocator.alloc_u64_cell();
+
+        constraint_builder.push(
+            name,
+            Box::new(move |meta| {
+                let magnitude = magnitude_expr(meta, is_f32, value);
+                let infinity = infinity_expr(meta, is_f32);
+
+                vec![
+                    nan_diff.u64_cell.expr(meta)
+                        - is_nan.expr(meta)
+                            * (magnitude.clone() - infinity.clone() - constant_from!(1))
+                        - (constant_from!(1) - is_nan.expr(meta)) * (infinity - magnitude),
+                ]
+            }),
+        );
+
+        Self { is_nan, nan_diff }
+    }
+
+    pub(super) fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        vtype: FloatType,
+        value: u64,
+    ) -> Result<bool, Error> {
+        let magnitude = float_magnitude(vtype, value);
+        let infinity = vtype.infinity_bits();
+        let is_nan = magnitude > infinity;
+
+        self.is_nan.assign_bool(ctx, is_nan)?;
+        self.nan_diff.assign(
+            ctx,
+            if is_nan {
+                magnitude - infinity - 1
+            } else {
+                infinity - magnitude
+            },
+        )?;
+
+        Ok(is_nan)
+    }
+}
+
+pub(super) fn alloc_float_order_key<F: FieldExt>(
+    name: &'static str,
+    allocator: &mut EventTableCellAllocator<F>,
+    constraint_builder: &mut ConstraintBuilder<F>,
+    is_f32: AllocatedBitCell<F>,
+    value: AllocatedU64CellWithFlagBitDyn<F>,
+) -> AllocatedUnlimitedCell<F> {
+    let key = allocator.alloc_unlimited_cell();
+
+    constraint_builder.push(
+        name,
+        Box::new(move |meta| {
+            vec![
+                key.expr(meta)
+                    - magnitude_expr(meta, is_f32, value)
+                        * (constant_from!(1) - constant_from!(2) * value.flag_bit_cell.expr(meta)),
+            ]
+        }),
+    );
+
+    key
+}
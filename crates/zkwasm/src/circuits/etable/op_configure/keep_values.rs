@@ -0,0 +1,169 @@
+//! Shared gadget for the values kept by `br`, `br_if`, `br_table` and `return`.
+//!
+//! The kept values are the topmost `keep` values of the stack, they are moved
+//! from `sp + offset + i` to `sp + offset + drop + i`. Slot `i` holds the value
+//! at depth `i`, enabled slots must be a prefix of all slots.
+
+use crate::circuits::cell::*;
+use crate::circuits::etable::allocator::*;
+use crate::circuits::etable::ConstraintBuilder;
+use crate::circuits::etable::EventTableCommonConfig;
+use crate::circuits::utils::step_status::StepStatus;
+use crate::circuits::utils::table_entry::MemoryRWEntry;
+use crate::circuits::utils::Context;
+use crate::constant_from;
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::Error;
+use halo2_proofs::plonk::Expression;
+use halo2_proofs::plonk::VirtualCells;
+use specs::itable::MAX_KEEP_VALUES;
+use specs::mtable::LocationType;
+use specs::mtable::VarType;
+use specs::types::ValueType;
+
+#[derive(Clone, Copy)]
+pub(super) struct KeepValueCells<F: FieldExt> {
+    offset: u32,
+
+    keep: [AllocatedBitCell<F>; MAX_KEEP_VALUES],
+    is_i32: [AllocatedBitCell<F>; MAX_KEEP_VALUES],
+
+    memory_table_lookup_stack_read: [AllocatedMemoryTableLookupReadCell<F>; MAX_KEEP_VALUES],
+    memory_table_lookup_stack_write: [AllocatedMemoryTableLookupWriteCell<F>; MAX_KEEP_VALUES],
+}
+
+impl<F: FieldExt> KeepValueCells<F> {
+    /// Moves the kept values if `enable` is set, `offset` is the distance
+    /// between sp and the topmost kept value.
+    pub(super) fn configure(
+        common_config: &EventTableCommonConfig<F>,
+        allocator: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+        offset: u32,
+        drop: impl Fn(&mut VirtualCells<'_, F>) -> Expression<F> + Copy + 'static,
+        enable: impl Fn(&mut VirtualCells<'_, F>) -> Expression<F> + Copy + 'static,
+    ) -> Self {
+        let keep = [0; MAX_KEEP_VALUES].map(|_| allocator.alloc_bit_cell());
+        let is_i32 = [0; MAX_KEEP_VALUES].map(|_| allocator.alloc_bit_cell());
+
+        let eid = common_config.eid_cell;
+        let sp = common_config.sp_cell;
+
+        let memory_table_lookup_stack_read = std::array::from_fn(|i| {
+            let keep = keep[i];
+            let is_i32 = is_i32[i];
+
+            allocator.alloc_memory_table_lookup_read_cell_with_value(
+                "keep value stack read",
+                constraint_builder,
+                eid,
+                move |____| constant_from!(LocationType::Stack as u64),
+                move |meta| sp.expr(meta) + constant_from!(offset as usize + i),
+                move |meta| is_i32.expr(meta),
+                move |meta| keep.expr(meta) * enable(meta),
+            )
+        });
+
+        let memory_table_lookup_stack_write = std::array::from_fn(|i| {
+            let keep = keep[i];
+            let is_i32 = is_i32[i];
+            let value = memory_table_lookup_stack_read[i].value_cell;
+
+            allocator.alloc_memory_table_lookup_write_cell(
+                "keep value stack write",
+                constraint_builder,
+                eid,
+                move |____| constant_from!(LocationType::Stack as u64),
+                move |meta| sp.expr(meta) + drop(meta) + constant_from!(offset as usize + i),
+                move |meta| is_i32.expr(meta),
+                move |meta| value.expr(meta),
+                move |meta| keep.expr(meta) * enable(meta),
+            )
+        });
+
+        constraint_builder.push(
+            "keep values: enabled slots are a prefix",
+            Box::new(move |meta| {
+                keep.windows(2)
+                    .map(|pair| pair[1].expr(meta) * (constant_from!(1) - pair[0].expr(meta)))
+                    .collect()
+            }),
+        );
+
+        Self {
+            offset,
+            keep,
+            is_i32,
+            memory_table_lookup_stack_read,
+            memory_table_lookup_stack_write,
+        }
+    }
+
+    /// Number of kept values.
+    pub(super) fn count(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        self.keep
+            .iter()
+            .fold(constant_from!(0), |acc, keep| acc + keep.expr(meta))
+    }
+
+    /// Types of the kept values, bit `i` is set if the value at depth `i` is i32.
+    pub(super) fn is_i32_encode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        self.is_i32
+            .iter()
+            .enumerate()
+            .fold(constant_from!(0), |acc, (i, is_i32)| {
+                acc + is_i32.expr(meta) * constant_from!(1u64 << i)
+            })
+    }
+
+    /// `entries` are the memory table entries of the kept values, `None` if
+    /// the values are not moved by this step.
+    pub(super) fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step: &StepStatus<F>,
+        drop: u32,
+        keep: &[ValueType],
+        keep_values: &[u64],
+        entries: Option<&[MemoryRWEntry]>,
+    ) -> Result<(), Error> {
+        assert_eq!(keep.len(), keep_values.len());
+        assert!(
+            keep.len() <= MAX_KEEP_VALUES,
+            "at most {} kept values are supported",
+            MAX_KEEP_VALUES
+        );
+
+        for (i, (vtype, value)) in keep.iter().zip(keep_values).enumerate() {
+            let is_i32 = VarType::from(vtype) == VarType::I32;
+
+            self.keep[i].assign_bool(ctx, true)?;
+            self.is_i32[i].assign_bool(ctx, is_i32)?;
+
+            if let Some(entries) = entries {
+                self.memory_table_lookup_stack_read[i].assign(
+                    ctx,
+                    entries[i].start_eid,
+                    step.current.eid,
+                    entries[i].end_eid,
+                    step.current.sp + self.offset + i as u32,
+                    LocationType::Stack,
+                    is_i32,
+                    *value,
+                )?;
+
+                self.memory_table_lookup_stack_write[i].assign(
+                    ctx,
+                    step.current.eid,
+                    entries[keep.len() + i].end_eid,
+                    step.current.sp + self.offset + drop + i as u32,
+                    LocationType::Stack,
+                    is_i32,
+                    *value,
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
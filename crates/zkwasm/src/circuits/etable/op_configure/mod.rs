@@ -1,3 +1,7 @@
+mod bulk_memory;
+mod float;
+mod keep_values;
+
 pub mod op_bin;
 pub mod op_bin_bit;
 pub mod op_bin_shift;
@@ -11,12 +15,18 @@ pub mod op_call_indirect;
 pub mod op_const;
 pub mod op_conversion;
 pub mod op_drop;
+pub mod op_float_bin;
+pub mod op_float_conversion;
+pub mod op_float_rel;
+pub mod op_float_unary;
 pub mod op_global_get;
 pub mod op_global_set;
 pub mod op_load;
 pub mod op_local_get;
 pub mod op_local_set;
 pub mod op_local_tee;
+pub mod op_memory_copy;
+pub mod op_memory_fill;
 pub mod op_memory_grow;
 pub mod op_memory_size;
 pub mod op_rel;
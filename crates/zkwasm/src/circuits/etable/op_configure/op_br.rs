@@ -14,18 +14,14 @@ use halo2_proofs::plonk::Expression;
 use halo2_proofs::plonk::VirtualCells;
 use specs::encode::opcode::encode_br;
 use specs::etable::EventTableEntry;
-use specs::mtable::LocationType;
-use specs::mtable::VarType;
 use specs::step::StepInfo;
 
+use super::keep_values::KeepValueCells;
+
 pub struct BrConfig<F: FieldExt> {
-    keep_cell: AllocatedBitCell<F>,
-    is_i32_cell: AllocatedBitCell<F>,
     drop_cell: AllocatedCommonRangeCell<F>,
     dst_pc_cell: AllocatedCommonRangeCell<F>,
-    value_cell: AllocatedU64Cell<F>,
-    memory_table_lookup_stack_read: AllocatedMemoryTableLookupReadCell<F>,
-    memory_table_lookup_stack_write: AllocatedMemoryTableLookupWriteCell<F>,
+    keep_values: KeepValueCells<F>,
 }
 
 pub struct BrConfigBuilder;
@@ -36,44 +32,22 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrConfigBuilder {
         allocator: &mut EventTableCellAllocator<F>,
         constraint_builder: &mut ConstraintBuilder<F>,
     ) -> Box<dyn EventTableOpcodeConfig<F>> {
-        let keep_cell = allocator.alloc_bit_cell();
-        let is_i32_cell = allocator.alloc_bit_cell();
         let drop_cell = allocator.alloc_common_range_cell();
         let dst_pc_cell = allocator.alloc_common_range_cell();
-        let value_cell = allocator.alloc_u64_cell();
-
-        let eid = common_config.eid_cell;
-        let sp = common_config.sp_cell;
 
-        let memory_table_lookup_stack_read = allocator.alloc_memory_table_lookup_read_cell(
-            "op_br stack read",
+        let keep_values = KeepValueCells::configure(
+            common_config,
+            allocator,
             constraint_builder,
-            eid,
-            move |____| constant_from!(LocationType::Stack as u64),
-            move |meta| sp.expr(meta) + constant_from!(1),
-            move |meta| is_i32_cell.expr(meta),
-            move |meta| value_cell.u64_cell.expr(meta),
-            move |meta| keep_cell.expr(meta),
-        );
-        let memory_table_lookup_stack_write = allocator.alloc_memory_table_lookup_write_cell(
-            "op_br stack write",
-            constraint_builder,
-            eid,
-            move |____| constant_from!(LocationType::Stack as u64),
-            move |meta| sp.expr(meta) + drop_cell.expr(meta) + constant_from!(1),
-            move |meta| is_i32_cell.expr(meta),
-            move |meta| value_cell.u64_cell.expr(meta),
-            move |meta| keep_cell.expr(meta),
+            1,
+            move |meta| drop_cell.expr(meta),
+            move |____| constant_from!(1),
         );
 
         Box::new(BrConfig {
-            keep_cell,
-            is_i32_cell,
             drop_cell,
             dst_pc_cell,
-            value_cell,
-            memory_table_lookup_stack_read,
-            memory_table_lookup_stack_write,
+            keep_values,
         })
     }
 }
@@ -82,7 +56,8 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrConfig<F> {
     fn opcode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
         encode_br(
             self.drop_cell.expr(meta),
-            self.keep_cell.expr(meta),
+            self.keep_values.count(meta),
+            self.keep_values.is_i32_encode(meta),
             self.dst_pc_cell.expr(meta),
         )
     }
@@ -101,38 +76,15 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrConfig<F> {
                 dst_pc,
                 ..
             } => {
-                assert!(keep.len() <= 1);
-
                 self.drop_cell.assign(ctx, F::from(*drop as u64))?;
-
-                if !keep.is_empty() {
-                    let keep_type: VarType = keep[0].into();
-
-                    self.keep_cell.assign(ctx, F::one())?;
-                    self.value_cell.assign(ctx, keep_values[0])?;
-                    self.is_i32_cell.assign(ctx, F::from(keep_type as u64))?;
-
-                    self.memory_table_lookup_stack_read.assign(
-                        ctx,
-                        entry.memory_rw_entires[0].start_eid,
-                        step.current.eid,
-                        entry.memory_rw_entires[0].end_eid,
-                        step.current.sp + 1,
-                        LocationType::Stack,
-                        VarType::from(keep[0]) == VarType::I32,
-                        keep_values[0],
-                    )?;
-
-                    self.memory_table_lookup_stack_write.assign(
-                        ctx,
-                        step.current.eid,
-                        entry.memory_rw_entires[1].end_eid,
-                        step.current.sp + *drop + 1,
-                        LocationType::Stack,
-                        VarType::from(keep[0]) == VarType::I32,
-                        keep_values[0],
-                    )?;
-                }
+                self.keep_values.assign(
+                    ctx,
+                    step,
+                    *drop,
+                    keep,
+                    keep_values,
+                    Some(&entry.memory_rw_entires),
+                )?;
 
                 self.dst_pc_cell.assign(ctx, F::from((*dst_pc) as u64))?;
             }
@@ -147,7 +99,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrConfig<F> {
     }
 
     fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
-        Some(self.keep_cell.expr(meta))
+        Some(self.keep_values.count(meta))
     }
 
     fn memory_writing_ops(&self, entry: &EventTableEntry) -> u32 {
@@ -20,24 +20,22 @@ use specs::itable::OpcodeClass;
 use specs::itable::OPCODE_ARG0_SHIFT;
 use specs::itable::OPCODE_ARG1_SHIFT;
 use specs::itable::OPCODE_CLASS_SHIFT;
+use specs::itable::OPCODE_KEEP_TYPE_SHIFT;
 use specs::mtable::LocationType;
-use specs::mtable::VarType;
 use specs::step::StepInfo;
 
+use super::keep_values::KeepValueCells;
+
 pub struct BrIfConfig<F: FieldExt> {
     cond_cell: AllocatedU64Cell<F>,
     cond_inv_cell: AllocatedUnlimitedCell<F>,
     cond_is_zero_cell: AllocatedBitCell<F>,
     cond_is_not_zero_cell: AllocatedBitCell<F>,
 
-    keep_cell: AllocatedBitCell<F>,
-    is_i32_cell: AllocatedBitCell<F>,
     drop_cell: AllocatedCommonRangeCell<F>,
     dst_pc_cell: AllocatedCommonRangeCell<F>,
-    value_cell: AllocatedU64Cell<F>,
+    keep_values: KeepValueCells<F>,
     memory_table_lookup_stack_read_cond: AllocatedMemoryTableLookupReadCell<F>,
-    memory_table_lookup_stack_read_return_value: AllocatedMemoryTableLookupReadCell<F>,
-    memory_table_lookup_stack_write_return_value: AllocatedMemoryTableLookupWriteCell<F>,
 }
 
 pub struct BrIfConfigBuilder;
@@ -67,11 +65,8 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrIfConfigBuilder {
             }),
         ));
 
-        let keep_cell = allocator.alloc_bit_cell();
-        let is_i32_cell = allocator.alloc_bit_cell();
         let drop_cell = allocator.alloc_common_range_cell();
         let dst_pc_cell = allocator.alloc_common_range_cell();
-        let value_cell = allocator.alloc_u64_cell();
 
         let eid = common_config.eid_cell;
         let sp = common_config.sp_cell;
@@ -86,42 +81,25 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrIfConfigBuilder {
             move |meta| cond_cell.u64_cell.expr(meta),
             move |____| constant_from!(1),
         );
-        let memory_table_lookup_stack_read_return_value = allocator
-            .alloc_memory_table_lookup_read_cell(
-                "op_br_if stack read return value",
-                constraint_builder,
-                eid,
-                move |____| constant_from!(LocationType::Stack as u64),
-                move |meta| sp.expr(meta) + constant_from!(2),
-                move |meta| is_i32_cell.expr(meta),
-                move |meta| value_cell.u64_cell.expr(meta),
-                move |meta| keep_cell.expr(meta) * cond_is_not_zero_cell.expr(meta),
-            );
-        let memory_table_lookup_stack_write_return_value = allocator
-            .alloc_memory_table_lookup_write_cell(
-                "op_br_if stack write return value",
-                constraint_builder,
-                eid,
-                move |____| constant_from!(LocationType::Stack as u64),
-                move |meta| sp.expr(meta) + drop_cell.expr(meta) + constant_from!(2),
-                move |meta| is_i32_cell.expr(meta),
-                move |meta| value_cell.u64_cell.expr(meta),
-                move |meta| keep_cell.expr(meta) * cond_is_not_zero_cell.expr(meta),
-            );
+
+        let keep_values = KeepValueCells::configure(
+            common_config,
+            allocator,
+            constraint_builder,
+            2,
+            move |meta| drop_cell.expr(meta),
+            move |meta| cond_is_not_zero_cell.expr(meta),
+        );
 
         Box::new(BrIfConfig {
             cond_cell,
             cond_inv_cell,
             cond_is_zero_cell,
             cond_is_not_zero_cell,
-            keep_cell,
-            is_i32_cell,
             drop_cell,
             dst_pc_cell,
-            value_cell,
+            keep_values,
             memory_table_lookup_stack_read_cond,
-            memory_table_lookup_stack_read_return_value,
-            memory_table_lookup_stack_write_return_value,
         })
     }
 }
@@ -132,8 +110,12 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrIfConfig<F> {
             &(BigUint::from(OpcodeClass::BrIf as u64) << OPCODE_CLASS_SHIFT)
         )) + self.drop_cell.expr(meta)
             * constant!(bn_to_field(&(BigUint::from(1u64) << OPCODE_ARG0_SHIFT)))
-            + self.keep_cell.expr(meta)
+            + self.keep_values.count(meta)
                 * constant!(bn_to_field(&(BigUint::from(1u64) << OPCODE_ARG1_SHIFT)))
+            + self.keep_values.is_i32_encode(meta)
+                * constant!(bn_to_field(
+                    &(BigUint::from(1u64) << OPCODE_KEEP_TYPE_SHIFT)
+                ))
             + self.dst_pc_cell.expr(meta)
     }
 
@@ -151,8 +133,6 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrIfConfig<F> {
                 keep,
                 keep_values,
             } => {
-                assert!(keep.len() <= 1);
-
                 let cond = *condition as u32 as u64;
 
                 self.memory_table_lookup_stack_read_cond.assign(
@@ -168,35 +148,14 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrIfConfig<F> {
 
                 self.drop_cell.assign(ctx, F::from(*drop as u64))?;
 
-                if !keep.is_empty() {
-                    let keep_type: VarType = keep[0].into();
-
-                    self.keep_cell.assign(ctx, F::one())?;
-                    self.value_cell.assign(ctx, keep_values[0])?;
-                    self.is_i32_cell.assign(ctx, F::from(keep_type as u64))?;
-                    if *condition != 0 {
-                        self.memory_table_lookup_stack_read_return_value.assign(
-                            ctx,
-                            entry.memory_rw_entires[1].start_eid,
-                            step.current.eid,
-                            entry.memory_rw_entires[1].end_eid,
-                            step.current.sp + 2,
-                            LocationType::Stack,
-                            VarType::from(keep[0]) == VarType::I32,
-                            keep_values[0],
-                        )?;
-
-                        self.memory_table_lookup_stack_write_return_value.assign(
-                            ctx,
-                            step.current.eid,
-                            entry.memory_rw_entires[2].end_eid,
-                            step.current.sp + *drop + 2,
-                            LocationType::Stack,
-                            VarType::from(keep[0]) == VarType::I32,
-                            keep_values[0],
-                        )?;
-                    }
-                }
+                self.keep_values.assign(
+                    ctx,
+                    step,
+                    *drop,
+                    keep,
+                    keep_values,
+                    (*condition != 0).then_some(&entry.memory_rw_entires[1..]),
+                )?;
 
                 self.cond_cell.assign(ctx, cond)?;
                 if cond != 0 {
@@ -220,7 +179,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrIfConfig<F> {
     }
 
     fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
-        Some(self.cond_is_not_zero_cell.expr(meta) * self.keep_cell.expr(meta))
+        Some(self.cond_is_not_zero_cell.expr(meta) * self.keep_values.count(meta))
     }
 
     fn memory_writing_ops(&self, entry: &EventTableEntry) -> u32 {
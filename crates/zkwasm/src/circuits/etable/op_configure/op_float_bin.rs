@@ -0,0 +1,445 @@
+use crate::circuits::cell::*;
+use crate::circuits::etable::allocator::*;
+use crate::circuits::etable::ConstraintBuilder;
+use crate::circuits::etable::EventTableCommonConfig;
+use crate::circuits::etable::EventTableOpcodeConfig;
+use crate::circuits::etable::EventTableOpcodeConfigBuilder;
+use crate::circuits::utils::bn_to_field;
+use crate::circuits::utils::step_status::StepStatus;
+use crate::circuits::utils::table_entry::EventTableEntryWithMemoryInfo;
+use crate::circuits::utils::Context;
+use crate::constant;
+use crate::constant_from;
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::Error;
+use halo2_proofs::plonk::Expression;
+use halo2_proofs::plonk::VirtualCells;
+use num_bigint::BigUint;
+use specs::etable::EventTableEntry;
+use specs::itable::FloatBinOp;
+use specs::itable::OpcodeClass;
+use specs::itable::OPCODE_ARG0_SHIFT;
+use specs::itable::OPCODE_ARG1_SHIFT;
+use specs::itable::OPCODE_CLASS_SHIFT;
+use specs::mtable::FloatType;
+use specs::mtable::LocationType;
+use specs::step::StepInfo;
+
+use super::float::*;
+
+/// The exact binary operations, min, max and copysign.
+pub struct FloatBinConfig<F: FieldExt> {
+    is_f32: AllocatedBitCell<F>,
+
+    lhs: AllocatedU64CellWithFlagBitDyn<F>,
+    rhs: AllocatedU64CellWithFlagBitDyn<F>,
+
+    lhs_nan: FloatNanCells<F>,
+    rhs_nan: FloatNanCells<F>,
+    any_nan: AllocatedUnlimitedCell<F>,
+
+    lhs_key: AllocatedUnlimitedCell<F>,
+    rhs_key: AllocatedUnlimitedCell<F>,
+
+    diff: AllocatedU64Cell<F>,
+    diff_inv: AllocatedUnlimitedCell<F>,
+
+    res_is_eq: AllocatedBitCell<F>,
+    res_is_lt: AllocatedBitCell<F>,
+    res_is_gt: AllocatedBitCell<F>,
+
+    l_pos_r_neg: AllocatedUnlimitedCell<F>,
+    l_neg_r_pos: AllocatedUnlimitedCell<F>,
+
+    ordered_res: AllocatedUnlimitedCell<F>,
+    nan_res: AllocatedUnlimitedCell<F>,
+    nan_res_is_canonical: AllocatedBitCell<F>,
+    res: AllocatedUnlimitedCell<F>,
+
+    op_is_min: AllocatedBitCell<F>,
+    op_is_max: AllocatedBitCell<F>,
+    op_is_copysign: AllocatedBitCell<F>,
+
+    memory_table_lookup_stack_read_lhs: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_stack_read_rhs: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_stack_write: AllocatedMemoryTableLookupWriteCell<F>,
+}
+
+pub struct FloatBinConfigBuilder {}
+
+impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for FloatBinConfigBuilder {
+    fn configure(
+        common_config: &EventTableCommonConfig<F>,
+        allocator: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+    ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        let is_f32 = allocator.alloc_bit_cell();
+
+        let lhs = allocator
+            .alloc_u64_with_flag_bit_cell_dyn(constraint_builder, move |meta| is_f32.expr(meta));
+        let rhs = allocator
+            .alloc_u64_with_flag_bit_cell_dyn(constraint_builder, move |meta| is_f32.expr(meta));
+
+        let lhs_nan = FloatNanCells::configure(
+            "float bin: lhs nan",
+            allocator,
+            constraint_builder,
+            is_f32,
+            lhs,
+        );
+        let rhs_nan = FloatNanCells::configure(
+            "float bin: rhs nan",
+            allocator,
+            constraint_builder,
+            is_f32,
+            rhs,
+        );
+        let any_nan = allocator.alloc_unlimited_cell();
+
+        let lhs_key = alloc_float_order_key(
+            "float bin: lhs key",
+            allocator,
+            constraint_builder,
+            is_f32,
+            lhs,
+        );
+        let rhs_key = alloc_float_order_key(
+            "float bin: rhs key",
+            allocator,
+            constraint_builder,
+            is_f32,
+            rhs,
+        );
+
+        let diff = allocator.alloc_u64_cell();
+        let diff_inv = allocator.alloc_unlimited_cell();
+
+        let res_is_eq = allocator.alloc_bit_cell();
+        let res_is_lt = allocator.alloc_bit_cell();
+        let res_is_gt = allocator.alloc_bit_cell();
+
+        let l_pos_r_neg = allocator.alloc_unlimited_cell();
+        let l_neg_r_pos = allocator.alloc_unlimited_cell();
+
+        let ordered_res = allocator.alloc_unlimited_cell();
+        let nan_res = allocator.alloc_unlimited_cell();
+        let nan_res_is_canonical = allocator.alloc_bit_cell();
+        let res = allocator.alloc_unlimited_cell();
+
+        let op_is_min = allocator.alloc_bit_cell();
+        let op_is_max = allocator.alloc_bit_cell();
+        let op_is_copysign = allocator.alloc_bit_cell();
+
+        constraint_builder.push(
+            "float bin: selector",
+            Box::new(move |meta| {
+                vec![
+                    (op_is_min.expr(meta) + op_is_max.expr(meta) + op_is_copysign.expr(meta)
+                        - constant_from!(1)),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "float bin: compare diff",
+            Box::new(move |meta| {
+                vec![
+                    (lhs_key.expr(meta) + res_is_lt.expr(meta) * diff.u64_cell.expr(meta)
+                        - res_is_gt.expr(meta) * diff.u64_cell.expr(meta)
+                        - rhs_key.expr(meta)),
+                    (res_is_gt.expr(meta) + res_is_lt.expr(meta) + res_is_eq.expr(meta)
+                        - constant_from!(1)),
+                    (diff.u64_cell.expr(meta) * res_is_eq.expr(meta)),
+                    (diff.u64_cell.expr(meta) * diff_inv.expr(meta) + res_is_eq.expr(meta)
+                        - constant_from!(1)),
+                ]
+            }),
+        );
+
+        // Equal keys with different signs can only be the two zeros, where
+        // min picks -0 and max picks +0.
+        constraint_builder.push(
+            "float bin: min/max ordered res",
+            Box::new(move |meta| {
+                let half = half_expr(meta, is_f32);
+
+                vec![
+                    l_pos_r_neg.expr(meta)
+                        - (constant_from!(1) - lhs.flag_bit_cell.expr(meta))
+                            * rhs.flag_bit_cell.expr(meta),
+                    l_neg_r_pos.expr(meta)
+                        - lhs.flag_bit_cell.expr(meta)
+                            * (constant_from!(1) - rhs.flag_bit_cell.expr(meta)),
+                    op_is_min.expr(meta)
+                        * (ordered_res.expr(meta)
+                            - res_is_lt.expr(meta) * lhs.u64_cell.expr(meta)
+                            - res_is_gt.expr(meta) * rhs.u64_cell.expr(meta)
+                            - res_is_eq.expr(meta)
+                                * (lhs.u64_cell.expr(meta)
+                                    + l_pos_r_neg.expr(meta) * half.clone())),
+                    op_is_max.expr(meta)
+                        * (ordered_res.expr(meta)
+                            - res_is_gt.expr(meta) * lhs.u64_cell.expr(meta)
+                            - res_is_lt.expr(meta) * rhs.u64_cell.expr(meta)
+                            - res_is_eq.expr(meta)
+                                * (lhs.u64_cell.expr(meta) - l_neg_r_pos.expr(meta) * half)),
+                ]
+            }),
+        );
+
+        // A NaN operand yields either the first NaN operand or the canonical NaN.
+        constraint_builder.push(
+            "float bin: min/max nan res",
+            Box::new(move |meta| {
+                let propagated = lhs_nan.is_nan.expr(meta) * lhs.u64_cell.expr(meta)
+                    + (constant_from!(1) - lhs_nan.is_nan.expr(meta)) * rhs.u64_cell.expr(meta);
+
+                vec![
+                    any_nan.expr(meta)
+                        - (lhs_nan.is_nan.expr(meta) + rhs_nan.is_nan.expr(meta)
+                            - lhs_nan.is_nan.expr(meta) * rhs_nan.is_nan.expr(meta)),
+                    nan_res.expr(meta)
+                        - nan_res_is_canonical.expr(meta) * canonical_nan_expr(meta, is_f32)
+                        - (constant_from!(1) - nan_res_is_canonical.expr(meta)) * propagated,
+                    (op_is_min.expr(meta) + op_is_max.expr(meta))
+                        * (res.expr(meta)
+                            - (constant_from!(1) - any_nan.expr(meta)) * ordered_res.expr(meta)
+                            - any_nan.expr(meta) * nan_res.expr(meta)),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "float bin: copysign",
+            Box::new(move |meta| {
+                vec![
+                    op_is_copysign.expr(meta)
+                        * (res.expr(meta)
+                            - magnitude_expr(meta, is_f32, lhs)
+                            - rhs.flag_bit_cell.expr(meta) * half_expr(meta, is_f32)),
+                ]
+            }),
+        );
+
+        let eid = common_config.eid_cell;
+        let sp = common_config.sp_cell;
+
+        let memory_table_lookup_stack_read_rhs = allocator.alloc_memory_table_lookup_read_cell(
+            "op_float_bin stack read",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(1),
+            move |meta| is_f32.expr(meta),
+            move |meta| rhs.u64_cell.expr(meta),
+            move |____| constant_from!(1),
+        );
+
+        let memory_table_lookup_stack_read_lhs = allocator.alloc_memory_table_lookup_read_cell(
+            "op_float_bin stack read",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(2),
+            move |meta| is_f32.expr(meta),
+            move |meta| lhs.u64_cell.expr(meta),
+            move |____| constant_from!(1),
+        );
+
+        let memory_table_lookup_stack_write = allocator.alloc_memory_table_lookup_write_cell(
+            "op_float_bin stack write",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(2),
+            move |meta| is_f32.expr(meta),
+            move |meta| res.expr(meta),
+            move |____| constant_from!(1),
+        );
+
+        Box::new(FloatBinConfig {
+            is_f32,
+            lhs,
+            rhs,
+            lhs_nan,
+            rhs_nan,
+            any_nan,
+            lhs_key,
+            rhs_key,
+            diff,
+            diff_inv,
+            res_is_eq,
+            res_is_lt,
+            res_is_gt,
+            l_pos_r_neg,
+            l_neg_r_pos,
+            ordered_res,
+            nan_res,
+            nan_res_is_canonical,
+            res,
+            op_is_min,
+            op_is_max,
+            op_is_copysign,
+            memory_table_lookup_stack_read_lhs,
+            memory_table_lookup_stack_read_rhs,
+            memory_table_lookup_stack_write,
+        })
+    }
+}
+
+impl<F: FieldExt> EventTableOpcodeConfig<F> for FloatBinConfig<F> {
+    fn opcode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        let subop =
+            |meta: &mut VirtualCells<'_, F>, op: &AllocatedBitCell<F>, class: FloatBinOp| {
+                op.expr(meta)
+                    * constant!(bn_to_field(
+                        &(BigUint::from(class as u64) << OPCODE_ARG0_SHIFT)
+                    ))
+            };
+
+        constant!(bn_to_field(
+            &(BigUint::from(OpcodeClass::FloatBin as u64) << OPCODE_CLASS_SHIFT)
+        )) + subop(meta, &self.op_is_min, FloatBinOp::Min)
+            + subop(meta, &self.op_is_max, FloatBinOp::Max)
+            + subop(meta, &self.op_is_copysign, FloatBinOp::Copysign)
+            + self.is_f32.expr(meta)
+                * constant!(bn_to_field(
+                    &(BigUint::from(FloatType::F32 as u64) << OPCODE_ARG1_SHIFT)
+                ))
+    }
+
+    fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step: &mut StepStatus<F>,
+        entry: &EventTableEntryWithMemoryInfo,
+    ) -> Result<(), Error> {
+        let (class, vtype, lhs, rhs, value) = match entry.eentry.step_info {
+            StepInfo::FloatBinOp {
+                class,
+                vtype,
+                left,
+                right,
+                value,
+            } => (class, vtype, left, right, value),
+
+            _ => unreachable!(),
+        };
+
+        let is_f32 = vtype == FloatType::F32;
+        let lhs_neg = lhs & vtype.sign_bit() != 0;
+        let rhs_neg = rhs & vtype.sign_bit() != 0;
+
+        self.is_f32.assign_bool(ctx, is_f32)?;
+        self.lhs.assign(ctx, lhs, is_f32)?;
+        self.rhs.assign(ctx, rhs, is_f32)?;
+
+        let lhs_is_nan = self.lhs_nan.assign(ctx, vtype, lhs)?;
+        let rhs_is_nan = self.rhs_nan.assign(ctx, vtype, rhs)?;
+        let any_nan = lhs_is_nan || rhs_is_nan;
+        self.any_nan.assign_bool(ctx, any_nan)?;
+
+        let lhs_key = float_order_key(vtype, lhs);
+        let rhs_key = float_order_key(vtype, rhs);
+        self.lhs_key.assign(ctx, i64_to_field(lhs_key))?;
+        self.rhs_key.assign(ctx, i64_to_field(rhs_key))?;
+
+        let diff = lhs_key.abs_diff(rhs_key);
+        self.diff.assign(ctx, diff)?;
+        if diff != 0 {
+            self.diff_inv.assign(ctx, step.field_helper.invert(diff))?;
+        }
+
+        self.res_is_eq.assign_bool(ctx, lhs_key == rhs_key)?;
+        self.res_is_gt.assign_bool(ctx, lhs_key > rhs_key)?;
+        self.res_is_lt.assign_bool(ctx, lhs_key < rhs_key)?;
+
+        self.l_pos_r_neg.assign_bool(ctx, !lhs_neg && rhs_neg)?;
+        self.l_neg_r_pos.assign_bool(ctx, lhs_neg && !rhs_neg)?;
+
+        let ordered_res = match class {
+            FloatBinOp::Min => Some(if lhs_key < rhs_key || (lhs_key == rhs_key && lhs_neg) {
+                lhs
+            } else if lhs_key > rhs_key {
+                rhs
+            } else {
+                lhs | (rhs & vtype.sign_bit())
+            }),
+            FloatBinOp::Max => Some(if lhs_key > rhs_key || (lhs_key == rhs_key && !lhs_neg) {
+                lhs
+            } else if lhs_key < rhs_key {
+                rhs
+            } else {
+                lhs & rhs
+            }),
+            _ => None,
+        };
+
+        if let Some(ordered_res) = ordered_res {
+            self.ordered_res.assign(ctx, ordered_res.into())?;
+        }
+
+        let propagated = if lhs_is_nan { lhs } else { rhs };
+        let nan_res = if any_nan && value != propagated {
+            self.nan_res_is_canonical.assign(ctx, F::one())?;
+            vtype.canonical_nan_bits()
+        } else {
+            propagated
+        };
+        self.nan_res.assign(ctx, nan_res.into())?;
+
+        self.res.assign(ctx, value.into())?;
+
+        match class {
+            FloatBinOp::Min => self.op_is_min.assign(ctx, F::one())?,
+            FloatBinOp::Max => self.op_is_max.assign(ctx, F::one())?,
+            FloatBinOp::Copysign => self.op_is_copysign.assign(ctx, F::one())?,
+        };
+
+        self.memory_table_lookup_stack_read_rhs.assign(
+            ctx,
+            entry.memory_rw_entires[0].start_eid,
+            step.current.eid,
+            entry.memory_rw_entires[0].end_eid,
+            step.current.sp + 1,
+            LocationType::Stack,
+            is_f32,
+            rhs,
+        )?;
+
+        self.memory_table_lookup_stack_read_lhs.assign(
+            ctx,
+            entry.memory_rw_entires[1].start_eid,
+            step.current.eid,
+            entry.memory_rw_entires[1].end_eid,
+            step.current.sp + 2,
+            LocationType::Stack,
+            is_f32,
+            lhs,
+        )?;
+
+        self.memory_table_lookup_stack_write.assign(
+            ctx,
+            step.current.eid,
+            entry.memory_rw_entires[2].end_eid,
+            step.current.sp + 2,
+            LocationType::Stack,
+            is_f32,
+            value,
+        )?;
+
+        Ok(())
+    }
+
+    fn mops(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(1))
+    }
+
+    fn memory_writing_ops(&self, _: &EventTableEntry) -> u32 {
+        1
+    }
+
+    fn sp_diff(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant!(F::one()))
+    }
+}
@@ -0,0 +1,206 @@
+use crate::circuits::cell::*;
+use crate::circuits::etable::allocator::*;
+use crate::circuits::etable::ConstraintBuilder;
+use crate::circuits::etable::EventTableCommonConfig;
+use crate::circuits::etable::EventTableOpcodeConfig;
+use crate::circuits::etable::EventTableOpcodeConfigBuilder;
+use crate::circuits::utils::bn_to_field;
+use crate::circuits::utils::step_status::StepStatus;
+use crate::circuits::utils::table_entry::EventTableEntryWithMemoryInfo;
+use crate::circuits::utils::Context;
+use crate::constant;
+use crate::constant_from;
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::Error;
+use halo2_proofs::plonk::Expression;
+use halo2_proofs::plonk::VirtualCells;
+use num_bigint::BigUint;
+use specs::etable::EventTableEntry;
+use specs::itable::FloatConversionOp;
+use specs::itable::OpcodeClass;
+use specs::itable::OPCODE_ARG0_SHIFT;
+use specs::itable::OPCODE_CLASS_SHIFT;
+use specs::mtable::LocationType;
+use specs::mtable::VarType;
+use specs::step::StepInfo;
+
+/// The reinterpret conversions, they keep the raw bits untouched.
+pub struct FloatConversionConfig<F: FieldExt> {
+    is_i32: AllocatedBitCell<F>,
+
+    op_is_i32_reinterpret_f32: AllocatedBitCell<F>,
+    op_is_i64_reinterpret_f64: AllocatedBitCell<F>,
+    op_is_f32_reinterpret_i32: AllocatedBitCell<F>,
+    op_is_f64_reinterpret_i64: AllocatedBitCell<F>,
+
+    memory_table_lookup_stack_read: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_stack_write: AllocatedMemoryTableLookupWriteCell<F>,
+}
+
+pub struct FloatConversionConfigBuilder {}
+
+impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for FloatConversionConfigBuilder {
+    fn configure(
+        common_config: &EventTableCommonConfig<F>,
+        allocator: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+    ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        let is_i32 = allocator.alloc_bit_cell();
+
+        let op_is_i32_reinterpret_f32 = allocator.alloc_bit_cell();
+        let op_is_i64_reinterpret_f64 = allocator.alloc_bit_cell();
+        let op_is_f32_reinterpret_i32 = allocator.alloc_bit_cell();
+        let op_is_f64_reinterpret_i64 = allocator.alloc_bit_cell();
+
+        constraint_builder.push(
+            "float conversion: selector",
+            Box::new(move |meta| {
+                vec![
+                    (op_is_i32_reinterpret_f32.expr(meta)
+                        + op_is_i64_reinterpret_f64.expr(meta)
+                        + op_is_f32_reinterpret_i32.expr(meta)
+                        + op_is_f64_reinterpret_i64.expr(meta)
+                        - constant_from!(1)),
+                    (is_i32.expr(meta)
+                        - op_is_i32_reinterpret_f32.expr(meta)
+                        - op_is_f32_reinterpret_i32.expr(meta)),
+                ]
+            }),
+        );
+
+        let eid = common_config.eid_cell;
+        let sp = common_config.sp_cell;
+
+        let memory_table_lookup_stack_read = allocator
+            .alloc_memory_table_lookup_read_cell_with_value(
+                "op_float_conversion stack read",
+                constraint_builder,
+                eid,
+                move |____| constant_from!(LocationType::Stack as u64),
+                move |meta| sp.expr(meta) + constant_from!(1),
+                move |meta| is_i32.expr(meta),
+                move |____| constant_from!(1),
+            );
+
+        let value = memory_table_lookup_stack_read.value_cell;
+
+        let memory_table_lookup_stack_write = allocator.alloc_memory_table_lookup_write_cell(
+            "op_float_conversion stack write",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(1),
+            move |meta| is_i32.expr(meta),
+            move |meta| value.expr(meta),
+            move |____| constant_from!(1),
+        );
+
+        Box::new(FloatConversionConfig {
+            is_i32,
+            op_is_i32_reinterpret_f32,
+            op_is_i64_reinterpret_f64,
+            op_is_f32_reinterpret_i32,
+            op_is_f64_reinterpret_i64,
+            memory_table_lookup_stack_read,
+            memory_table_lookup_stack_write,
+        })
+    }
+}
+
+impl<F: FieldExt> EventTableOpcodeConfig<F> for FloatConversionConfig<F> {
+    fn opcode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        let subop =
+            |meta: &mut VirtualCells<'_, F>, op: &AllocatedBitCell<F>, class: FloatConversionOp| {
+                op.expr(meta)
+                    * constant!(bn_to_field(
+                        &(BigUint::from(class as u64) << OPCODE_ARG0_SHIFT)
+                    ))
+            };
+
+        constant!(bn_to_field(
+            &(BigUint::from(OpcodeClass::FloatConversion as u64) << OPCODE_CLASS_SHIFT)
+        )) + subop(
+            meta,
+            &self.op_is_i32_reinterpret_f32,
+            FloatConversionOp::I32ReinterpretF32,
+        ) + subop(
+            meta,
+            &self.op_is_i64_reinterpret_f64,
+            FloatConversionOp::I64ReinterpretF64,
+        ) + subop(
+            meta,
+            &self.op_is_f32_reinterpret_i32,
+            FloatConversionOp::F32ReinterpretI32,
+        ) + subop(
+            meta,
+            &self.op_is_f64_reinterpret_i64,
+            FloatConversionOp::F64ReinterpretI64,
+        )
+    }
+
+    fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step: &mut StepStatus<F>,
+        entry: &EventTableEntryWithMemoryInfo,
+    ) -> Result<(), Error> {
+        let (class, value, result) = match entry.eentry.step_info {
+            StepInfo::FloatConversion {
+                class,
+                value,
+                result,
+            } => (class, value, result),
+
+            _ => unreachable!(),
+        };
+
+        let is_i32 = class.value_type() == VarType::I32;
+        self.is_i32.assign_bool(ctx, is_i32)?;
+
+        match class {
+            FloatConversionOp::I32ReinterpretF32 => {
+                self.op_is_i32_reinterpret_f32.assign(ctx, F::one())?
+            }
+            FloatConversionOp::I64ReinterpretF64 => {
+                self.op_is_i64_reinterpret_f64.assign(ctx, F::one())?
+            }
+            FloatConversionOp::F32ReinterpretI32 => {
+                self.op_is_f32_reinterpret_i32.assign(ctx, F::one())?
+            }
+            FloatConversionOp::F64ReinterpretI64 => {
+                self.op_is_f64_reinterpret_i64.assign(ctx, F::one())?
+            }
+        };
+
+        self.memory_table_lookup_stack_read.assign(
+            ctx,
+            entry.memory_rw_entires[0].start_eid,
+            step.current.eid,
+            entry.memory_rw_entires[0].end_eid,
+            step.current.sp + 1,
+            LocationType::Stack,
+            is_i32,
+            value,
+        )?;
+
+        self.memory_table_lookup_stack_write.assign(
+            ctx,
+            step.current.eid,
+            entry.memory_rw_entires[1].end_eid,
+            step.current.sp + 1,
+            LocationType::Stack,
+            is_i32,
+            result,
+        )?;
+
+        Ok(())
+    }
+
+    fn mops(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(1))
+    }
+
+    fn memory_writing_ops(&self, _: &EventTableEntry) -> u32 {
+        1
+    }
+}
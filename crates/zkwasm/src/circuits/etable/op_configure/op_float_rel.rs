@@ -0,0 +1,370 @@
+use crate::circuits::cell::*;
+use crate::circuits::etable::allocator::*;
+use crate::circuits::etable::ConstraintBuilder;
+use crate::circuits::etable::EventTableCommonConfig;
+use crate::circuits::etable::EventTableOpcodeConfig;
+use crate::circuits::etable::EventTableOpcodeConfigBuilder;
+use crate::circuits::utils::bn_to_field;
+use crate::circuits::utils::step_status::StepStatus;
+use crate::circuits::utils::table_entry::EventTableEntryWithMemoryInfo;
+use crate::circuits::utils::Context;
+use crate::constant;
+use crate::constant_from;
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::Error;
+use halo2_proofs::plonk::Expression;
+use halo2_proofs::plonk::VirtualCells;
+use num_bigint::BigUint;
+use specs::etable::EventTableEntry;
+use specs::itable::FloatRelOp;
+use specs::itable::OpcodeClass;
+use specs::itable::OPCODE_ARG0_SHIFT;
+use specs::itable::OPCODE_ARG1_SHIFT;
+use specs::itable::OPCODE_CLASS_SHIFT;
+use specs::mtable::FloatType;
+use specs::mtable::LocationType;
+use specs::step::StepInfo;
+
+use super::float::*;
+
+pub struct FloatRelConfig<F: FieldExt> {
+    is_f32: AllocatedBitCell<F>,
+
+    lhs: AllocatedU64CellWithFlagBitDyn<F>,
+    rhs: AllocatedU64CellWithFlagBitDyn<F>,
+
+    lhs_nan: FloatNanCells<F>,
+    rhs_nan: FloatNanCells<F>,
+    any_nan: AllocatedUnlimitedCell<F>,
+
+    lhs_key: AllocatedUnlimitedCell<F>,
+    rhs_key: AllocatedUnlimitedCell<F>,
+
+    diff: AllocatedU64Cell<F>,
+    diff_inv: AllocatedUnlimitedCell<F>,
+
+    res: AllocatedUnlimitedCell<F>,
+
+    res_is_eq: AllocatedBitCell<F>,
+    res_is_lt: AllocatedBitCell<F>,
+    res_is_gt: AllocatedBitCell<F>,
+
+    op_is_eq: AllocatedBitCell<F>,
+    op_is_ne: AllocatedBitCell<F>,
+    op_is_lt: AllocatedBitCell<F>,
+    op_is_gt: AllocatedBitCell<F>,
+    op_is_le: AllocatedBitCell<F>,
+    op_is_ge: AllocatedBitCell<F>,
+
+    memory_table_lookup_stack_read_lhs: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_stack_read_rhs: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_stack_write: AllocatedMemoryTableLookupWriteCell<F>,
+}
+
+pub struct FloatRelConfigBuilder {}
+
+impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for FloatRelConfigBuilder {
+    fn configure(
+        common_config: &EventTableCommonConfig<F>,
+        allocator: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+    ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        let is_f32 = allocator.alloc_bit_cell();
+
+        let lhs = allocator
+            .alloc_u64_with_flag_bit_cell_dyn(constraint_builder, move |meta| is_f32.expr(meta));
+        let rhs = allocator
+            .alloc_u64_with_flag_bit_cell_dyn(constraint_builder, move |meta| is_f32.expr(meta));
+
+        let lhs_nan = FloatNanCells::configure(
+            "float rel: lhs nan",
+            allocator,
+            constraint_builder,
+            is_f32,
+            lhs,
+        );
+        let rhs_nan = FloatNanCells::configure(
+            "float rel: rhs nan",
+            allocator,
+            constraint_builder,
+            is_f32,
+            rhs,
+        );
+        let any_nan = allocator.alloc_unlimited_cell();
+
+        let lhs_key = alloc_float_order_key(
+            "float rel: lhs key",
+            allocator,
+            constraint_builder,
+            is_f32,
+            lhs,
+        );
+        let rhs_key = alloc_float_order_key(
+            "float rel: rhs key",
+            allocator,
+            constraint_builder,
+            is_f32,
+            rhs,
+        );
+
+        let diff = allocator.alloc_u64_cell();
+        let diff_inv = allocator.alloc_unlimited_cell();
+        let res = allocator.alloc_unlimited_cell();
+
+        let res_is_eq = allocator.alloc_bit_cell();
+        let res_is_lt = allocator.alloc_bit_cell();
+        let res_is_gt = allocator.alloc_bit_cell();
+
+        let op_is_eq = allocator.alloc_bit_cell();
+        let op_is_ne = allocator.alloc_bit_cell();
+        let op_is_lt = allocator.alloc_bit_cell();
+        let op_is_gt = allocator.alloc_bit_cell();
+        let op_is_le = allocator.alloc_bit_cell();
+        let op_is_ge = allocator.alloc_bit_cell();
+
+        constraint_builder.push(
+            "float rel: selector",
+            Box::new(move |meta| {
+                vec![
+                    (op_is_eq.expr(meta)
+                        + op_is_ne.expr(meta)
+                        + op_is_lt.expr(meta)
+                        + op_is_gt.expr(meta)
+                        + op_is_le.expr(meta)
+                        + op_is_ge.expr(meta)
+                        - constant_from!(1)),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "float rel: compare diff",
+            Box::new(move |meta| {
+                vec![
+                    (lhs_key.expr(meta) + res_is_lt.expr(meta) * diff.u64_cell.expr(meta)
+                        - res_is_gt.expr(meta) * diff.u64_cell.expr(meta)
+                        - rhs_key.expr(meta)),
+                    (res_is_gt.expr(meta) + res_is_lt.expr(meta) + res_is_eq.expr(meta)
+                        - constant_from!(1)),
+                    (diff.u64_cell.expr(meta) * res_is_eq.expr(meta)),
+                    (diff.u64_cell.expr(meta) * diff_inv.expr(meta) + res_is_eq.expr(meta)
+                        - constant_from!(1)),
+                ]
+            }),
+        );
+
+        // Every comparison involving NaN is unordered: only `ne` holds.
+        constraint_builder.push(
+            "float rel: compare op res",
+            Box::new(move |meta| {
+                let ordered = constant_from!(1) - any_nan.expr(meta);
+
+                vec![
+                    any_nan.expr(meta)
+                        - (lhs_nan.is_nan.expr(meta) + rhs_nan.is_nan.expr(meta)
+                            - lhs_nan.is_nan.expr(meta) * rhs_nan.is_nan.expr(meta)),
+                    op_is_eq.expr(meta) * (res.expr(meta) - ordered.clone() * res_is_eq.expr(meta)),
+                    op_is_ne.expr(meta)
+                        * (res.expr(meta)
+                            - ordered.clone() * (constant_from!(1) - res_is_eq.expr(meta))
+                            - any_nan.expr(meta)),
+                    op_is_lt.expr(meta) * (res.expr(meta) - ordered.clone() * res_is_lt.expr(meta)),
+                    op_is_gt.expr(meta) * (res.expr(meta) - ordered.clone() * res_is_gt.expr(meta)),
+                    op_is_le.expr(meta)
+                        * (res.expr(meta)
+                            - ordered.clone() * (res_is_lt.expr(meta) + res_is_eq.expr(meta))),
+                    op_is_ge.expr(meta)
+                        * (res.expr(meta)
+                            - ordered * (res_is_gt.expr(meta) + res_is_eq.expr(meta))),
+                ]
+            }),
+        );
+
+        let eid = common_config.eid_cell;
+        let sp = common_config.sp_cell;
+
+        let memory_table_lookup_stack_read_rhs = allocator.alloc_memory_table_lookup_read_cell(
+            "op_float_rel stack read",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(1),
+            move |meta| is_f32.expr(meta),
+            move |meta| rhs.u64_cell.expr(meta),
+            move |____| constant_from!(1),
+        );
+
+        let memory_table_lookup_stack_read_lhs = allocator.alloc_memory_table_lookup_read_cell(
+            "op_float_rel stack read",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(2),
+            move |meta| is_f32.expr(meta),
+            move |meta| lhs.u64_cell.expr(meta),
+            move |____| constant_from!(1),
+        );
+
+        let memory_table_lookup_stack_write = allocator.alloc_memory_table_lookup_write_cell(
+            "op_float_rel stack write",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(2),
+            move |____| constant_from!(1),
+            move |meta| res.expr(meta),
+            move |____| constant_from!(1),
+        );
+
+        Box::new(FloatRelConfig {
+            is_f32,
+            lhs,
+            rhs,
+            lhs_nan,
+            rhs_nan,
+            any_nan,
+            lhs_key,
+            rhs_key,
+            diff,
+            diff_inv,
+            res,
+            res_is_eq,
+            res_is_lt,
+            res_is_gt,
+            op_is_eq,
+            op_is_ne,
+            op_is_lt,
+            op_is_gt,
+            op_is_le,
+            op_is_ge,
+            memory_table_lookup_stack_read_lhs,
+            memory_table_lookup_stack_read_rhs,
+            memory_table_lookup_stack_write,
+        })
+    }
+}
+
+impl<F: FieldExt> EventTableOpcodeConfig<F> for FloatRelConfig<F> {
+    fn opcode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        let subop =
+            |meta: &mut VirtualCells<'_, F>, op: &AllocatedBitCell<F>, class: FloatRelOp| {
+                op.expr(meta)
+                    * constant!(bn_to_field(
+                        &(BigUint::from(class as u64) << OPCODE_ARG0_SHIFT)
+                    ))
+            };
+
+        constant!(bn_to_field(
+            &(BigUint::from(OpcodeClass::FloatRel as u64) << OPCODE_CLASS_SHIFT)
+        )) + subop(meta, &self.op_is_eq, FloatRelOp::Eq)
+            + subop(meta, &self.op_is_ne, FloatRelOp::Ne)
+            + subop(meta, &self.op_is_lt, FloatRelOp::Lt)
+            + subop(meta, &self.op_is_gt, FloatRelOp::Gt)
+            + subop(meta, &self.op_is_le, FloatRelOp::Le)
+            + subop(meta, &self.op_is_ge, FloatRelOp::Ge)
+            + self.is_f32.expr(meta)
+                * constant!(bn_to_field(
+                    &(BigUint::from(FloatType::F32 as u64) << OPCODE_ARG1_SHIFT)
+                ))
+    }
+
+    fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step: &mut StepStatus<F>,
+        entry: &EventTableEntryWithMemoryInfo,
+    ) -> Result<(), Error> {
+        let (class, vtype, lhs, rhs, value) = match entry.eentry.step_info {
+            StepInfo::FloatComp {
+                class,
+                vtype,
+                left,
+                right,
+                value,
+            } => (class, vtype, left, right, value),
+
+            _ => unreachable!(),
+        };
+
+        let is_f32 = vtype == FloatType::F32;
+
+        self.is_f32.assign_bool(ctx, is_f32)?;
+        self.lhs.assign(ctx, lhs, is_f32)?;
+        self.rhs.assign(ctx, rhs, is_f32)?;
+
+        let lhs_is_nan = self.lhs_nan.assign(ctx, vtype, lhs)?;
+        let rhs_is_nan = self.rhs_nan.assign(ctx, vtype, rhs)?;
+        self.any_nan.assign_bool(ctx, lhs_is_nan || rhs_is_nan)?;
+
+        let lhs_key = float_order_key(vtype, lhs);
+        let rhs_key = float_order_key(vtype, rhs);
+        self.lhs_key.assign(ctx, i64_to_field(lhs_key))?;
+        self.rhs_key.assign(ctx, i64_to_field(rhs_key))?;
+
+        let diff = lhs_key.abs_diff(rhs_key);
+        self.diff.assign(ctx, diff)?;
+        if diff != 0 {
+            self.diff_inv.assign(ctx, step.field_helper.invert(diff))?;
+        }
+
+        self.res_is_eq.assign_bool(ctx, lhs_key == rhs_key)?;
+        self.res_is_gt.assign_bool(ctx, lhs_key > rhs_key)?;
+        self.res_is_lt.assign_bool(ctx, lhs_key < rhs_key)?;
+        self.res.assign_bool(ctx, value)?;
+
+        match class {
+            FloatRelOp::Eq => self.op_is_eq.assign(ctx, F::one())?,
+            FloatRelOp::Ne => self.op_is_ne.assign(ctx, F::one())?,
+            FloatRelOp::Lt => self.op_is_lt.assign(ctx, F::one())?,
+            FloatRelOp::Gt => self.op_is_gt.assign(ctx, F::one())?,
+            FloatRelOp::Le => self.op_is_le.assign(ctx, F::one())?,
+            FloatRelOp::Ge => self.op_is_ge.assign(ctx, F::one())?,
+        };
+
+        self.memory_table_lookup_stack_read_rhs.assign(
+            ctx,
+            entry.memory_rw_entires[0].start_eid,
+            step.current.eid,
+            entry.memory_rw_entires[0].end_eid,
+            step.current.sp + 1,
+            LocationType::Stack,
+            is_f32,
+            rhs,
+        )?;
+
+        self.memory_table_lookup_stack_read_lhs.assign(
+            ctx,
+            entry.memory_rw_entires[1].start_eid,
+            step.current.eid,
+            entry.memory_rw_entires[1].end_eid,
+            step.current.sp + 2,
+            LocationType::Stack,
+            is_f32,
+            lhs,
+        )?;
+
+        self.memory_table_lookup_stack_write.assign(
+            ctx,
+            step.current.eid,
+            entry.memory_rw_entires[2].end_eid,
+            step.current.sp + 2,
+            LocationType::Stack,
+            true,
+            value as u64,
+        )?;
+
+        Ok(())
+    }
+
+    fn mops(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(1))
+    }
+
+    fn memory_writing_ops(&self, _: &EventTableEntry) -> u32 {
+        1
+    }
+
+    fn sp_diff(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant!(F::one()))
+    }
+}
@@ -0,0 +1,198 @@
+use crate::circuits::cell::*;
+use crate::circuits::etable::allocator::*;
+use crate::circuits::etable::ConstraintBuilder;
+use crate::circuits::etable::EventTableCommonConfig;
+use crate::circuits::etable::EventTableOpcodeConfig;
+use crate::circuits::etable::EventTableOpcodeConfigBuilder;
+use crate::circuits::utils::bn_to_field;
+use crate::circuits::utils::step_status::StepStatus;
+use crate::circuits::utils::table_entry::EventTableEntryWithMemoryInfo;
+use crate::circuits::utils::Context;
+use crate::constant;
+use crate::constant_from;
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::Error;
+use halo2_proofs::plonk::Expression;
+use halo2_proofs::plonk::VirtualCells;
+use num_bigint::BigUint;
+use specs::etable::EventTableEntry;
+use specs::itable::FloatUnaryOp;
+use specs::itable::OpcodeClass;
+use specs::itable::OPCODE_ARG0_SHIFT;
+use specs::itable::OPCODE_ARG1_SHIFT;
+use specs::itable::OPCODE_CLASS_SHIFT;
+use specs::mtable::FloatType;
+use specs::mtable::LocationType;
+use specs::step::StepInfo;
+
+use super::float::*;
+
+/// The sign manipulating operations, abs and neg.
+pub struct FloatUnaryConfig<F: FieldExt> {
+    is_f32: AllocatedBitCell<F>,
+
+    operand: AllocatedU64CellWithFlagBitDyn<F>,
+    res: AllocatedUnlimitedCell<F>,
+
+    op_is_abs: AllocatedBitCell<F>,
+    op_is_neg: AllocatedBitCell<F>,
+
+    memory_table_lookup_stack_read: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_stack_write: AllocatedMemoryTableLookupWriteCell<F>,
+}
+
+pub struct FloatUnaryConfigBuilder {}
+
+impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for FloatUnaryConfigBuilder {
+    fn configure(
+        common_config: &EventTableCommonConfig<F>,
+        allocator: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+    ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        let is_f32 = allocator.alloc_bit_cell();
+
+        let operand = allocator
+            .alloc_u64_with_flag_bit_cell_dyn(constraint_builder, move |meta| is_f32.expr(meta));
+        let res = allocator.alloc_unlimited_cell();
+
+        let op_is_abs = allocator.alloc_bit_cell();
+        let op_is_neg = allocator.alloc_bit_cell();
+
+        constraint_builder.push(
+            "float unary: selector",
+            Box::new(move |meta| {
+                vec![op_is_abs.expr(meta) + op_is_neg.expr(meta) - constant_from!(1)]
+            }),
+        );
+
+        constraint_builder.push(
+            "float unary: abs/neg",
+            Box::new(move |meta| {
+                let magnitude = magnitude_expr(meta, is_f32, operand);
+
+                vec![
+                    op_is_abs.expr(meta) * (res.expr(meta) - magnitude.clone()),
+                    op_is_neg.expr(meta)
+                        * (res.expr(meta)
+                            - magnitude
+                            - (constant_from!(1) - operand.flag_bit_cell.expr(meta))
+                                * half_expr(meta, is_f32)),
+                ]
+            }),
+        );
+
+        let eid = common_config.eid_cell;
+        let sp = common_config.sp_cell;
+
+        let memory_table_lookup_stack_read = allocator.alloc_memory_table_lookup_read_cell(
+            "op_float_unary stack read",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(1),
+            move |meta| is_f32.expr(meta),
+            move |meta| operand.u64_cell.expr(meta),
+            move |____| constant_from!(1),
+        );
+
+        let memory_table_lookup_stack_write = allocator.alloc_memory_table_lookup_write_cell(
+            "op_float_unary stack write",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(1),
+            move |meta| is_f32.expr(meta),
+            move |meta| res.expr(meta),
+            move |____| constant_from!(1),
+        );
+
+        Box::new(FloatUnaryConfig {
+            is_f32,
+            operand,
+            res,
+            op_is_abs,
+            op_is_neg,
+            memory_table_lookup_stack_read,
+            memory_table_lookup_stack_write,
+        })
+    }
+}
+
+impl<F: FieldExt> EventTableOpcodeConfig<F> for FloatUnaryConfig<F> {
+    fn opcode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        constant!(bn_to_field(
+            &(BigUint::from(OpcodeClass::FloatUnary as u64) << OPCODE_CLASS_SHIFT)
+        )) + self.op_is_abs.expr(meta)
+            * constant!(bn_to_field(
+                &(BigUint::from(FloatUnaryOp::Abs as u64) << OPCODE_ARG0_SHIFT)
+            ))
+            + self.op_is_neg.expr(meta)
+                * constant!(bn_to_field(
+                    &(BigUint::from(FloatUnaryOp::Neg as u64) << OPCODE_ARG0_SHIFT)
+                ))
+            + self.is_f32.expr(meta)
+                * constant!(bn_to_field(
+                    &(BigUint::from(FloatType::F32 as u64) << OPCODE_ARG1_SHIFT)
+                ))
+    }
+
+    fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step: &mut StepStatus<F>,
+        entry: &EventTableEntryWithMemoryInfo,
+    ) -> Result<(), Error> {
+        let (class, vtype, operand, result) = match entry.eentry.step_info {
+            StepInfo::FloatUnaryOp {
+                class,
+                vtype,
+                operand,
+                result,
+            } => (class, vtype, operand, result),
+
+            _ => unreachable!(),
+        };
+
+        let is_f32 = vtype == FloatType::F32;
+
+        self.is_f32.assign_bool(ctx, is_f32)?;
+        self.operand.assign(ctx, operand, is_f32)?;
+        self.res.assign(ctx, result.into())?;
+
+        match class {
+            FloatUnaryOp::Abs => self.op_is_abs.assign(ctx, F::one())?,
+            FloatUnaryOp::Neg => self.op_is_neg.assign(ctx, F::one())?,
+        };
+
+        self.memory_table_lookup_stack_read.assign(
+            ctx,
+            entry.memory_rw_entires[0].start_eid,
+            step.current.eid,
+            entry.memory_rw_entires[0].end_eid,
+            step.current.sp + 1,
+            LocationType::Stack,
+            is_f32,
+            operand,
+        )?;
+
+        self.memory_table_lookup_stack_write.assign(
+            ctx,
+            step.current.eid,
+            entry.memory_rw_entires[1].end_eid,
+            step.current.sp + 1,
+            LocationType::Stack,
+            is_f32,
+            result,
+        )?;
+
+        Ok(())
+    }
+
+    fn mops(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(1))
+    }
+
+    fn memory_writing_ops(&self, _: &EventTableEntry) -> u32 {
+        1
+    }
+}
@@ -0,0 +1,535 @@
+use crate::circuits::bit_table::BitTableOp;
+use crate::circuits::cell::*;
+use crate::circuits::etable::allocator::*;
+use crate::circuits::etable::ConstraintBuilder;
+use crate::circuits::etable::EventTableCommonConfig;
+use crate::circuits::etable::EventTableOpcodeConfig;
+use crate::circuits::etable::EventTableOpcodeConfigBuilder;
+use crate::circuits::mtable::utils::block_from_address;
+use crate::circuits::mtable::utils::byte_offset_from_address;
+use crate::circuits::mtable::utils::WASM_BLOCKS_PER_PAGE;
+use crate::circuits::mtable::utils::WASM_BLOCK_BYTE_SIZE;
+use crate::circuits::rtable::pow_table_power_encode;
+use crate::circuits::utils::bn_to_field;
+use crate::circuits::utils::step_status::StepStatus;
+use crate::circuits::utils::table_entry::EventTableEntryWithMemoryInfo;
+use crate::circuits::utils::Context;
+use crate::constant;
+use crate::constant_from;
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::Error;
+use halo2_proofs::plonk::Expression;
+use halo2_proofs::plonk::VirtualCells;
+use num_bigint::BigUint;
+use specs::etable::EventTableEntry;
+use specs::itable::BitOp;
+use specs::itable::OpcodeClass;
+use specs::itable::OPCODE_CLASS_SHIFT;
+use specs::mtable::LocationType;
+use specs::step::StepInfo;
+
+use super::bulk_memory::MemoryWriteCells;
+
+/// Each step copies one byte, or a whole block if both ranges are aligned at
+/// the copied end and at least 8 bytes are left. If `dst <= src` the bytes are
+/// copied in ascending order from `src` to `dst` and both are increased,
+/// otherwise they are copied in descending order from `src + len - 1` to
+/// `dst + len - 1` and only `len` is decreased. The step with `len == 0` pops
+/// the operands and moves to the next instruction.
+///
+/// Ranges with different alignments are always copied byte by byte.
+pub struct MemoryCopyConfig<F: FieldExt> {
+    is_finished: AllocatedBitCell<F>,
+    len_inv: AllocatedUnlimitedCell<F>,
+
+    is_word: AllocatedBitCell<F>,
+    /// len - 8 if is_word
+    word_len_diff: AllocatedU64Cell<F>,
+
+    is_forward: AllocatedBitCell<F>,
+    /// src - dst if is_forward, otherwise dst - src - 1
+    order_diff: AllocatedU64Cell<F>,
+
+    src_block_index: AllocatedU32Cell<F>,
+    src_inner_pos_bits: [AllocatedBitCell<F>; 3],
+    src_inner_pos: AllocatedUnlimitedCell<F>,
+    src_address_within_allocated_pages_helper: AllocatedCommonRangeCell<F>,
+    byte: AllocatedU8Cell<F>,
+
+    lookup_pow_modulus: AllocatedUnlimitedCell<F>,
+    lookup_pow_power: AllocatedUnlimitedCell<F>,
+    bit_table_lookup: AllocatedBitTableLookupCells<F>,
+
+    memory_write: MemoryWriteCells<F>,
+
+    memory_table_lookup_stack_read_len: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_stack_read_src: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_stack_read_dst: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_heap_read_src: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_stack_write_dst: AllocatedMemoryTableLookupWriteCell<F>,
+    memory_table_lookup_stack_write_src: AllocatedMemoryTableLookupWriteCell<F>,
+    memory_table_lookup_stack_write_len: AllocatedMemoryTableLookupWriteCell<F>,
+}
+
+pub struct MemoryCopyConfigBuilder {}
+
+impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for MemoryCopyConfigBuilder {
+    fn configure(
+        common_config: &EventTableCommonConfig<F>,
+        allocator: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+    ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        let is_finished = allocator.alloc_bit_cell();
+        let len_inv = allocator.alloc_unlimited_cell();
+
+        let is_word = allocator.alloc_bit_cell();
+        let word_len_diff = allocator.alloc_u64_cell();
+
+        let is_forward = allocator.alloc_bit_cell();
+        let order_diff = allocator.alloc_u64_cell();
+
+        let src_block_index = allocator.alloc_u32_cell();
+        let src_inner_pos_bits = [0; 3].map(|_| allocator.alloc_bit_cell());
+        let src_inner_pos = allocator.alloc_unlimited_cell();
+        let src_address_within_allocated_pages_helper = allocator.alloc_common_range_cell();
+        let byte = allocator.alloc_u8_cell();
+
+        let lookup_pow_modulus = common_config.pow_table_lookup_modulus_cell;
+        let lookup_pow_power = common_config.pow_table_lookup_power_cell;
+        let bit_table_lookup = common_config.bit_table_lookup_cells;
+
+        let eid = common_config.eid_cell;
+        let sp = common_config.sp_cell;
+        let current_memory_page_size = common_config.mpages_cell;
+
+        let memory_table_lookup_stack_read_len = allocator
+            .alloc_memory_table_lookup_read_cell_with_value(
+                "op_memory_copy stack read len",
+                constraint_builder,
+                eid,
+                move |____| constant_from!(LocationType::Stack as u64),
+                move |meta| sp.expr(meta) + constant_from!(1),
+                move |____| constant_from!(1),
+                move |____| constant_from!(1),
+            );
+
+        let memory_table_lookup_stack_read_src = allocator
+            .alloc_memory_table_lookup_read_cell_with_value(
+                "op_memory_copy stack read src",
+                constraint_builder,
+                eid,
+                move |____| constant_from!(LocationType::Stack as u64),
+                move |meta| sp.expr(meta) + constant_from!(2),
+                move |____| constant_from!(1),
+                move |____| constant_from!(1),
+            );
+
+        let memory_table_lookup_stack_read_dst = allocator
+            .alloc_memory_table_lookup_read_cell_with_value(
+                "op_memory_copy stack read dst",
+                constraint_builder,
+                eid,
+                move |____| constant_from!(LocationType::Stack as u64),
+                move |meta| sp.expr(meta) + constant_from!(3),
+                move |____| constant_from!(1),
+                move |____| constant_from!(1),
+            );
+
+        let len = memory_table_lookup_stack_read_len.value_cell;
+        let src = memory_table_lookup_stack_read_src.value_cell;
+        let dst = memory_table_lookup_stack_read_dst.value_cell;
+
+        let memory_table_lookup_heap_read_src = allocator
+            .alloc_memory_table_lookup_read_cell_with_value(
+                "op_memory_copy load src",
+                constraint_builder,
+                eid,
+                move |____| constant_from!(LocationType::Heap as u64),
+                move |meta| src_block_index.expr(meta),
+                move |____| constant_from!(0),
+                move |meta| constant_from!(1) - is_finished.expr(meta),
+            );
+
+        let src_block_value = memory_table_lookup_heap_read_src.value_cell;
+
+        // Offset of the copied byte or block from `src` and `dst`, the backward copy starts
+        // from the end of the ranges.
+        let backward_shift = move |meta: &mut VirtualCells<'_, F>| {
+            (constant_from!(1) - is_forward.expr(meta))
+                * (len.expr(meta) - constant_from!(1) - is_word.expr(meta) * constant_from!(7))
+        };
+
+        let memory_write = MemoryWriteCells::configure(
+            common_config,
+            allocator,
+            constraint_builder,
+            move |meta| dst.expr(meta) + backward_shift(meta),
+            move |meta| byte.expr(meta),
+            move |meta| src_block_value.expr(meta),
+            move |meta| is_word.expr(meta),
+            move |meta| constant_from!(1) - is_finished.expr(meta),
+        );
+
+        let memory_table_lookup_stack_write_dst = allocator.alloc_memory_table_lookup_write_cell(
+            "op_memory_copy stack write dst",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(3),
+            move |____| constant_from!(1),
+            move |meta| {
+                dst.expr(meta)
+                    + is_forward.expr(meta)
+                        * (constant_from!(1) + is_word.expr(meta) * constant_from!(7))
+            },
+            move |meta| constant_from!(1) - is_finished.expr(meta),
+        );
+
+        let memory_table_lookup_stack_write_src = allocator.alloc_memory_table_lookup_write_cell(
+            "op_memory_copy stack write src",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(2),
+            move |____| constant_from!(1),
+            move |meta| {
+                src.expr(meta)
+                    + is_forward.expr(meta)
+                        * (constant_from!(1) + is_word.expr(meta) * constant_from!(7))
+            },
+            move |meta| constant_from!(1) - is_finished.expr(meta),
+        );
+
+        let memory_table_lookup_stack_write_len = allocator.alloc_memory_table_lookup_write_cell(
+            "op_memory_copy stack write len",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(1),
+            move |____| constant_from!(1),
+            move |meta| len.expr(meta) - constant_from!(1) - is_word.expr(meta) * constant_from!(7),
+            move |meta| constant_from!(1) - is_finished.expr(meta),
+        );
+
+        constraint_builder.push(
+            "op_memory_copy is_finished",
+            Box::new(move |meta| {
+                vec![
+                    len.expr(meta) * len_inv.expr(meta) + is_finished.expr(meta)
+                        - constant_from!(1),
+                    len.expr(meta) * is_finished.expr(meta),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_memory_copy word",
+            Box::new(move |meta| {
+                vec![
+                    (len.expr(meta) - constant_from!(8) - word_len_diff.expr(meta))
+                        * is_word.expr(meta),
+                    src_inner_pos.expr(meta)
+                        * is_word.expr(meta)
+                        * (constant_from!(1) - is_finished.expr(meta)),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_memory_copy direction",
+            Box::new(move |meta| {
+                vec![
+                    is_forward.expr(meta)
+                        * (src.expr(meta) - dst.expr(meta) - order_diff.expr(meta))
+                        + (constant_from!(1) - is_forward.expr(meta))
+                            * (dst.expr(meta)
+                                - src.expr(meta)
+                                - constant_from!(1)
+                                - order_diff.expr(meta)),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_memory_copy src block index",
+            Box::new(move |meta| {
+                let src_address = src.expr(meta) + backward_shift(meta);
+
+                vec![
+                    src_inner_pos.expr(meta)
+                        - src_inner_pos_bits[0].expr(meta)
+                        - src_inner_pos_bits[1].expr(meta) * constant_from!(2)
+                        - src_inner_pos_bits[2].expr(meta) * constant_from!(4),
+                    (src_block_index.expr(meta) * constant_from!(WASM_BLOCK_BYTE_SIZE)
+                        + src_inner_pos.expr(meta)
+                        - src_address)
+                        * (constant_from!(1) - is_finished.expr(meta)),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_memory_copy src allocated address",
+            Box::new(move |meta| {
+                vec![
+                    (src_block_index.expr(meta)
+                        + constant_from!(1)
+                        + src_address_within_allocated_pages_helper.expr(meta)
+                        - current_memory_page_size.expr(meta)
+                            * constant_from!(WASM_BLOCKS_PER_PAGE))
+                        * (constant_from!(1) - is_finished.expr(meta)),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_memory_copy pick src byte",
+            Box::new(move |meta| {
+                let enable = constant_from!(1) - is_finished.expr(meta);
+
+                vec![
+                    (lookup_pow_power.expr(meta)
+                        - pow_table_power_encode(src_inner_pos.expr(meta) * constant_from!(8)))
+                        * enable.clone(),
+                    (bit_table_lookup.op.expr(meta)
+                        - constant_from!(BitTableOp::BinaryBit(BitOp::And).index()))
+                        * enable.clone(),
+                    (bit_table_lookup.left.expr(meta) - src_block_value.expr(meta))
+                        * enable.clone(),
+                    (bit_table_lookup.right.expr(meta)
+                        - lookup_pow_modulus.expr(meta) * constant_from!(0xff))
+                        * enable.clone(),
+                    (bit_table_lookup.result.expr(meta)
+                        - lookup_pow_modulus.expr(meta) * byte.expr(meta))
+                        * enable,
+                ]
+            }),
+        );
+
+        Box::new(MemoryCopyConfig {
+            is_finished,
+            len_inv,
+            is_word,
+            word_len_diff,
+            is_forward,
+            order_diff,
+            src_block_index,
+            src_inner_pos_bits,
+            src_inner_pos,
+            src_address_within_allocated_pages_helper,
+            byte,
+            lookup_pow_modulus,
+            lookup_pow_power,
+            bit_table_lookup,
+            memory_write,
+            memory_table_lookup_stack_read_len,
+            memory_table_lookup_stack_read_src,
+            memory_table_lookup_stack_read_dst,
+            memory_table_lookup_heap_read_src,
+            memory_table_lookup_stack_write_dst,
+            memory_table_lookup_stack_write_src,
+            memory_table_lookup_stack_write_len,
+        })
+    }
+}
+
+impl<F: FieldExt> EventTableOpcodeConfig<F> for MemoryCopyConfig<F> {
+    fn opcode(&self, _meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        constant!(bn_to_field(
+            &(BigUint::from(OpcodeClass::MemoryCopy as u64) << OPCODE_CLASS_SHIFT)
+        ))
+    }
+
+    fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step: &mut StepStatus<F>,
+        entry: &EventTableEntryWithMemoryInfo,
+    ) -> Result<(), Error> {
+        match entry.eentry.step_info {
+            StepInfo::MemoryCopy {
+                dst,
+                src,
+                len,
+                is_word,
+                src_block_value,
+                pre_block_value,
+                updated_block_value,
+            } => {
+                let is_forward = dst <= src;
+                let width = if is_word { 8 } else { 1 };
+
+                self.is_finished.assign_bool(ctx, len == 0)?;
+                if len != 0 {
+                    self.len_inv
+                        .assign(ctx, step.field_helper.invert(len as u64))?;
+                }
+
+                self.is_word.assign_bool(ctx, is_word)?;
+                if is_word {
+                    self.word_len_diff.assign(ctx, (len - 8) as u64)?;
+                }
+
+                self.is_forward.assign_bool(ctx, is_forward)?;
+                self.order_diff.assign(
+                    ctx,
+                    if is_forward {
+                        (src - dst) as u64
+                    } else {
+                        (dst - src - 1) as u64
+                    },
+                )?;
+
+                self.memory_table_lookup_stack_read_len.assign(
+                    ctx,
+                    entry.memory_rw_entires[0].start_eid,
+                    step.current.eid,
+                    entry.memory_rw_entires[0].end_eid,
+                    step.current.sp + 1,
+                    LocationType::Stack,
+                    true,
+                    len as u64,
+                )?;
+
+                self.memory_table_lookup_stack_read_src.assign(
+                    ctx,
+                    entry.memory_rw_entires[1].start_eid,
+                    step.current.eid,
+                    entry.memory_rw_entires[1].end_eid,
+                    step.current.sp + 2,
+                    LocationType::Stack,
+                    true,
+                    src as u64,
+                )?;
+
+                self.memory_table_lookup_stack_read_dst.assign(
+                    ctx,
+                    entry.memory_rw_entires[2].start_eid,
+                    step.current.eid,
+                    entry.memory_rw_entires[2].end_eid,
+                    step.current.sp + 3,
+                    LocationType::Stack,
+                    true,
+                    dst as u64,
+                )?;
+
+                if len != 0 {
+                    let (src_address, dst_address) = if is_forward {
+                        (src, dst)
+                    } else {
+                        (src + len - width, dst + len - width)
+                    };
+
+                    let src_block_index = block_from_address(src_address);
+                    let src_inner_pos = byte_offset_from_address(src_address);
+
+                    self.src_block_index.assign(ctx, src_block_index)?;
+                    self.src_inner_pos.assign_u32(ctx, src_inner_pos)?;
+                    for (i, bit) in self.src_inner_pos_bits.iter().enumerate() {
+                        bit.assign_bool(ctx, src_inner_pos & (1 << i) != 0)?;
+                    }
+                    self.src_address_within_allocated_pages_helper.assign_u32(
+                        ctx,
+                        step.current.allocated_memory_pages * WASM_BLOCKS_PER_PAGE
+                            - (src_block_index + 1),
+                    )?;
+
+                    let modulus = 1u64 << (src_inner_pos * 8);
+                    let byte = (src_block_value >> (src_inner_pos * 8)) & 0xff;
+
+                    self.byte.assign(ctx, byte.into())?;
+                    self.lookup_pow_modulus.assign(ctx, modulus.into())?;
+                    self.lookup_pow_power.assign_bn(
+                        ctx,
+                        &pow_table_power_encode(BigUint::from(src_inner_pos * 8)),
+                    )?;
+                    self.bit_table_lookup.assign(
+                        ctx,
+                        BitTableOp::BinaryBit(BitOp::And),
+                        src_block_value,
+                        modulus * 0xff,
+                        modulus * byte,
+                    )?;
+
+                    self.memory_table_lookup_heap_read_src.assign(
+                        ctx,
+                        entry.memory_rw_entires[3].start_eid,
+                        step.current.eid,
+                        entry.memory_rw_entires[3].end_eid,
+                        src_block_index,
+                        LocationType::Heap,
+                        false,
+                        src_block_value,
+                    )?;
+
+                    self.memory_write.assign(
+                        ctx,
+                        step,
+                        dst_address,
+                        pre_block_value,
+                        updated_block_value,
+                        (&entry.memory_rw_entires[4], &entry.memory_rw_entires[5]),
+                    )?;
+
+                    self.memory_table_lookup_stack_write_dst.assign(
+                        ctx,
+                        step.current.eid,
+                        entry.memory_rw_entires[6].end_eid,
+                        step.current.sp + 3,
+                        LocationType::Stack,
+                        true,
+                        (dst + is_forward as u32 * width) as u64,
+                    )?;
+
+                    self.memory_table_lookup_stack_write_src.assign(
+                        ctx,
+                        step.current.eid,
+                        entry.memory_rw_entires[7].end_eid,
+                        step.current.sp + 2,
+                        LocationType::Stack,
+                        true,
+                        (src + is_forward as u32 * width) as u64,
+                    )?;
+
+                    self.memory_table_lookup_stack_write_len.assign(
+                        ctx,
+                        step.current.eid,
+                        entry.memory_rw_entires[8].end_eid,
+                        step.current.sp + 1,
+                        LocationType::Stack,
+                        true,
+                        (len - width) as u64,
+                    )?;
+                }
+
+                Ok(())
+            }
+
+            _ => unreachable!(),
+        }
+    }
+
+    fn sp_diff(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(3) * self.is_finished.expr(meta))
+    }
+
+    fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(4) * (constant_from!(1) - self.is_finished.expr(meta)))
+    }
+
+    fn memory_writing_ops(&self, entry: &EventTableEntry) -> u32 {
+        match entry.step_info {
+            StepInfo::MemoryCopy { len: 0, .. } => 0,
+            StepInfo::MemoryCopy { .. } => 4,
+            _ => unreachable!(),
+        }
+    }
+
+    fn next_iid(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        Some(common_config.iid_cell.expr(meta) + self.is_finished.expr(meta))
+    }
+}
@@ -0,0 +1,323 @@
+use crate::circuits::cell::*;
+use crate::circuits::etable::allocator::*;
+use crate::circuits::etable::ConstraintBuilder;
+use crate::circuits::etable::EventTableCommonConfig;
+use crate::circuits::etable::EventTableOpcodeConfig;
+use crate::circuits::etable::EventTableOpcodeConfigBuilder;
+use crate::circuits::utils::bn_to_field;
+use crate::circuits::utils::step_status::StepStatus;
+use crate::circuits::utils::table_entry::EventTableEntryWithMemoryInfo;
+use crate::circuits::utils::Context;
+use crate::constant;
+use crate::constant_from;
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::Error;
+use halo2_proofs::plonk::Expression;
+use halo2_proofs::plonk::VirtualCells;
+use num_bigint::BigUint;
+use specs::etable::EventTableEntry;
+use specs::itable::OpcodeClass;
+use specs::itable::OPCODE_CLASS_SHIFT;
+use specs::mtable::LocationType;
+use specs::step::StepInfo;
+
+use super::bulk_memory::MemoryWriteCells;
+
+/// Each step writes one byte at `offset`, or the whole block if `offset` is
+/// aligned and at least 8 bytes are left, then advances `offset` and `len` on
+/// the stack by the written width without moving sp. The step with
+/// `len == 0` pops the operands and moves to the next instruction.
+pub struct MemoryFillConfig<F: FieldExt> {
+    is_finished: AllocatedBitCell<F>,
+    len_inv: AllocatedUnlimitedCell<F>,
+
+    is_word: AllocatedBitCell<F>,
+    /// len - 8 if is_word
+    word_len_diff: AllocatedU64Cell<F>,
+
+    byte: AllocatedU8Cell<F>,
+    value_high: AllocatedU32Cell<F>,
+
+    memory_write: MemoryWriteCells<F>,
+
+    memory_table_lookup_stack_read_len: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_stack_read_value: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_stack_read_offset: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_stack_write_offset: AllocatedMemoryTableLookupWriteCell<F>,
+    memory_table_lookup_stack_write_len: AllocatedMemoryTableLookupWriteCell<F>,
+}
+
+pub struct MemoryFillConfigBuilder {}
+
+impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for MemoryFillConfigBuilder {
+    fn configure(
+        common_config: &EventTableCommonConfig<F>,
+        allocator: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+    ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        let is_finished = allocator.alloc_bit_cell();
+        let len_inv = allocator.alloc_unlimited_cell();
+
+        let is_word = allocator.alloc_bit_cell();
+        let word_len_diff = allocator.alloc_u64_cell();
+
+        let byte = allocator.alloc_u8_cell();
+        let value_high = allocator.alloc_u32_cell();
+
+        let eid = common_config.eid_cell;
+        let sp = common_config.sp_cell;
+
+        let memory_table_lookup_stack_read_len = allocator
+            .alloc_memory_table_lookup_read_cell_with_value(
+                "op_memory_fill stack read len",
+                constraint_builder,
+                eid,
+                move |____| constant_from!(LocationType::Stack as u64),
+                move |meta| sp.expr(meta) + constant_from!(1),
+                move |____| constant_from!(1),
+                move |____| constant_from!(1),
+            );
+
+        let memory_table_lookup_stack_read_value = allocator
+            .alloc_memory_table_lookup_read_cell_with_value(
+                "op_memory_fill stack read value",
+                constraint_builder,
+                eid,
+                move |____| constant_from!(LocationType::Stack as u64),
+                move |meta| sp.expr(meta) + constant_from!(2),
+                move |____| constant_from!(1),
+                move |____| constant_from!(1),
+            );
+
+        let memory_table_lookup_stack_read_offset = allocator
+            .alloc_memory_table_lookup_read_cell_with_value(
+                "op_memory_fill stack read offset",
+                constraint_builder,
+                eid,
+                move |____| constant_from!(LocationType::Stack as u64),
+                move |meta| sp.expr(meta) + constant_from!(3),
+                move |____| constant_from!(1),
+                move |____| constant_from!(1),
+            );
+
+        let len = memory_table_lookup_stack_read_len.value_cell;
+        let value = memory_table_lookup_stack_read_value.value_cell;
+        let offset = memory_table_lookup_stack_read_offset.value_cell;
+
+        let memory_write = MemoryWriteCells::configure(
+            common_config,
+            allocator,
+            constraint_builder,
+            move |meta| offset.expr(meta),
+            move |meta| byte.expr(meta),
+            move |meta| byte.expr(meta) * constant_from!(0x0101010101010101u64),
+            move |meta| is_word.expr(meta),
+            move |meta| constant_from!(1) - is_finished.expr(meta),
+        );
+
+        let memory_table_lookup_stack_write_offset = allocator
+            .alloc_memory_table_lookup_write_cell(
+                "op_memory_fill stack write offset",
+                constraint_builder,
+                eid,
+                move |____| constant_from!(LocationType::Stack as u64),
+                move |meta| sp.expr(meta) + constant_from!(3),
+                move |____| constant_from!(1),
+                move |meta| {
+                    offset.expr(meta) + constant_from!(1) + is_word.expr(meta) * constant_from!(7)
+                },
+                move |meta| constant_from!(1) - is_finished.expr(meta),
+            );
+
+        let memory_table_lookup_stack_write_len = allocator.alloc_memory_table_lookup_write_cell(
+            "op_memory_fill stack write len",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(1),
+            move |____| constant_from!(1),
+            move |meta| len.expr(meta) - constant_from!(1) - is_word.expr(meta) * constant_from!(7),
+            move |meta| constant_from!(1) - is_finished.expr(meta),
+        );
+
+        constraint_builder.push(
+            "op_memory_fill is_finished",
+            Box::new(move |meta| {
+                vec![
+                    len.expr(meta) * len_inv.expr(meta) + is_finished.expr(meta)
+                        - constant_from!(1),
+                    len.expr(meta) * is_finished.expr(meta),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_memory_fill word",
+            Box::new(move |meta| {
+                vec![
+                    (len.expr(meta) - constant_from!(8) - word_len_diff.expr(meta))
+                        * is_word.expr(meta),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_memory_fill value",
+            Box::new(move |meta| {
+                vec![
+                    value_high.expr(meta) * constant_from!(1 << 8) + byte.expr(meta)
+                        - value.expr(meta),
+                ]
+            }),
+        );
+
+        Box::new(MemoryFillConfig {
+            is_finished,
+            len_inv,
+            is_word,
+            word_len_diff,
+            byte,
+            value_high,
+            memory_write,
+            memory_table_lookup_stack_read_len,
+            memory_table_lookup_stack_read_value,
+            memory_table_lookup_stack_read_offset,
+            memory_table_lookup_stack_write_offset,
+            memory_table_lookup_stack_write_len,
+        })
+    }
+}
+
+impl<F: FieldExt> EventTableOpcodeConfig<F> for MemoryFillConfig<F> {
+    fn opcode(&self, _meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        constant!(bn_to_field(
+            &(BigUint::from(OpcodeClass::MemoryFill as u64) << OPCODE_CLASS_SHIFT)
+        ))
+    }
+
+    fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step: &mut StepStatus<F>,
+        entry: &EventTableEntryWithMemoryInfo,
+    ) -> Result<(), Error> {
+        match entry.eentry.step_info {
+            StepInfo::MemoryFill {
+                offset,
+                value,
+                len,
+                is_word,
+                pre_block_value,
+                updated_block_value,
+            } => {
+                let width = if is_word { 8 } else { 1 };
+
+                self.is_finished.assign_bool(ctx, len == 0)?;
+                if len != 0 {
+                    self.len_inv
+                        .assign(ctx, step.field_helper.invert(len as u64))?;
+                }
+
+                self.is_word.assign_bool(ctx, is_word)?;
+                if is_word {
+                    self.word_len_diff.assign(ctx, (len - 8) as u64)?;
+                }
+
+                self.byte.assign(ctx, (value & 0xff).into())?;
+                self.value_high.assign(ctx, value >> 8)?;
+
+                self.memory_table_lookup_stack_read_len.assign(
+                    ctx,
+                    entry.memory_rw_entires[0].start_eid,
+                    step.current.eid,
+                    entry.memory_rw_entires[0].end_eid,
+                    step.current.sp + 1,
+                    LocationType::Stack,
+                    true,
+                    len as u64,
+                )?;
+
+                self.memory_table_lookup_stack_read_value.assign(
+                    ctx,
+                    entry.memory_rw_entires[1].start_eid,
+                    step.current.eid,
+                    entry.memory_rw_entires[1].end_eid,
+                    step.current.sp + 2,
+                    LocationType::Stack,
+                    true,
+                    value as u64,
+                )?;
+
+                self.memory_table_lookup_stack_read_offset.assign(
+                    ctx,
+                    entry.memory_rw_entires[2].start_eid,
+                    step.current.eid,
+                    entry.memory_rw_entires[2].end_eid,
+                    step.current.sp + 3,
+                    LocationType::Stack,
+                    true,
+                    offset as u64,
+                )?;
+
+                if len != 0 {
+                    self.memory_write.assign(
+                        ctx,
+                        step,
+                        offset,
+                        pre_block_value,
+                        updated_block_value,
+                        (&entry.memory_rw_entires[3], &entry.memory_rw_entires[4]),
+                    )?;
+
+                    self.memory_table_lookup_stack_write_offset.assign(
+                        ctx,
+                        step.current.eid,
+                        entry.memory_rw_entires[5].end_eid,
+                        step.current.sp + 3,
+                        LocationType::Stack,
+                        true,
+                        (offset + width) as u64,
+                    )?;
+
+                    self.memory_table_lookup_stack_write_len.assign(
+                        ctx,
+                        step.current.eid,
+                        entry.memory_rw_entires[6].end_eid,
+                        step.current.sp + 1,
+                        LocationType::Stack,
+                        true,
+                        (len - width) as u64,
+                    )?;
+                }
+
+                Ok(())
+            }
+
+            _ => unreachable!(),
+        }
+    }
+
+    fn sp_diff(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(3) * self.is_finished.expr(meta))
+    }
+
+    fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(3) * (constant_from!(1) - self.is_finished.expr(meta)))
+    }
+
+    fn memory_writing_ops(&self, entry: &EventTableEntry) -> u32 {
+        match entry.step_info {
+            StepInfo::MemoryFill { len: 0, .. } => 0,
+            StepInfo::MemoryFill { .. } => 3,
+            _ => unreachable!(),
+        }
+    }
+
+    fn next_iid(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        Some(common_config.iid_cell.expr(meta) + self.is_finished.expr(meta))
+    }
+}
@@ -24,20 +24,16 @@ use specs::itable::OpcodeClass;
 use specs::itable::OPCODE_ARG0_SHIFT;
 use specs::itable::OPCODE_ARG1_SHIFT;
 use specs::itable::OPCODE_CLASS_SHIFT;
-use specs::mtable::LocationType;
-use specs::mtable::VarType;
 use specs::step::StepInfo;
 
+use super::keep_values::KeepValueCells;
+
 pub struct ReturnConfig<F: FieldExt> {
-    keep: AllocatedBitCell<F>,
     drop: AllocatedCommonRangeCell<F>,
-    is_i32: AllocatedBitCell<F>,
-    value: AllocatedU64Cell<F>,
+    keep_values: KeepValueCells<F>,
     // always assign to one to support sliced frame table lookup
     is_returned_cell: AllocatedBitCell<F>,
     frame_table_lookup: AllocatedUnlimitedCell<F>,
-    memory_table_lookup_stack_read: AllocatedMemoryTableLookupReadCell<F>,
-    memory_table_lookup_stack_write: AllocatedMemoryTableLookupWriteCell<F>,
 }
 
 pub struct ReturnConfigBuilder;
@@ -48,39 +44,22 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for ReturnConfigBuilder {
         allocator: &mut EventTableCellAllocator<F>,
         constraint_builder: &mut ConstraintBuilder<F>,
     ) -> Box<dyn EventTableOpcodeConfig<F>> {
-        let keep = allocator.alloc_bit_cell();
         let drop = allocator.alloc_common_range_cell();
-        let is_i32 = allocator.alloc_bit_cell();
-        let value = allocator.alloc_u64_cell();
 
         let frame_table_lookup = common_config.jtable_lookup_cell;
 
         let fid_cell = common_config.fid_cell;
         let iid_cell = common_config.iid_cell;
         let frame_id_cell = common_config.frame_id_cell;
-        let eid = common_config.eid_cell;
-        let sp = common_config.sp_cell;
         let is_returned_cell = common_config.is_returned_cell;
 
-        let memory_table_lookup_stack_read = allocator.alloc_memory_table_lookup_read_cell(
-            "op_return stack read",
-            constraint_builder,
-            eid,
-            move |____| constant_from!(LocationType::Stack as u64),
-            move |meta| sp.expr(meta) + constant_from!(1),
-            move |meta| is_i32.expr(meta),
-            move |meta| value.u64_cell.expr(meta),
-            move |meta| keep.expr(meta),
-        );
-        let memory_table_lookup_stack_write = allocator.alloc_memory_table_lookup_write_cell(
-            "op_return stack write",
+        let keep_values = KeepValueCells::configure(
+            common_config,
+            allocator,
             constraint_builder,
-            eid,
-            move |____| constant_from!(LocationType::Stack as u64),
-            move |meta| sp.expr(meta) + drop.expr(meta) + constant_from!(1),
-            move |meta| is_i32.expr(meta),
-            move |meta| value.u64_cell.expr(meta),
-            move |meta| keep.expr(meta),
+            1,
+            move |meta| drop.expr(meta),
+            move |____| constant_from!(1),
         );
 
         constraint_builder.constraints.push((
@@ -105,14 +84,10 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for ReturnConfigBuilder {
         ));
 
         Box::new(ReturnConfig {
-            keep,
             drop,
-            is_i32,
-            value,
+            keep_values,
             frame_table_lookup,
             is_returned_cell,
-            memory_table_lookup_stack_read,
-            memory_table_lookup_stack_write,
         })
     }
 }
@@ -123,9 +98,9 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ReturnConfig<F> {
             &(BigUint::from(OpcodeClass::Return as u64) << OPCODE_CLASS_SHIFT)
         )) + self.drop.expr(meta)
             * constant!(bn_to_field(&(BigUint::from(1u64) << OPCODE_ARG0_SHIFT)))
-            + self.keep.expr(meta)
+            + self.keep_values.count(meta)
                 * constant!(bn_to_field(&(BigUint::from(1u64) << OPCODE_ARG1_SHIFT)))
-            + self.is_i32.expr(meta)
+            + self.keep_values.is_i32_encode(meta)
     }
 
     fn assign(
@@ -141,40 +116,15 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ReturnConfig<F> {
                 keep_values,
                 ..
             } => {
-                assert!(keep.len() <= 1);
-                assert_eq!(keep.len(), keep_values.len());
-
                 self.drop.assign(ctx, F::from(*drop as u64))?;
-
-                if keep_values.is_empty() {
-                    self.keep.assign(ctx, 0.into())?;
-                } else {
-                    self.keep.assign(ctx, 1.into())?;
-                    self.is_i32
-                        .assign(ctx, (VarType::from(keep[0]) as u64).into())?;
-                    self.value.assign(ctx, keep_values[0])?;
-
-                    self.memory_table_lookup_stack_read.assign(
-                        ctx,
-                        entry.memory_rw_entires[0].start_eid,
-                        step.current.eid,
-                        entry.memory_rw_entires[0].end_eid,
-                        step.current.sp + 1,
-                        LocationType::Stack,
-                        VarType::from(keep[0]) == VarType::I32,
-                        keep_values[0],
-                    )?;
-
-                    self.memory_table_lookup_stack_write.assign(
-                        ctx,
-                        step.current.eid,
-                        entry.memory_rw_entires[1].end_eid,
-                        step.current.sp + drop + 1,
-                        LocationType::Stack,
-                        VarType::from(keep[0]) == VarType::I32,
-                        keep_values[0],
-                    )?;
-                }
+                self.keep_values.assign(
+                    ctx,
+                    step,
+                    *drop,
+                    keep,
+                    keep_values,
+                    Some(&entry.memory_rw_entires),
+                )?;
 
                 self.frame_table_lookup.cell.assign_bn(
                     ctx,
@@ -200,19 +150,12 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ReturnConfig<F> {
     }
 
     fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
-        Some(self.keep.expr(meta))
+        Some(self.keep_values.count(meta))
     }
 
     fn memory_writing_ops(&self, entry: &EventTableEntry) -> u32 {
         match &entry.step_info {
-            StepInfo::Return { keep, .. } => {
-                if !keep.is_empty() {
-                    assert!(keep.len() == 1);
-                    1
-                } else {
-                    0
-                }
-            }
+            StepInfo::Return { keep, .. } => keep.len() as u32,
             _ => unreachable!(),
         }
     }
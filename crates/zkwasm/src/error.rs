@@ -1,10 +1,74 @@
+use std::time::Duration;
+
 use thiserror::Error;
 
 #[derive(Debug, Error)]
-pub enum CompilationError {}
+pub enum CompilationError {
+    #[error("failed to instantiate wasm module: {0}")]
+    Instantiation(String),
+}
 
-#[derive(Debug, Error)]
-pub enum ExecutionError {}
+/// Failure reported by a host function, it aborts the execution as a trap.
+#[derive(Debug, Clone, Error)]
+pub enum HostCallError {
+    #[error("require is not satisfied, which is a false assertion in the wasm code. Please check the logic of your image or input.")]
+    RequireNotSatisfied,
+    #[error("failed to read public input, please checkout your input")]
+    PublicInputsExhausted,
+    #[error("failed to read private input, please checkout your input")]
+    PrivateInputsExhausted,
+    #[error("failed to pop value from context_in array, please check you inputs")]
+    ContextInputsExhausted,
+    #[error("wasm_input expects 0 (private) or 1 (public) but {0} provided")]
+    InvalidWasmInputArgument(i32),
+    #[error("host function is called out of order: {0}")]
+    InvalidCallSequence(String),
+    #[error("host function is called with an invalid argument: {0}")]
+    InvalidArgument(String),
+    #[error("external host call of op {0} exceeds the recorded external host call table")]
+    ReplayExhausted(usize),
+    #[error("external host call of op {op} diverges from the recorded call of op {recorded_op} with value {recorded_value} at position {position}")]
+    ReplayDiverged {
+        position: usize,
+        op: usize,
+        recorded_op: usize,
+        recorded_value: u64,
+    },
+}
+
+#[derive(Debug, Clone, Error)]
+pub enum ExecutionError {
+    #[error("host function {function} failed at eid {eid} (fid {fid}, iid {iid}): {error}")]
+    HostCall {
+        /// Index of the calling instruction in the execution trace, starting from 1.
+        eid: u32,
+        fid: u32,
+        iid: u32,
+        function: String,
+        #[source]
+        error: HostCallError,
+    },
+    #[error("Instructions exceed the limit({limit}), execution is terminated at eid {eid}.")]
+    InstructionLimitExceeded { limit: usize, eid: u32 },
+    #[error("Committed slices({slices}) exceed the limit({limit}), execution is terminated at eid {eid}.")]
+    SliceLimitExceeded {
+        limit: usize,
+        slices: usize,
+        eid: u32,
+    },
+    #[error("Execution replays {replayed} of {recorded} recorded external host calls, the rest are not called.")]
+    ReplayIncomplete { replayed: usize, recorded: usize },
+    #[error("Bulk memory access is out of bounds, execution is terminated at eid {eid}.")]
+    MemoryAccessOutOfBounds { eid: u32 },
+    #[error("Execution takes {elapsed:?} which exceeds the limit({limit:?}), execution is terminated at eid {eid}.")]
+    TimeLimitExceeded {
+        limit: Duration,
+        elapsed: Duration,
+        eid: u32,
+    },
+}
+
+impl wasmi::HostError for ExecutionError {}
 
 #[derive(Debug, Error)]
 pub enum BuildingCircuitError {
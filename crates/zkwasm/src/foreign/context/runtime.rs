@@ -5,6 +5,7 @@ use specs::host_function::Signature;
 use specs::types::ValueType;
 use wasmi::RuntimeArgs;
 
+use crate::error::HostCallError;
 use crate::runtime::host::host_env::HostEnv;
 use crate::runtime::host::ForeignContext;
 
@@ -30,10 +31,10 @@ impl Context {
         self.outputs.push(value)
     }
 
-    pub fn read_context(&mut self) -> u64 {
+    pub fn read_context(&mut self) -> Result<u64, HostCallError> {
         self.inputs
             .pop()
-            .expect("Failed to pop value from context_in array, please check you inputs")
+            .ok_or(HostCallError::ContextInputsExhausted)
     }
 }
 
@@ -62,7 +63,9 @@ pub fn register_context_foreign(env: &mut HostEnv, context_input: Vec<u64>) {
             |_obs, context: &mut dyn ForeignContext, _args: RuntimeArgs| {
                 let context = context.downcast_mut::<Context>().unwrap();
 
-                Some(wasmi::RuntimeValue::I64(context.read_context() as i64))
+                Ok(Some(wasmi::RuntimeValue::I64(
+                    context.read_context()? as i64
+                )))
             },
         ),
     );
@@ -82,7 +85,7 @@ pub fn register_context_foreign(env: &mut HostEnv, context_input: Vec<u64>) {
                 let value: i64 = args.nth(0);
                 context.write_context(value as u64);
 
-                None
+                Ok(None)
             },
         ),
     );
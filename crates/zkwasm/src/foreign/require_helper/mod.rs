@@ -3,6 +3,7 @@ use std::rc::Rc;
 use specs::host_function::HostPlugin;
 use specs::types::ValueType;
 
+use crate::error::HostCallError;
 use crate::runtime::host::host_env::HostEnv;
 use crate::runtime::host::ForeignContext;
 use crate::runtime::monitor::observer::Observer;
@@ -18,14 +19,10 @@ pub fn register_require_foreign(env: &mut HostEnv) {
             let cond: u32 = args.nth(0);
 
             if cond == 0 {
-                panic!(
-                    "require is not satisfied, which is a \
-                false assertion in the wasm code. Please check \
-                the logic of your image or input."
-                )
+                return Err(HostCallError::RequireNotSatisfied);
             }
 
-            None
+            Ok(None)
         },
     );
 
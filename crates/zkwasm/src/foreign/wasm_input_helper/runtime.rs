@@ -4,6 +4,7 @@ use std::rc::Rc;
 use specs::host_function::HostPlugin;
 use specs::types::ValueType;
 
+use crate::error::HostCallError;
 use crate::runtime::host::host_env::HostEnv;
 use crate::runtime::host::ForeignContext;
 use crate::runtime::host::ForeignStatics;
@@ -28,18 +29,17 @@ impl Context {
         }
     }
 
-    pub fn pop_public(&mut self) -> u64 {
+    pub fn pop_public(&mut self) -> Result<u64, HostCallError> {
         if self.public_inputs.is_empty() {
-            panic!("failed to read public input, please checkout your input");
+            return Err(HostCallError::PublicInputsExhausted);
         }
-        self.public_inputs.remove(0)
+        Ok(self.public_inputs.remove(0))
     }
 
-    pub fn pop_private(&mut self) -> u64 {
-        if self.private_inputs.is_empty() {
-            panic!("failed to read private input, please checkout your input");
-        }
-        self.private_inputs.pop_front().unwrap()
+    pub fn pop_private(&mut self) -> Result<u64, HostCallError> {
+        self.private_inputs
+            .pop_front()
+            .ok_or(HostCallError::PrivateInputsExhausted)
     }
 
     fn push_public(&mut self, value: u64) {
@@ -51,15 +51,15 @@ impl Context {
         self.output.push(value);
     }
 
-    pub fn wasm_input(&mut self, arg: i32) -> u64 {
-        assert!(arg == 0 || arg == 1);
-
-        if arg == 1 {
-            let value = self.pop_public();
-            self.push_public(value);
-            value
-        } else {
-            self.pop_private()
+    pub fn wasm_input(&mut self, arg: i32) -> Result<u64, HostCallError> {
+        match arg {
+            1 => {
+                let value = self.pop_public()?;
+                self.push_public(value);
+                Ok(value)
+            }
+            0 => self.pop_private(),
+            _ => Err(HostCallError::InvalidWasmInputArgument(arg)),
         }
     }
 
@@ -91,9 +91,9 @@ pub fn register_wasm_input_foreign(
         |_observer: &Observer, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
             let context = context.downcast_mut::<Context>().unwrap();
             let arg: i32 = args.nth(0);
-            let input = context.wasm_input(arg);
+            let input = context.wasm_input(arg)?;
 
-            Some(wasmi::RuntimeValue::I64(input as i64))
+            Ok(Some(wasmi::RuntimeValue::I64(input as i64)))
         },
     );
 
@@ -104,7 +104,7 @@ pub fn register_wasm_input_foreign(
             let value: i64 = args.nth(0);
             context.wasm_output(value as u64);
 
-            None
+            Ok(None)
         },
     );
 
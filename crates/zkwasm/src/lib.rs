@@ -12,7 +12,7 @@ pub mod foreign;
 pub mod loader;
 pub mod runtime;
 
-mod profile;
+pub mod profile;
 
 #[cfg(test)]
 pub mod test;
@@ -4,7 +4,69 @@ use std::fmt::Display;
 pub enum PreCheckErr {
     ZkmainNotExists,
     ZkmainIsNotFunction,
-    // ZkmainTypeNotMatch,
+    ZkmainTypeNotMatch,
+    /// Initial pages, maximal pages and K.
+    InitialPagesExceedLimit(u32, u32, u32),
+    /// Declared maximum pages, maximal pages and K.
+    MaximalPagesExceedLimit(u32, u32, u32),
+    InvalidPhantomFunctionPattern(String),
+    PhantomFunctionNotExists(String),
+    /// Index of the data segment.
+    PassiveDataSegment(u32),
+    UnsupportedInstruction {
+        function: String,
+        instruction: String,
+    },
+    /// Type index, number of results and the supported limit.
+    TooManyResults(u32, usize, usize),
+}
+
+impl Display for PreCheckErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PreCheckErr::ZkmainNotExists => write!(f, "zkmain is not exported."),
+            PreCheckErr::ZkmainIsNotFunction => write!(f, "zkmain is not a function."),
+            PreCheckErr::ZkmainTypeNotMatch => {
+                write!(f, "zkmain should take no parameters and return no results.")
+            }
+            PreCheckErr::InitialPagesExceedLimit(pages, limit, k) => write!(
+                f,
+                "Initial pages({}) exceed the limit({}). Current K is {}, consider increasing the circuit size K.",
+                pages, limit, k
+            ),
+            PreCheckErr::MaximalPagesExceedLimit(pages, limit, k) => write!(
+                f,
+                "Maximal pages({}) exceed the limit({}). Current K is {}, consider increasing the circuit size K.",
+                pages, limit, k
+            ),
+            PreCheckErr::InvalidPhantomFunctionPattern(pattern) => {
+                write!(f, "Phantom function pattern {} is not a valid regex.", pattern)
+            }
+            PreCheckErr::PhantomFunctionNotExists(pattern) => write!(
+                f,
+                "Phantom function pattern {} does not match any exported function.",
+                pattern
+            ),
+            PreCheckErr::PassiveDataSegment(index) => write!(
+                f,
+                "Data segment {} is passive, passive data segments are not supported.",
+                index
+            ),
+            PreCheckErr::UnsupportedInstruction {
+                function,
+                instruction,
+            } => write!(
+                f,
+                "Instruction {} in function {} is not supported.",
+                instruction, function
+            ),
+            PreCheckErr::TooManyResults(type_index, results, limit) => write!(
+                f,
+                "Type {} has {} results, at most {} results are supported.",
+                type_index, results, limit
+            ),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -18,6 +80,8 @@ pub enum Error {
 
 impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+        match self {
+            Error::PreCheck(err) => write!(f, "Precheck failed: {}", err),
+        }
     }
 }
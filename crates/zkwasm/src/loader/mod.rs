@@ -2,6 +2,18 @@ use anyhow::Result;
 use halo2_proofs::arithmetic::CurveAffine;
 use halo2_proofs::poly::commitment::Params;
 use log::warn;
+use parity_wasm::elements::BulkInstruction;
+use parity_wasm::elements::External;
+use parity_wasm::elements::FunctionType;
+use parity_wasm::elements::ImportCountType;
+use parity_wasm::elements::Instruction;
+use parity_wasm::elements::Internal;
+use parity_wasm::elements::MemoryType;
+use parity_wasm::elements::Module as ParityModule;
+use parity_wasm::elements::Type;
+use regex::Regex;
+
+use specs::itable::MAX_KEEP_VALUES;
 
 use specs::CompilationTable;
 
@@ -10,6 +22,7 @@ use wasmi::NotStartedModuleRef;
 use wasmi::RuntimeValue;
 
 use crate::checksum::ImageCheckSum;
+use crate::circuits::image_table::compute_maximal_pages;
 
 use crate::loader::err::Error;
 use crate::loader::err::PreCheckErr;
@@ -24,7 +37,7 @@ use anyhow::anyhow;
 
 pub use wasmi::Module;
 
-mod err;
+pub mod err;
 pub mod slice;
 
 const ENTRY: &str = "zkmain";
@@ -40,17 +53,71 @@ pub struct ZkWasmLoader {
 }
 
 impl ZkWasmLoader {
-    pub fn parse_module(image: &Vec<u8>) -> Result<Module> {
-        fn precheck(_module: &Module) -> Result<()> {
-            #[allow(dead_code)]
-            fn check_zkmain_exists(module: &Module) -> Result<()> {
-                use parity_wasm::elements::Internal;
+    /// Parse the wasm image and reject it early if it cannot be proved with
+    /// circuit size `k` and the given phantom function patterns.
+    pub fn parse_module(image: &Vec<u8>, k: u32, phantom_functions: &[String]) -> Result<Module> {
+        Self::parse_module_with_entry(image, k, phantom_functions, ENTRY)
+    }
 
-                let export = module.module().export_section().unwrap();
+    /// Same as `parse_module` but checks `entry` instead of `zkmain`, tests export
+    /// their own entry functions.
+    pub(crate) fn parse_module_with_entry(
+        image: &Vec<u8>,
+        k: u32,
+        phantom_functions: &[String],
+        entry: &str,
+    ) -> Result<Module> {
+        fn precheck(
+            module: &Module,
+            k: u32,
+            phantom_functions: &[String],
+            entry: &str,
+        ) -> Result<()> {
+            fn function_type(module: &ParityModule, fid: u32) -> Option<&FunctionType> {
+                let imported_functions = module.import_count(ImportCountType::Function) as u32;
+
+                let type_ref = if fid < imported_functions {
+                    module
+                        .import_section()?
+                        .entries()
+                        .iter()
+                        .filter_map(|entry| match entry.external() {
+                            External::Function(type_ref) => Some(*type_ref),
+                            _ => None,
+                        })
+                        .nth(fid as usize)?
+                } else {
+                    module
+                        .function_section()?
+                        .entries()
+                        .get((fid - imported_functions) as usize)?
+                        .type_ref()
+                };
+
+                match module.type_section()?.types().get(type_ref as usize)? {
+                    Type::Function(function_type) => Some(function_type),
+                }
+            }
 
-                if let Some(entry) = export.entries().iter().find(|entry| entry.field() == ENTRY) {
+            fn check_zkmain(module: &ParityModule, entry: &str) -> Result<()> {
+                let entry = module.export_section().and_then(|export| {
+                    export
+                        .entries()
+                        .iter()
+                        .find(|export| export.field() == entry)
+                });
+
+                if let Some(entry) = entry {
                     match entry.internal() {
-                        Internal::Function(_fid) => Ok(()),
+                        Internal::Function(fid) => match function_type(module, *fid) {
+                            Some(function_type)
+                                if function_type.params().is_empty()
+                                    && function_type.results().is_empty() =>
+                            {
+                                Ok(())
+                            }
+                            _ => Err(anyhow!(Error::PreCheck(PreCheckErr::ZkmainTypeNotMatch))),
+                        },
                         _ => Err(anyhow!(Error::PreCheck(PreCheckErr::ZkmainIsNotFunction))),
                     }
                 } else {
@@ -58,13 +125,211 @@ impl ZkWasmLoader {
                 }
             }
 
-            #[cfg(not(test))]
-            check_zkmain_exists(_module)?;
-            // TODO: check the signature of zkmain function.
-            // TODO: check the relation between maximal pages and K.
-            // TODO: check the instructions of phantom functions.
-            // TODO: check phantom functions exists.
-            // TODO: check if instructions are supported.
+            fn check_pages(module: &ParityModule, k: u32) -> Result<()> {
+                let imported_memory = module.import_section().and_then(|section| {
+                    section
+                        .entries()
+                        .iter()
+                        .find_map(|entry| match entry.external() {
+                            External::Memory(memory) => Some(memory.limits()),
+                            _ => None,
+                        })
+                });
+                let limits = imported_memory.or_else(|| {
+                    module
+                        .memory_section()
+                        .and_then(|section| section.entries().first())
+                        .map(|memory| memory.limits())
+                });
+
+                if let Some(limits) = limits {
+                    let maximal_pages = compute_maximal_pages(k);
+
+                    if limits.initial() > maximal_pages {
+                        return Err(anyhow!(Error::PreCheck(
+                            PreCheckErr::InitialPagesExceedLimit(
+                                limits.initial(),
+                                maximal_pages,
+                                k
+                            )
+                        )));
+                    }
+
+                    if let Some(maximum) = limits.maximum() {
+                        if maximum > maximal_pages {
+                            return Err(anyhow!(Error::PreCheck(
+                                PreCheckErr::MaximalPagesExceedLimit(maximum, maximal_pages, k)
+                            )));
+                        }
+                    }
+                }
+
+                Ok(())
+            }
+
+            fn check_phantom_functions_exist(
+                module: &ParityModule,
+                phantom_functions: &[String],
+            ) -> Result<()> {
+                let exported_functions = module
+                    .export_section()
+                    .map(|export| {
+                        export
+                            .entries()
+                            .iter()
+                            .filter(|entry| matches!(entry.internal(), Internal::Function(_)))
+                            .map(|entry| entry.field())
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+
+                for pattern in phantom_functions {
+                    let regex = Regex::new(pattern).map_err(|_| {
+                        anyhow!(Error::PreCheck(PreCheckErr::InvalidPhantomFunctionPattern(
+                            pattern.clone()
+                        )))
+                    })?;
+
+                    if !exported_functions.iter().any(|name| regex.is_match(name)) {
+                        return Err(anyhow!(Error::PreCheck(
+                            PreCheckErr::PhantomFunctionNotExists(pattern.clone())
+                        )));
+                    }
+                }
+
+                Ok(())
+            }
+
+            /// Rejects passive data segments, their bytes are not part of the image
+            /// so `memory.init` cannot be proved.
+            fn check_data_segments(module: &ParityModule) -> Result<()> {
+                let segments = module
+                    .data_section()
+                    .map(|data| data.entries())
+                    .unwrap_or_default();
+
+                for (index, segment) in segments.iter().enumerate() {
+                    if segment.offset().is_none() {
+                        return Err(anyhow!(Error::PreCheck(PreCheckErr::PassiveDataSegment(
+                            index as u32
+                        ))));
+                    }
+                }
+
+                Ok(())
+            }
+
+            /// Rejects instructions without an etable config in every function body,
+            /// phantom functions included.
+            fn check_instructions(module: &ParityModule) -> Result<()> {
+                let imported_functions = module.import_count(ImportCountType::Function) as u32;
+                let names = module.names_section().and_then(|names| names.functions());
+
+                let bodies = module
+                    .code_section()
+                    .map(|code| code.bodies())
+                    .unwrap_or_default();
+
+                for (index, body) in bodies.iter().enumerate() {
+                    // memory.init and data.drop are only useful with passive data segments,
+                    // table instructions are not supported by the interpreter.
+                    // Floats are limited to the exact operations of `FloatBinOp`,
+                    // `FloatUnaryOp`, `FloatRelOp` and `FloatConversionOp`, the rounding
+                    // arithmetic and the numeric conversions have no circuit.
+                    let unsupported = body.code().elements().iter().find(|instruction| {
+                        matches!(
+                            instruction,
+                            Instruction::Bulk(
+                                BulkInstruction::MemoryInit(_)
+                                    | BulkInstruction::MemoryDrop(_)
+                                    | BulkInstruction::TableInit(_)
+                                    | BulkInstruction::TableDrop(_)
+                                    | BulkInstruction::TableCopy
+                            ) | Instruction::F32Add
+                                | Instruction::F32Sub
+                                | Instruction::F32Mul
+                                | Instruction::F32Div
+                                | Instruction::F64Add
+                                | Instruction::F64Sub
+                                | Instruction::F64Mul
+                                | Instruction::F64Div
+                                | Instruction::F32Ceil
+                                | Instruction::F32Floor
+                                | Instruction::F32Trunc
+                                | Instruction::F32Nearest
+                                | Instruction::F32Sqrt
+                                | Instruction::F64Ceil
+                                | Instruction::F64Floor
+                                | Instruction::F64Trunc
+                                | Instruction::F64Nearest
+                                | Instruction::F64Sqrt
+                                | Instruction::I32TruncSF32
+                                | Instruction::I32TruncUF32
+                                | Instruction::I32TruncSF64
+                                | Instruction::I32TruncUF64
+                                | Instruction::I64TruncSF32
+                                | Instruction::I64TruncUF32
+                                | Instruction::I64TruncSF64
+                                | Instruction::I64TruncUF64
+                                | Instruction::F32ConvertSI32
+                                | Instruction::F32ConvertUI32
+                                | Instruction::F32ConvertSI64
+                                | Instruction::F32ConvertUI64
+                                | Instruction::F32DemoteF64
+                                | Instruction::F64ConvertSI32
+                                | Instruction::F64ConvertUI32
+                                | Instruction::F64ConvertSI64
+                                | Instruction::F64ConvertUI64
+                                | Instruction::F64PromoteF32
+                        )
+                    });
+
+                    if let Some(instruction) = unsupported {
+                        let fid = imported_functions + index as u32;
+                        let function = names
+                            .and_then(|names| names.names().get(fid))
+                            .cloned()
+                            .unwrap_or_else(|| format!("#{}", fid));
+
+                        return Err(anyhow!(Error::PreCheck(
+                            PreCheckErr::UnsupportedInstruction {
+                                function,
+                                instruction: format!("{:?}", instruction),
+                            }
+                        )));
+                    }
+                }
+
+                Ok(())
+            }
+
+            fn check_results(module: &ParityModule) -> Result<()> {
+                let types = module
+                    .type_section()
+                    .map(|section| section.types())
+                    .unwrap_or_default();
+
+                for (index, Type::Function(function_type)) in types.iter().enumerate() {
+                    if function_type.results().len() > MAX_KEEP_VALUES {
+                        return Err(anyhow!(Error::PreCheck(PreCheckErr::TooManyResults(
+                            index as u32,
+                            function_type.results().len(),
+                            MAX_KEEP_VALUES
+                        ))));
+                    }
+                }
+
+                Ok(())
+            }
+
+            let module = module.module();
+
+            check_pages(module, k)?;
+            check_phantom_functions_exist(module, phantom_functions)?;
+            check_data_segments(module)?;
+            check_instructions(module)?;
+            check_results(module)?;
+            check_zkmain(module, entry)?;
 
             Ok(())
         }
@@ -76,7 +341,20 @@ impl ZkWasmLoader {
             warn!("Failed to parse name section of the wasm binary.");
         }
 
-        precheck(&module)?;
+        precheck(&module, k, phantom_functions, entry)?;
+
+        // A memory without a declared maximum could grow beyond the pages the
+        // circuit supports, cap it so that such a `memory.grow` fails with -1.
+        if let Some(memory) = module
+            .module
+            .memory_section_mut()
+            .and_then(|section| section.entries_mut().first_mut())
+        {
+            if memory.limits().maximum().is_none() {
+                *memory =
+                    MemoryType::new(memory.limits().initial(), Some(compute_maximal_pages(k)));
+            }
+        }
 
         Ok(module)
     }
@@ -1,17 +1,227 @@
-//use instruction_statistic::InstructionStatistic;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io;
+use std::io::Write;
 
+use parity_wasm::elements::Module;
+use specs::host_function::HostFunctionDesc;
+use specs::itable::InstructionTable;
+use specs::itable::OpcodeClass;
+use specs::mtable::AccessType;
+use specs::slice_backend::Slice;
 use specs::slice_backend::SliceBackend;
+use specs::step::StepInfo;
 use specs::Tables;
 
+use crate::runtime::memory_event_of_step;
+
 mod helper;
-// mod instruction_statistic;
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Statistic {
+    pub steps: usize,
+    pub memory_reads: usize,
+    pub memory_writes: usize,
+}
+
+impl Statistic {
+    pub fn memory_entries(&self) -> usize {
+        self.memory_reads + self.memory_writes
+    }
+}
+
+/// Execution profile collected from the event tables.
+#[derive(Debug, Default)]
+pub struct Profile {
+    function_names: HashMap<u32, String>,
+    host_function_names: HashMap<usize, String>,
+
+    pub etable_entries: usize,
+    pub mtable_entries: usize,
+    pub opcodes: BTreeMap<OpcodeClass, Statistic>,
+    /// Steps executed in the function itself, excluding its callees.
+    pub functions: BTreeMap<u32, Statistic>,
+    pub host_calls: BTreeMap<String, usize>,
+
+    /// Number of steps executed with each call stack, the outermost function first.
+    folded_stacks: HashMap<Vec<u32>, usize>,
+    call_stack: Vec<u32>,
+}
+
+impl Profile {
+    /// `host_functions` is the function description table of the host env, it
+    /// names the external host calls.
+    pub fn new(module: &Module, host_functions: &HashMap<usize, HostFunctionDesc>) -> Self {
+        Profile {
+            function_names: function_names(module),
+            host_function_names: host_functions
+                .iter()
+                .map(|(index, desc)| (*index, desc.name().clone()))
+                .collect(),
+            ..Profile::default()
+        }
+    }
+
+    /// Slices must be profiled in order, the call stack continues across slices.
+    pub fn profile_slice(&mut self, itable: &InstructionTable, slice: &Slice) {
+        for entry in slice.etable.entries() {
+            if self.call_stack.is_empty() {
+                self.call_stack.push(entry.fid);
+            }
+
+            let mentries = memory_event_of_step(entry);
+            let reads = mentries
+                .iter()
+                .filter(|entry| entry.atype == AccessType::Read)
+                .count();
+            let writes = mentries.len() - reads;
+
+            let opcode: OpcodeClass = (&entry.get_instruction(itable).opcode).into();
+            for statistic in [
+                self.opcodes.entry(opcode).or_default(),
+                self.functions.entry(entry.fid).or_default(),
+            ] {
+                statistic.steps += 1;
+                statistic.memory_reads += reads;
+                statistic.memory_writes += writes;
+            }
+
+            self.etable_entries += 1;
+            self.mtable_entries += mentries.len();
+            *self
+                .folded_stacks
+                .entry(self.call_stack.clone())
+                .or_default() += 1;
+
+            match &entry.step_info {
+                StepInfo::Call { index } => self.call_stack.push(*index),
+                StepInfo::CallIndirect { func_index, .. } => self.call_stack.push(*func_index),
+                StepInfo::Return { .. } => {
+                    self.call_stack.pop();
+                }
+                StepInfo::CallHost { function_name, .. } => {
+                    *self.host_calls.entry(function_name.clone()).or_default() += 1
+                }
+                StepInfo::ExternalHostCall { op, .. } => {
+                    let name = self
+                        .host_function_names
+                        .get(op)
+                        .cloned()
+                        .unwrap_or_else(|| format!("op {}", op));
+
+                    *self.host_calls.entry(name).or_default() += 1
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn function_name(&self, fid: u32) -> String {
+        self.function_names
+            .get(&fid)
+            .cloned()
+            .unwrap_or_else(|| format!("func[{}]", fid))
+    }
+
+    /// Writes the call stacks in the folded format consumed by `flamegraph.pl`
+    /// and inferno, one line per stack.
+    pub fn write_folded_stacks(&self, writer: &mut impl Write) -> io::Result<()> {
+        let mut lines = self
+            .folded_stacks
+            .iter()
+            .map(|(stack, steps)| {
+                let stack = stack
+                    .iter()
+                    .map(|fid| self.function_name(*fid))
+                    .collect::<Vec<_>>()
+                    .join(";");
+
+                (stack, steps)
+            })
+            .collect::<Vec<_>>();
+        lines.sort();
+
+        for (stack, steps) in lines {
+            writeln!(writer, "{} {}", stack, steps)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Profile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let percent = |n: usize, total: usize| n as f64 / usize::max(total, 1) as f64 * 100f64;
+
+        writeln!(f, "etable entries: {}", self.etable_entries)?;
+        writeln!(f, "mtable entries: {}", self.mtable_entries)?;
+
+        writeln!(f, "opcodes:")?;
+        let mut opcodes = self.opcodes.iter().collect::<Vec<_>>();
+        opcodes.sort_by(|a, b| b.1.steps.cmp(&a.1.steps));
+        for (opcode, statistic) in opcodes {
+            writeln!(
+                f,
+                "  {:?}: {} ({:.2}%), mentries: {} ({:.2}%), read: {}, write: {}",
+                opcode,
+                statistic.steps,
+                percent(statistic.steps, self.etable_entries),
+                statistic.memory_entries(),
+                percent(statistic.memory_entries(), self.mtable_entries),
+                statistic.memory_reads,
+                statistic.memory_writes,
+            )?;
+        }
+
+        writeln!(f, "functions:")?;
+        let mut functions = self.functions.iter().collect::<Vec<_>>();
+        functions.sort_by(|a, b| b.1.steps.cmp(&a.1.steps));
+        for (fid, statistic) in functions {
+            writeln!(
+                f,
+                "  {} (fid {}): {} ({:.2}%), mentries: {} ({:.2}%)",
+                self.function_name(*fid),
+                fid,
+                statistic.steps,
+                percent(statistic.steps, self.etable_entries),
+                statistic.memory_entries(),
+                percent(statistic.memory_entries(), self.mtable_entries),
+            )?;
+        }
+
+        writeln!(f, "host calls:")?;
+        for (name, calls) in &self.host_calls {
+            writeln!(f, "  {}: {}", name, calls)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Function names from the name section of the module.
+pub fn function_names(module: &Module) -> HashMap<u32, String> {
+    module
+        .names_section()
+        .and_then(|names| names.functions())
+        .map(|functions| {
+            functions
+                .names()
+                .iter()
+                .map(|(fid, name)| (fid, name.clone()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
 
 pub trait Profiler {
-    fn profile_tables(&self);
+    fn profile_tables(&self, profile: &mut Profile);
 }
 
-impl<B: SliceBackend> Profiler for Tables<B> {
-    fn profile_tables(&self) {
-        //self.profile_instruction();
+impl<B: SliceBackend + Clone> Profiler for Tables<B> {
+    fn profile_tables(&self, profile: &mut Profile) {
+        for slice in &self.execution_tables.slice_backend {
+            profile.profile_slice(&self.compilation_tables.itable, &slice.clone().into());
+        }
     }
 }
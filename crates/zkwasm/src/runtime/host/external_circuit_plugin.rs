@@ -1,5 +1,6 @@
 use specs::external_host_call_table::ExternalHostCallSignature;
 use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::rc::Rc;
 use wasmi::FuncInstance;
@@ -7,18 +8,26 @@ use wasmi::ModuleImportResolver;
 use wasmi::RuntimeArgs;
 use wasmi::RuntimeValue;
 
+use crate::error::HostCallError;
 use crate::runtime::monitor::observer::Observer;
 
 use super::ForeignContext;
 use super::ForeignPlugin;
 use super::ForeignStatics;
 use super::MatchForeignOpSignature;
+use super::StateDiff;
 
 pub(super) struct ForeignOp {
     pub op_index: usize,
     pub sig: ExternalHostCallSignature,
     pub plugin: Rc<ForeignPlugin>,
-    pub cb: Rc<dyn Fn(&Observer, &mut dyn ForeignContext, RuntimeArgs) -> Option<RuntimeValue>>,
+    pub cb: Rc<
+        dyn Fn(
+            &Observer,
+            &mut dyn ForeignContext,
+            RuntimeArgs,
+        ) -> Result<Option<RuntimeValue>, HostCallError>,
+    >,
 }
 
 pub struct ExternalCircuitEnv {
@@ -54,6 +63,31 @@ impl ExternalCircuitEnv {
         sig: ExternalHostCallSignature,
         plugin: Rc<ForeignPlugin>,
         cb: Rc<dyn Fn(&Observer, &mut dyn ForeignContext, RuntimeArgs) -> Option<RuntimeValue>>,
+    ) {
+        self.register_fallible_function(
+            name,
+            op_index,
+            sig,
+            plugin,
+            Rc::new(move |observer, context, args| Ok(cb(observer, context, args))),
+        )
+    }
+
+    /// Register a foreign function which fails on invalid calls from the
+    /// guest, the error aborts the execution as a trap.
+    pub fn register_fallible_function(
+        &mut self,
+        name: &str,
+        op_index: usize,
+        sig: ExternalHostCallSignature,
+        plugin: Rc<ForeignPlugin>,
+        cb: Rc<
+            dyn Fn(
+                &Observer,
+                &mut dyn ForeignContext,
+                RuntimeArgs,
+            ) -> Result<Option<RuntimeValue>, HostCallError>,
+        >,
     ) {
         assert!(!*self.finalized.borrow());
 
@@ -81,6 +115,23 @@ impl ExternalCircuitEnv {
         }
         m
     }
+
+    /// State diffs of all plugins which wrote a tree, keyed by the plugin name.
+    pub fn get_state_diffs(&self) -> BTreeMap<String, Vec<StateDiff>> {
+        let mut m = BTreeMap::new();
+        for v in self.functions.values() {
+            let plugin_name = &v.plugin.name;
+
+            if !m.contains_key(plugin_name) {
+                let diffs = (v.plugin.ctx).as_ref().borrow().expose_state_diffs();
+
+                if !diffs.is_empty() {
+                    m.insert(plugin_name.to_string(), diffs);
+                }
+            }
+        }
+        m
+    }
 }
 
 impl ModuleImportResolver for ExternalCircuitEnv {
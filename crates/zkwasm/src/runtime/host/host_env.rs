@@ -7,6 +7,8 @@ use std::rc::Rc;
 use std::time::Instant;
 
 use log::debug;
+use specs::external_host_call_table::ExternalHostCallEntry;
+use specs::external_host_call_table::ExternalHostCallSignature;
 use specs::host_function::HostFunctionDesc;
 
 use wasmi::Externals;
@@ -15,6 +17,8 @@ use wasmi::RuntimeArgs;
 use wasmi::RuntimeValue;
 use wasmi::Trap;
 
+use crate::error::ExecutionError;
+use crate::error::HostCallError;
 use crate::runtime::host::HostFunctionExecutionEnv;
 use crate::runtime::monitor::observer::Observer;
 
@@ -29,6 +33,8 @@ pub struct HostEnv {
 
     finalized: Rc<RefCell<bool>>,
     cached_lookup: Option<HashMap<usize, HostFunction>>,
+    /// Number of recorded external host calls and the counter of replayed ones.
+    replay: Option<(usize, Rc<RefCell<usize>>)>,
 
     /// Profile foreign function time
     time_profile: BTreeMap<String, u128>,
@@ -52,6 +58,7 @@ impl HostEnv {
             internal_env: InternalCircuitEnv::new(finalized.clone()),
             external_env: ExternalCircuitEnv::new(finalized.clone()),
             cached_lookup: None,
+            replay: None,
             finalized,
             time_profile: BTreeMap::new(),
         }
@@ -123,6 +130,69 @@ impl HostEnv {
         *finalized = true;
     }
 
+    /// Answers external host calls from the recorded `entries` instead of
+    /// their plugins, a call which diverges from the record fails and
+    /// `finish_replay` fails if some entries are not replayed.
+    pub fn replay_external_host_calls(&mut self, entries: Rc<Vec<ExternalHostCallEntry>>) {
+        let cursor = Rc::new(RefCell::new(0));
+        self.replay = Some((entries.len(), cursor.clone()));
+
+        for function in self
+            .cached_lookup
+            .as_mut()
+            .expect("HostEnv has not been finalized. Please invoke finalized() first.")
+            .values_mut()
+        {
+            if let HostFunctionDesc::External { op, sig, .. } = function.desc {
+                let entries = entries.clone();
+                let cursor = cursor.clone();
+
+                function.execution_env.cb = Rc::new(move |_observer, _context, args| {
+                    let mut cursor = cursor.borrow_mut();
+                    let position = *cursor;
+                    let recorded = entries
+                        .get(position)
+                        .ok_or(HostCallError::ReplayExhausted(op))?;
+
+                    let matched = match sig {
+                        ExternalHostCallSignature::Argument => {
+                            !recorded.is_ret && recorded.value == args.nth::<u64>(0)
+                        }
+                        ExternalHostCallSignature::Return => recorded.is_ret,
+                    };
+
+                    if recorded.op != op || !matched {
+                        return Err(HostCallError::ReplayDiverged {
+                            position,
+                            op,
+                            recorded_op: recorded.op,
+                            recorded_value: recorded.value,
+                        });
+                    }
+
+                    *cursor += 1;
+
+                    Ok(recorded
+                        .is_ret
+                        .then(|| RuntimeValue::I64(recorded.value as i64)))
+                });
+            }
+        }
+    }
+
+    /// Checks that the execution replayed all recorded external host calls.
+    pub fn finish_replay(&self) -> Result<(), ExecutionError> {
+        match &self.replay {
+            Some((recorded, replayed)) if *replayed.borrow() < *recorded => {
+                Err(ExecutionError::ReplayIncomplete {
+                    replayed: *replayed.borrow(),
+                    recorded: *recorded,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
     pub fn function_description_table(&self) -> HashMap<usize, HostFunctionDesc> {
         assert!(
             *self.finalized.borrow(),
@@ -170,7 +240,7 @@ impl Externals for ExecEnv {
     ) -> Result<Option<RuntimeValue>, Trap> {
         match self.host_env.cached_lookup.as_ref().unwrap().get(&index) {
             Some(HostFunction {
-                desc: _desc,
+                desc,
                 execution_env: HostFunctionExecutionEnv { ctx, cb },
             }) => {
                 let mut ctx = (*ctx).borrow_mut();
@@ -187,11 +257,21 @@ impl Externals for ExecEnv {
                 #[cfg(feature = "profile")]
                 self.host_env
                     .time_profile
-                    .entry(_desc.name().to_string())
+                    .entry(desc.name().to_string())
                     .and_modify(|d| *d += duration.as_millis())
                     .or_insert(duration.as_millis());
 
-                Ok(r)
+                r.map_err(|error| {
+                    let observer = self.observer.borrow();
+
+                    Trap::Host(Box::new(ExecutionError::HostCall {
+                        eid: observer.counter as u32,
+                        fid: observer.fid,
+                        iid: observer.iid,
+                        function: desc.name().to_string(),
+                        error,
+                    }))
+                })
             }
             None => unreachable!(),
         }
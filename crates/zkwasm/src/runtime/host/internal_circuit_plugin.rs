@@ -8,6 +8,7 @@ use wasmi::ModuleImportResolver;
 use wasmi::RuntimeArgs;
 use wasmi::RuntimeValue;
 
+use crate::error::HostCallError;
 use crate::runtime::monitor::observer::Observer;
 
 use super::ForeignContext;
@@ -18,7 +19,13 @@ pub(super) struct ForeignOp {
     pub index_within_plugin: usize,
     pub sig: Signature,
     pub plugin: HostPlugin,
-    pub cb: Rc<dyn Fn(&Observer, &mut dyn ForeignContext, RuntimeArgs) -> Option<RuntimeValue>>,
+    pub cb: Rc<
+        dyn Fn(
+            &Observer,
+            &mut dyn ForeignContext,
+            RuntimeArgs,
+        ) -> Result<Option<RuntimeValue>, HostCallError>,
+    >,
 }
 
 pub struct InternalCircuitEnv {
@@ -64,7 +71,13 @@ impl InternalCircuitEnv {
         sig: Signature,
         plugin: HostPlugin,
         index_within_plugin: usize,
-        cb: Rc<dyn Fn(&Observer, &mut dyn ForeignContext, RuntimeArgs) -> Option<RuntimeValue>>,
+        cb: Rc<
+            dyn Fn(
+                &Observer,
+                &mut dyn ForeignContext,
+                RuntimeArgs,
+            ) -> Result<Option<RuntimeValue>, HostCallError>,
+        >,
     ) {
         assert!(!*self.finalized.borrow());
 
@@ -2,14 +2,19 @@ use self::default_env::ExecutionArg;
 use self::host_env::HostEnv;
 use downcast_rs::impl_downcast;
 use downcast_rs::Downcast;
+use serde::Deserialize;
+use serde::Serialize;
 use specs::external_host_call_table::ExternalHostCallSignature;
 use specs::host_function::HostFunctionDesc;
 use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::rc::Rc;
 use wasmi::RuntimeArgs;
 use wasmi::RuntimeValue;
 use wasmi::Signature;
 
+use crate::error::HostCallError;
+
 use super::monitor::observer::Observer;
 use super::monitor::plugins::table::FlushStrategy;
 
@@ -18,6 +23,7 @@ pub mod external_circuit_plugin;
 
 pub mod host_env;
 mod internal_circuit_plugin;
+pub mod replay_env;
 
 trait MatchForeignOpSignature {
     fn match_wasmi_signature(&self, signature: &Signature) -> bool;
@@ -48,6 +54,42 @@ pub struct ForeignStatics {
     pub max_round: usize,
 }
 
+/// A leaf written by a host plugin, `old_leaf` is the leaf before the first
+/// write and `new_leaf` is the leaf after the last write.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LeafWrite {
+    pub old_leaf: [u64; 4],
+    pub new_leaf: [u64; 4],
+}
+
+/// Writes to a tree which transform `root_before` into `root_after`, keyed by
+/// the address of the leaf.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StateDiff {
+    pub root_before: [u64; 4],
+    pub root_after: [u64; 4],
+    pub writes: BTreeMap<u64, LeafWrite>,
+}
+
+impl StateDiff {
+    pub fn new(root: [u64; 4]) -> Self {
+        StateDiff {
+            root_before: root,
+            root_after: root,
+            writes: BTreeMap::new(),
+        }
+    }
+
+    /// Records a write of the leaf at `address`, the first old leaf of an
+    /// address is kept.
+    pub fn record(&mut self, address: u64, old_leaf: [u64; 4], new_leaf: [u64; 4]) {
+        self.writes
+            .entry(address)
+            .and_modify(|write| write.new_leaf = new_leaf)
+            .or_insert(LeafWrite { old_leaf, new_leaf });
+    }
+}
+
 /// Context of the plugin.
 ///
 /// # Examples
@@ -77,6 +119,11 @@ pub trait ForeignContext: Downcast {
     fn expose_context_outputs(&self) -> Vec<u64> {
         unreachable!()
     }
+
+    /// State diffs of the trees written by the plugin.
+    fn expose_state_diffs(&self) -> Vec<StateDiff> {
+        vec![]
+    }
 }
 impl_downcast!(ForeignContext);
 
@@ -88,7 +135,13 @@ pub struct ForeignPlugin {
 #[derive(Clone)]
 struct HostFunctionExecutionEnv {
     ctx: Rc<RefCell<Box<dyn ForeignContext>>>,
-    cb: Rc<dyn Fn(&Observer, &mut dyn ForeignContext, RuntimeArgs) -> Option<RuntimeValue>>,
+    cb: Rc<
+        dyn Fn(
+            &Observer,
+            &mut dyn ForeignContext,
+            RuntimeArgs,
+        ) -> Result<Option<RuntimeValue>, HostCallError>,
+    >,
 }
 
 #[derive(Clone)]
@@ -106,3 +159,40 @@ pub trait HostEnvBuilder {
     // Create a flush strategy to hint the monitor when to flush the table
     fn create_flush_strategy(&self) -> Box<dyn FlushStrategy>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::LeafWrite;
+    use super::StateDiff;
+
+    #[test]
+    fn test_state_diff_record() {
+        let mut diff = StateDiff::new([1; 4]);
+
+        diff.record(7, [0; 4], [2; 4]);
+        diff.record(3, [0; 4], [3; 4]);
+        // the first old leaf and the last new leaf of an address are kept
+        diff.record(7, [2; 4], [4; 4]);
+        diff.record(7, [4; 4], [5; 4]);
+
+        assert_eq!(
+            diff.writes.into_iter().collect::<Vec<_>>(),
+            vec![
+                (
+                    3,
+                    LeafWrite {
+                        old_leaf: [0; 4],
+                        new_leaf: [3; 4],
+                    }
+                ),
+                (
+                    7,
+                    LeafWrite {
+                        old_leaf: [0; 4],
+                        new_leaf: [5; 4],
+                    }
+                ),
+            ]
+        );
+    }
+}
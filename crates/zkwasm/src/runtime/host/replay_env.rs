@@ -0,0 +1,60 @@
+use std::rc::Rc;
+
+use specs::external_host_call_table::ExternalHostCallEntry;
+use specs::external_host_call_table::ExternalHostCallTable;
+
+use crate::runtime::monitor::plugins::table::FlushStrategy;
+
+use super::default_env::ExecutionArg;
+use super::host_env::HostEnv;
+use super::HostEnvBuilder;
+
+/// Replays the external host calls of an execution from its recorded external
+/// host call tables, so that it can be executed again without the databases and
+/// witnesses behind the host functions.
+///
+/// The env of `inner` registers the host functions, their plugins are not
+/// invoked and `tree_db` of the execution arg is not accessed. The execution
+/// fails if it diverges from the record or leaves recorded calls unreplayed.
+///
+/// Internal host calls (`wasm_input`, the context, `require` and the log
+/// functions) are not recorded in the external host call tables, the env of
+/// `inner` still answers them from the execution arg, so the replay needs the
+/// public, private and context inputs of the recorded execution.
+pub struct ReplayHostEnvBuilder<B: HostEnvBuilder> {
+    inner: B,
+    entries: Rc<Vec<ExternalHostCallEntry>>,
+}
+
+impl<B: HostEnvBuilder> ReplayHostEnvBuilder<B> {
+    /// `tables` are the external host call tables of all slices in order.
+    pub fn new(inner: B, tables: Vec<ExternalHostCallTable>) -> Self {
+        let entries = tables
+            .iter()
+            .flat_map(|table| table.entries().clone())
+            .collect();
+
+        Self {
+            inner,
+            entries: Rc::new(entries),
+        }
+    }
+}
+
+impl<B: HostEnvBuilder> HostEnvBuilder for ReplayHostEnvBuilder<B> {
+    fn create_env_without_value(&self) -> HostEnv {
+        self.inner.create_env_without_value()
+    }
+
+    fn create_env(&self, arg: ExecutionArg) -> HostEnv {
+        let mut env = self.inner.create_env(arg);
+
+        env.replay_external_host_calls(self.entries.clone());
+
+        env
+    }
+
+    fn create_flush_strategy(&self) -> Box<dyn FlushStrategy> {
+        self.inner.create_flush_strategy()
+    }
+}
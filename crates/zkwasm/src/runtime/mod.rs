@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 
 use halo2_proofs::arithmetic::FieldExt;
@@ -8,10 +9,12 @@ use specs::mtable::LocationType;
 use specs::mtable::MemoryTableEntry;
 use specs::mtable::VarType;
 use specs::step::StepInfo;
+use specs::types::ValueType;
 
 use crate::foreign::context::ContextOutput;
 
 use self::host::ForeignStatics;
+use self::host::StateDiff;
 use self::wasmi_interpreter::WasmiRuntime;
 
 pub mod host;
@@ -34,6 +37,8 @@ pub struct ExecutionResult<R> {
     pub guest_statics: usize, // total instructions used in guest circuits
     pub outputs: Vec<u64>,
     pub context_outputs: ContextOutput,
+    /// Trees written by host plugins, keyed by the plugin name.
+    pub state_diffs: BTreeMap<String, Vec<StateDiff>>,
 }
 
 impl<R> ExecutionResult<R> {
@@ -58,50 +63,7 @@ pub fn memory_event_of_step(event: &EventTableEntry) -> Vec<MemoryTableEntry> {
             keep,
             keep_values,
             ..
-        } => {
-            assert_eq!(keep.len(), keep_values.len());
-            assert!(keep.len() <= 1);
-
-            let mut sp = sp_before_execution + 1;
-            let mut ops = vec![];
-
-            {
-                for i in 0..keep.len() {
-                    ops.push(MemoryTableEntry {
-                        eid,
-                        offset: sp,
-                        ltype: LocationType::Stack,
-                        atype: AccessType::Read,
-                        vtype: keep[i].into(),
-                        is_mutable: true,
-                        value: keep_values[i],
-                    });
-
-                    sp += 1;
-                }
-            }
-
-            sp += drop;
-            sp -= 1;
-
-            {
-                for i in 0..keep.len() {
-                    ops.push(MemoryTableEntry {
-                        eid,
-                        offset: sp,
-                        ltype: LocationType::Stack,
-                        atype: AccessType::Write,
-                        vtype: keep[i].into(),
-                        is_mutable: true,
-                        value: keep_values[i],
-                    });
-
-                    sp -= 1;
-                }
-            }
-
-            ops
-        }
+        } => keep_value_ops(eid, sp_before_execution, *drop, keep, keep_values),
         StepInfo::BrIfEqz {
             condition,
             drop,
@@ -109,14 +71,9 @@ pub fn memory_event_of_step(event: &EventTableEntry) -> Vec<MemoryTableEntry> {
             keep_values,
             ..
         } => {
-            assert_eq!(keep.len(), keep_values.len());
-            assert!(keep.len() <= 1);
-
-            let mut sp = sp_before_execution + 1;
-
             let mut ops = vec![MemoryTableEntry {
                 eid,
-                offset: sp,
+                offset: sp_before_execution + 1,
                 ltype: LocationType::Stack,
                 atype: AccessType::Read,
                 vtype: VarType::I32,
@@ -124,45 +81,14 @@ pub fn memory_event_of_step(event: &EventTableEntry) -> Vec<MemoryTableEntry> {
                 value: *condition as u32 as u64,
             }];
 
-            sp += 1;
-
-            if *condition != 0 {
-                return ops;
-            }
-
-            {
-                for i in 0..keep.len() {
-                    ops.push(MemoryTableEntry {
-                        eid,
-                        offset: sp,
-                        ltype: LocationType::Stack,
-                        atype: AccessType::Read,
-                        vtype: keep[i].into(),
-                        is_mutable: true,
-                        value: keep_values[i],
-                    });
-
-                    sp += 1;
-                }
-            }
-
-            sp += drop;
-            sp -= 1;
-
-            {
-                for i in 0..keep.len() {
-                    ops.push(MemoryTableEntry {
-                        eid,
-                        offset: sp,
-                        ltype: LocationType::Stack,
-                        atype: AccessType::Write,
-                        vtype: keep[i].into(),
-                        is_mutable: true,
-                        value: keep_values[i],
-                    });
-
-                    sp -= 1;
-                }
+            if *condition == 0 {
+                ops.append(&mut keep_value_ops(
+                    eid,
+                    sp_before_execution + 1,
+                    *drop,
+                    keep,
+                    keep_values,
+                ));
             }
 
             ops
@@ -174,14 +100,9 @@ pub fn memory_event_of_step(event: &EventTableEntry) -> Vec<MemoryTableEntry> {
             keep_values,
             ..
         } => {
-            assert_eq!(keep.len(), keep_values.len());
-            assert!(keep.len() <= 1);
-
-            let mut sp = sp_before_execution + 1;
-
             let mut ops = vec![MemoryTableEntry {
                 eid,
-                offset: sp,
+                offset: sp_before_execution + 1,
                 ltype: LocationType::Stack,
                 atype: AccessType::Read,
                 vtype: VarType::I32,
@@ -189,45 +110,14 @@ pub fn memory_event_of_step(event: &EventTableEntry) -> Vec<MemoryTableEntry> {
                 value: *condition as u32 as u64,
             }];
 
-            sp += 1;
-
-            if *condition == 0 {
-                return ops;
-            }
-
-            {
-                for i in 0..keep.len() {
-                    ops.push(MemoryTableEntry {
-                        eid,
-                        offset: sp,
-                        ltype: LocationType::Stack,
-                        atype: AccessType::Read,
-                        vtype: keep[i].into(),
-                        is_mutable: true,
-                        value: keep_values[i],
-                    });
-
-                    sp += 1;
-                }
-            }
-
-            sp += drop;
-            sp -= 1;
-
-            {
-                for i in 0..keep.len() {
-                    ops.push(MemoryTableEntry {
-                        eid,
-                        offset: sp,
-                        ltype: LocationType::Stack,
-                        atype: AccessType::Write,
-                        vtype: keep[i].into(),
-                        is_mutable: true,
-                        value: keep_values[i],
-                    });
-
-                    sp -= 1;
-                }
+            if *condition != 0 {
+                ops.append(&mut keep_value_ops(
+                    eid,
+                    sp_before_execution + 1,
+                    *drop,
+                    keep,
+                    keep_values,
+                ));
             }
 
             ops
@@ -239,14 +129,9 @@ pub fn memory_event_of_step(event: &EventTableEntry) -> Vec<MemoryTableEntry> {
             keep_values,
             ..
         } => {
-            assert_eq!(keep.len(), keep_values.len());
-            assert!(keep.len() <= 1);
-
-            let mut sp = sp_before_execution + 1;
-
             let mut ops = vec![MemoryTableEntry {
                 eid,
-                offset: sp,
+                offset: sp_before_execution + 1,
                 ltype: LocationType::Stack,
                 atype: AccessType::Read,
                 vtype: VarType::I32,
@@ -254,42 +139,13 @@ pub fn memory_event_of_step(event: &EventTableEntry) -> Vec<MemoryTableEntry> {
                 value: *index as u32 as u64,
             }];
 
-            sp += 1;
-
-            {
-                for i in 0..keep.len() {
-                    ops.push(MemoryTableEntry {
-                        eid,
-                        offset: sp,
-                        ltype: LocationType::Stack,
-                        atype: AccessType::Read,
-                        vtype: keep[i].into(),
-                        is_mutable: true,
-                        value: keep_values[i],
-                    });
-
-                    sp += 1;
-                }
-            }
-
-            sp += drop;
-            sp -= 1;
-
-            {
-                for i in 0..keep.len() {
-                    ops.push(MemoryTableEntry {
-                        eid,
-                        offset: sp,
-                        ltype: LocationType::Stack,
-                        atype: AccessType::Write,
-                        vtype: keep[i].into(),
-                        is_mutable: true,
-                        value: keep_values[i],
-                    });
-
-                    sp -= 1;
-                }
-            }
+            ops.append(&mut keep_value_ops(
+                eid,
+                sp_before_execution + 1,
+                *drop,
+                keep,
+                keep_values,
+            ));
 
             ops
         }
@@ -297,50 +153,7 @@ pub fn memory_event_of_step(event: &EventTableEntry) -> Vec<MemoryTableEntry> {
             drop,
             keep,
             keep_values,
-        } => {
-            assert_eq!(keep.len(), keep_values.len());
-            assert!(keep.len() <= 1);
-
-            let mut sp = sp_before_execution + 1;
-            let mut ops = vec![];
-
-            {
-                for i in 0..keep.len() {
-                    ops.push(MemoryTableEntry {
-                        eid,
-                        offset: sp,
-                        ltype: LocationType::Stack,
-                        atype: AccessType::Read,
-                        vtype: keep[i].into(),
-                        is_mutable: true,
-                        value: keep_values[i],
-                    });
-
-                    sp += 1;
-                }
-            }
-
-            sp += drop;
-            sp -= 1;
-
-            {
-                for i in 0..keep.len() {
-                    ops.push(MemoryTableEntry {
-                        eid,
-                        offset: sp,
-                        ltype: LocationType::Stack,
-                        atype: AccessType::Write,
-                        vtype: keep[i].into(),
-                        is_mutable: true,
-                        value: keep_values[i],
-                    });
-
-                    sp -= 1;
-                }
-            }
-
-            ops
-        }
+        } => keep_value_ops(eid, sp_before_execution, *drop, keep, keep_values),
         StepInfo::Drop { .. } => vec![],
         StepInfo::Select {
             val1,
@@ -801,6 +614,93 @@ pub fn memory_event_of_step(event: &EventTableEntry) -> Vec<MemoryTableEntry> {
             &[*result as u32 as u64],
         ),
 
+        StepInfo::MemoryFill {
+            offset,
+            value,
+            len,
+            is_word,
+            pre_block_value,
+            updated_block_value,
+        } => {
+            let mut ops = mem_op_from_stack_only_step(
+                sp_before_execution,
+                eid,
+                VarType::I32,
+                VarType::I32,
+                &[*len as u64, *value as u64, *offset as u64],
+                &[],
+            );
+
+            if *len != 0 {
+                let width = if *is_word { 8 } else { 1 };
+
+                ops.extend(bulk_memory_iteration_heap_ops(
+                    eid,
+                    *offset,
+                    *pre_block_value,
+                    *updated_block_value,
+                ));
+                ops.push(stack_write_i32(
+                    eid,
+                    sp_before_execution + 3,
+                    offset + width,
+                ));
+                ops.push(stack_write_i32(eid, sp_before_execution + 1, len - width));
+            }
+
+            ops
+        }
+        StepInfo::MemoryCopy {
+            dst,
+            src,
+            len,
+            is_word,
+            src_block_value,
+            pre_block_value,
+            updated_block_value,
+        } => {
+            let mut ops = mem_op_from_stack_only_step(
+                sp_before_execution,
+                eid,
+                VarType::I32,
+                VarType::I32,
+                &[*len as u64, *src as u64, *dst as u64],
+                &[],
+            );
+
+            if *len != 0 {
+                let width = if *is_word { 8 } else { 1 };
+                let forward = dst <= src;
+                let (src_address, dst_address) = if forward {
+                    (*src, *dst)
+                } else {
+                    (src + len - width, dst + len - width)
+                };
+                let shift = if forward { width } else { 0 };
+
+                ops.push(MemoryTableEntry {
+                    eid,
+                    offset: src_address / 8,
+                    ltype: LocationType::Heap,
+                    atype: AccessType::Read,
+                    vtype: VarType::I64,
+                    is_mutable: true,
+                    value: *src_block_value,
+                });
+                ops.extend(bulk_memory_iteration_heap_ops(
+                    eid,
+                    dst_address,
+                    *pre_block_value,
+                    *updated_block_value,
+                ));
+                ops.push(stack_write_i32(eid, sp_before_execution + 3, dst + shift));
+                ops.push(stack_write_i32(eid, sp_before_execution + 2, src + shift));
+                ops.push(stack_write_i32(eid, sp_before_execution + 1, len - width));
+            }
+
+            ops
+        }
+
         StepInfo::I32Const { value } => mem_op_from_stack_only_step(
             sp_before_execution,
             eid,
@@ -933,9 +833,148 @@ pub fn memory_event_of_step(event: &EventTableEntry) -> Vec<MemoryTableEntry> {
             &[*value as u64],
             &[*result as u64],
         ),
+
+        StepInfo::FloatBinOp {
+            vtype,
+            left,
+            right,
+            value,
+            ..
+        } => mem_op_from_stack_only_step(
+            sp_before_execution,
+            eid,
+            (*vtype).into(),
+            (*vtype).into(),
+            &[*right, *left],
+            &[*value],
+        ),
+        StepInfo::FloatUnaryOp {
+            vtype,
+            operand,
+            result,
+            ..
+        } => mem_op_from_stack_only_step(
+            sp_before_execution,
+            eid,
+            (*vtype).into(),
+            (*vtype).into(),
+            &[*operand],
+            &[*result],
+        ),
+        StepInfo::FloatComp {
+            vtype,
+            left,
+            right,
+            value,
+            ..
+        } => mem_op_from_stack_only_step(
+            sp_before_execution,
+            eid,
+            (*vtype).into(),
+            VarType::I32,
+            &[*right, *left],
+            &[*value as u64],
+        ),
+        StepInfo::FloatConversion {
+            class,
+            value,
+            result,
+        } => mem_op_from_stack_only_step(
+            sp_before_execution,
+            eid,
+            class.value_type(),
+            class.result_type(),
+            &[*value],
+            &[*result],
+        ),
     }
 }
 
+/// Moves the kept values `sp + 1 + i` to `sp + 1 + drop + i`. All values are
+/// read before any of them is written because the two ranges may overlap.
+fn keep_value_ops(
+    eid: u32,
+    sp: u32,
+    drop: u32,
+    keep: &[ValueType],
+    keep_values: &[u64],
+) -> Vec<MemoryTableEntry> {
+    assert_eq!(keep.len(), keep_values.len());
+
+    let reads = keep
+        .iter()
+        .zip(keep_values)
+        .enumerate()
+        .map(|(i, (vtype, value))| MemoryTableEntry {
+            eid,
+            offset: sp + 1 + i as u32,
+            ltype: LocationType::Stack,
+            atype: AccessType::Read,
+            vtype: vtype.into(),
+            is_mutable: true,
+            value: *value,
+        });
+
+    let writes = keep
+        .iter()
+        .zip(keep_values)
+        .enumerate()
+        .map(|(i, (vtype, value))| MemoryTableEntry {
+            eid,
+            offset: sp + 1 + drop + i as u32,
+            ltype: LocationType::Stack,
+            atype: AccessType::Write,
+            vtype: vtype.into(),
+            is_mutable: true,
+            value: *value,
+        });
+
+    reads.chain(writes).collect()
+}
+
+fn stack_write_i32(eid: u32, offset: u32, value: u32) -> MemoryTableEntry {
+    MemoryTableEntry {
+        eid,
+        offset,
+        ltype: LocationType::Stack,
+        atype: AccessType::Write,
+        vtype: VarType::I32,
+        is_mutable: true,
+        value: value as u64,
+    }
+}
+
+/// The heap accesses of one `memory.fill`/`memory.copy` iteration: the block
+/// containing `address` is read and written back with one byte or the whole
+/// block updated.
+fn bulk_memory_iteration_heap_ops(
+    eid: u32,
+    address: u32,
+    pre_block_value: u64,
+    updated_block_value: u64,
+) -> Vec<MemoryTableEntry> {
+    vec![
+        MemoryTableEntry {
+            eid,
+            offset: address / 8,
+            ltype: LocationType::Heap,
+            atype: AccessType::Read,
+            vtype: VarType::I64,
+            is_mutable: true,
+            value: pre_block_value,
+        },
+        MemoryTableEntry {
+            eid,
+            offset: address / 8,
+            ltype: LocationType::Heap,
+            atype: AccessType::Write,
+            vtype: VarType::I64,
+            is_mutable: true,
+            value: updated_block_value,
+        },
+    ]
+}
+
 pub(crate) fn mem_op_from_stack_only_step(
     sp_before_execution: u32,
     eid: u32,
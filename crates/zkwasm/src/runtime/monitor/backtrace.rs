@@ -0,0 +1,33 @@
+use std::fmt::Display;
+
+/// A wasm function frame, `iid` is the instruction being executed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub fid: u32,
+    pub iid: u32,
+    /// Function name from the name section.
+    pub name: Option<String>,
+}
+
+/// Wasm call stack of a failed execution, the innermost frame first.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Backtrace(pub Vec<Frame>);
+
+impl Display for Backtrace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "wasm backtrace:")?;
+
+        for (depth, frame) in self.0.iter().enumerate() {
+            write!(
+                f,
+                "\n  {}: {} (fid {}, iid {})",
+                depth,
+                frame.name.as_deref().unwrap_or("<unknown>"),
+                frame.fid,
+                frame.iid
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,12 @@
+use std::time::Duration;
+
+/// Budgets of an execution, `None` means unlimited.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExecutionLimits {
+    /// Maximal number of guest instructions.
+    pub instructions: Option<usize>,
+    /// Maximal number of slices committed to the slice backend.
+    pub slices: Option<usize>,
+    /// Maximal wall-clock time spent on executing instructions.
+    pub wall_clock: Option<Duration>,
+}
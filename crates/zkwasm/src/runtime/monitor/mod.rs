@@ -3,8 +3,11 @@ use std::rc::Rc;
 
 use wasmi::monitor::Monitor;
 
+use self::backtrace::Backtrace;
 use self::observer::Observer;
 
+pub mod backtrace;
+pub mod limits;
 pub mod observer;
 pub mod plugins;
 pub mod statistic_monitor;
@@ -12,4 +15,5 @@ pub mod table_monitor;
 
 pub trait WasmiMonitor: Monitor {
     fn expose_observer(&self) -> Rc<RefCell<Observer>>;
+    fn expose_backtrace(&self) -> Backtrace;
 }
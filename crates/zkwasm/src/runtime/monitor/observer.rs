@@ -2,4 +2,7 @@
 pub struct Observer {
     pub counter: usize,
     pub is_in_phantom: bool,
+    /// Function index and instruction index of the instruction being executed.
+    pub fid: u32,
+    pub iid: u32,
 }
@@ -1,5 +1,7 @@
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
+use std::time::Instant;
 
 use parity_wasm::elements::ValueType;
 use wasmi::func::FuncInstanceInternal;
@@ -8,27 +10,116 @@ use wasmi::monitor::Monitor;
 use wasmi::runner::InstructionOutcome;
 use wasmi::FuncRef;
 use wasmi::Trap;
-use wasmi::TrapCode;
 
 use super::phantom::PhantomHelper;
+use crate::error::ExecutionError;
+use crate::profile::function_names;
+use crate::runtime::monitor::backtrace::Backtrace;
+use crate::runtime::monitor::backtrace::Frame;
+use crate::runtime::monitor::limits::ExecutionLimits;
 use crate::runtime::monitor::Observer;
 
+const WALL_CLOCK_CHECK_INTERVAL: usize = 1 << 12;
+
 pub struct StatisticPlugin {
     phantom_helper: PhantomHelper,
     observer: Rc<RefCell<Observer>>,
-    instruction_limit: Option<usize>,
+    limits: ExecutionLimits,
+    started_at: Option<Instant>,
+    steps: usize,
+
+    /// The entry function, it runs once the start function returns.
+    entry_fid: u32,
+    /// Positions of the calls waiting for their callee to return.
+    call_sites: Vec<(u32, u32)>,
+    function_names: HashMap<u32, String>,
 }
 
 impl StatisticPlugin {
-    pub fn new(
-        phantom_regex: &[String],
-        wasm_input: FuncRef,
-        instruction_limit: Option<usize>,
-    ) -> Self {
+    pub fn new(phantom_regex: &[String], wasm_input: FuncRef, limits: ExecutionLimits) -> Self {
         Self {
             phantom_helper: PhantomHelper::new(phantom_regex, wasm_input),
             observer: Rc::new(RefCell::new(Observer::default())),
-            instruction_limit,
+            limits,
+            started_at: None,
+            steps: 0,
+            entry_fid: 0,
+            call_sites: vec![],
+            function_names: HashMap::new(),
+        }
+    }
+
+    /// Call stack of the instruction being executed.
+    pub fn backtrace(&self) -> Backtrace {
+        let observer = self.observer.borrow();
+
+        Backtrace(
+            std::iter::once((observer.fid, observer.iid))
+                .chain(self.call_sites.iter().rev().copied())
+                .map(|(fid, iid)| Frame {
+                    fid,
+                    iid,
+                    name: self.function_names.get(&fid).cloned(),
+                })
+                .collect(),
+        )
+    }
+
+    fn check_limits(&mut self) -> Result<(), Trap> {
+        let eid = self.observer.borrow().counter;
+
+        if let Some(limit) = self.limits.instructions {
+            if eid > limit {
+                return Err(Trap::Host(Box::new(
+                    ExecutionError::InstructionLimitExceeded {
+                        limit,
+                        eid: eid as u32,
+                    },
+                )));
+            }
+        }
+
+        if let Some(limit) = self.limits.wall_clock {
+            let started_at = *self.started_at.get_or_insert_with(Instant::now);
+
+            self.steps += 1;
+            // Reading the clock is much slower than executing an instruction.
+            if self.steps % WALL_CLOCK_CHECK_INTERVAL == 0 {
+                let elapsed = started_at.elapsed();
+
+                if elapsed > limit {
+                    return Err(Trap::Host(Box::new(ExecutionError::TimeLimitExceeded {
+                        limit,
+                        elapsed,
+                        eid: eid as u32,
+                    })));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Position of the instruction executed after `(fid, iid)`.
+    fn next_position(&mut self, fid: u32, iid: u32, outcome: &InstructionOutcome) -> (u32, u32) {
+        match outcome {
+            InstructionOutcome::Branch(target) => (fid, target.dst_pc),
+            InstructionOutcome::ExecuteCall(func_ref) => {
+                if let FuncInstanceInternal::Internal { index, .. } = func_ref.as_internal() {
+                    self.call_sites.push((fid, iid));
+
+                    (*index as u32, 0)
+                } else {
+                    // The host function runs after this hook, stay on the call
+                    // until it returns.
+                    (fid, iid)
+                }
+            }
+            InstructionOutcome::Return(_) => self
+                .call_sites
+                .pop()
+                .map_or((self.entry_fid, 0), |(fid, iid)| (fid, iid + 1)),
+            _ => (fid, iid + 1),
         }
     }
 
@@ -47,13 +138,25 @@ impl Monitor for StatisticPlugin {
         self.phantom_helper
             .register_module(module, module_ref, entry)?;
 
+        self.function_names = function_names(module);
+
+        let fid_of = |func_ref: &FuncRef| module_ref.func_index_by_func_ref(func_ref);
+        self.entry_fid = module_ref
+            .export_by_name(entry)
+            .and_then(|export| export.as_func().map(fid_of))
+            .unwrap_or_default();
+
+        let mut observer = self.observer.borrow_mut();
+        observer.fid = module.start_section().unwrap_or(self.entry_fid);
+        observer.iid = 0;
+
         Ok(())
     }
 
     fn invoke_instruction_post_hook(
         &mut self,
         fid: u32,
-        _iid: u32,
+        iid: u32,
         _sp: u32,
         _allocated_memory_pages: u32,
         value_stack: &wasmi::runner::ValueStack,
@@ -64,10 +167,13 @@ impl Monitor for StatisticPlugin {
         self.observer.borrow_mut().counter +=
             !self.phantom_helper.is_in_phantom_function() as usize;
 
-        if let Some(instruction_limit) = self.instruction_limit {
-            if self.observer.borrow_mut().counter > instruction_limit {
-                return Err(Trap::Code(TrapCode::InstructionExceedsLimit));
-            }
+        self.check_limits()?;
+
+        let (next_fid, next_iid) = self.next_position(fid, iid, outcome);
+        {
+            let mut observer = self.observer.borrow_mut();
+            observer.fid = next_fid;
+            observer.iid = next_iid;
         }
 
         match outcome {
@@ -106,4 +212,8 @@ impl Monitor for StatisticPlugin {
 
         Ok(())
     }
+
+    fn invoke_call_host_post_hook(&mut self, _return_value: Option<wasmi::RuntimeValue>) {
+        self.observer.borrow_mut().iid += 1;
+    }
 }
@@ -1,14 +1,21 @@
+use std::collections::HashMap;
+
 use parity_wasm::elements::ValueType;
 use specs::external_host_call_table::ExternalHostCallSignature;
 use specs::itable::BinOp;
 use specs::itable::BitOp;
 use specs::itable::BrTarget;
 use specs::itable::ConversionOp;
+use specs::itable::FloatBinOp;
+use specs::itable::FloatConversionOp;
+use specs::itable::FloatRelOp;
+use specs::itable::FloatUnaryOp;
 use specs::itable::Opcode;
 use specs::itable::RelOp;
 use specs::itable::ShiftOp;
 use specs::itable::TestOp;
 use specs::itable::UnaryOp;
+use specs::mtable::FloatType;
 use specs::mtable::MemoryReadSize;
 use specs::mtable::MemoryStoreSize;
 use specs::mtable::VarType;
@@ -93,29 +100,17 @@ impl<'a> InstructionIntoOpcode for wasmi::isa::Instruction<'a> {
             },
             Instruction::Br(Target { dst_pc, drop_keep }) => Opcode::Br {
                 drop: drop_keep.drop,
-                keep: if let Keep::Single(t) = drop_keep.keep {
-                    vec![t.into()]
-                } else {
-                    vec![]
-                },
+                keep: keep_types(&drop_keep.keep),
                 dst_pc,
             },
             Instruction::BrIfEqz(Target { dst_pc, drop_keep }) => Opcode::BrIfEqz {
                 drop: drop_keep.drop,
-                keep: if let Keep::Single(t) = drop_keep.keep {
-                    vec![t.into()]
-                } else {
-                    vec![]
-                },
+                keep: keep_types(&drop_keep.keep),
                 dst_pc,
             },
             Instruction::BrIfNez(Target { dst_pc, drop_keep }) => Opcode::BrIf {
                 drop: drop_keep.drop,
-                keep: if let Keep::Single(t) = drop_keep.keep {
-                    vec![t.into()]
-                } else {
-                    vec![]
-                },
+                keep: keep_types(&drop_keep.keep),
                 dst_pc,
             },
             Instruction::BrTable(targets) => Opcode::BrTable {
@@ -124,14 +119,9 @@ impl<'a> InstructionIntoOpcode for wasmi::isa::Instruction<'a> {
                     .iter()
                     .map(|t| {
                         if let InstructionInternal::BrTableTarget(target) = t {
-                            let keep_type = match target.drop_keep.keep {
-                                Keep::None => vec![],
-                                Keep::Single(t) => vec![t.into()],
-                            };
-
                             BrTarget {
                                 drop: target.drop_keep.drop,
-                                keep: keep_type,
+                                keep: keep_types(&target.drop_keep.keep),
                                 dst_pc: target.dst_pc,
                             }
                         } else {
@@ -143,11 +133,7 @@ impl<'a> InstructionIntoOpcode for wasmi::isa::Instruction<'a> {
             Instruction::Unreachable => Opcode::Unreachable,
             Instruction::Return(drop_keep) => Opcode::Return {
                 drop: drop_keep.drop,
-                keep: if let Keep::Single(t) = drop_keep.keep {
-                    vec![t.into()]
-                } else {
-                    vec![]
-                },
+                keep: keep_types(&drop_keep.keep),
             },
             Instruction::Call(func_index) => {
                 let func_desc = function_mapping(func_index);
@@ -185,8 +171,16 @@ impl<'a> InstructionIntoOpcode for wasmi::isa::Instruction<'a> {
                 vtype: VarType::I64,
                 size: MemoryReadSize::I64,
             },
-            Instruction::F32Load(_) => todo!(),
-            Instruction::F64Load(_) => todo!(),
+            Instruction::F32Load(offset) => Opcode::Load {
+                offset,
+                vtype: VarType::I32,
+                size: MemoryReadSize::U32,
+            },
+            Instruction::F64Load(offset) => Opcode::Load {
+                offset,
+                vtype: VarType::I64,
+                size: MemoryReadSize::I64,
+            },
             Instruction::I32Load8S(offset) => Opcode::Load {
                 offset,
                 vtype: VarType::I32,
@@ -247,8 +241,16 @@ impl<'a> InstructionIntoOpcode for wasmi::isa::Instruction<'a> {
                 vtype: VarType::I64,
                 size: MemoryStoreSize::Byte64,
             },
-            Instruction::F32Store(_) => todo!(),
-            Instruction::F64Store(_) => todo!(),
+            Instruction::F32Store(offset) => Opcode::Store {
+                offset,
+                vtype: VarType::I32,
+                size: MemoryStoreSize::Byte32,
+            },
+            Instruction::F64Store(offset) => Opcode::Store {
+                offset,
+                vtype: VarType::I64,
+                size: MemoryStoreSize::Byte64,
+            },
             Instruction::I32Store8(offset) => Opcode::Store {
                 offset,
                 vtype: VarType::I32,
@@ -276,6 +278,8 @@ impl<'a> InstructionIntoOpcode for wasmi::isa::Instruction<'a> {
             },
             Instruction::CurrentMemory => Opcode::MemorySize,
             Instruction::GrowMemory => Opcode::MemoryGrow,
+            Instruction::MemoryFill => Opcode::MemoryFill,
+            Instruction::MemoryCopy => Opcode::MemoryCopy,
             Instruction::I32Const(v) => Opcode::Const {
                 vtype: VarType::I32,
                 value: v as u32 as u64,
@@ -284,8 +288,14 @@ impl<'a> InstructionIntoOpcode for wasmi::isa::Instruction<'a> {
                 vtype: VarType::I64,
                 value: v as u64,
             },
-            Instruction::F32Const(_) => todo!(),
-            Instruction::F64Const(_) => todo!(),
+            Instruction::F32Const(v) => Opcode::Const {
+                vtype: VarType::I32,
+                value: v as u64,
+            },
+            Instruction::F64Const(v) => Opcode::Const {
+                vtype: VarType::I64,
+                value: v,
+            },
             Instruction::I32Eqz => Opcode::Test {
                 class: TestOp::Eqz,
                 vtype: VarType::I32,
@@ -374,18 +384,22 @@ impl<'a> InstructionIntoOpcode for wasmi::isa::Instruction<'a> {
                 class: RelOp::UnsignedGe,
                 vtype: VarType::I64,
             },
-            Instruction::F32Eq => todo!(),
-            Instruction::F32Ne => todo!(),
-            Instruction::F32Lt => todo!(),
-            Instruction::F32Gt => todo!(),
-            Instruction::F32Le => todo!(),
-            Instruction::F32Ge => todo!(),
-            Instruction::F64Eq => todo!(),
-            Instruction::F64Ne => todo!(),
-            Instruction::F64Lt => todo!(),
-            Instruction::F64Gt => todo!(),
-            Instruction::F64Le => todo!(),
-            Instruction::F64Ge => todo!(),
+            Instruction::F32Eq
+            | Instruction::F32Ne
+            | Instruction::F32Lt
+            | Instruction::F32Gt
+            | Instruction::F32Le
+            | Instruction::F32Ge
+            | Instruction::F64Eq
+            | Instruction::F64Ne
+            | Instruction::F64Lt
+            | Instruction::F64Gt
+            | Instruction::F64Le
+            | Instruction::F64Ge => {
+                let (class, vtype) = float_rel_op(&self);
+
+                Opcode::FloatRel { class, vtype }
+            }
             Instruction::I32Clz => Opcode::Unary {
                 class: UnaryOp::Clz,
                 vtype: VarType::I32,
@@ -530,65 +544,86 @@ impl<'a> InstructionIntoOpcode for wasmi::isa::Instruction<'a> {
                 class: ShiftOp::Rotr,
                 vtype: VarType::I64,
             },
-            Instruction::F32Abs => todo!(),
-            Instruction::F32Neg => todo!(),
-            Instruction::F32Ceil => todo!(),
-            Instruction::F32Floor => todo!(),
-            Instruction::F32Trunc => todo!(),
-            Instruction::F32Nearest => todo!(),
-            Instruction::F32Sqrt => todo!(),
-            Instruction::F32Add => todo!(),
-            Instruction::F32Sub => todo!(),
-            Instruction::F32Mul => todo!(),
-            Instruction::F32Div => todo!(),
-            Instruction::F32Min => todo!(),
-            Instruction::F32Max => todo!(),
-            Instruction::F32Copysign => todo!(),
-            Instruction::F64Abs => todo!(),
-            Instruction::F64Neg => todo!(),
-            Instruction::F64Ceil => todo!(),
-            Instruction::F64Floor => todo!(),
-            Instruction::F64Trunc => todo!(),
-            Instruction::F64Nearest => todo!(),
-            Instruction::F64Sqrt => todo!(),
-            Instruction::F64Add => todo!(),
-            Instruction::F64Sub => todo!(),
-            Instruction::F64Mul => todo!(),
-            Instruction::F64Div => todo!(),
-            Instruction::F64Min => todo!(),
-            Instruction::F64Max => todo!(),
-            Instruction::F64Copysign => todo!(),
+            Instruction::F32Abs
+            | Instruction::F32Neg
+            | Instruction::F64Abs
+            | Instruction::F64Neg => {
+                let (class, vtype) = float_unary_op(&self);
+
+                Opcode::FloatUnary { class, vtype }
+            }
+            Instruction::F32Min
+            | Instruction::F32Max
+            | Instruction::F32Copysign
+            | Instruction::F64Min
+            | Instruction::F64Max
+            | Instruction::F64Copysign => {
+                let (class, vtype) = float_bin_op(&self);
+
+                Opcode::FloatBin { class, vtype }
+            }
+            Instruction::F32Add
+            | Instruction::F32Sub
+            | Instruction::F32Mul
+            | Instruction::F32Div
+            | Instruction::F32Ceil
+            | Instruction::F32Floor
+            | Instruction::F32Trunc
+            | Instruction::F32Nearest
+            | Instruction::F32Sqrt
+            | Instruction::F64Add
+            | Instruction::F64Sub
+            | Instruction::F64Mul
+            | Instruction::F64Div
+            | Instruction::F64Ceil
+            | Instruction::F64Floor
+            | Instruction::F64Trunc
+            | Instruction::F64Nearest
+            | Instruction::F64Sqrt
+            | Instruction::I32TruncSF32
+            | Instruction::I32TruncUF32
+            | Instruction::I32TruncSF64
+            | Instruction::I32TruncUF64
+            | Instruction::I64TruncSF32
+            | Instruction::I64TruncUF32
+            | Instruction::I64TruncSF64
+            | Instruction::I64TruncUF64
+            | Instruction::F32ConvertSI32
+            | Instruction::F32ConvertUI32
+            | Instruction::F32ConvertSI64
+            | Instruction::F32ConvertUI64
+            | Instruction::F32DemoteF64
+            | Instruction::F64ConvertSI32
+            | Instruction::F64ConvertUI32
+            | Instruction::F64ConvertSI64
+            | Instruction::F64ConvertUI64
+            | Instruction::F64PromoteF32 => {
+                unreachable!(
+                    "{:?} has no circuit and is rejected by the loader precheck",
+                    self
+                )
+            }
             Instruction::I32WrapI64 => Opcode::Conversion {
                 class: ConversionOp::I32WrapI64,
             },
-            Instruction::I32TruncSF32 => todo!(),
-            Instruction::I32TruncUF32 => todo!(),
-            Instruction::I32TruncSF64 => todo!(),
-            Instruction::I32TruncUF64 => todo!(),
             Instruction::I64ExtendSI32 => Opcode::Conversion {
                 class: ConversionOp::I64ExtendI32s,
             },
             Instruction::I64ExtendUI32 => Opcode::Conversion {
                 class: ConversionOp::I64ExtendI32u,
             },
-            Instruction::I64TruncSF32 => todo!(),
-            Instruction::I64TruncUF32 => todo!(),
-            Instruction::I64TruncSF64 => todo!(),
-            Instruction::I64TruncUF64 => todo!(),
-            Instruction::F32ConvertSI32 => todo!(),
-            Instruction::F32ConvertUI32 => todo!(),
-            Instruction::F32ConvertSI64 => todo!(),
-            Instruction::F32ConvertUI64 => todo!(),
-            Instruction::F32DemoteF64 => todo!(),
-            Instruction::F64ConvertSI32 => todo!(),
-            Instruction::F64ConvertUI32 => todo!(),
-            Instruction::F64ConvertSI64 => todo!(),
-            Instruction::F64ConvertUI64 => todo!(),
-            Instruction::F64PromoteF32 => todo!(),
-            Instruction::I32ReinterpretF32 => todo!(),
-            Instruction::I64ReinterpretF64 => todo!(),
-            Instruction::F32ReinterpretI32 => todo!(),
-            Instruction::F64ReinterpretI64 => todo!(),
+            Instruction::I32ReinterpretF32 => Opcode::FloatConversion {
+                class: float_conversion_op(&self),
+            },
+            Instruction::I64ReinterpretF64 => Opcode::FloatConversion {
+                class: float_conversion_op(&self),
+            },
+            Instruction::F32ReinterpretI32 => Opcode::FloatConversion {
+                class: float_conversion_op(&self),
+            },
+            Instruction::F64ReinterpretI64 => Opcode::FloatConversion {
+                class: float_conversion_op(&self),
+            },
             Instruction::I32Extend8S => Opcode::Conversion {
                 class: ConversionOp::I32Extend8S,
             },
@@ -651,6 +686,18 @@ pub(super) enum RunInstructionTracePre {
     },
 
     GrowMemory(i32),
+    MemoryFill {
+        offset: u32,
+        value: u32,
+        len: u32,
+        blocks: Option<HashMap<u32, u64>>,
+    },
+    MemoryCopy {
+        dst: u32,
+        src: u32,
+        len: u32,
+        blocks: Option<HashMap<u32, u64>>,
+    },
 
     I32BinOp {
         left: i32,
@@ -701,6 +748,12 @@ pub(super) enum RunInstructionTracePre {
         vtype: VarType,
     },
 
+    FloatBinOp {
+        left: u64,
+        right: u64,
+    },
+    FloatSingle(u64),
+
     Drop,
     Select {
         val1: u64,
@@ -762,12 +815,15 @@ pub(super) fn run_instruction_pre(
         }),
 
         isa::Instruction::I32Load(offset)
+        | isa::Instruction::F32Load(offset)
         | isa::Instruction::I32Load8S(offset)
         | isa::Instruction::I32Load8U(offset)
         | isa::Instruction::I32Load16S(offset)
         | isa::Instruction::I32Load16U(offset) => {
             let load_size = match *instructions {
-                isa::Instruction::I32Load(..) => MemoryReadSize::U32,
+                isa::Instruction::I32Load(..) | isa::Instruction::F32Load(..) => {
+                    MemoryReadSize::U32
+                }
                 isa::Instruction::I32Load8S(..) => MemoryReadSize::S8,
                 isa::Instruction::I32Load8U(..) => MemoryReadSize::U8,
                 isa::Instruction::I32Load16S(..) => MemoryReadSize::S16,
@@ -787,6 +843,7 @@ pub(super) fn run_instruction_pre(
             })
         }
         isa::Instruction::I64Load(offset)
+        | isa::Instruction::F64Load(offset)
         | isa::Instruction::I64Load8S(offset)
         | isa::Instruction::I64Load8U(offset)
         | isa::Instruction::I64Load16S(offset)
@@ -794,7 +851,9 @@ pub(super) fn run_instruction_pre(
         | isa::Instruction::I64Load32S(offset)
         | isa::Instruction::I64Load32U(offset) => {
             let load_size = match *instructions {
-                isa::Instruction::I64Load(..) => MemoryReadSize::I64,
+                isa::Instruction::I64Load(..) | isa::Instruction::F64Load(..) => {
+                    MemoryReadSize::I64
+                }
                 isa::Instruction::I64Load8S(..) => MemoryReadSize::S8,
                 isa::Instruction::I64Load8U(..) => MemoryReadSize::U8,
                 isa::Instruction::I64Load16S(..) => MemoryReadSize::S16,
@@ -815,12 +874,15 @@ pub(super) fn run_instruction_pre(
             })
         }
         isa::Instruction::I32Store(offset)
+        | isa::Instruction::F32Store(offset)
         | isa::Instruction::I32Store8(offset)
         | isa::Instruction::I32Store16(offset) => {
             let store_size = match *instructions {
                 isa::Instruction::I32Store8(_) => MemoryStoreSize::Byte8,
                 isa::Instruction::I32Store16(_) => MemoryStoreSize::Byte16,
-                isa::Instruction::I32Store(_) => MemoryStoreSize::Byte32,
+                isa::Instruction::I32Store(_) | isa::Instruction::F32Store(_) => {
+                    MemoryStoreSize::Byte32
+                }
                 _ => unreachable!(),
             };
 
@@ -866,11 +928,14 @@ pub(super) fn run_instruction_pre(
             })
         }
         isa::Instruction::I64Store(offset)
+        | isa::Instruction::F64Store(offset)
         | isa::Instruction::I64Store8(offset)
         | isa::Instruction::I64Store16(offset)
         | isa::Instruction::I64Store32(offset) => {
             let store_size = match *instructions {
-                isa::Instruction::I64Store(..) => MemoryStoreSize::Byte64,
+                isa::Instruction::I64Store(..) | isa::Instruction::F64Store(..) => {
+                    MemoryStoreSize::Byte64
+                }
                 isa::Instruction::I64Store8(..) => MemoryStoreSize::Byte8,
                 isa::Instruction::I64Store16(..) => MemoryStoreSize::Byte16,
                 isa::Instruction::I64Store32(..) => MemoryStoreSize::Byte32,
@@ -923,9 +988,43 @@ pub(super) fn run_instruction_pre(
         isa::Instruction::GrowMemory => Some(RunInstructionTracePre::GrowMemory(
             <_>::from_value_internal(*value_stack.pick(1)),
         )),
+        isa::Instruction::MemoryFill => {
+            let len = <_>::from_value_internal(*value_stack.pick(1));
+            let value = <_>::from_value_internal(*value_stack.pick(2));
+            let offset = <_>::from_value_internal(*value_stack.pick(3));
+
+            let mut blocks = HashMap::new();
+            let blocks = read_blocks(function_context, offset, len, &mut blocks).map(|_| blocks);
+
+            Some(RunInstructionTracePre::MemoryFill {
+                offset,
+                value,
+                len,
+                blocks,
+            })
+        }
+        isa::Instruction::MemoryCopy => {
+            let len = <_>::from_value_internal(*value_stack.pick(1));
+            let src = <_>::from_value_internal(*value_stack.pick(2));
+            let dst = <_>::from_value_internal(*value_stack.pick(3));
+
+            let mut blocks = HashMap::new();
+            let blocks = read_blocks(function_context, src, len, &mut blocks)
+                .and_then(|_| read_blocks(function_context, dst, len, &mut blocks))
+                .map(|_| blocks);
+
+            Some(RunInstructionTracePre::MemoryCopy {
+                dst,
+                src,
+                len,
+                blocks,
+            })
+        }
 
         isa::Instruction::I32Const(_) => None,
         isa::Instruction::I64Const(_) => None,
+        isa::Instruction::F32Const(_) => None,
+        isa::Instruction::F64Const(_) => None,
 
         isa::Instruction::I32Eqz => Some(RunInstructionTracePre::I32Single(
             <_>::from_value_internal(*value_stack.pick(1)),
@@ -1040,6 +1139,59 @@ pub(super) fn run_instruction_pre(
             value: <_>::from_value_internal(*value_stack.pick(1)),
         }),
 
+        isa::Instruction::F32Min
+        | isa::Instruction::F32Max
+        | isa::Instruction::F32Copysign
+        | isa::Instruction::F64Min
+        | isa::Instruction::F64Max
+        | isa::Instruction::F64Copysign => {
+            let (_, vtype) = float_bin_op(instructions);
+
+            Some(RunInstructionTracePre::FloatBinOp {
+                left: from_value_internal_to_u64_with_typ(vtype.into(), *value_stack.pick(2)),
+                right: from_value_internal_to_u64_with_typ(vtype.into(), *value_stack.pick(1)),
+            })
+        }
+        isa::Instruction::F32Eq
+        | isa::Instruction::F32Ne
+        | isa::Instruction::F32Lt
+        | isa::Instruction::F32Gt
+        | isa::Instruction::F32Le
+        | isa::Instruction::F32Ge
+        | isa::Instruction::F64Eq
+        | isa::Instruction::F64Ne
+        | isa::Instruction::F64Lt
+        | isa::Instruction::F64Gt
+        | isa::Instruction::F64Le
+        | isa::Instruction::F64Ge => {
+            let (_, vtype) = float_rel_op(instructions);
+
+            Some(RunInstructionTracePre::FloatBinOp {
+                left: from_value_internal_to_u64_with_typ(vtype.into(), *value_stack.pick(2)),
+                right: from_value_internal_to_u64_with_typ(vtype.into(), *value_stack.pick(1)),
+            })
+        }
+        isa::Instruction::F32Abs
+        | isa::Instruction::F32Neg
+        | isa::Instruction::F64Abs
+        | isa::Instruction::F64Neg => {
+            let (_, vtype) = float_unary_op(instructions);
+
+            Some(RunInstructionTracePre::FloatSingle(
+                from_value_internal_to_u64_with_typ(vtype.into(), *value_stack.pick(1)),
+            ))
+        }
+        isa::Instruction::I32ReinterpretF32
+        | isa::Instruction::I64ReinterpretF64
+        | isa::Instruction::F32ReinterpretI32
+        | isa::Instruction::F64ReinterpretI64 => {
+            let class = float_conversion_op(instructions);
+
+            Some(RunInstructionTracePre::FloatSingle(
+                from_value_internal_to_u64_with_typ(class.value_type(), *value_stack.pick(1)),
+            ))
+        }
+
         _ => {
             println!("{:?}", *instructions);
             unimplemented!()
@@ -1047,6 +1199,168 @@ pub(super) fn run_instruction_pre(
     }
 }
 
+/// Types of the kept values, the topmost value first. `Keep::Multi` comes
+/// from the multi-value support of the wasmi fork and lists the types bottom
+/// first.
+fn keep_value_types(keep: &Keep) -> Vec<wasmi::ValueType> {
+    match keep {
+        Keep::None => vec![],
+        Keep::Single(t) => vec![*t],
+        Keep::Multi(types) => types.iter().rev().copied().collect(),
+    }
+}
+
+fn keep_types(keep: &Keep) -> Vec<specs::types::ValueType> {
+    keep_value_types(keep)
+        .into_iter()
+        .map(|t| t.into())
+        .collect()
+}
+
+/// Values kept by a `br*`/`return`, read from the stack after the instruction
+/// is executed.
+fn keep_values(keep: &Keep, value_stack: &ValueStack) -> Vec<u64> {
+    keep_value_types(keep)
+        .into_iter()
+        .enumerate()
+        .map(|(depth, t)| {
+            from_value_internal_to_u64_with_typ(t.into(), *value_stack.pick(depth + 1))
+        })
+        .collect()
+}
+
+/// Reads the blocks covering `[address, address + len)` into `blocks`,
+/// returns `None` if the range is out of bounds.
+fn read_blocks(
+    function_context: &FunctionContext,
+    address: u32,
+    len: u32,
+    blocks: &mut HashMap<u32, u64>,
+) -> Option<()> {
+    if len == 0 {
+        return Some(());
+    }
+
+    let last_address = address.checked_add(len - 1)?;
+    let memory = function_context.memory.clone()?;
+
+    for block in address / 8..=last_address / 8 {
+        let mut buf = [0u8; 8];
+        memory.get_into(block * 8, &mut buf).ok()?;
+        blocks.insert(block, u64::from_le_bytes(buf));
+    }
+
+    Some(())
+}
+
+fn write_byte(block_value: u64, address: u32, byte: u8) -> u64 {
+    let shift = (address % 8) * 8;
+
+    (block_value & !(0xff << shift)) | ((byte as u64) << shift)
+}
+
+impl RunInstructionTracePre {
+    /// `memory.fill` and `memory.copy` are traced as one step per aligned
+    /// block, with one step per byte for the unaligned head and tail. Returns
+    /// the steps except the last one with zero length left which is built by
+    /// `run_instruction_post`, or `None` if the range is out of bounds.
+    pub(super) fn bulk_memory_iterations(&self) -> Option<Vec<StepInfo>> {
+        match self {
+            RunInstructionTracePre::MemoryFill {
+                offset,
+                value,
+                len,
+                blocks,
+            } => {
+                let mut blocks = blocks.clone()?;
+                let mut steps = vec![];
+                let (mut offset, mut len) = (*offset, *len);
+
+                while len != 0 {
+                    let is_word = offset % 8 == 0 && len >= 8;
+                    let pre_block_value = blocks[&(offset / 8)];
+                    let updated_block_value = if is_word {
+                        u64::from_le_bytes([*value as u8; 8])
+                    } else {
+                        write_byte(pre_block_value, offset, *value as u8)
+                    };
+                    blocks.insert(offset / 8, updated_block_value);
+
+                    steps.push(StepInfo::MemoryFill {
+                        offset,
+                        value: *value,
+                        len,
+                        is_word,
+                        pre_block_value,
+                        updated_block_value,
+                    });
+
+                    let width = if is_word { 8 } else { 1 };
+                    offset += width;
+                    len -= width;
+                }
+
+                Some(steps)
+            }
+            RunInstructionTracePre::MemoryCopy {
+                dst,
+                src,
+                len,
+                blocks,
+            } => {
+                let mut blocks = blocks.clone()?;
+                let mut steps = vec![];
+                let forward = dst <= src;
+                let (mut dst, mut src, mut len) = (*dst, *src, *len);
+
+                while len != 0 {
+                    let is_word = len >= 8
+                        && if forward {
+                            dst % 8 == 0 && src % 8 == 0
+                        } else {
+                            (dst + len - 1) % 8 == 7 && (src + len - 1) % 8 == 7
+                        };
+                    let width = if is_word { 8 } else { 1 };
+                    let (dst_address, src_address) = if forward {
+                        (dst, src)
+                    } else {
+                        (dst + len - width, src + len - width)
+                    };
+
+                    let src_block_value = blocks[&(src_address / 8)];
+                    let pre_block_value = blocks[&(dst_address / 8)];
+                    let updated_block_value = if is_word {
+                        src_block_value
+                    } else {
+                        let byte = (src_block_value >> ((src_address % 8) * 8)) as u8;
+                        write_byte(pre_block_value, dst_address, byte)
+                    };
+                    blocks.insert(dst_address / 8, updated_block_value);
+
+                    steps.push(StepInfo::MemoryCopy {
+                        dst,
+                        src,
+                        len,
+                        is_word,
+                        src_block_value,
+                        pre_block_value,
+                        updated_block_value,
+                    });
+
+                    if forward {
+                        dst += width;
+                        src += width;
+                    }
+                    len -= width;
+                }
+
+                Some(steps)
+            }
+            _ => Some(vec![]),
+        }
+    }
+}
+
 impl<B: SliceBackendBuilder> TablePlugin<B> {
     pub(super) fn run_instruction_post(
         &self,
@@ -1119,18 +1433,8 @@ impl<B: SliceBackendBuilder> TablePlugin<B> {
             isa::Instruction::Br(target) => StepInfo::Br {
                 dst_pc: target.dst_pc,
                 drop: target.drop_keep.drop,
-                keep: if let Keep::Single(t) = target.drop_keep.keep {
-                    vec![t.into()]
-                } else {
-                    vec![]
-                },
-                keep_values: match target.drop_keep.keep {
-                    Keep::Single(t) => vec![from_value_internal_to_u64_with_typ(
-                        t.into(),
-                        *value_stack.top(),
-                    )],
-                    Keep::None => vec![],
-                },
+                keep: keep_types(&target.drop_keep.keep),
+                keep_values: keep_values(&target.drop_keep.keep, value_stack),
             },
             isa::Instruction::BrIfEqz(target) => {
                 if let RunInstructionTracePre::BrIfEqz { value } = current_event.unwrap() {
@@ -1138,18 +1442,8 @@ impl<B: SliceBackendBuilder> TablePlugin<B> {
                         condition: value,
                         dst_pc: target.dst_pc,
                         drop: target.drop_keep.drop,
-                        keep: if let Keep::Single(t) = target.drop_keep.keep {
-                            vec![t.into()]
-                        } else {
-                            vec![]
-                        },
-                        keep_values: match target.drop_keep.keep {
-                            Keep::Single(t) => vec![from_value_internal_to_u64_with_typ(
-                                t.into(),
-                                *value_stack.top(),
-                            )],
-                            Keep::None => vec![],
-                        },
+                        keep: keep_types(&target.drop_keep.keep),
+                        keep_values: keep_values(&target.drop_keep.keep, value_stack),
                     }
                 } else {
                     unreachable!()
@@ -1161,18 +1455,8 @@ impl<B: SliceBackendBuilder> TablePlugin<B> {
                         condition: value,
                         dst_pc: target.dst_pc,
                         drop: target.drop_keep.drop,
-                        keep: if let Keep::Single(t) = target.drop_keep.keep {
-                            vec![t.into()]
-                        } else {
-                            vec![]
-                        },
-                        keep_values: match target.drop_keep.keep {
-                            Keep::Single(t) => vec![from_value_internal_to_u64_with_typ(
-                                t.into(),
-                                *value_stack.top(),
-                            )],
-                            Keep::None => vec![],
-                        },
+                        keep: keep_types(&target.drop_keep.keep),
+                        keep_values: keep_values(&target.drop_keep.keep, value_stack),
                     }
                 } else {
                     unreachable!()
@@ -1184,18 +1468,11 @@ impl<B: SliceBackendBuilder> TablePlugin<B> {
                         index,
                         dst_pc: targets.get(index as u32).dst_pc,
                         drop: targets.get(index as u32).drop_keep.drop,
-                        keep: if let Keep::Single(t) = targets.get(index as u32).drop_keep.keep {
-                            vec![t.into()]
-                        } else {
-                            vec![]
-                        },
-                        keep_values: match targets.get(index as u32).drop_keep.keep {
-                            Keep::Single(t) => vec![from_value_internal_to_u64_with_typ(
-                                t.into(),
-                                *value_stack.top(),
-                            )],
-                            Keep::None => vec![],
-                        },
+                        keep: keep_types(&targets.get(index as u32).drop_keep.keep),
+                        keep_values: keep_values(
+                            &targets.get(index as u32).drop_keep.keep,
+                            value_stack,
+                        ),
                     }
                 } else {
                     unreachable!()
@@ -1211,18 +1488,8 @@ impl<B: SliceBackendBuilder> TablePlugin<B> {
 
                 StepInfo::Return {
                     drop,
-                    keep: if let Keep::Single(t) = keep {
-                        vec![t.into()]
-                    } else {
-                        vec![]
-                    },
-                    keep_values: match keep {
-                        Keep::Single(t) => vec![from_value_internal_to_u64_with_typ(
-                            t.into(),
-                            *value_stack.top(),
-                        )],
-                        Keep::None => vec![],
-                    },
+                    keep: keep_types(&keep),
+                    keep_values: keep_values(&keep, value_stack),
                 }
             }
 
@@ -1331,11 +1598,13 @@ impl<B: SliceBackendBuilder> TablePlugin<B> {
             }
 
             isa::Instruction::I32Load(..)
+            | isa::Instruction::F32Load(..)
             | isa::Instruction::I32Load8U(..)
             | isa::Instruction::I32Load8S(..)
             | isa::Instruction::I32Load16U(..)
             | isa::Instruction::I32Load16S(..)
             | isa::Instruction::I64Load(..)
+            | isa::Instruction::F64Load(..)
             | isa::Instruction::I64Load8U(..)
             | isa::Instruction::I64Load8S(..)
             | isa::Instruction::I64Load16U(..)
@@ -1393,9 +1662,11 @@ impl<B: SliceBackendBuilder> TablePlugin<B> {
                 }
             }
             isa::Instruction::I32Store(..)
+            | isa::Instruction::F32Store(..)
             | isa::Instruction::I32Store8(..)
             | isa::Instruction::I32Store16(..)
             | isa::Instruction::I64Store(..)
+            | isa::Instruction::F64Store(..)
             | isa::Instruction::I64Store8(..)
             | isa::Instruction::I64Store16(..)
             | isa::Instruction::I64Store32(..) => {
@@ -1453,6 +1724,42 @@ impl<B: SliceBackendBuilder> TablePlugin<B> {
             }
 
             isa::Instruction::CurrentMemory => StepInfo::MemorySize,
+            isa::Instruction::MemoryFill => {
+                if let RunInstructionTracePre::MemoryFill {
+                    offset, value, len, ..
+                } = current_event.unwrap()
+                {
+                    StepInfo::MemoryFill {
+                        offset: offset + len,
+                        value,
+                        len: 0,
+                        is_word: false,
+                        pre_block_value: 0,
+                        updated_block_value: 0,
+                    }
+                } else {
+                    unreachable!()
+                }
+            }
+            isa::Instruction::MemoryCopy => {
+                if let RunInstructionTracePre::MemoryCopy { dst, src, len, .. } =
+                    current_event.unwrap()
+                {
+                    let shift = if dst <= src { len } else { 0 };
+
+                    StepInfo::MemoryCopy {
+                        dst: dst + shift,
+                        src: src + shift,
+                        len: 0,
+                        is_word: false,
+                        src_block_value: 0,
+                        pre_block_value: 0,
+                        updated_block_value: 0,
+                    }
+                } else {
+                    unreachable!()
+                }
+            }
             isa::Instruction::GrowMemory => {
                 if let RunInstructionTracePre::GrowMemory(grow_size) = current_event.unwrap() {
                     StepInfo::MemoryGrow {
@@ -1466,6 +1773,12 @@ impl<B: SliceBackendBuilder> TablePlugin<B> {
 
             isa::Instruction::I32Const(value) => StepInfo::I32Const { value },
             isa::Instruction::I64Const(value) => StepInfo::I64Const { value },
+            isa::Instruction::F32Const(value) => StepInfo::I32Const {
+                value: value as i32,
+            },
+            isa::Instruction::F64Const(value) => StepInfo::I64Const {
+                value: value as i64,
+            },
 
             isa::Instruction::I32Eqz => {
                 if let RunInstructionTracePre::I32Single(value) = current_event.unwrap() {
@@ -2183,6 +2496,95 @@ impl<B: SliceBackendBuilder> TablePlugin<B> {
                 }
             }
 
+            isa::Instruction::F32Min
+            | isa::Instruction::F32Max
+            | isa::Instruction::F32Copysign
+            | isa::Instruction::F64Min
+            | isa::Instruction::F64Max
+            | isa::Instruction::F64Copysign => {
+                if let RunInstructionTracePre::FloatBinOp { left, right } = current_event.unwrap() {
+                    let (class, vtype) = float_bin_op(instructions);
+
+                    StepInfo::FloatBinOp {
+                        class,
+                        vtype,
+                        left,
+                        right,
+                        value: from_value_internal_to_u64_with_typ(
+                            vtype.into(),
+                            *value_stack.top(),
+                        ),
+                    }
+                } else {
+                    unreachable!()
+                }
+            }
+            isa::Instruction::F32Eq
+            | isa::Instruction::F32Ne
+            | isa::Instruction::F32Lt
+            | isa::Instruction::F32Gt
+            | isa::Instruction::F32Le
+            | isa::Instruction::F32Ge
+            | isa::Instruction::F64Eq
+            | isa::Instruction::F64Ne
+            | isa::Instruction::F64Lt
+            | isa::Instruction::F64Gt
+            | isa::Instruction::F64Le
+            | isa::Instruction::F64Ge => {
+                if let RunInstructionTracePre::FloatBinOp { left, right } = current_event.unwrap() {
+                    let (class, vtype) = float_rel_op(instructions);
+
+                    StepInfo::FloatComp {
+                        class,
+                        vtype,
+                        left,
+                        right,
+                        value: <_>::from_value_internal(*value_stack.top()),
+                    }
+                } else {
+                    unreachable!()
+                }
+            }
+            isa::Instruction::F32Abs
+            | isa::Instruction::F32Neg
+            | isa::Instruction::F64Abs
+            | isa::Instruction::F64Neg => {
+                if let RunInstructionTracePre::FloatSingle(operand) = current_event.unwrap() {
+                    let (class, vtype) = float_unary_op(instructions);
+
+                    StepInfo::FloatUnaryOp {
+                        class,
+                        vtype,
+                        operand,
+                        result: from_value_internal_to_u64_with_typ(
+                            vtype.into(),
+                            *value_stack.top(),
+                        ),
+                    }
+                } else {
+                    unreachable!()
+                }
+            }
+            isa::Instruction::I32ReinterpretF32
+            | isa::Instruction::I64ReinterpretF64
+            | isa::Instruction::F32ReinterpretI32
+            | isa::Instruction::F64ReinterpretI64 => {
+                if let RunInstructionTracePre::FloatSingle(value) = current_event.unwrap() {
+                    let class = float_conversion_op(instructions);
+
+                    StepInfo::FloatConversion {
+                        class,
+                        value,
+                        result: from_value_internal_to_u64_with_typ(
+                            class.result_type(),
+                            *value_stack.top(),
+                        ),
+                    }
+                } else {
+                    unreachable!()
+                }
+            }
+
             _ => {
                 println!("{:?}", instructions);
                 unimplemented!()
@@ -2190,3 +2592,53 @@ impl<B: SliceBackendBuilder> TablePlugin<B> {
         }
     }
 }
+
+fn float_bin_op(instruction: &isa::Instruction) -> (FloatBinOp, FloatType) {
+    match instruction {
+        isa::Instruction::F32Min => (FloatBinOp::Min, FloatType::F32),
+        isa::Instruction::F32Max => (FloatBinOp::Max, FloatType::F32),
+        isa::Instruction::F32Copysign => (FloatBinOp::Copysign, FloatType::F32),
+        isa::Instruction::F64Min => (FloatBinOp::Min, FloatType::F64),
+        isa::Instruction::F64Max => (FloatBinOp::Max, FloatType::F64),
+        isa::Instruction::F64Copysign => (FloatBinOp::Copysign, FloatType::F64),
+        _ => unreachable!(),
+    }
+}
+
+fn float_unary_op(instruction: &isa::Instruction) -> (FloatUnaryOp, FloatType) {
+    match instruction {
+        isa::Instruction::F32Abs => (FloatUnaryOp::Abs, FloatType::F32),
+        isa::Instruction::F32Neg => (FloatUnaryOp::Neg, FloatType::F32),
+        isa::Instruction::F64Abs => (FloatUnaryOp::Abs, FloatType::F64),
+        isa::Instruction::F64Neg => (FloatUnaryOp::Neg, FloatType::F64),
+        _ => unreachable!(),
+    }
+}
+
+fn float_rel_op(instruction: &isa::Instruction) -> (FloatRelOp, FloatType) {
+    match instruction {
+        isa::Instruction::F32Eq => (FloatRelOp::Eq, FloatType::F32),
+        isa::Instruction::F32Ne => (FloatRelOp::Ne, FloatType::F32),
+        isa::Instruction::F32Lt => (FloatRelOp::Lt, FloatType::F32),
+        isa::Instruction::F32Gt => (FloatRelOp::Gt, FloatType::F32),
+        isa::Instruction::F32Le => (FloatRelOp::Le, FloatType::F32),
+        isa::Instruction::F32Ge => (FloatRelOp::Ge, FloatType::F32),
+        isa::Instruction::F64Eq => (FloatRelOp::Eq, FloatType::F64),
+        isa::Instruction::F64Ne => (FloatRelOp::Ne, FloatType::F64),
+        isa::Instruction::F64Lt => (FloatRelOp::Lt, FloatType::F64),
+        isa::Instruction::F64Gt => (FloatRelOp::Gt, FloatType::F64),
+        isa::Instruction::F64Le => (FloatRelOp::Le, FloatType::F64),
+        isa::Instruction::F64Ge => (FloatRelOp::Ge, FloatType::F64),
+        _ => unreachable!(),
+    }
+}
+
+fn float_conversion_op(instruction: &isa::Instruction) -> FloatConversionOp {
+    match instruction {
+        isa::Instruction::I32ReinterpretF32 => FloatConversionOp::I32ReinterpretF32,
+        isa::Instruction::I64ReinterpretF64 => FloatConversionOp::I64ReinterpretF64,
+        isa::Instruction::F32ReinterpretI32 => FloatConversionOp::F32ReinterpretI32,
+        isa::Instruction::F64ReinterpretI64 => FloatConversionOp::F64ReinterpretI64,
+        _ => unreachable!(),
+    }
+}
@@ -42,6 +42,7 @@ use wasmi::Trap;
 use wasmi::DEFAULT_VALUE_STACK_LIMIT;
 
 use crate::circuits::compute_slice_capability;
+use crate::error::ExecutionError;
 use crate::foreign::context::try_get_context_input_from_step_info;
 use crate::foreign::context::try_get_context_output_from_step_info;
 
@@ -52,6 +53,7 @@ use self::instruction::PhantomFunction;
 use self::instruction::RunInstructionTracePre;
 
 use super::phantom::PhantomHelper;
+use super::tracer::TracerPlugin;
 
 pub mod transaction;
 
@@ -116,6 +118,9 @@ pub struct TablePlugin<B: SliceBackendBuilder> {
     module_ref: Option<wasmi::ModuleRef>,
     unresolved_event: Option<RunInstructionTracePre>,
     unresolved_host_call: Option<EventTableEntry>,
+
+    slice_limit: Option<usize>,
+    tracer: Option<TracerPlugin>,
 }
 
 impl<B: SliceBackendBuilder> TablePlugin<B> {
@@ -126,6 +131,7 @@ impl<B: SliceBackendBuilder> TablePlugin<B> {
         host_function_desc: HashMap<usize, HostFunctionDesc>,
         phantom_regex: &[String],
         wasm_input: FuncRef,
+        slice_limit: Option<usize>,
     ) -> Self {
         let capacity = compute_slice_capability(k);
 
@@ -155,9 +161,20 @@ impl<B: SliceBackendBuilder> TablePlugin<B> {
             module_ref: None,
             unresolved_event: None,
             unresolved_host_call: None,
+
+            slice_limit,
+            tracer: None,
         }
     }
 
+    pub fn set_tracer(&mut self, tracer: TracerPlugin) {
+        self.tracer = Some(tracer);
+    }
+
+    pub fn take_tracer(&mut self) -> Option<TracerPlugin> {
+        self.tracer.take()
+    }
+
     pub fn into_compilation_table(&self) -> CompilationTable {
         let itable: InstructionTable = self.itable.clone().into();
         let imtable = InitMemoryTable::new(self.init_memory_table.clone());
@@ -238,6 +255,10 @@ impl<B: SliceBackendBuilder> TablePlugin<B> {
             step_info,
         };
 
+        if let Some(tracer) = self.tracer.as_mut() {
+            tracer.trace(&self.itable.get(fid, iid).opcode, &event);
+        }
+
         self.host_transaction.push_event(event);
     }
 
@@ -353,6 +374,10 @@ impl<B: SliceBackendBuilder> Monitor for TablePlugin<B> {
         self.phantom_helper
             .register_module(module, module_ref, entry)?;
 
+        if let Some(tracer) = self.tracer.as_mut() {
+            tracer.register_module(module);
+        }
+
         let mut type_idx_of_func = vec![];
 
         // register static frame entries
@@ -609,6 +634,25 @@ impl<B: SliceBackendBuilder> Monitor for TablePlugin<B> {
         if !self.phantom_helper.is_in_phantom_function() {
             let current_event = self.unresolved_event.take();
 
+            if let Some(event) = current_event.as_ref() {
+                let iterations = event.bulk_memory_iterations().ok_or_else(|| {
+                    Trap::Host(Box::new(ExecutionError::MemoryAccessOutOfBounds {
+                        eid: self.eid + 1,
+                    }))
+                })?;
+
+                for step_info in iterations {
+                    self.append_log(
+                        fid,
+                        iid,
+                        sp,
+                        allocated_memory_pages,
+                        *self.last_jump_eid.last().unwrap(),
+                        step_info,
+                    );
+                }
+            }
+
             let step_info = self.run_instruction_post(
                 self.module_ref.as_ref().unwrap(),
                 current_event,
@@ -710,6 +754,18 @@ impl<B: SliceBackendBuilder> Monitor for TablePlugin<B> {
             _ => {}
         }
 
+        if let Some(limit) = self.slice_limit {
+            let slices = self.host_transaction.committed_slices();
+
+            if slices > limit {
+                return Err(Trap::Host(Box::new(ExecutionError::SliceLimitExceeded {
+                    limit,
+                    slices,
+                    eid: self.eid,
+                })));
+            }
+        }
+
         Ok(())
     }
 
@@ -12,5 +12,6 @@ pub(super) trait TransactionSlicer<B: SliceBackendBuilder> {
     fn push_event(&mut self, event: EventTableEntry);
     fn frame_table_builder_get(&self) -> &FrameTableBuilder;
     fn frame_table_builder_get_mut(&mut self) -> &mut FrameTableBuilder;
+    fn committed_slices(&self) -> usize;
     fn finalize(self) -> Vec<B::Output>;
 }
@@ -616,4 +616,8 @@ impl<B: SliceBackendBuilder> TransactionSlicer<B> for HostTransaction<B> {
     fn frame_table_builder_get_mut(&mut self) -> &mut FrameTableBuilder {
         &mut self.slice_builder.frame_table_builder
     }
+
+    fn committed_slices(&self) -> usize {
+        self.slices.len()
+    }
 }
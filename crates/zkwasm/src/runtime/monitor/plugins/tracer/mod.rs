@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io;
+use std::io::Write;
+use std::ops::RangeInclusive;
+
+use parity_wasm::elements::Module;
+use serde::Serialize;
+use specs::etable::EventTableEntry;
+use specs::itable::Opcode;
+use specs::mtable::AccessType;
+use specs::mtable::LocationType;
+use specs::mtable::MemoryTableEntry;
+use specs::mtable::VarType;
+
+use crate::profile::function_names;
+use crate::runtime::memory_event_of_step;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TraceFormat {
+    #[default]
+    Text,
+    /// One JSON object per line.
+    JsonLines,
+}
+
+/// Steps to be traced, all steps are traced by default.
+#[derive(Debug, Clone, Default)]
+pub struct TraceFilter {
+    /// Names or indexes of the traced functions, empty means all functions.
+    pub functions: Vec<String>,
+    pub eids: Option<RangeInclusive<u32>>,
+}
+
+impl TraceFilter {
+    fn is_traced(&self, fid: u32, name: Option<&str>, eid: u32) -> bool {
+        let eid_matched = self.eids.as_ref().map_or(true, |eids| eids.contains(&eid));
+
+        let function_matched = self.functions.is_empty()
+            || self
+                .functions
+                .iter()
+                .any(|function| Some(function.as_str()) == name || *function == fid.to_string());
+
+        eid_matched && function_matched
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Access {
+    pub atype: AccessType,
+    pub ltype: LocationType,
+    pub vtype: VarType,
+    pub offset: u32,
+    pub value: u64,
+}
+
+impl From<&MemoryTableEntry> for Access {
+    fn from(entry: &MemoryTableEntry) -> Self {
+        Access {
+            atype: entry.atype,
+            ltype: entry.ltype,
+            vtype: entry.vtype,
+            offset: entry.offset,
+            value: entry.value,
+        }
+    }
+}
+
+impl Display for Access {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let atype = match self.atype {
+            AccessType::Read => "r",
+            AccessType::Write => "w",
+            AccessType::Init => "init",
+        };
+        let ltype = match self.ltype {
+            LocationType::Stack => "stack",
+            LocationType::Heap => "heap",
+            LocationType::Global => "global",
+        };
+        let vtype = match self.vtype {
+            VarType::I32 => "i32",
+            VarType::I64 => "i64",
+        };
+
+        write!(
+            f,
+            "{} {}[{}]:{}={:#x}",
+            atype, ltype, self.offset, vtype, self.value
+        )
+    }
+}
+
+/// A traced step, `stack` holds the operands and results of the step and
+/// `memory` holds its heap and global accesses.
+#[derive(Debug, Serialize)]
+pub struct TraceRecord<'a> {
+    pub eid: u32,
+    pub fid: u32,
+    pub function: Option<&'a str>,
+    pub iid: u32,
+    pub opcode: &'a Opcode,
+    pub sp: u32,
+    pub stack: Vec<Access>,
+    pub memory: Vec<Access>,
+}
+
+impl Display for TraceRecord<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let join = |accesses: &[Access]| {
+            accesses
+                .iter()
+                .map(|access| access.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        write!(f, "{} ", self.eid)?;
+        match self.function {
+            Some(name) => write!(f, "{}", name)?,
+            None => write!(f, "func[{}]", self.fid)?,
+        }
+        write!(
+            f,
+            ":{} sp={} {:?} stack=[{}] memory=[{}]",
+            self.iid,
+            self.sp,
+            self.opcode,
+            join(&self.stack),
+            join(&self.memory)
+        )
+    }
+}
+
+/// Writes each step of the execution as one line.
+pub struct TracerPlugin {
+    writer: Box<dyn Write>,
+    format: TraceFormat,
+    filter: TraceFilter,
+    function_names: HashMap<u32, String>,
+    // The first write error, reported by `finalize`.
+    error: Option<io::Error>,
+}
+
+impl TracerPlugin {
+    pub fn new(writer: Box<dyn Write>, format: TraceFormat, filter: TraceFilter) -> Self {
+        TracerPlugin {
+            writer,
+            format,
+            filter,
+            function_names: HashMap::new(),
+            error: None,
+        }
+    }
+
+    pub(crate) fn register_module(&mut self, module: &Module) {
+        self.function_names = function_names(module);
+    }
+
+    pub(crate) fn trace(&mut self, opcode: &Opcode, entry: &EventTableEntry) {
+        if self.error.is_some() {
+            return;
+        }
+
+        let function = self
+            .function_names
+            .get(&entry.fid)
+            .map(|name| name.as_str());
+        if !self.filter.is_traced(entry.fid, function, entry.eid) {
+            return;
+        }
+
+        let (stack, memory): (Vec<_>, Vec<_>) = memory_event_of_step(entry)
+            .iter()
+            .map(Access::from)
+            .partition(|access| access.ltype == LocationType::Stack);
+
+        let record = TraceRecord {
+            eid: entry.eid,
+            fid: entry.fid,
+            function,
+            iid: entry.iid,
+            opcode,
+            sp: entry.sp,
+            stack,
+            memory,
+        };
+
+        let result = match self.format {
+            TraceFormat::Text => writeln!(self.writer, "{}", record),
+            TraceFormat::JsonLines => serde_json::to_writer(&mut self.writer, &record)
+                .map_err(io::Error::from)
+                .and_then(|_| writeln!(self.writer)),
+        };
+
+        self.error = result.err();
+    }
+
+    /// Flushes the trace and reports the first write error if any.
+    pub fn finalize(mut self) -> io::Result<()> {
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+
+        self.writer.flush()
+    }
+}
@@ -16,6 +16,8 @@ use wasmi::ValueType;
 
 use crate::runtime::host::host_env::HostEnv;
 
+use super::backtrace::Backtrace;
+use super::limits::ExecutionLimits;
 use super::observer::Observer;
 use super::plugins::statistic::StatisticPlugin;
 use super::WasmiMonitor;
@@ -34,7 +36,14 @@ impl StatisticMonitor {
             .expect("Failed to resolve wasm_input function, please make sure it is imported in the wasm image.");
 
         Self {
-            statistic_plugin: StatisticPlugin::new(phantom_regex, wasm_input, instruction_limit),
+            statistic_plugin: StatisticPlugin::new(
+                phantom_regex,
+                wasm_input,
+                ExecutionLimits {
+                    instructions: instruction_limit,
+                    ..ExecutionLimits::default()
+                },
+            ),
         }
     }
 }
@@ -104,4 +113,8 @@ impl WasmiMonitor for StatisticMonitor {
     fn expose_observer(&self) -> Rc<RefCell<Observer>> {
         self.statistic_plugin.expose_observer()
     }
+
+    fn expose_backtrace(&self) -> Backtrace {
+        self.statistic_plugin.backtrace()
+    }
 }
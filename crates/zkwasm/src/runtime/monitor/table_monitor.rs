@@ -19,10 +19,13 @@ use wasmi::ValueType;
 
 use crate::runtime::host::host_env::HostEnv;
 
+use super::backtrace::Backtrace;
+use super::limits::ExecutionLimits;
 use super::observer::Observer;
 use super::plugins::statistic::StatisticPlugin;
 use super::plugins::table::FlushStrategy;
 use super::plugins::table::TablePlugin;
+use super::plugins::tracer::TracerPlugin;
 use super::WasmiMonitor;
 
 pub struct TableMonitor<B: SliceBackendBuilder> {
@@ -37,6 +40,7 @@ impl<B: SliceBackendBuilder> TableMonitor<B> {
         flush_strategy: Box<dyn FlushStrategy>,
         phantom_regex: &[String],
         env: &HostEnv,
+        limits: ExecutionLimits,
     ) -> Self {
         let wasm_input = env
             .resolve_func(
@@ -53,11 +57,22 @@ impl<B: SliceBackendBuilder> TableMonitor<B> {
                 env.function_description_table(),
                 phantom_regex,
                 wasm_input.clone(),
+                limits.slices,
             ),
-            statistic_plugin: StatisticPlugin::new(phantom_regex, wasm_input, None),
+            statistic_plugin: StatisticPlugin::new(phantom_regex, wasm_input, limits),
         }
     }
 
+    /// Traces each step of the execution with `tracer`.
+    pub fn with_tracer(mut self, tracer: TracerPlugin) -> Self {
+        self.table_plugin.set_tracer(tracer);
+        self
+    }
+
+    pub fn take_tracer(&mut self) -> Option<TracerPlugin> {
+        self.table_plugin.take_tracer()
+    }
+
     pub fn into_compilation_table(self) -> CompilationTable {
         self.table_plugin.into_compilation_table()
     }
@@ -148,4 +163,8 @@ impl<B: SliceBackendBuilder> WasmiMonitor for TableMonitor<B> {
     fn expose_observer(&self) -> Rc<RefCell<Observer>> {
         self.statistic_plugin.expose_observer()
     }
+
+    fn expose_backtrace(&self) -> Backtrace {
+        self.statistic_plugin.backtrace()
+    }
 }
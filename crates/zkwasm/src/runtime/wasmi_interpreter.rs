@@ -5,6 +5,8 @@ use wasmi::ImportResolver;
 use wasmi::ModuleInstance;
 use wasmi::RuntimeValue;
 
+use crate::error::CompilationError;
+use crate::error::ExecutionError;
 use crate::foreign::context::ContextOutput;
 
 use super::host::host_env::ExecEnv;
@@ -13,6 +15,18 @@ use super::monitor::WasmiMonitor;
 use super::CompiledImage;
 use super::ExecutionResult;
 
+/// Surfaces the `ExecutionError` raised by a host function instead of the wasmi
+/// trap wrapping it.
+fn into_execution_error(err: wasmi::Error) -> anyhow::Error {
+    match err
+        .as_host_error()
+        .and_then(|host_error| host_error.downcast_ref::<ExecutionError>())
+    {
+        Some(execution_error) => anyhow::Error::new(execution_error.clone()),
+        None => anyhow::Error::new(err),
+    }
+}
+
 pub trait Execution<R> {
     fn run(self, monitor: &mut dyn WasmiMonitor, externals: HostEnv) -> Result<ExecutionResult<R>>;
 }
@@ -30,14 +44,19 @@ impl Execution<RuntimeValue> for CompiledImage<wasmi::NotStartedModuleRef<'_>> {
         let instance = self
             .instance
             .run_start_tracer(&mut exec_env, monitor)
-            .unwrap();
+            .map_err(|err| into_execution_error(err).context(monitor.expose_backtrace()))?;
+
+        let result = instance
+            .invoke_export_trace(&self.entry, &[], &mut exec_env, monitor)
+            .map_err(|err| into_execution_error(err).context(monitor.expose_backtrace()))?;
 
-        let result = instance.invoke_export_trace(&self.entry, &[], &mut exec_env, monitor)?;
+        exec_env.host_env.finish_replay()?;
 
         let host_statics = exec_env
             .host_env
             .external_env
             .get_statics(exec_env.host_env.k);
+        let state_diffs = exec_env.host_env.external_env.get_state_diffs();
         let public_inputs_and_outputs = exec_env
             .host_env
             .internal_env
@@ -66,6 +85,7 @@ impl Execution<RuntimeValue> for CompiledImage<wasmi::NotStartedModuleRef<'_>> {
             public_inputs_and_outputs,
             outputs,
             context_outputs,
+            state_diffs,
         })
     }
 }
@@ -79,8 +99,8 @@ impl WasmiRuntime {
         imports: &I,
         entry: &str,
     ) -> Result<CompiledImage<wasmi::NotStartedModuleRef<'a>>> {
-        let instance =
-            ModuleInstance::new(module, imports).expect("failed to instantiate wasm module");
+        let instance = ModuleInstance::new(module, imports)
+            .map_err(|err| CompilationError::Instantiation(err.to_string()))?;
         monitor.register_module(instance.loaded_module.module(), &instance.instance, entry)?;
 
         Ok(CompiledImage {
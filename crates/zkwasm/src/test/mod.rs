@@ -3,6 +3,7 @@ use crate::loader::ZkWasmLoader;
 use crate::runtime::host::default_env::DefaultHostEnvBuilder;
 use crate::runtime::host::default_env::ExecutionArg;
 use crate::runtime::host::HostEnvBuilder;
+use crate::runtime::monitor::limits::ExecutionLimits;
 use crate::runtime::monitor::table_monitor::TableMonitor;
 
 use anyhow::Result;
@@ -14,10 +15,15 @@ use wabt::Features;
 mod test_wasm_instructions;
 
 mod spec;
+mod test_execution_error;
+mod test_precheck;
+mod test_profile;
+mod test_replay;
 mod test_rlp;
 #[cfg(feature = "continuation")]
 mod test_rlp_slice;
 mod test_start;
+mod test_tracer;
 
 /// Run the function and generate trace, then test circuit with mock prover.
 pub fn test_circuit_with_env(
@@ -27,7 +33,7 @@ pub fn test_circuit_with_env(
     public_inputs: Vec<u64>,
     private_inputs: Vec<u64>,
 ) -> Result<()> {
-    let module = ZkWasmLoader::parse_module(&wasm)?;
+    let module = ZkWasmLoader::parse_module_with_entry(&wasm, k, &[], &function_name)?;
     let env_builder = DefaultHostEnvBuilder::new(k);
 
     let env = env_builder.create_env(ExecutionArg {
@@ -43,6 +49,7 @@ pub fn test_circuit_with_env(
         env_builder.create_flush_strategy(),
         &vec![],
         &env,
+        ExecutionLimits::default(),
     );
     let mut loader = ZkWasmLoader::new(k, env)?;
     loader.set_entry(function_name);
@@ -57,15 +64,22 @@ pub fn test_circuit_with_env(
     Ok(())
 }
 
+/// Wasm proposals supported by the circuits.
+fn wabt_features() -> Features {
+    let mut features = Features::new();
+    features.enable_sign_extension();
+    features.enable_bulk_memory();
+    features.enable_multi_value();
+
+    features
+}
+
 /// Run test function and generate trace, then test circuit with mock prover. Only tests should
 /// use this function.
 fn test_circuit_noexternal(textual_repr: &str) -> Result<()> {
     use crate::circuits::MIN_K;
 
-    let mut features = Features::new();
-    features.enable_sign_extension();
-
-    let wasm = wat2wasm_with_features(textual_repr, features).expect("failed to parse wat");
+    let wasm = wat2wasm_with_features(textual_repr, wabt_features()).expect("failed to parse wat");
 
     test_circuit_with_env(MIN_K, wasm, "test".to_string(), vec![], vec![])?;
 
@@ -1,94 +1,532 @@
-/*
-    use wast::Error;
-
-    #[cfg(test)]
-    fn run_spec_test(_file_name: &str) -> Result<(), Error> {
-        todo!();
-
-        /*
-        let path = format!("src/test/spec/{}.wast", file_name);
-        let file = fs::read_to_string(&path).unwrap();
-
-        let mut lexer = Lexer::new(&file);
-        lexer.allow_confusing_unicode(true);
-        let parse_buffer = ParseBuffer::new_with_lexer(lexer)?;
-
-        let wast: Wast = wast::parser::parse(&parse_buffer)?;
-        let imports = ImportsBuilder::default();
-        let mut externals = NopExternals;
-
-        let compiler = WasmInterpreter::new();
-        let mut compile_outcome = None;
-
-        for directive in wast.directives {
-            match directive {
-                wast::WastDirective::Wat(wat) => match wat {
-                    wast::QuoteWat::Wat(wat) => match wat {
-                        wast::Wat::Module(module) => {
-                            let compiled = compiler
-                                .compile_from_wast(module, &imports, &HashMap::default())
-                                .unwrap();
-                            compile_outcome = Some(compiled);
+//! Runs the official WebAssembly spec tests (`.wast`) against the circuits.
+//!
+//! All `assert_return` and `invoke` directives following a module are compiled
+//! into a single entry function appended to that module. The entry function
+//! calls every exported function in order and hits `unreachable` if a result
+//! differs from the expected one, then the trace of the entry function is
+//! checked by the mock prover. `assert_trap` only requires the execution to
+//! trap because a trapped execution has no complete trace to prove.
+
+use std::fs;
+
+use anyhow::anyhow;
+use anyhow::Result;
+use parity_wasm::elements::BlockType;
+use parity_wasm::elements::ExportEntry;
+use parity_wasm::elements::External;
+use parity_wasm::elements::Func;
+use parity_wasm::elements::FuncBody;
+use parity_wasm::elements::FunctionType;
+use parity_wasm::elements::Instruction;
+use parity_wasm::elements::Instructions;
+use parity_wasm::elements::Internal;
+use parity_wasm::elements::Local;
+use parity_wasm::elements::Module;
+use parity_wasm::elements::Type;
+use parity_wasm::elements::ValueType;
+use specs::slice_backend::InMemoryBackendBuilder;
+use wabt::script::Action;
+use wabt::script::CommandKind;
+use wabt::script::ScriptParser;
+use wabt::script::Value;
+
+use crate::circuits::MIN_K;
+use crate::loader::err::Error;
+use crate::loader::err::PreCheckErr;
+use crate::loader::ZkWasmLoader;
+use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+use crate::runtime::host::default_env::ExecutionArg;
+use crate::runtime::host::HostEnvBuilder;
+use crate::runtime::monitor::limits::ExecutionLimits;
+use crate::runtime::monitor::table_monitor::TableMonitor;
+use crate::test::test_circuit_with_env;
+use crate::test::wabt_features;
+
+const ENTRY: &str = "zkwasm_spec_entry";
+
+/// Directives known to fail, as `(file, line, reason)`. A listed directive
+/// that passes is reported as a failure so that the list stays accurate.
+const EXPECTED_FAILURES: &[(&str, u64, &str)] = &[];
+
+#[derive(Clone)]
+struct Invoke {
+    line: u64,
+    field: String,
+    args: Vec<Value>,
+    /// `None` if the results are dropped without being checked.
+    expected: Option<Vec<Value>>,
+}
+
+struct SpecModule {
+    line: u64,
+    name: Option<String>,
+    wasm: Vec<u8>,
+    /// Reason for skipping all directives on this module.
+    unsupported: Option<String>,
+    invokes: Vec<Invoke>,
+}
+
+#[derive(Default)]
+struct SpecTestReport {
+    passed: usize,
+    skipped: Vec<(u64, String)>,
+    failed: Vec<(u64, String)>,
+}
+
+impl SpecTestReport {
+    fn skip(&mut self, line: u64, reason: impl Into<String>) {
+        self.skipped.push((line, reason.into()));
+    }
+}
+
+fn const_instruction(value: &Value) -> Instruction {
+    match value {
+        Value::I32(v) => Instruction::I32Const(*v),
+        Value::I64(v) => Instruction::I64Const(*v),
+        Value::F32(v) => Instruction::F32Const(v.to_bits()),
+        Value::F64(v) => Instruction::F64Const(v.to_bits()),
+        Value::V128(_) => unreachable!("v128 values are skipped"),
+    }
+}
+
+/// Leaves 1 on the stack if the top of the stack differs from `expected`.
+/// Floats are compared by bits.
+fn ne_instructions(expected: &Value) -> Vec<Instruction> {
+    match expected {
+        Value::I32(v) => vec![Instruction::I32Const(*v), Instruction::I32Ne],
+        Value::I64(v) => vec![Instruction::I64Const(*v), Instruction::I64Ne],
+        Value::F32(v) => vec![
+            Instruction::I32ReinterpretF32,
+            Instruction::I32Const(v.to_bits() as i32),
+            Instruction::I32Ne,
+        ],
+        Value::F64(v) => vec![
+            Instruction::I64ReinterpretF64,
+            Instruction::I64Const(v.to_bits() as i64),
+            Instruction::I64Ne,
+        ],
+        Value::V128(_) => unreachable!("v128 values are skipped"),
+    }
+}
+
+/// Appends the exported entry function which performs `invokes` in order.
+fn with_spec_entry(wasm: &[u8], invokes: &[Invoke]) -> Result<Vec<u8>> {
+    let mut module: Module = parity_wasm::deserialize_buffer(wasm)?;
+
+    let types: Vec<FunctionType> = module
+        .type_section()
+        .map(|section| {
+            section
+                .types()
+                .iter()
+                .map(|Type::Function(t)| t.clone())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut function_types: Vec<u32> = module
+        .import_section()
+        .map(|section| {
+            section
+                .entries()
+                .iter()
+                .filter_map(|entry| match entry.external() {
+                    External::Function(type_ref) => Some(*type_ref),
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    function_types.extend(
+        module
+            .function_section()
+            .map(|section| {
+                section
+                    .entries()
+                    .iter()
+                    .map(|f| f.type_ref())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default(),
+    );
+
+    let function_index = |field: &str| -> Result<u32> {
+        module
+            .export_section()
+            .and_then(|section| {
+                section
+                    .entries()
+                    .iter()
+                    .find_map(|entry| match entry.internal() {
+                        Internal::Function(index) if entry.field() == field => Some(*index),
+                        _ => None,
+                    })
+            })
+            .ok_or_else(|| anyhow!("function {} is not exported", field))
+    };
+
+    let mut locals: Vec<ValueType> = vec![];
+    let mut body = vec![];
+
+    for invoke in invokes {
+        let index = function_index(&invoke.field)?;
+        let results = types[function_types[index as usize] as usize].results();
+
+        body.extend(invoke.args.iter().map(const_instruction));
+        body.push(Instruction::Call(index));
+
+        match &invoke.expected {
+            None => body.extend(results.iter().map(|_| Instruction::Drop)),
+            Some(expected) => {
+                if expected.len() != results.len() {
+                    return Err(anyhow!(
+                        "line {}: expect {} results but {} returns {}",
+                        invoke.line,
+                        expected.len(),
+                        invoke.field,
+                        results.len()
+                    ));
+                }
+
+                let base = locals.len() as u32;
+                locals.extend_from_slice(results);
+
+                for i in (0..results.len() as u32).rev() {
+                    body.push(Instruction::SetLocal(base + i));
+                }
+
+                for (i, value) in expected.iter().enumerate() {
+                    body.push(Instruction::GetLocal(base + i as u32));
+                    body.extend(ne_instructions(value));
+                    body.push(Instruction::If(BlockType::NoResult));
+                    body.push(Instruction::Unreachable);
+                    body.push(Instruction::End);
+                }
+            }
+        }
+    }
+    body.push(Instruction::End);
+
+    let entry_index = function_types.len() as u32;
+    let entry_type = types.len() as u32;
+
+    module
+        .type_section_mut()
+        .ok_or_else(|| anyhow!("no type section"))?
+        .types_mut()
+        .push(Type::Function(FunctionType::new(vec![], vec![])));
+    module
+        .function_section_mut()
+        .ok_or_else(|| anyhow!("no function section"))?
+        .entries_mut()
+        .push(Func::new(entry_type));
+    module
+        .code_section_mut()
+        .ok_or_else(|| anyhow!("no code section"))?
+        .bodies_mut()
+        .push(FuncBody::new(
+            locals.into_iter().map(|t| Local::new(1, t)).collect(),
+            Instructions::new(body),
+        ));
+    module
+        .export_section_mut()
+        .ok_or_else(|| anyhow!("no export section"))?
+        .entries_mut()
+        .push(ExportEntry::new(
+            ENTRY.to_owned(),
+            Internal::Function(entry_index),
+        ));
+
+    Ok(parity_wasm::serialize(module)?)
+}
+
+/// Runs the entry function without proving it.
+fn execute(wasm: Vec<u8>) -> Result<()> {
+    let module = ZkWasmLoader::parse_module_with_entry(&wasm, MIN_K, &[], ENTRY)?;
+    let env_builder = DefaultHostEnvBuilder::new(MIN_K);
+
+    let env = env_builder.create_env(ExecutionArg {
+        public_inputs: vec![],
+        private_inputs: vec![],
+        context_inputs: vec![],
+        indexed_witness: Default::default(),
+        tree_db: None,
+    });
+    let mut monitor = TableMonitor::new(
+        MIN_K,
+        InMemoryBackendBuilder,
+        env_builder.create_flush_strategy(),
+        &vec![],
+        &env,
+        ExecutionLimits::default(),
+    );
+    let mut loader = ZkWasmLoader::new(MIN_K, env)?;
+    loader.set_entry(ENTRY.to_owned());
+
+    let runner = loader.compile(&module, &mut monitor)?;
+    loader.run(runner, &mut monitor)?;
+
+    Ok(())
+}
+
+/// `invokes` with their results dropped without being checked.
+fn replay(invokes: &[Invoke]) -> Vec<Invoke> {
+    invokes
+        .iter()
+        .map(|invoke| Invoke {
+            expected: None,
+            ..invoke.clone()
+        })
+        .collect()
+}
+
+fn prove_module(module: SpecModule, report: &mut SpecTestReport) {
+    if let Some(reason) = &module.unsupported {
+        for invoke in &module.invokes {
+            report.skip(invoke.line, reason);
+        }
+        return;
+    }
+
+    if module.invokes.is_empty() {
+        return;
+    }
+
+    let result = with_spec_entry(&module.wasm, &module.invokes)
+        .and_then(|wasm| test_circuit_with_env(MIN_K, wasm, ENTRY.to_owned(), vec![], vec![]));
+
+    match result {
+        Ok(()) => report.passed += module.invokes.len(),
+        Err(err) => {
+            // Find the failing directives by executing them one by one.
+            let mut failed = vec![];
+            for (index, invoke) in module.invokes.iter().enumerate() {
+                let mut invokes = replay(&module.invokes[..index]);
+                invokes.push(invoke.clone());
+
+                if let Err(err) = with_spec_entry(&module.wasm, &invokes).and_then(execute) {
+                    failed.push((invoke.line, err.to_string()));
+                }
+            }
+
+            if failed.is_empty() {
+                failed.push((module.line, format!("circuit of module fails: {}", err)));
+            } else {
+                report.passed += module.invokes.len() - failed.len();
+            }
+
+            report.failed.append(&mut failed);
+        }
+    }
+}
+
+fn check_trap(module: &SpecModule, invoke: Invoke, report: &mut SpecTestReport) {
+    let line = invoke.line;
+
+    if let Some(reason) = &module.unsupported {
+        report.skip(line, reason);
+        return;
+    }
+
+    let mut invokes = replay(&module.invokes);
+    invokes.push(invoke);
+
+    match with_spec_entry(&module.wasm, &invokes).map(execute) {
+        Ok(Err(_)) => report.passed += 1,
+        Ok(Ok(())) => report.failed.push((line, "expect a trap".to_owned())),
+        Err(err) => report.failed.push((line, err.to_string())),
+    }
+}
+
+fn has_unsupported_value(values: &[Value]) -> bool {
+    values.iter().any(|value| matches!(value, Value::V128(_)))
+}
+
+fn run_spec_test(file_name: &str) -> Result<SpecTestReport> {
+    let path = format!(
+        "{}/src/test/spec/{}.wast",
+        env!("CARGO_MANIFEST_DIR"),
+        file_name
+    );
+    let source = fs::read(&path)?;
+    let mut parser =
+        ScriptParser::from_source_and_name_with_features(&source, &path, wabt_features())?;
+
+    let mut report = SpecTestReport::default();
+    let mut current: Option<SpecModule> = None;
+
+    while let Some(command) = parser.next()? {
+        let line = command.line;
+
+        match command.kind {
+            CommandKind::Module { module, name } => {
+                if let Some(module) = current.take() {
+                    prove_module(module, &mut report);
+                }
+
+                let wasm = module.into_vec();
+                let unsupported = parity_wasm::deserialize_buffer::<Module>(&wasm)
+                    .map_err(|err| err.to_string())
+                    .and_then(|module| {
+                        match module.import_section().and_then(|section| {
+                            section
+                                .entries()
+                                .iter()
+                                .find(|entry| entry.module() != "env")
+                        }) {
+                            Some(entry) => Err(format!("imports from {}", entry.module())),
+                            None => Ok(()),
                         }
-                        wast::Wat::Component(_) => todo!(),
-                    },
-                    wast::QuoteWat::QuoteModule(_, _) => todo!(),
-                    wast::QuoteWat::QuoteComponent(_, _) => todo!(),
-                },
-                wast::WastDirective::AssertMalformed { .. } => todo!(),
-                wast::WastDirective::AssertInvalid { .. } => todo!(),
-                wast::WastDirective::Register { .. } => todo!(),
-                wast::WastDirective::Invoke(..) => todo!(),
-                wast::WastDirective::AssertTrap { .. } => todo!(),
-                wast::WastDirective::AssertReturn {
-                    span: _span,
-                    exec,
-                    results: _results,
-                } => {
-                    let from_wastarg = |arg: &WastArg| match arg {
-                        WastArg::Core(core) => match core {
-                            wast::core::WastArgCore::I32(v) => Value::I32(*v),
-                            wast::core::WastArgCore::I64(v) => Value::I64(*v),
-                            wast::core::WastArgCore::F32(_) => todo!(),
-                            wast::core::WastArgCore::F64(_) => todo!(),
-                            wast::core::WastArgCore::V128(_) => todo!(),
-                            wast::core::WastArgCore::RefNull(_) => todo!(),
-                            wast::core::WastArgCore::RefExtern(_) => todo!(),
-                        },
-                        WastArg::Component(_) => todo!(),
-                    };
+                    })
+                    .err();
 
-                    let _actual_results = match exec {
-                        wast::WastExecute::Invoke(invoke) => compiler.run(
-                            &mut externals,
-                            &compile_outcome.unwrap(),
-                            invoke.name,
-                            invoke.args.iter().map(|arg| from_wastarg(arg)).collect(),
-                        ),
-                        wast::WastExecute::Wat(_) => todo!(),
-                        wast::WastExecute::Get { .. } => todo!(),
+                current = Some(SpecModule {
+                    line,
+                    name,
+                    wasm,
+                    unsupported,
+                    invokes: vec![],
+                });
+            }
+            CommandKind::AssertReturn { action, expected } => match (action, current.as_mut()) {
+                (
+                    Action::Invoke {
+                        module: target,
+                        field,
+                        args,
+                    },
+                    Some(module),
+                ) if target.is_none() || target == module.name => {
+                    if has_unsupported_value(&args) || has_unsupported_value(&expected) {
+                        report.skip(line, "v128 values");
+                    } else {
+                        module.invokes.push(Invoke {
+                            line,
+                            field,
+                            args,
+                            expected: Some(expected),
+                        });
                     }
-                    .unwrap();
+                }
+                _ => report.skip(line, "unsupported action"),
+            },
+            CommandKind::PerformAction(action) => match (action, current.as_mut()) {
+                (
+                    Action::Invoke {
+                        module: target,
+                        field,
+                        args,
+                    },
+                    Some(module),
+                ) if (target.is_none() || target == module.name)
+                    && !has_unsupported_value(&args) =>
+                {
+                    module.invokes.push(Invoke {
+                        line,
+                        field,
+                        args,
+                        expected: None,
+                    });
+                }
+                _ => report.skip(line, "unsupported action"),
+            },
+            CommandKind::AssertTrap { action, .. } => match (action, current.as_mut()) {
+                (
+                    Action::Invoke {
+                        module: target,
+                        field,
+                        args,
+                    },
+                    Some(module),
+                ) if (target.is_none() || target == module.name)
+                    && !has_unsupported_value(&args) =>
+                {
+                    let invoke = Invoke {
+                        line,
+                        field,
+                        args,
+                        expected: None,
+                    };
 
-                    todo!()
+                    check_trap(module, invoke, &mut report);
+                }
+                _ => report.skip(line, "unsupported action"),
+            },
+            CommandKind::AssertInvalid { module, .. } => {
+                // Invalid modules export no entry, the entry is checked last so missing
+                // it means that the module passed every other check.
+                match ZkWasmLoader::parse_module_with_entry(&module.into_vec(), MIN_K, &[], ENTRY)
+                    .map_err(|err| err.downcast::<Error>())
+                {
+                    Ok(_) | Err(Ok(Error::PreCheck(PreCheckErr::ZkmainNotExists))) => report
+                        .failed
+                        .push((line, "invalid module is accepted".to_owned())),
+                    Err(_) => report.passed += 1,
                 }
-                wast::WastDirective::AssertExhaustion { .. } => todo!(),
-                wast::WastDirective::AssertUnlinkable { .. } => todo!(),
-                wast::WastDirective::AssertException { .. } => todo!(),
             }
+            CommandKind::AssertReturnCanonicalNan { .. }
+            | CommandKind::AssertReturnArithmeticNan { .. } => report.skip(line, "NaN results"),
+            CommandKind::AssertExhaustion { .. } => report.skip(line, "call stack exhaustion"),
+            CommandKind::AssertMalformed { .. } => report.skip(line, "malformed module"),
+            CommandKind::AssertUninstantiable { .. } => report.skip(line, "uninstantiable module"),
+            CommandKind::AssertUnlinkable { .. } => report.skip(line, "unlinkable module"),
+            CommandKind::Register { .. } => report.skip(line, "module registration"),
         }
-        Ok(())
-        */
     }
 
-    /*
-    mod tests {
-        use super::run_spec_test;
+    if let Some(module) = current.take() {
+        prove_module(module, &mut report);
+    }
 
-        #[test]
-        fn test_spec_i32() {
-            run_spec_test("i32").unwrap()
-        }
+    Ok(report)
+}
+
+/// Runs `file_name.wast` and fails on any directive that neither passes nor
+/// is listed in `EXPECTED_FAILURES`.
+fn test_spec(file_name: &str) {
+    let report = run_spec_test(file_name).unwrap();
+
+    let expected_failures = EXPECTED_FAILURES
+        .iter()
+        .filter(|(file, ..)| *file == file_name)
+        .collect::<Vec<_>>();
+
+    let unexpected_failures = report
+        .failed
+        .iter()
+        .filter(|(line, _)| !expected_failures.iter().any(|(_, l, _)| l == line))
+        .collect::<Vec<_>>();
+    let unexpected_passes = expected_failures
+        .iter()
+        .filter(|(_, line, _)| !report.failed.iter().any(|(l, _)| l == line))
+        .collect::<Vec<_>>();
+
+    println!(
+        "{}.wast: {} passed, {} skipped, {} failed",
+        file_name,
+        report.passed,
+        report.skipped.len(),
+        report.failed.len()
+    );
+    for (line, reason) in &report.skipped {
+        println!("  skipped line {}: {}", line, reason);
     }
-    */
-*/
+
+    assert!(
+        unexpected_failures.is_empty(),
+        "{}.wast: unexpected failures {:?}",
+        file_name,
+        unexpected_failures
+    );
+    assert!(
+        unexpected_passes.is_empty(),
+        "{}.wast: expected failures pass now, remove them from EXPECTED_FAILURES {:?}",
+        file_name,
+        unexpected_passes
+    );
+}
+
+#[test]
+fn test_spec_i32() {
+    test_spec("i32")
+}
@@ -0,0 +1,183 @@
+mod tests {
+    use crate::circuits::MIN_K;
+    use crate::error::ExecutionError;
+    use crate::error::HostCallError;
+    use crate::loader::ZkWasmLoader;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::default_env::ExecutionArg;
+    use crate::runtime::host::HostEnvBuilder;
+    use crate::runtime::monitor::backtrace::Backtrace;
+    use crate::runtime::monitor::backtrace::Frame;
+    use crate::runtime::monitor::limits::ExecutionLimits;
+    use crate::runtime::monitor::table_monitor::TableMonitor;
+    use specs::slice_backend::InMemoryBackendBuilder;
+
+    fn run(textual_repr: &str, public_inputs: Vec<u64>, limits: ExecutionLimits) -> anyhow::Error {
+        let wasm = wabt::Wat2Wasm::new()
+            .write_debug_names(true)
+            .convert(textual_repr)
+            .expect("failed to parse wat")
+            .as_ref()
+            .to_vec();
+        let module = ZkWasmLoader::parse_module_with_entry(&wasm, MIN_K, &[], "test").unwrap();
+        let env_builder = DefaultHostEnvBuilder::new(MIN_K);
+
+        let env = env_builder.create_env(ExecutionArg {
+            public_inputs,
+            private_inputs: vec![],
+            context_inputs: vec![],
+            indexed_witness: Default::default(),
+            tree_db: None,
+        });
+        let mut monitor = TableMonitor::new(
+            MIN_K,
+            InMemoryBackendBuilder,
+            env_builder.create_flush_strategy(),
+            &vec![],
+            &env,
+            limits,
+        );
+        let mut loader = ZkWasmLoader::new(MIN_K, env).unwrap();
+        loader.set_entry("test".to_string());
+
+        let runner = loader.compile(&module, &mut monitor).unwrap();
+        loader
+            .run(runner, &mut monitor)
+            .err()
+            .expect("execution should fail")
+    }
+
+    #[test]
+    fn test_require_false() {
+        let textual_repr = r#"
+        (module
+            (import "env" "require" (func $require (param i32)))
+
+            (func (export "test")
+              (call $require (i32.const 1))
+              (call $require (i32.const 0))
+            )
+        )
+        "#;
+
+        let ExecutionError::HostCall {
+            eid,
+            fid,
+            iid,
+            function,
+            error,
+        } = run(textual_repr, vec![], ExecutionLimits::default())
+            .downcast()
+            .unwrap()
+        else {
+            panic!("unexpected execution error")
+        };
+
+        assert_eq!(eid, 4);
+        assert_eq!(fid, 1);
+        assert_eq!(iid, 3);
+        assert_eq!(function, "require");
+        assert!(matches!(error, HostCallError::RequireNotSatisfied));
+    }
+
+    #[test]
+    fn test_public_inputs_exhausted() {
+        let textual_repr = r#"
+        (module
+            (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+
+            (func (export "test")
+              (drop (call $wasm_input (i32.const 1)))
+              (drop (call $wasm_input (i32.const 1)))
+            )
+        )
+        "#;
+
+        let ExecutionError::HostCall {
+            function, error, ..
+        } = run(textual_repr, vec![1], ExecutionLimits::default())
+            .downcast()
+            .unwrap()
+        else {
+            panic!("unexpected execution error")
+        };
+
+        assert_eq!(function, "wasm_input");
+        assert!(matches!(error, HostCallError::PublicInputsExhausted));
+    }
+
+    #[test]
+    fn test_backtrace() {
+        let textual_repr = r#"
+        (module
+            (func $inner
+              (drop (i32.const 0))
+              (unreachable)
+            )
+
+            (func $outer
+              (call $inner)
+            )
+
+            (func (export "test")
+              (drop (i32.const 0))
+              (call $outer)
+            )
+        )
+        "#;
+
+        let err = run(textual_repr, vec![], ExecutionLimits::default());
+        let backtrace = err.downcast_ref::<Backtrace>().unwrap();
+
+        assert_eq!(
+            backtrace.0,
+            vec![
+                Frame {
+                    fid: 0,
+                    iid: 2,
+                    name: Some("inner".to_string()),
+                },
+                Frame {
+                    fid: 1,
+                    iid: 0,
+                    name: Some("outer".to_string()),
+                },
+                Frame {
+                    fid: 2,
+                    iid: 2,
+                    name: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_instruction_limit() {
+        let textual_repr = r#"
+        (module
+            (func (export "test")
+              (loop
+                (br 0)
+              )
+            )
+        )
+        "#;
+
+        let err = run(
+            textual_repr,
+            vec![],
+            ExecutionLimits {
+                instructions: Some(100),
+                ..ExecutionLimits::default()
+            },
+        );
+
+        assert!(matches!(
+            err.downcast::<ExecutionError>().unwrap(),
+            ExecutionError::InstructionLimitExceeded {
+                limit: 100,
+                eid: 101
+            }
+        ));
+    }
+}
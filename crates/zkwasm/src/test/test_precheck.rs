@@ -0,0 +1,197 @@
+mod tests {
+    use crate::circuits::image_table::compute_maximal_pages;
+    use crate::circuits::MIN_K;
+    use crate::loader::err::Error;
+    use crate::loader::err::PreCheckErr;
+    use crate::loader::ZkWasmLoader;
+    use crate::test::wabt_features;
+    use wabt::wat2wasm_with_features;
+
+    fn precheck(textual_repr: &str, phantom_functions: &[String]) -> Option<PreCheckErr> {
+        precheck_with_entry(textual_repr, phantom_functions, "test")
+    }
+
+    fn precheck_with_entry(
+        textual_repr: &str,
+        phantom_functions: &[String],
+        entry: &str,
+    ) -> Option<PreCheckErr> {
+        let wasm =
+            wat2wasm_with_features(textual_repr, wabt_features()).expect("failed to parse wat");
+
+        ZkWasmLoader::parse_module_with_entry(&wasm, MIN_K, phantom_functions, entry)
+            .err()
+            .map(|err| match err.downcast::<Error>().unwrap() {
+                Error::PreCheck(err) => err,
+            })
+    }
+
+    #[test]
+    fn test_precheck_ok() {
+        let textual_repr = r#"
+        (module
+            (memory $0 1)
+            (func (export "test"))
+            (func (export "phantom"))
+        )
+        "#;
+
+        assert!(precheck(textual_repr, &["phantom".to_string()]).is_none());
+    }
+
+    #[test]
+    fn test_precheck_initial_pages() {
+        let textual_repr = format!(
+            r#"
+            (module
+                (memory $0 {})
+                (func (export "test"))
+            )
+            "#,
+            compute_maximal_pages(MIN_K) + 1
+        );
+
+        assert!(matches!(
+            precheck(&textual_repr, &[]),
+            Some(PreCheckErr::InitialPagesExceedLimit(_, _, MIN_K))
+        ));
+    }
+
+    #[test]
+    fn test_precheck_maximal_pages() {
+        let textual_repr = format!(
+            r#"
+            (module
+                (memory $0 1 {})
+                (func (export "test"))
+            )
+            "#,
+            compute_maximal_pages(MIN_K) + 1
+        );
+
+        assert!(matches!(
+            precheck(&textual_repr, &[]),
+            Some(PreCheckErr::MaximalPagesExceedLimit(_, _, MIN_K))
+        ));
+    }
+
+    #[test]
+    fn test_precheck_zkmain() {
+        assert!(
+            precheck_with_entry(r#"(module (func (export "zkmain")))"#, &[], "zkmain").is_none()
+        );
+        assert!(matches!(
+            precheck_with_entry(r#"(module (func (export "test")))"#, &[], "zkmain"),
+            Some(PreCheckErr::ZkmainNotExists)
+        ));
+        assert!(matches!(
+            precheck_with_entry(r#"(module (memory (export "zkmain") 1))"#, &[], "zkmain"),
+            Some(PreCheckErr::ZkmainIsNotFunction)
+        ));
+        assert!(matches!(
+            precheck_with_entry(
+                r#"(module (func (export "zkmain") (param i32)))"#,
+                &[],
+                "zkmain"
+            ),
+            Some(PreCheckErr::ZkmainTypeNotMatch)
+        ));
+        assert!(matches!(
+            precheck_with_entry(
+                r#"(module (func (export "zkmain") (result i32) (i32.const 0)))"#,
+                &[],
+                "zkmain"
+            ),
+            Some(PreCheckErr::ZkmainTypeNotMatch)
+        ));
+    }
+
+    #[test]
+    fn test_precheck_phantom_functions() {
+        let textual_repr = r#"
+        (module
+            (func (export "test"))
+        )
+        "#;
+
+        assert!(matches!(
+            precheck(textual_repr, &["phantom".to_string()]),
+            Some(PreCheckErr::PhantomFunctionNotExists(_))
+        ));
+        assert!(matches!(
+            precheck(textual_repr, &["(".to_string()]),
+            Some(PreCheckErr::InvalidPhantomFunctionPattern(_))
+        ));
+    }
+
+    #[test]
+    fn test_precheck_passive_data_segment() {
+        let textual_repr = r#"
+        (module
+            (memory $0 1)
+            (data (i32.const 0) "zk")
+            (data "zkwasm")
+            (func $test (export "test"))
+        )
+        "#;
+
+        assert!(matches!(
+            precheck(textual_repr, &[]),
+            Some(PreCheckErr::PassiveDataSegment(1))
+        ));
+    }
+
+    #[test]
+    fn test_precheck_unsupported_instruction() {
+        let textual_repr = r#"
+        (module
+            (memory $0 1)
+            (data (i32.const 0) "zkwasm")
+            (func $test (export "test")
+              (data.drop 0)
+            )
+        )
+        "#;
+
+        assert!(matches!(
+            precheck(textual_repr, &[]),
+            Some(PreCheckErr::UnsupportedInstruction { .. })
+        ));
+    }
+
+    #[test]
+    fn test_precheck_unsupported_instruction_in_phantom_function() {
+        let textual_repr = r#"
+        (module
+            (func (export "test"))
+            (func (export "phantom") (result f32)
+              (f32.sqrt (f32.const 2))
+            )
+        )
+        "#;
+
+        assert!(matches!(
+            precheck(textual_repr, &["phantom".to_string()]),
+            Some(PreCheckErr::UnsupportedInstruction { .. })
+        ));
+    }
+
+    #[test]
+    fn test_precheck_too_many_results() {
+        let textual_repr = r#"
+        (module
+            (func (export "test") (result i32 i32 i32 i32)
+              (i32.const 0)
+              (i32.const 1)
+              (i32.const 2)
+              (i32.const 3)
+            )
+        )
+        "#;
+
+        assert!(matches!(
+            precheck(textual_repr, &[]),
+            Some(PreCheckErr::TooManyResults(_, 4, _))
+        ));
+    }
+}
@@ -0,0 +1,80 @@
+mod tests {
+    use crate::circuits::MIN_K;
+    use crate::loader::ZkWasmLoader;
+    use crate::profile::Profile;
+    use crate::profile::Profiler;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::default_env::ExecutionArg;
+    use crate::runtime::host::HostEnvBuilder;
+    use crate::runtime::monitor::limits::ExecutionLimits;
+    use crate::runtime::monitor::table_monitor::TableMonitor;
+    use specs::itable::OpcodeClass;
+    use specs::slice_backend::InMemoryBackendBuilder;
+
+    #[test]
+    fn test_profile() {
+        let textual_repr = r#"
+        (module
+            (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+
+            (func $inner (result i64)
+              (call $wasm_input (i32.const 1))
+            )
+
+            (func $test (export "test")
+              (drop (call $inner))
+              (drop (call $inner))
+            )
+        )
+        "#;
+
+        let wasm = wabt::Wat2Wasm::new()
+            .write_debug_names(true)
+            .convert(textual_repr)
+            .expect("failed to parse wat")
+            .as_ref()
+            .to_vec();
+        let module = ZkWasmLoader::parse_module_with_entry(&wasm, MIN_K, &[], "test").unwrap();
+        let env_builder = DefaultHostEnvBuilder::new(MIN_K);
+
+        let env = env_builder.create_env(ExecutionArg {
+            public_inputs: vec![1, 2],
+            private_inputs: vec![],
+            context_inputs: vec![],
+            indexed_witness: Default::default(),
+            tree_db: None,
+        });
+        let mut profile = Profile::new(&module, &env.function_description_table());
+        let mut monitor = TableMonitor::new(
+            MIN_K,
+            InMemoryBackendBuilder,
+            env_builder.create_flush_strategy(),
+            &vec![],
+            &env,
+            ExecutionLimits::default(),
+        );
+        let mut loader = ZkWasmLoader::new(MIN_K, env).unwrap();
+        loader.set_entry("test".to_string());
+
+        let runner = loader.compile(&module, &mut monitor).unwrap();
+        loader.run(runner, &mut monitor).unwrap();
+
+        monitor.into_tables().profile_tables(&mut profile);
+
+        // test: call, drop, call, drop, return
+        // inner: i32.const, call_host, return
+        assert_eq!(profile.etable_entries, 11);
+        assert_eq!(profile.functions[&1].steps, 6);
+        assert_eq!(profile.functions[&2].steps, 5);
+        assert_eq!(profile.opcodes[&OpcodeClass::Call].steps, 2);
+        assert_eq!(profile.opcodes[&OpcodeClass::Return].steps, 3);
+        assert_eq!(profile.host_calls["wasm_input"], 2);
+
+        let mut folded_stacks = vec![];
+        profile.write_folded_stacks(&mut folded_stacks).unwrap();
+        assert_eq!(
+            String::from_utf8(folded_stacks).unwrap(),
+            "test 5\ntest;inner 6\n"
+        );
+    }
+}
@@ -0,0 +1,206 @@
+use std::rc::Rc;
+
+use specs::external_host_call_table::ExternalHostCallEntry;
+use specs::external_host_call_table::ExternalHostCallSignature;
+use specs::external_host_call_table::ExternalHostCallTable;
+use specs::slice_backend::InMemoryBackendBuilder;
+use wasmi::RuntimeValue;
+
+use crate::circuits::MIN_K;
+use crate::error::ExecutionError;
+use crate::error::HostCallError;
+use crate::loader::ZkWasmLoader;
+use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+use crate::runtime::host::default_env::ExecutionArg;
+use crate::runtime::host::host_env::HostEnv;
+use crate::runtime::host::replay_env::ReplayHostEnvBuilder;
+use crate::runtime::host::ForeignContext;
+use crate::runtime::host::HostEnvBuilder;
+use crate::runtime::monitor::limits::ExecutionLimits;
+use crate::runtime::monitor::plugins::table::FlushStrategy;
+use crate::runtime::monitor::table_monitor::TableMonitor;
+use crate::runtime::ExecutionResult;
+
+#[derive(Default)]
+struct Context {
+    acc: u64,
+}
+impl ForeignContext for Context {}
+
+/// Registers an accumulator which is pushed and popped by external host calls.
+struct AccumulatorHostEnvBuilder;
+
+impl HostEnvBuilder for AccumulatorHostEnvBuilder {
+    fn create_env_without_value(&self) -> HostEnv {
+        let mut env = HostEnv::new(MIN_K);
+
+        let plugin = env
+            .external_env
+            .register_plugin("foreign_accumulator", Box::<Context>::default());
+        env.external_env.register_function(
+            "foreign_push",
+            0,
+            ExternalHostCallSignature::Argument,
+            plugin.clone(),
+            Rc::new(
+                |_obs, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
+                    let context = context.downcast_mut::<Context>().unwrap();
+                    context.acc += args.nth::<u64>(0);
+                    None
+                },
+            ),
+        );
+        env.external_env.register_function(
+            "foreign_pop",
+            1,
+            ExternalHostCallSignature::Return,
+            plugin,
+            Rc::new(
+                |_obs, context: &mut dyn ForeignContext, _args: wasmi::RuntimeArgs| {
+                    let context = context.downcast_mut::<Context>().unwrap();
+                    Some(RuntimeValue::I64(context.acc as i64))
+                },
+            ),
+        );
+        env.finalize();
+
+        env
+    }
+
+    fn create_env(&self, _arg: ExecutionArg) -> HostEnv {
+        self.create_env_without_value()
+    }
+
+    fn create_flush_strategy(&self) -> Box<dyn FlushStrategy> {
+        crate::runtime::host::default_env::DefaultHostEnvBuilder::new(MIN_K).create_flush_strategy()
+    }
+}
+
+fn run(env_builder: &dyn HostEnvBuilder) -> anyhow::Result<ExecutionResult<RuntimeValue>> {
+    let textual_repr = r#"
+        (module
+            (import "env" "foreign_push" (func $push (param i64)))
+            (import "env" "foreign_pop" (func $pop (result i64)))
+
+            (func (export "test") (result i64)
+              (call $push (i64.const 5))
+              (call $push (i64.const 10))
+              (call $pop)
+            )
+        )
+        "#;
+
+    let wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
+    let module = ZkWasmLoader::parse_module_with_entry(&wasm, MIN_K, &[], "test")?;
+
+    let env = env_builder.create_env(ExecutionArg {
+        public_inputs: vec![],
+        private_inputs: vec![],
+        context_inputs: vec![],
+        indexed_witness: Default::default(),
+        tree_db: None,
+    });
+    let mut monitor = TableMonitor::new(
+        MIN_K,
+        InMemoryBackendBuilder,
+        env_builder.create_flush_strategy(),
+        &vec![],
+        &env,
+        ExecutionLimits::default(),
+    );
+    let mut loader = ZkWasmLoader::new(MIN_K, env)?;
+    loader.set_entry("test".to_string());
+
+    let runner = loader.compile(&module, &mut monitor)?;
+    loader.run(runner, &mut monitor)
+}
+
+fn table(entries: &[(usize, u64, bool)]) -> ExternalHostCallTable {
+    ExternalHostCallTable::new(
+        entries
+            .iter()
+            .map(|(op, value, is_ret)| ExternalHostCallEntry {
+                op: *op,
+                value: *value,
+                is_ret: *is_ret,
+            })
+            .collect(),
+    )
+}
+
+#[test]
+fn test_replay() {
+    let result = run(&AccumulatorHostEnvBuilder).unwrap();
+    assert_eq!(result.result, Some(RuntimeValue::I64(15)));
+
+    // the popped value is answered by the record instead of the accumulator
+    let env_builder = ReplayHostEnvBuilder::new(
+        AccumulatorHostEnvBuilder,
+        vec![
+            table(&[(0, 5, false)]),
+            table(&[(0, 10, false), (1, 42, true)]),
+        ],
+    );
+    let result = run(&env_builder).unwrap();
+    assert_eq!(result.result, Some(RuntimeValue::I64(42)));
+}
+
+#[test]
+fn test_replay_incomplete() {
+    let env_builder = ReplayHostEnvBuilder::new(
+        AccumulatorHostEnvBuilder,
+        vec![table(&[
+            (0, 5, false),
+            (0, 10, false),
+            (1, 15, true),
+            (0, 1, false),
+        ])],
+    );
+
+    let error: ExecutionError = run(&env_builder).err().unwrap().downcast().unwrap();
+
+    assert!(matches!(
+        error,
+        ExecutionError::ReplayIncomplete {
+            replayed: 3,
+            recorded: 4,
+        }
+    ));
+}
+
+#[test]
+fn test_replay_diverged() {
+    let env_builder = ReplayHostEnvBuilder::new(
+        AccumulatorHostEnvBuilder,
+        vec![table(&[(0, 5, false), (0, 11, false), (1, 15, true)])],
+    );
+
+    let ExecutionError::HostCall {
+        function, error, ..
+    } = run(&env_builder).err().unwrap().downcast().unwrap()
+    else {
+        panic!("unexpected execution error")
+    };
+
+    assert_eq!(function, "foreign_push");
+    assert!(matches!(
+        error,
+        HostCallError::ReplayDiverged {
+            position: 1,
+            op: 0,
+            recorded_op: 0,
+            recorded_value: 11,
+        }
+    ));
+
+    let env_builder =
+        ReplayHostEnvBuilder::new(AccumulatorHostEnvBuilder, vec![table(&[(0, 5, false)])]);
+
+    let ExecutionError::HostCall { error, .. } =
+        run(&env_builder).err().unwrap().downcast().unwrap()
+    else {
+        panic!("unexpected execution error")
+    };
+
+    assert!(matches!(error, HostCallError::ReplayExhausted(0)));
+}
@@ -0,0 +1,137 @@
+mod tests {
+    use std::cell::RefCell;
+    use std::io;
+    use std::io::Write;
+    use std::rc::Rc;
+
+    use crate::circuits::MIN_K;
+    use crate::loader::ZkWasmLoader;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::default_env::ExecutionArg;
+    use crate::runtime::host::HostEnvBuilder;
+    use crate::runtime::monitor::limits::ExecutionLimits;
+    use crate::runtime::monitor::plugins::tracer::TraceFilter;
+    use crate::runtime::monitor::plugins::tracer::TraceFormat;
+    use crate::runtime::monitor::plugins::tracer::TracerPlugin;
+    use crate::runtime::monitor::table_monitor::TableMonitor;
+    use specs::slice_backend::InMemoryBackendBuilder;
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    const TEXTUAL_REPR: &str = r#"
+        (module
+            (memory 1)
+
+            (func $inner (param i32) (result i32)
+              (i32.store (i32.const 0) (local.get 0))
+              (i32.load (i32.const 0))
+            )
+
+            (func $test (export "test")
+              (drop (call $inner (i32.const 42)))
+            )
+        )
+        "#;
+
+    fn trace(format: TraceFormat, filter: TraceFilter) -> String {
+        let wasm = wabt::Wat2Wasm::new()
+            .write_debug_names(true)
+            .convert(TEXTUAL_REPR)
+            .expect("failed to parse wat")
+            .as_ref()
+            .to_vec();
+        let module = ZkWasmLoader::parse_module_with_entry(&wasm, MIN_K, &[], "test").unwrap();
+        let env_builder = DefaultHostEnvBuilder::new(MIN_K);
+
+        let env = env_builder.create_env(ExecutionArg {
+            public_inputs: vec![],
+            private_inputs: vec![],
+            context_inputs: vec![],
+            indexed_witness: Default::default(),
+            tree_db: None,
+        });
+        let buffer = SharedBuffer::default();
+        let mut monitor = TableMonitor::new(
+            MIN_K,
+            InMemoryBackendBuilder,
+            env_builder.create_flush_strategy(),
+            &vec![],
+            &env,
+            ExecutionLimits::default(),
+        )
+        .with_tracer(TracerPlugin::new(Box::new(buffer.clone()), format, filter));
+        let mut loader = ZkWasmLoader::new(MIN_K, env).unwrap();
+        loader.set_entry("test".to_string());
+
+        let runner = loader.compile(&module, &mut monitor).unwrap();
+        loader.run(runner, &mut monitor).unwrap();
+        monitor.take_tracer().unwrap().finalize().unwrap();
+
+        let trace = buffer.0.borrow().clone();
+        String::from_utf8(trace).unwrap()
+    }
+
+    #[test]
+    fn test_trace_function_filter() {
+        let trace = trace(
+            TraceFormat::JsonLines,
+            TraceFilter {
+                functions: vec!["inner".to_string()],
+                eids: None,
+            },
+        );
+
+        let records = trace
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+
+        // i32.const, local.get, i32.store, i32.const, i32.load, return
+        assert_eq!(records.len(), 6);
+        assert!(records.iter().all(|record| record["function"] == "inner"));
+
+        let store = &records[2];
+        assert!(store["opcode"].get("Store").is_some());
+        assert_eq!(store["stack"].as_array().unwrap().len(), 2);
+        assert!(store["memory"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|access| access["atype"] == "Write" && access["ltype"] == "Heap"));
+
+        let load = &records[4];
+        assert!(load["opcode"].get("Load").is_some());
+        assert!(load["memory"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|access| access["atype"] == "Read" && access["value"] == 42));
+    }
+
+    #[test]
+    fn test_trace_eid_filter() {
+        let trace = trace(
+            TraceFormat::Text,
+            TraceFilter {
+                functions: vec![],
+                eids: Some(2..=3),
+            },
+        );
+
+        let lines = trace.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("2 test:"));
+        assert!(lines[1].starts_with("3 inner:0 "));
+    }
+}
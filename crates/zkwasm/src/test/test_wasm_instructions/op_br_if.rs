@@ -113,3 +113,25 @@ fn test_br_if_block_with_drop_do_jump_ok() {
 
     test_circuit_noexternal(textual_repr).unwrap();
 }
+
+#[test]
+fn test_br_if_multi_value_ok() {
+    let textual_repr = r#"
+        (module
+            (func (export "test")
+              (block (result i32 i64)
+                (i32.const 1)
+                (i64.const 2)
+                (i32.const 0)
+                br_if 0
+                (i32.const 1)
+                br_if 0
+              )
+              drop
+              drop
+            )
+           )
+        "#;
+
+    test_circuit_noexternal(textual_repr).unwrap();
+}
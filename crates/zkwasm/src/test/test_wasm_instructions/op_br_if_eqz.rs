@@ -17,3 +17,21 @@ fn test_br_if_eqz_ok() {
 
     test_circuit_noexternal(textual_repr).unwrap();
 }
+
+#[test]
+fn test_br_if_eqz_multi_value_ok() {
+    let textual_repr = r#"
+        (module
+            (func (export "test")
+              (if (result i64 i32) (i32.const 0)
+                (then (i64.const 1) (i32.const 2))
+                (else (i64.const 3) (i32.const 4))
+              )
+              drop
+              drop
+            )
+           )
+        "#;
+
+    test_circuit_noexternal(textual_repr).unwrap();
+}
@@ -0,0 +1,208 @@
+use std::vec;
+
+use crate::circuits::MIN_K;
+use crate::loader::err::Error;
+use crate::loader::err::PreCheckErr;
+use crate::loader::ZkWasmLoader;
+use crate::test::test_circuit_noexternal;
+use crate::test::wabt_features;
+use wabt::wat2wasm_with_features;
+
+const FLOAT_VALUES: [&str; 10] = [
+    "0", "-0", "1.5", "-1.5", "0x1p-149", "inf", "-inf", "nan", "-nan", "nan:0x1",
+];
+
+#[test]
+fn test_op_float_rel() {
+    let ops = vec!["eq", "ne", "lt", "gt", "le", "ge"];
+
+    let mut textual_repr = r#"
+            (module
+                (func (export "test")"#
+        .to_owned();
+
+    for t in ["f32", "f64"] {
+        for op in ops.iter() {
+            for l in FLOAT_VALUES.iter() {
+                for r in FLOAT_VALUES.iter() {
+                    textual_repr = format!(
+                        r#"{}
+                            ({}.const {})
+                            ({}.const {})
+                            ({}.{})
+                            (drop)
+                            "#,
+                        textual_repr, t, l, t, r, t, op
+                    );
+                }
+            }
+        }
+    }
+
+    textual_repr = format!("{}))", textual_repr);
+    test_circuit_noexternal(&textual_repr).unwrap()
+}
+
+#[test]
+fn test_op_float_bin() {
+    let ops = vec!["min", "max", "copysign"];
+
+    let mut textual_repr = r#"
+            (module
+                (func (export "test")"#
+        .to_owned();
+
+    for t in ["f32", "f64"] {
+        for op in ops.iter() {
+            for l in FLOAT_VALUES.iter() {
+                for r in FLOAT_VALUES.iter() {
+                    textual_repr = format!(
+                        r#"{}
+                            ({}.const {})
+                            ({}.const {})
+                            ({}.{})
+                            (drop)
+                            "#,
+                        textual_repr, t, l, t, r, t, op
+                    );
+                }
+            }
+        }
+    }
+
+    textual_repr = format!("{}))", textual_repr);
+    test_circuit_noexternal(&textual_repr).unwrap()
+}
+
+#[test]
+fn test_op_float_unary() {
+    let mut textual_repr = r#"
+            (module
+                (func (export "test")"#
+        .to_owned();
+
+    for t in ["f32", "f64"] {
+        for op in ["abs", "neg"] {
+            for v in FLOAT_VALUES.iter() {
+                textual_repr = format!(
+                    r#"{}
+                        ({}.const {})
+                        ({}.{})
+                        (drop)
+                        "#,
+                    textual_repr, t, v, t, op
+                );
+            }
+        }
+    }
+
+    textual_repr = format!("{}))", textual_repr);
+    test_circuit_noexternal(&textual_repr).unwrap()
+}
+
+#[test]
+fn test_op_float_reinterpret() {
+    let textual_repr = r#"
+        (module
+            (func (export "test")
+              (f32.const -1.5)
+              (i32.reinterpret_f32)
+              (f32.reinterpret_i32)
+              (drop)
+
+              (f64.const nan:0x1)
+              (i64.reinterpret_f64)
+              (f64.reinterpret_i64)
+              (drop)
+            )
+           )
+        "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_op_float_load_store() {
+    let textual_repr = r#"
+        (module
+            (memory $0 1)
+            (func (export "test")
+              (f32.store offset=0 (i32.const 0) (f32.const -1.5))
+              (f64.store offset=8 (i32.const 0) (f64.const inf))
+              (f32.load offset=0 (i32.const 0))
+              (drop)
+              (f64.load offset=8 (i32.const 0))
+              (drop)
+            )
+           )
+        "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+/// The rounding operations have no circuits, they must be rejected before tracing.
+fn assert_unsupported(body: &str) {
+    let textual_repr = format!(
+        r#"
+        (module
+            (func (export "test")
+              {}
+              (drop)
+            )
+           )
+        "#,
+        body
+    );
+
+    let wasm = wat2wasm_with_features(&textual_repr, wabt_features()).unwrap();
+    let err = ZkWasmLoader::parse_module_with_entry(&wasm, MIN_K, &[], "test")
+        .err()
+        .unwrap_or_else(|| panic!("{} is accepted", body));
+
+    assert!(matches!(
+        err.downcast::<Error>().unwrap(),
+        Error::PreCheck(PreCheckErr::UnsupportedInstruction { .. })
+    ));
+}
+
+#[test]
+fn test_op_float_arithmetic_unsupported() {
+    for t in ["f32", "f64"] {
+        for op in ["add", "sub", "mul", "div"] {
+            assert_unsupported(&format!(
+                "({}.{} ({}.const 1.5) ({}.const -0.5))",
+                t, op, t, t
+            ));
+        }
+
+        for op in ["ceil", "floor", "trunc", "nearest", "sqrt"] {
+            assert_unsupported(&format!("({}.{} ({}.const 1.5))", t, op, t));
+        }
+    }
+}
+
+#[test]
+fn test_op_float_conversion_unsupported() {
+    for (op, operand) in [
+        ("i32.trunc_f32_s", "(f32.const -1.5)"),
+        ("i32.trunc_f32_u", "(f32.const 1.5)"),
+        ("i32.trunc_f64_s", "(f64.const -1.5)"),
+        ("i32.trunc_f64_u", "(f64.const 1.5)"),
+        ("i64.trunc_f32_s", "(f32.const -1.5)"),
+        ("i64.trunc_f32_u", "(f32.const 1.5)"),
+        ("i64.trunc_f64_s", "(f64.const -1.5)"),
+        ("i64.trunc_f64_u", "(f64.const 1.5)"),
+        ("f32.convert_i32_s", "(i32.const -1)"),
+        ("f32.convert_i32_u", "(i32.const -1)"),
+        ("f32.convert_i64_s", "(i64.const -1)"),
+        ("f32.convert_i64_u", "(i64.const -1)"),
+        ("f32.demote_f64", "(f64.const 1.5)"),
+        ("f64.convert_i32_s", "(i32.const -1)"),
+        ("f64.convert_i32_u", "(i32.const -1)"),
+        ("f64.convert_i64_s", "(i64.const -1)"),
+        ("f64.convert_i64_u", "(i64.const -1)"),
+        ("f64.promote_f32", "(f32.const 1.5)"),
+    ] {
+        assert_unsupported(&format!("({} {})", op, operand));
+    }
+}
@@ -0,0 +1,145 @@
+use crate::test::test_circuit_noexternal;
+
+#[test]
+fn test_memory_copy() {
+    let textual_repr = r#"
+        (module
+            (memory 1 1)
+            (data (i32.const 0) "\01\02\03\04\05\06\07\08\09\0a\0b\0c")
+
+            (func (export "test")
+                (memory.copy (i32.const 21) (i32.const 1) (i32.const 10))
+                (i32.const 16)
+                (i64.load offset=8)
+                (drop)
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_memory_copy_overlap_forward() {
+    let textual_repr = r#"
+        (module
+            (memory 1 1)
+            (data (i32.const 0) "\01\02\03\04\05\06\07\08\09\0a\0b\0c")
+
+            (func (export "test")
+                (memory.copy (i32.const 0) (i32.const 3) (i32.const 9))
+                (i32.const 0)
+                (i64.load offset=0)
+                (drop)
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_memory_copy_overlap_backward() {
+    let textual_repr = r#"
+        (module
+            (memory 1 1)
+            (data (i32.const 0) "\01\02\03\04\05\06\07\08\09\0a\0b\0c")
+
+            (func (export "test")
+                (memory.copy (i32.const 3) (i32.const 0) (i32.const 9))
+                (i32.const 0)
+                (i64.load offset=8)
+                (drop)
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_memory_copy_words_forward() {
+    let textual_repr = r#"
+        (module
+            (memory 1 1)
+            (data (i32.const 0) "\01\02\03\04\05\06\07\08\09\0a\0b\0c\0d\0e\0f\10\11\12\13\14")
+
+            (func (export "test")
+                (memory.copy (i32.const 3) (i32.const 11) (i32.const 19))
+                (i32.const 0)
+                (i64.load offset=8)
+                (drop)
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_memory_copy_words_backward() {
+    let textual_repr = r#"
+        (module
+            (memory 1 1)
+            (data (i32.const 0) "\01\02\03\04\05\06\07\08\09\0a\0b\0c\0d\0e\0f\10\11\12\13\14")
+
+            (func (export "test")
+                (memory.copy (i32.const 9) (i32.const 1) (i32.const 19))
+                (i32.const 0)
+                (i64.load offset=16)
+                (drop)
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_memory_copy_misaligned() {
+    let textual_repr = r#"
+        (module
+            (memory 1 1)
+            (data (i32.const 0) "\01\02\03\04\05\06\07\08\09\0a\0b\0c\0d\0e\0f\10\11\12\13\14")
+
+            (func (export "test")
+                (memory.copy (i32.const 32) (i32.const 3) (i32.const 17))
+                (i32.const 32)
+                (i64.load offset=8)
+                (drop)
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_memory_copy_empty() {
+    let textual_repr = r#"
+        (module
+            (memory 1 1)
+
+            (func (export "test")
+                (memory.copy (i32.const 65536) (i32.const 0) (i32.const 0))
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_memory_copy_out_of_bounds() {
+    let textual_repr = r#"
+        (module
+            (memory 1 1)
+
+            (func (export "test")
+                (memory.copy (i32.const 0) (i32.const 65530) (i32.const 8))
+            )
+        )
+    "#;
+
+    assert!(test_circuit_noexternal(textual_repr).is_err())
+}
@@ -0,0 +1,82 @@
+use crate::test::test_circuit_noexternal;
+
+#[test]
+fn test_memory_fill() {
+    let textual_repr = r#"
+        (module
+            (memory 1 1)
+
+            (func (export "test")
+                (memory.fill (i32.const 3) (i32.const 0x1ab) (i32.const 13))
+                (i32.const 0)
+                (i64.load offset=8)
+                (drop)
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_memory_fill_words() {
+    let textual_repr = r#"
+        (module
+            (memory 1 1)
+
+            (func (export "test")
+                (memory.fill (i32.const 5) (i32.const 0x1ab) (i32.const 30))
+                (i32.const 0)
+                (i64.load offset=32)
+                (drop)
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_memory_fill_empty() {
+    let textual_repr = r#"
+        (module
+            (memory 1 1)
+
+            (func (export "test")
+                (memory.fill (i32.const 65536) (i32.const 1) (i32.const 0))
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_memory_fill_end_of_memory() {
+    let textual_repr = r#"
+        (module
+            (memory 1 1)
+
+            (func (export "test")
+                (memory.fill (i32.const 65533) (i32.const 0xff) (i32.const 3))
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_memory_fill_out_of_bounds() {
+    let textual_repr = r#"
+        (module
+            (memory 1 1)
+
+            (func (export "test")
+                (memory.fill (i32.const 65533) (i32.const 0xff) (i32.const 4))
+            )
+        )
+    "#;
+
+    assert!(test_circuit_noexternal(textual_repr).is_err())
+}
@@ -1,3 +1,5 @@
+use crate::circuits::image_table::compute_maximal_pages;
+use crate::circuits::MIN_K;
 use crate::test::test_circuit_noexternal;
 
 #[test]
@@ -32,6 +34,29 @@ fn test_memory_grow_fail() {
     test_circuit_noexternal(textual_repr).unwrap()
 }
 
+#[test]
+fn test_memory_grow_beyond_maximal_pages() {
+    let textual_repr = format!(
+        r#"
+        (module
+            (memory 1)
+
+            (func (export "test")
+                (memory.grow (i32.const {}))
+                (i32.const -1)
+                (i32.ne)
+                (if
+                    (then unreachable)
+                )
+            )
+        )
+    "#,
+        compute_maximal_pages(MIN_K)
+    );
+
+    test_circuit_noexternal(&textual_repr).unwrap()
+}
+
 #[test]
 fn test_memory_grow_lazy_init() {
     let textual_repr = r#"
@@ -42,3 +42,23 @@ fn test_return_with_keep_ok() {
 
     test_circuit_noexternal(textual_repr).unwrap();
 }
+
+#[test]
+fn test_return_multi_value_ok() {
+    let textual_repr = r#"
+            (module
+                (func $swap (param i32 i64) (result i64 i32)
+                  (local.get 1)
+                  (local.get 0)
+                )
+
+                (func (export "test")
+                  (call $swap (i32.const 1) (i64.const 2))
+                  drop
+                  drop
+                )
+               )
+            "#;
+
+    test_circuit_noexternal(textual_repr).unwrap();
+}
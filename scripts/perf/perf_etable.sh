@@ -1,2 +0,0 @@
-python3 call_stack.py $1 $2 | ./flamegraph.pl > $3
-